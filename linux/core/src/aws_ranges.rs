@@ -1,4 +1,4 @@
-use reqwest;
+use crate::errors::{self, ErrorKind};
 use serde_json::Value;
 use std::net::{IpAddr, Ipv4Addr};
 use std::sync::{Arc, Mutex};
@@ -18,6 +18,12 @@ pub struct AwsIpService {
     fetch_lock: Arc<AsyncMutex<()>>,
 }
 
+impl Default for AwsIpService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl AwsIpService {
     pub fn new() -> Self {
         Self {
@@ -26,7 +32,14 @@ impl AwsIpService {
         }
     }
 
-    async fn refresh(&self) -> Result<(), Box<dyn std::error::Error>> {
+    // Downloaded once per run, lookups fail with StaleRanges until that worked
+    async fn refresh(&self) -> anyhow::Result<()> {
+        if crate::overrides::get().offline {
+            return Err(errors::error(
+                ErrorKind::StaleRanges,
+                "The AWS IP ranges can't be downloaded, network requests are disabled (--offline)",
+            ));
+        }
         let _guard = self.fetch_lock.lock().await;
         {
             let cidrs = self.cidrs.lock().unwrap();
@@ -35,14 +48,12 @@ impl AwsIpService {
             }
         }
         let url = "https://ip-ranges.amazonaws.com/ip-ranges.json";
-        let client = reqwest::Client::new();
-        let resp = client
-            .get(url)
-            .header("User-Agent", "make-your-choice")
-            .send()
-            .await?
-            .json::<Value>()
-            .await?;
+        let resp = async {
+            let client = crate::net::client(None)?;
+            anyhow::Ok(client.get(url).send().await?.json::<Value>().await?)
+        }
+        .await
+        .map_err(|e| errors::error(ErrorKind::StaleRanges, format!("Failed to download the AWS IP ranges: {:#}", e)))?;
 
         let mut list = Vec::new();
         if let Some(prefixes) = resp.get("prefixes").and_then(|p| p.as_array()) {
@@ -71,27 +82,36 @@ impl AwsIpService {
     }
 
     pub async fn get_region(&self, ip_str: &str) -> Option<String> {
-        self.refresh().await.ok()?;
-
+        if crate::demo::enabled() {
+            return crate::demo::region_of(ip_str);
+        }
         let ip: IpAddr = ip_str.parse().ok()?;
         let ip_v4 = match ip {
             IpAddr::V4(v4) => v4,
             IpAddr::V6(_) => return None,
         };
 
-        let ip_val = u32::from(ip_v4);
-        let cidrs = self.cidrs.lock().unwrap();
+        if self.refresh().await.is_ok() {
+            let ip_val = u32::from(ip_v4);
+            let cidrs = self.cidrs.lock().unwrap();
 
-        let mut best: Option<&AwsCidr> = None;
-        for cidr in cidrs.iter() {
-            if (ip_val & cidr.mask) == cidr.network {
-                if best.map_or(true, |b| cidr.prefix_len > b.prefix_len) {
-                    best = Some(cidr);
+            let mut best: Option<&AwsCidr> = None;
+            for cidr in cidrs.iter() {
+                if (ip_val & cidr.mask) == cidr.network {
+                    if best.map_or(true, |b| cidr.prefix_len > b.prefix_len) {
+                        best = Some(cidr);
+                    }
                 }
             }
+            if let Some(best) = best {
+                return Some(Self::get_pretty_region_name(&best.region));
+            }
         }
 
-        best.map(|c| Self::get_pretty_region_name(&c.region))
+        // Without the published ranges, or for addresses missing from them, the
+        // reverse name of EC2 hosts still tells the region
+        let name = crate::dns::reverse(ip_v4).await.ok()?;
+        region_from_reverse_name(&name).map(Self::get_pretty_region_name)
     }
 
     pub fn get_pretty_region_name(region_code: &str) -> String {
@@ -122,6 +142,17 @@ impl AwsIpService {
     }
 }
 
+// ec2-3-120-0-1.eu-central-1.compute.amazonaws.com, with compute-1 for us-east-1
+fn region_from_reverse_name(name: &str) -> Option<&str> {
+    let name = name.trim_end_matches('.');
+    if name.ends_with(".compute-1.amazonaws.com") {
+        return Some("us-east-1");
+    }
+    let rest = name.strip_suffix(".compute.amazonaws.com")?;
+    let region = rest.rsplit('.').next()?;
+    region.ends_with(|c: char| c.is_ascii_digit()).then_some(region)
+}
+
 fn parse_ipv4_cidr(cidr: &str) -> Option<(u32, u32, u8)> {
     let mut parts = cidr.split('/');
     let ip_str = parts.next()?;
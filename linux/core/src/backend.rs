@@ -0,0 +1,87 @@
+use crate::hosts::HostsManager;
+use crate::region::{ApplyMode, RegionInfo};
+use crate::settings::AppliedSelection;
+use anyhow::Result;
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+// A way of keeping the game on the chosen servers. The window, the command line and the
+// background service apply, revert and inspect selections only through this trait. The
+// hosts file (hosts.rs) is the only backend so far; a firewall, a DNS proxy or a network
+// namespace would be another implementation.
+//
+// Anything that touches the network or writes is async, runs on the tokio runtime and
+// stops when its future is dropped, so the window can offer Cancel and the background
+// service never parks a thread on DNS. status and detect_conflicts only read local state
+// and stay plain calls, the window uses them while redrawing.
+pub trait EnforcementBackend: Send + Sync {
+    // Short name for logs, e.g. "hosts"
+    fn id(&self) -> &'static str;
+
+    // What apply would put in place, without changing anything: one line per entry or
+    // rule, in the backend's own format
+    fn plan<'a>(
+        &'a self,
+        regions: &'a HashMap<String, RegionInfo>,
+        blocked_regions: &'a HashMap<String, RegionInfo>,
+        applied: &'a AppliedSelection,
+    ) -> BoxFuture<'a, Result<Vec<String>>>;
+
+    // `progress` hears about each step as it starts
+    fn apply<'a>(
+        &'a self,
+        regions: &'a HashMap<String, RegionInfo>,
+        blocked_regions: &'a HashMap<String, RegionInfo>,
+        applied: &'a AppliedSelection,
+        progress: &'a (dyn Fn(Progress) + Sync),
+    ) -> BoxFuture<'a, Result<()>>;
+
+    fn revert(&self) -> BoxFuture<'_, Result<()>>;
+
+    // What is enforced right now
+    fn status(&self, regions: &HashMap<String, RegionInfo>) -> ActiveConfiguration;
+
+    // Rules outside the backend's control that work against it, e.g. other hosts
+    // entries for the game's hostnames. `regions` includes the blocked ones.
+    fn detect_conflicts(&self, regions: &HashMap<String, RegionInfo>) -> Result<Vec<String>>;
+
+    // Opaque state for Undo, written back with restore
+    fn snapshot(&self) -> BoxFuture<'_, Result<String>>;
+
+    fn restore(&self, snapshot: String) -> BoxFuture<'_, Result<()>>;
+}
+
+// A step of apply
+pub enum Progress {
+    // Looking up the address of this hostname
+    Resolving(String),
+    // Putting the result in place
+    Writing,
+}
+
+// What a backend currently enforces
+pub enum ActiveConfiguration {
+    Inactive,
+    Gatekeep { allowed: Vec<String> },
+    UniversalRedirect { target_ip: String },
+}
+
+impl ActiveConfiguration {
+    // "inactive" or the apply mode's id, for the command line and D-Bus
+    pub fn mode_id(&self) -> &'static str {
+        match self {
+            ActiveConfiguration::Inactive => "inactive",
+            ActiveConfiguration::Gatekeep { .. } => ApplyMode::Gatekeep.id(),
+            ActiveConfiguration::UniversalRedirect { .. } => ApplyMode::UniversalRedirect.id(),
+        }
+    }
+}
+
+// The backend to enforce selections with; only the hosts file for now
+pub fn current(discord_url: &str) -> Arc<dyn EnforcementBackend> {
+    Arc::new(HostsManager::new(discord_url.to_string()))
+}
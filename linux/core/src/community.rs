@@ -0,0 +1,215 @@
+use crate::region::{self, RegionInfo};
+use crate::settings::{CommunitySettings, UserSettings};
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+// Opt-in stability reports. Once turned on and pointed at a server, the app sends what
+// it saw per AWS region once an hour and takes the stable marks from everyone's reports
+// instead of the built-in guesses in region.rs.
+//
+//   POST <endpoint>/reports
+//     {"version": "2.3.0", "regions": {"eu-west-1": {"pings": 720, "lost": 3,
+//      "matches": 4, "disconnects": 1}}}
+//   GET <endpoint>/stability
+//     {"regions": {"eu-west-1": {"stable": true, "reports": 532}}}
+//
+// Reports carry no addresses, names or ids, only the app version and the counts.
+// Outcomes are counted in memory either way and dropped while sharing is off.
+
+const SUBMIT_INTERVAL: Duration = Duration::from_secs(60 * 60);
+// Matches shorter than this count as disconnects rather than played matches
+const MIN_MATCH: Duration = Duration::from_secs(3 * 60);
+// The aggregate only overrides the built-in mark with enough reports behind it
+const MIN_REPORTS: u64 = 50;
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+struct Outcome {
+    pings: u64,
+    lost: u64,
+    matches: u64,
+    disconnects: u64,
+}
+
+impl Outcome {
+    fn add(&mut self, other: &Outcome) {
+        self.pings += other.pings;
+        self.lost += other.lost;
+        self.matches += other.matches;
+        self.disconnects += other.disconnects;
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Aggregate {
+    regions: HashMap<String, RegionStability>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RegionStability {
+    stable: bool,
+    #[serde(default)]
+    reports: u64,
+}
+
+// By server name, turned into AWS codes when sent
+static OUTCOMES: Mutex<Option<HashMap<String, Outcome>>> = Mutex::new(None);
+static CURRENT_MATCH: Mutex<Option<(String, Instant)>> = Mutex::new(None);
+
+fn cache_file() -> PathBuf {
+    crate::paths::cache_dir().join("community-stability.json")
+}
+
+fn record(region: &str, update: impl FnOnce(&mut Outcome)) {
+    let mut outcomes = OUTCOMES.lock().unwrap();
+    update(outcomes.get_or_insert_with(HashMap::new).entry(region.to_string()).or_default());
+}
+
+// A finished ping round, -1 for lost probes
+pub fn record_pings(results: &HashMap<String, i64>) {
+    for (region, latency) in results {
+        record(region, |outcome| {
+            outcome.pings += 1;
+            if *latency < 0 {
+                outcome.lost += 1;
+            }
+        });
+    }
+}
+
+// Called with the server name when the match monitor sees a match start, None when
+// the server is unknown
+pub fn match_started(region: Option<&str>) {
+    *CURRENT_MATCH.lock().unwrap() = region.map(|region| (region.to_string(), Instant::now()));
+}
+
+pub fn match_ended() {
+    let Some((region, started)) = CURRENT_MATCH.lock().unwrap().take() else {
+        return;
+    };
+    let early = started.elapsed() < MIN_MATCH;
+    crate::matches::record(&region, early);
+    record(&region, |outcome| {
+        if early {
+            outcome.disconnects += 1;
+        } else {
+            outcome.matches += 1;
+        }
+    });
+}
+
+// Sends and fetches every hour while sharing is on; the first round runs right away
+pub fn start(runtime: &tokio::runtime::Runtime) {
+    runtime.spawn(async {
+        loop {
+            let settings = UserSettings::load().unwrap_or_default().community;
+            if settings.enabled && !crate::overrides::get().offline {
+                if let Err(e) = submit(&settings).await {
+                    log_warn!("community", "Failed to send the stability report: {:#}", e);
+                }
+                if let Err(e) = refresh(&settings).await {
+                    log_warn!("community", "Failed to fetch the community stability: {:#}", e);
+                }
+            } else {
+                OUTCOMES.lock().unwrap().take();
+                if !settings.enabled {
+                    forget();
+                }
+            }
+            tokio::time::sleep(SUBMIT_INTERVAL).await;
+        }
+    });
+}
+
+fn endpoint(settings: &CommunitySettings, path: &str) -> Result<String> {
+    let base = settings.endpoint.trim().trim_end_matches('/');
+    if base.is_empty() {
+        bail!("No community server is set");
+    }
+    Ok(format!("{}/{}", base, path))
+}
+
+async fn submit(settings: &CommunitySettings) -> Result<()> {
+    let Some(outcomes) = OUTCOMES.lock().unwrap().take() else {
+        return Ok(());
+    };
+    let url = endpoint(settings, "reports")?;
+    let regions = region::get_selectable_regions();
+    let mut by_code: HashMap<&str, Outcome> = HashMap::new();
+    for (name, outcome) in &outcomes {
+        if let Some(code) = regions.get(name).and_then(region::aws_region_code) {
+            by_code.entry(code).or_default().add(outcome);
+        }
+    }
+    let report = serde_json::json!({
+        "version": env!("CARGO_PKG_VERSION"),
+        "regions": by_code,
+    });
+
+    let sent = async {
+        crate::net::client(Some(Duration::from_secs(15)))?
+            .post(&url)
+            .json(&report)
+            .send()
+            .await?
+            .error_for_status()?;
+        anyhow::Ok(())
+    }
+    .await;
+    if sent.is_err() {
+        // Kept for the next round
+        let mut pending = OUTCOMES.lock().unwrap();
+        let pending = pending.get_or_insert_with(HashMap::new);
+        for (name, outcome) in outcomes {
+            pending.entry(name).or_default().add(&outcome);
+        }
+    }
+    sent
+}
+
+async fn refresh(settings: &CommunitySettings) -> Result<()> {
+    let url = endpoint(settings, "stability")?;
+    let body = crate::net::client(Some(Duration::from_secs(15)))?
+        .get(&url)
+        .send()
+        .await?
+        .error_for_status()?
+        .text()
+        .await?;
+    serde_json::from_str::<Aggregate>(&body).context("Unexpected answer from the community server")?;
+
+    let path = cache_file();
+    if let Some(dir) = path.parent() {
+        tokio::fs::create_dir_all(dir).await?;
+    }
+    tokio::fs::write(&path, body)
+        .await
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+// The last fetched marks replace the built-in ones, used from the next start on
+pub fn apply_cached(regions: &mut HashMap<String, RegionInfo>) {
+    let Some(aggregate) = std::fs::read_to_string(cache_file())
+        .ok()
+        .and_then(|body| serde_json::from_str::<Aggregate>(&body).ok())
+    else {
+        return;
+    };
+    for info in regions.values_mut() {
+        let Some(code) = region::aws_region_code(info) else {
+            continue;
+        };
+        if let Some(stability) = aggregate.regions.get(code).filter(|s| s.reports >= MIN_REPORTS) {
+            info.stable = stability.stable;
+        }
+    }
+}
+
+// Back to the built-in marks, when sharing is turned off
+pub fn forget() {
+    let _ = std::fs::remove_file(cache_file());
+    OUTCOMES.lock().unwrap().take();
+}
@@ -0,0 +1,79 @@
+use crate::region::RegionInfo;
+use anyhow::{bail, Context, Result};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::PathBuf;
+
+// Hostnames set by the user per server, replacing the built-in ones in region.rs until
+// an update fixes them. Kept next to the settings, server name to its hostnames:
+//   Asia Pacific (Hong Kong):
+//     - gamelift.ap-east-1.amazonaws.com
+//     - gamelift-ping.ap-east-1.api.aws
+// Read when the server list is built, so a change is used from the next start on.
+
+pub type CustomHosts = BTreeMap<String, Vec<String>>;
+
+fn custom_hosts_file() -> PathBuf {
+    crate::paths::config_dir().join("custom-hostnames.yaml")
+}
+
+pub fn load() -> CustomHosts {
+    let path = custom_hosts_file();
+    let Ok(content) = fs::read_to_string(&path) else {
+        return CustomHosts::new();
+    };
+    serde_yaml::from_str(&content).unwrap_or_else(|e| {
+        log_warn!("regions", "Ignoring {}: {}", path.display(), e);
+        CustomHosts::new()
+    })
+}
+
+// Without any custom hostnames the file is removed
+pub fn save(custom: &CustomHosts) -> Result<()> {
+    let path = custom_hosts_file();
+    if custom.is_empty() {
+        return match fs::remove_file(&path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        };
+    }
+    for (name, hosts) in custom {
+        if hosts.is_empty() {
+            bail!("{} needs at least one hostname", name);
+        }
+        if let Some(host) = hosts.iter().find(|host| !is_valid_hostname(host)) {
+            bail!("\"{}\" is not a valid hostname", host);
+        }
+    }
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    }
+    fs::write(&path, serde_yaml::to_string(custom)?).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+// Letters, digits and hyphens between dots, as the hosts file takes them
+pub fn is_valid_hostname(host: &str) -> bool {
+    host.len() <= 253
+        && host.split('.').count() >= 2
+        && host.split('.').all(|label| {
+            (1..=63).contains(&label.len())
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-')
+        })
+}
+
+// Servers missing from the built-in list are skipped, as are lists that were edited
+// by hand into something the hosts file can't take
+pub fn apply(regions: &mut HashMap<String, RegionInfo>, custom: &CustomHosts) {
+    for (name, hosts) in custom {
+        let Some(info) = regions.get_mut(name) else {
+            continue;
+        };
+        if hosts.is_empty() || !hosts.iter().all(|host| is_valid_hostname(host)) {
+            log_warn!("regions", "Ignoring the custom hostnames of {}", name);
+            continue;
+        }
+        info.hosts = hosts.clone();
+    }
+}
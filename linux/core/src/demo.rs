@@ -0,0 +1,129 @@
+use crate::aws_ranges::AwsIpService;
+use std::collections::hash_map::DefaultHasher;
+use std::ffi::{CString, OsString};
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::io::{self, Write};
+use std::net::Ipv4Addr;
+use std::os::unix::ffi::{OsStrExt, OsStringExt};
+use std::os::unix::fs::{DirBuilderExt, MetadataExt};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// Stand-ins for --demo, so the whole app can be tried, documented and debugged without
+// root or a game: the hosts file and the settings live in a private scratch directory, pings
+// and DNS answers are made up, and the match monitor sees invented matches on the
+// servers below. Nothing goes out to the network.
+
+// Where fake matches are played, by their AWS code
+const MATCH_REGIONS: &[&str] = &["eu-central-1", "eu-west-1", "us-east-1", "us-west-2", "ap-northeast-1", "sa-east-1"];
+
+pub fn enabled() -> bool {
+    crate::overrides::get().demo
+}
+
+// Only this user can open it, so nobody else can plant links in place of the scratch
+// files: a folder in the user's runtime directory, or a fresh one from mkdtemp when
+// there is none. The system empties both on reboot, like the demo itself. None when
+// neither works, the demo is then refused.
+pub fn dir() -> Option<&'static Path> {
+    static DIR: OnceLock<Option<PathBuf>> = OnceLock::new();
+    DIR.get_or_init(|| {
+        let dir = match std::env::var_os("XDG_RUNTIME_DIR").filter(|dir| !dir.is_empty()) {
+            Some(runtime) => private_dir(&PathBuf::from(runtime).join("make-your-choice-demo")),
+            None => fresh_temp_dir(),
+        };
+        dir.map_err(|e| log_warn!("demo", "No private scratch directory: {}", e)).ok()
+    })
+    .as_deref()
+}
+
+// Creates the folder with mode 0700, or checks that an existing one is a real folder of
+// this user that nobody else can open
+fn private_dir(path: &Path) -> io::Result<PathBuf> {
+    match fs::DirBuilder::new().mode(0o700).create(path) {
+        Ok(()) => return Ok(path.to_path_buf()),
+        Err(e) if e.kind() != io::ErrorKind::AlreadyExists => return Err(e),
+        Err(_) => {}
+    }
+    let metadata = fs::symlink_metadata(path)?;
+    if !metadata.is_dir() || metadata.uid() != unsafe { libc::getuid() } || metadata.mode() & 0o077 != 0 {
+        return Err(io::Error::other(format!("{} is not a private directory", path.display())));
+    }
+    Ok(path.to_path_buf())
+}
+
+fn fresh_temp_dir() -> io::Result<PathBuf> {
+    let template = std::env::temp_dir().join("make-your-choice-demo-XXXXXX");
+    let mut bytes = CString::new(template.as_os_str().as_bytes())?.into_bytes_with_nul();
+    if unsafe { libc::mkdtemp(bytes.as_mut_ptr().cast()) }.is_null() {
+        return Err(io::Error::last_os_error());
+    }
+    bytes.pop();
+    Ok(PathBuf::from(OsString::from_vec(bytes)))
+}
+
+// Starts the scratch hosts file from the default template on first use. create_new
+// neither follows nor replaces whatever is already there.
+pub fn prepare() {
+    let hosts = crate::hosts::hosts_path();
+    if let Some(parent) = hosts.parent() {
+        let _ = fs::create_dir_all(parent);
+    }
+    let created = fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&hosts)
+        .and_then(|mut file| file.write_all(crate::hosts::DEFAULT_HOSTS.as_bytes()));
+    match created {
+        Ok(()) => log_info!("demo", "Using {} as the hosts file", hosts.display()),
+        Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {}
+        Err(e) => log_warn!("demo", "Failed to create {}: {}", hosts.display(), e),
+    }
+}
+
+fn hash(value: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn jitter() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|time| time.subsec_nanos() as u64)
+        .unwrap_or(0)
+}
+
+// The same server always lands around the same latency, give or take 15 ms; now and
+// then a probe is lost
+pub fn latency(hostname: &str) -> i64 {
+    let noise = jitter();
+    if noise.is_multiple_of(40) {
+        return -1;
+    }
+    let base = 15 + hash(hostname) % 260;
+    (base + noise % 31) as i64 - 15
+}
+
+// An address from the documentation range, stable per hostname
+pub fn resolve(hostname: &str) -> Ipv4Addr {
+    Ipv4Addr::new(198, 51, 100, 1 + (hash(hostname) % 254) as u8)
+}
+
+// Server address of the next fake match
+pub fn match_server() -> String {
+    let index = jitter() as usize % MATCH_REGIONS.len();
+    Ipv4Addr::new(203, 0, 113, 1 + index as u8).to_string()
+}
+
+// Region of a match_server address
+pub fn region_of(ip: &str) -> Option<String> {
+    let ip: Ipv4Addr = ip.parse().ok()?;
+    let [203, 0, 113, last] = ip.octets() else {
+        return None;
+    };
+    let code = MATCH_REGIONS.get((last as usize).checked_sub(1)?)?;
+    Some(AwsIpService::get_pretty_region_name(code))
+}
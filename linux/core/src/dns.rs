@@ -0,0 +1,410 @@
+use crate::errors::{self, ErrorKind};
+use crate::settings::{DnsSettings, DnsUpstream, UserSettings};
+use anyhow::{bail, Context, Result};
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::hash::{BuildHasher, Hasher};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tokio::net::UdpSocket;
+
+// The one resolver of the app: the hosts file sections, the pings and the match
+// monitor all look names up here. Answers come from the DNS servers directly, past the
+// hosts file. While Universal Redirect is active every GameLift hostname points at the
+// old target in /etc/hosts and blocked ones point at 0.0.0.0, so the system resolver
+// would only hand our own entries back.
+//
+// Upstreams, set in the settings or for one run with --dns-server:
+//   system  the servers in resolv.conf, then the system resolver for names the hosts
+//           file doesn't list
+//   udp     one DNS server, "IP" or "IP:PORT"
+//   https   DNS over HTTPS (RFC 8484), through the proxy like every other request
+
+// systemd-resolved answers from /etc/hosts on its stub, this file lists its upstreams
+const RESOLVED_UPSTREAMS: &str = "/run/systemd/resolve/resolv.conf";
+const RESOLV_CONF: &str = "/etc/resolv.conf";
+const TIMEOUT: Duration = Duration::from_secs(3);
+// Answers are kept for their TTL, but no longer than this
+const MAX_TTL: Duration = Duration::from_secs(10 * 60);
+// The system resolver doesn't tell the TTL
+const SYSTEM_TTL: Duration = Duration::from_secs(60);
+// Failures are remembered briefly, so a ping round doesn't wait on a dead server for
+// every region
+const FAILURE_TTL: Duration = Duration::from_secs(30);
+
+const TYPE_A: u16 = 1;
+const TYPE_PTR: u16 = 12;
+const CLASS_IN: u16 = 1;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Upstream {
+    System,
+    Udp(SocketAddr),
+    Https(String),
+}
+
+#[derive(Debug, Clone)]
+enum Record {
+    Address(Ipv4Addr),
+    Name(String),
+}
+
+static UPSTREAM: Mutex<Option<Upstream>> = Mutex::new(None);
+// By record type and name, with the error of a recent failure
+type Cache = HashMap<(u16, String), (Result<Record, String>, Instant)>;
+static CACHE: Mutex<Option<Cache>> = Mutex::new(None);
+
+// Takes the upstream from changed settings. Until then the first lookup reads them
+// itself, so the command line and the service need no setup.
+pub fn configure(settings: &DnsSettings) {
+    let upstream = from_settings(settings);
+    let mut current = UPSTREAM.lock().unwrap();
+    if current.as_ref() != Some(&upstream) {
+        CACHE.lock().unwrap().take();
+    }
+    *current = Some(upstream);
+}
+
+fn from_settings(settings: &DnsSettings) -> Upstream {
+    match settings.upstream {
+        DnsUpstream::System => Upstream::System,
+        DnsUpstream::Udp => match parse_server(&settings.server) {
+            Ok(server) => Upstream::Udp(server),
+            Err(e) => {
+                log_warn!("dns", "{:#}, using the system DNS servers", e);
+                Upstream::System
+            }
+        },
+        DnsUpstream::Https => match check_https_url(&settings.https_url) {
+            Ok(()) => Upstream::Https(settings.https_url.trim().to_string()),
+            Err(e) => {
+                log_warn!("dns", "{:#}, using the system DNS servers", e);
+                Upstream::System
+            }
+        },
+    }
+}
+
+fn upstream() -> Upstream {
+    if let Some(server) = crate::overrides::get().dns_server {
+        return Upstream::Udp(server);
+    }
+    UPSTREAM
+        .lock()
+        .unwrap()
+        .get_or_insert_with(|| from_settings(&UserSettings::load().unwrap_or_default().dns))
+        .clone()
+}
+
+// "IP" or "IP:PORT", port 53 when left out
+pub fn parse_server(server: &str) -> Result<SocketAddr> {
+    let server = server.trim();
+    server
+        .parse::<SocketAddr>()
+        .or_else(|_| server.parse::<IpAddr>().map(|ip| SocketAddr::new(ip, 53)))
+        .with_context(|| format!("Invalid DNS server {:?}", server))
+}
+
+// Whether `url` can be used for DNS over HTTPS, for validating the settings field
+pub fn check_https_url(url: &str) -> Result<()> {
+    let parsed = reqwest::Url::parse(url.trim()).with_context(|| format!("Invalid address {:?}", url))?;
+    if parsed.scheme() != "https" {
+        bail!("DNS over HTTPS needs an https:// address");
+    }
+    Ok(())
+}
+
+// Current address of a hostname, from the cache while its TTL lasts
+pub async fn resolve_ipv4(hostname: &str) -> Result<Ipv4Addr> {
+    lookup_ipv4(hostname, true).await
+}
+
+// Asks again, for addresses that are written into the hosts file
+pub async fn resolve_ipv4_fresh(hostname: &str) -> Result<Ipv4Addr> {
+    lookup_ipv4(hostname, false).await
+}
+
+// Addresses of several hostnames, looked up together and in the same order; names that
+// fail to resolve are logged and left out
+pub async fn resolve_all(hostnames: &[String]) -> Vec<(String, Ipv4Addr)> {
+    let tasks: Vec<_> = hostnames
+        .iter()
+        .cloned()
+        .map(|hostname| tokio::spawn(async move { (resolve_ipv4(&hostname).await, hostname) }))
+        .collect();
+    let mut addresses = Vec::new();
+    for task in tasks {
+        match task.await {
+            Ok((Ok(ip), hostname)) => addresses.push((hostname, ip)),
+            Ok((Err(e), _)) => log_warn!("dns", "{:#}", e),
+            Err(e) => log_warn!("dns", "Lookup task failed: {}", e),
+        }
+    }
+    addresses
+}
+
+async fn lookup_ipv4(hostname: &str, cached: bool) -> Result<Ipv4Addr> {
+    if crate::demo::enabled() {
+        return Ok(crate::demo::resolve(hostname));
+    }
+    let name = hostname.trim_end_matches('.').to_ascii_lowercase();
+    match lookup(TYPE_A, &name, cached).await {
+        Ok(Record::Address(ip)) => Ok(ip),
+        Ok(Record::Name(_)) => unreachable!("A lookups only return addresses"),
+        Err(e) => Err(errors::error(
+            ErrorKind::ResolveFailed,
+            format!("Failed to resolve hostname {}: {:#}", hostname, e),
+        )),
+    }
+}
+
+// Name behind an address, e.g. ec2-3-120-0-1.eu-central-1.compute.amazonaws.com
+pub async fn reverse(ip: Ipv4Addr) -> Result<String> {
+    let [a, b, c, d] = ip.octets();
+    let name = format!("{}.{}.{}.{}.in-addr.arpa", d, c, b, a);
+    match lookup(TYPE_PTR, &name, true).await? {
+        Record::Name(name) => Ok(name),
+        Record::Address(_) => unreachable!("PTR lookups only return names"),
+    }
+}
+
+async fn lookup(record_type: u16, name: &str, cached: bool) -> Result<Record> {
+    let key = (record_type, name.to_string());
+    if cached {
+        let cache = CACHE.lock().unwrap();
+        if let Some((record, expires)) = cache.as_ref().and_then(|cache| cache.get(&key)) {
+            if *expires > Instant::now() {
+                return record.clone().map_err(anyhow::Error::msg);
+            }
+        }
+    }
+
+    let result = ask(record_type, name).await;
+    let (record, ttl) = match &result {
+        Ok((record, ttl)) => (Ok(record.clone()), (*ttl).min(MAX_TTL)),
+        Err(e) => (Err(format!("{:#}", e)), FAILURE_TTL),
+    };
+    if !ttl.is_zero() {
+        CACHE
+            .lock()
+            .unwrap()
+            .get_or_insert_with(HashMap::new)
+            .insert(key, (record, Instant::now() + ttl));
+    }
+    result.map(|(record, _)| record)
+}
+
+async fn ask(record_type: u16, name: &str) -> Result<(Record, Duration)> {
+    match upstream() {
+        Upstream::Udp(server) => query_udp(server, record_type, name)
+            .await
+            .with_context(|| format!("DNS server {}", server)),
+        Upstream::Https(url) => query_https(&url, record_type, name)
+            .await
+            .with_context(|| format!("DNS server {}", url)),
+        Upstream::System => {
+            let mut last_error = None;
+            for server in nameservers() {
+                match query_udp(server, record_type, name).await {
+                    Ok(answer) => return Ok(answer),
+                    Err(e) => last_error = Some(e.context(format!("DNS server {}", server))),
+                }
+            }
+            let error = last_error.unwrap_or_else(|| anyhow::anyhow!("No DNS servers configured"));
+            if record_type != TYPE_A {
+                return Err(error);
+            }
+            log_warn!("dns", "{:#}, asking the system resolver", error);
+            Ok((Record::Address(system_lookup(name).await?), SYSTEM_TTL))
+        }
+    }
+}
+
+fn nameservers() -> Vec<SocketAddr> {
+    let content = std::fs::read_to_string(RESOLVED_UPSTREAMS)
+        .or_else(|_| std::fs::read_to_string(RESOLV_CONF))
+        .unwrap_or_default();
+    content
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("nameserver"))
+        .filter_map(|server| server.trim().parse::<Ipv4Addr>().ok())
+        // Local resolvers like the resolved stub or dnsmasq answer from the hosts file,
+        // without any left the system resolver is asked about names it doesn't list
+        .filter(|server| !server.is_loopback())
+        .map(|server| SocketAddr::from((server, 53)))
+        .collect()
+}
+
+// The system resolver reads the hosts file first, so it is only asked about names the
+// hosts file doesn't list
+async fn system_lookup(name: &str) -> Result<Ipv4Addr> {
+    let hosts = tokio::fs::read_to_string(crate::hosts::hosts_path()).await.unwrap_or_default();
+    let listed = hosts
+        .lines()
+        .map(|line| line.split('#').next().unwrap_or_default())
+        .flat_map(|line| line.split_whitespace().skip(1))
+        .any(|host| host.eq_ignore_ascii_case(name));
+    if listed {
+        bail!("The system resolver would only answer with the hosts file entry");
+    }
+
+    system_ipv4(name).await
+}
+
+// What programs on this machine get for a hostname, hosts file entries included. Shows
+// whether blocking is in effect, unlike the lookups above.
+pub async fn system_ipv4(hostname: &str) -> Result<Ipv4Addr> {
+    // The system doesn't read a file given with --hosts-file or the demo's scratch file,
+    // their entries are looked up here
+    let path = crate::hosts::hosts_path();
+    if path != std::path::Path::new(crate::hosts::DEFAULT_HOSTS_PATH) {
+        let hosts = tokio::fs::read_to_string(&path).await.unwrap_or_default();
+        let entry = hosts.lines().find_map(|line| {
+            let mut fields = line.split('#').next().unwrap_or_default().split_whitespace();
+            let ip = fields.next()?.parse::<Ipv4Addr>().ok()?;
+            fields.any(|host| host.eq_ignore_ascii_case(hostname)).then_some(ip)
+        });
+        if let Some(ip) = entry {
+            return Ok(ip);
+        }
+    }
+    if crate::demo::enabled() {
+        return Ok(crate::demo::resolve(hostname));
+    }
+    tokio::net::lookup_host(format!("{}:443", hostname))
+        .await?
+        .find_map(|addr| match addr.ip() {
+            IpAddr::V4(ip) => Some(ip),
+            IpAddr::V6(_) => None,
+        })
+        .context("No addresses found")
+}
+
+fn question(id: u16, record_type: u16, name: &str) -> Result<Vec<u8>> {
+    // Header: id, recursion desired, one question
+    let mut packet = Vec::with_capacity(64);
+    packet.extend_from_slice(&id.to_be_bytes());
+    packet.extend_from_slice(&[0x01, 0x00, 0x00, 0x01, 0, 0, 0, 0, 0, 0]);
+    for label in name.split('.') {
+        if label.is_empty() || label.len() > 63 {
+            bail!("Invalid hostname {}", name);
+        }
+        packet.push(label.len() as u8);
+        packet.extend_from_slice(label.as_bytes());
+    }
+    packet.push(0);
+    packet.extend_from_slice(&record_type.to_be_bytes());
+    packet.extend_from_slice(&CLASS_IN.to_be_bytes());
+    Ok(packet)
+}
+
+async fn query_udp(server: SocketAddr, record_type: u16, name: &str) -> Result<(Record, Duration)> {
+    // Random, so a forged answer can't guess it. RandomState is keyed from the OS.
+    let id = RandomState::new().build_hasher().finish() as u16;
+    let packet = question(id, record_type, name)?;
+
+    let local: SocketAddr = if server.is_ipv4() { "0.0.0.0:0" } else { "[::]:0" }.parse()?;
+    let socket = UdpSocket::bind(local).await?;
+    socket.connect(server).await?;
+    socket.send(&packet).await?;
+    let mut response = [0u8; 1500];
+    let len = tokio::time::timeout(TIMEOUT, socket.recv(&mut response))
+        .await
+        .context("No answer")??;
+    parse_answer(&response[..len], id, record_type).with_context(|| format!("Failed to resolve {}", name))
+}
+
+// Id 0 as RFC 8484 asks, so HTTP caches can share answers
+async fn query_https(url: &str, record_type: u16, name: &str) -> Result<(Record, Duration)> {
+    let packet = question(0, record_type, name)?;
+    let response = crate::net::client(Some(TIMEOUT))?
+        .post(url)
+        .header(reqwest::header::CONTENT_TYPE, "application/dns-message")
+        .header(reqwest::header::ACCEPT, "application/dns-message")
+        .body(packet)
+        .send()
+        .await?
+        .error_for_status()?
+        .bytes()
+        .await?;
+    parse_answer(&response, 0, record_type).with_context(|| format!("Failed to resolve {}", name))
+}
+
+// First record of the asked type with its TTL; CNAMEs before it are skipped
+fn parse_answer(response: &[u8], id: u16, record_type: u16) -> Result<(Record, Duration)> {
+    let read_u16 = |pos: usize| -> Result<u16> {
+        response
+            .get(pos..pos + 2)
+            .map(|bytes| u16::from_be_bytes([bytes[0], bytes[1]]))
+            .context("Truncated answer")
+    };
+
+    if read_u16(0)? != id {
+        bail!("Answer to another query");
+    }
+    let rcode = read_u16(2)? & 0x000f;
+    if rcode != 0 {
+        bail!("The server answered with error code {}", rcode);
+    }
+    let questions = read_u16(4)?;
+    let answers = read_u16(6)?;
+
+    let mut pos = 12;
+    for _ in 0..questions {
+        pos = skip_name(response, pos)? + 4;
+    }
+    for _ in 0..answers {
+        pos = skip_name(response, pos)?;
+        let answer_type = read_u16(pos)?;
+        let ttl = Duration::from_secs(((read_u16(pos + 4)? as u64) << 16) | read_u16(pos + 6)? as u64);
+        let length = read_u16(pos + 8)? as usize;
+        let data = response.get(pos + 10..pos + 10 + length).context("Truncated answer")?;
+        if answer_type == record_type {
+            match record_type {
+                TYPE_A if length == 4 => {
+                    return Ok((Record::Address(Ipv4Addr::new(data[0], data[1], data[2], data[3])), ttl));
+                }
+                TYPE_PTR => return Ok((Record::Name(read_name(response, pos + 10)?), ttl)),
+                _ => {}
+            }
+        }
+        pos += 10 + length;
+    }
+    bail!("No {} in the answer", if record_type == TYPE_A { "address" } else { "name" })
+}
+
+// Position after a name, which either ends with a zero length or a compression pointer
+fn skip_name(response: &[u8], mut pos: usize) -> Result<usize> {
+    loop {
+        let len = *response.get(pos).context("Truncated answer")?;
+        if len & 0xc0 == 0xc0 {
+            return Ok(pos + 2);
+        }
+        pos += 1 + len as usize;
+        if len == 0 {
+            return Ok(pos);
+        }
+    }
+}
+
+// A name spelled out, following compression pointers
+fn read_name(response: &[u8], mut pos: usize) -> Result<String> {
+    let mut labels = Vec::new();
+    // Real names have far fewer labels and pointers, more than this is a loop
+    for _ in 0..64 {
+        let len = *response.get(pos).context("Truncated answer")?;
+        if len & 0xc0 == 0xc0 {
+            let low = *response.get(pos + 1).context("Truncated answer")?;
+            pos = (((len & 0x3f) as usize) << 8) | low as usize;
+            continue;
+        }
+        if len == 0 {
+            return Ok(labels.join("."));
+        }
+        let label = response.get(pos + 1..pos + 1 + len as usize).context("Truncated answer")?;
+        labels.push(String::from_utf8_lossy(label).into_owned());
+        pos += 1 + len as usize;
+    }
+    bail!("Malformed name in the answer")
+}
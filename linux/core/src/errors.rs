@@ -0,0 +1,196 @@
+use std::fmt;
+
+use crate::N_;
+
+// What went wrong, for scripts that need more than the message. The ids and exit codes
+// are stable: the CLI exits with the code and adds the id to --json failures, D-Bus
+// callers get dev.lawliet.makeyourchoice.Error.<Name>, and the HTTP API and plugins get
+// "kind": "<id>" next to "error". Every kind has a short summary and most also a hint on
+// what to do next, both translated by the app and shown around the message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    // Anything without a more specific kind
+    Failed,
+    // Wrong command line or malformed request
+    Usage,
+    // The hosts file could not be read or written, usually missing rights
+    PermissionDenied,
+    // Entries outside the Make Your Choice section point the game's hostnames elsewhere
+    ConflictDetected,
+    // A hostname could not be turned into an address, e.g. the Universal Redirect target
+    ResolveFailed,
+    // A connection failed or timed out
+    NetworkError,
+    // Unknown server, profile or mode, or a selection the method can't use
+    InvalidSelection,
+    // The system resolver doesn't read the hosts file, entries would have no effect
+    ResolverBypass,
+    // The AWS IP ranges could not be downloaded, game servers can't be told apart
+    StaleRanges,
+    // There is no backup of a modified game file to restore
+    BackupMissing,
+}
+
+pub const ALL: &[ErrorKind] = &[
+    ErrorKind::Failed,
+    ErrorKind::Usage,
+    ErrorKind::PermissionDenied,
+    ErrorKind::ConflictDetected,
+    ErrorKind::ResolveFailed,
+    ErrorKind::NetworkError,
+    ErrorKind::InvalidSelection,
+    ErrorKind::ResolverBypass,
+    ErrorKind::StaleRanges,
+    ErrorKind::BackupMissing,
+];
+
+const DBUS_ERROR_PREFIX: &str = "dev.lawliet.makeyourchoice.Error";
+
+impl ErrorKind {
+    pub fn id(&self) -> &'static str {
+        match self {
+            ErrorKind::Failed => "failed",
+            ErrorKind::Usage => "usage",
+            ErrorKind::PermissionDenied => "permission-denied",
+            ErrorKind::ConflictDetected => "conflict-detected",
+            ErrorKind::ResolveFailed => "resolve-failed",
+            ErrorKind::NetworkError => "network-error",
+            ErrorKind::InvalidSelection => "invalid-selection",
+            ErrorKind::ResolverBypass => "resolver-bypass",
+            ErrorKind::StaleRanges => "stale-ranges",
+            ErrorKind::BackupMissing => "backup-missing",
+        }
+    }
+
+    // 0 is success and wrap passes on the game's own code, so these only come from the
+    // other commands
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            ErrorKind::Failed => 1,
+            ErrorKind::Usage => 2,
+            ErrorKind::PermissionDenied => 3,
+            ErrorKind::ConflictDetected => 4,
+            ErrorKind::ResolveFailed => 5,
+            ErrorKind::NetworkError => 6,
+            ErrorKind::InvalidSelection => 7,
+            ErrorKind::ResolverBypass => 8,
+            ErrorKind::StaleRanges => 9,
+            ErrorKind::BackupMissing => 10,
+        }
+    }
+
+    // One line on what went wrong, in English like the hints; the app translates it
+    // where it is shown, the message itself stays as the details
+    pub fn summary(&self) -> &'static str {
+        match self {
+            ErrorKind::Failed => N_("Something went wrong"),
+            ErrorKind::Usage => N_("The request wasn't understood"),
+            ErrorKind::PermissionDenied => N_("The hosts file can't be changed"),
+            ErrorKind::ConflictDetected => N_("Other entries redirect the game's servers"),
+            ErrorKind::ResolveFailed => N_("A server address couldn't be looked up"),
+            ErrorKind::NetworkError => N_("The connection failed"),
+            ErrorKind::InvalidSelection => N_("This selection can't be applied"),
+            ErrorKind::ResolverBypass => N_("The system ignores the hosts file"),
+            ErrorKind::StaleRanges => N_("The AWS address list is missing"),
+            ErrorKind::BackupMissing => N_("There is no backup to restore"),
+        }
+    }
+
+    // What the user can do about it, in English; the app translates it where it is
+    // shown. None where the message already says it.
+    pub fn remediation(&self) -> Option<&'static str> {
+        match self {
+            ErrorKind::Failed | ErrorKind::Usage | ErrorKind::InvalidSelection => None,
+            ErrorKind::PermissionDenied if cfg!(target_os = "macos") => {
+                Some(N_("Changing the hosts file needs root rights. Run the command again with sudo."))
+            }
+            ErrorKind::PermissionDenied => Some(
+                N_("Make Your Choice is missing the file capabilities it needs to change the hosts file. Open the app window once, which sets them again through pkexec, or reinstall it, and check that the file isn't locked with \"chattr +i\"."),
+            ),
+            ErrorKind::ConflictDetected => {
+                Some(N_("Remove the other entries for the game's servers from the hosts file, then apply again."))
+            }
+            ErrorKind::ResolveFailed => {
+                Some(N_("Check your internet connection and DNS server, or use Gatekeep, which doesn't need to look up addresses."))
+            }
+            ErrorKind::NetworkError => {
+                Some(N_("Check your internet connection. A firewall or proxy may be blocking Make Your Choice."))
+            }
+            ErrorKind::ResolverBypass => Some(
+                N_("Add \"files\" to the hosts line of /etc/nsswitch.conf, e.g. \"hosts: files dns\", so programs read the hosts file."),
+            ),
+            ErrorKind::StaleRanges => {
+                Some(N_("Connect to the internet once so the AWS address list can be downloaded, then try again."))
+            }
+            ErrorKind::BackupMissing => {
+                Some(N_("The original file may already be in place. If not, verify the game files in Steam to get it back."))
+            }
+        }
+    }
+
+    // e.g. dev.lawliet.makeyourchoice.Error.PermissionDenied
+    pub fn dbus_name(&self) -> String {
+        let name: String = self
+            .id()
+            .split('-')
+            .map(|word| {
+                let mut chars = word.chars();
+                chars
+                    .next()
+                    .map(|first| first.to_ascii_uppercase().to_string() + chars.as_str())
+                    .unwrap_or_default()
+            })
+            .collect();
+        format!("{}.{}", DBUS_ERROR_PREFIX, name)
+    }
+}
+
+// Carries the kind through an anyhow chain. Shows only the message, so `{:#}` output
+// reads the same as before.
+#[derive(Debug)]
+struct Categorized {
+    kind: ErrorKind,
+    message: String,
+}
+
+impl fmt::Display for Categorized {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for Categorized {}
+
+pub fn error(kind: ErrorKind, message: impl Into<String>) -> anyhow::Error {
+    anyhow::Error::new(Categorized {
+        kind,
+        message: message.into(),
+    })
+}
+
+// The kind set with error() anywhere in the chain wins; otherwise IO and HTTP errors
+// are recognized by their own kinds
+pub fn classify(error: &anyhow::Error) -> ErrorKind {
+    if let Some(categorized) = error.chain().find_map(|cause| cause.downcast_ref::<Categorized>()) {
+        return categorized.kind;
+    }
+    for cause in error.chain() {
+        if let Some(io) = cause.downcast_ref::<std::io::Error>() {
+            use std::io::ErrorKind as Io;
+            match io.kind() {
+                Io::PermissionDenied | Io::ReadOnlyFilesystem => return ErrorKind::PermissionDenied,
+                Io::ConnectionRefused
+                | Io::ConnectionReset
+                | Io::ConnectionAborted
+                | Io::NetworkUnreachable
+                | Io::HostUnreachable
+                | Io::TimedOut => return ErrorKind::NetworkError,
+                _ => {}
+            }
+        }
+        if cause.downcast_ref::<reqwest::Error>().is_some() {
+            return ErrorKind::NetworkError;
+        }
+    }
+    ErrorKind::Failed
+}
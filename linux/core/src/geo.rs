@@ -0,0 +1,114 @@
+use std::fs;
+
+// Rough distances to the data centers, for comparing servers. There is no location
+// lookup: the place comes from the time zone, whose main city tzdata lists with its
+// coordinates, so the numbers are as good as "Europe/Berlin" is for someone in Munich.
+
+const ZONE_TABLES: &[&str] = &["/usr/share/zoneinfo/zone1970.tab", "/usr/share/zoneinfo/zone.tab"];
+const EARTH_RADIUS_KM: f64 = 6371.0;
+
+// Latitude and longitude of the city each AWS region sits in or next to
+const DATA_CENTERS: &[(&str, f64, f64)] = &[
+    ("af-south-1", -33.92, 18.42),
+    ("ap-northeast-1", 35.68, 139.69),
+    ("ap-northeast-2", 37.57, 126.98),
+    ("ap-northeast-3", 34.69, 135.50),
+    ("ap-south-1", 19.08, 72.88),
+    ("ap-southeast-1", 1.35, 103.82),
+    ("ap-southeast-2", -33.87, 151.21),
+    ("ap-southeast-5", 3.139, 101.687),
+    ("ap-southeast-7", 13.76, 100.50),
+    ("ca-central-1", 45.50, -73.57),
+    ("cn-north-1", 39.90, 116.41),
+    ("cn-northwest-1", 38.49, 106.23),
+    ("eu-central-1", 50.11, 8.68),
+    ("eu-north-1", 59.33, 18.07),
+    ("eu-south-1", 45.46, 9.19),
+    ("eu-west-1", 53.35, -6.26),
+    ("eu-west-2", 51.51, -0.13),
+    ("eu-west-3", 48.86, 2.35),
+    ("me-south-1", 26.07, 50.56),
+    ("sa-east-1", -23.55, -46.63),
+    ("us-east-1", 39.04, -77.49),
+    ("us-east-2", 39.96, -83.00),
+    ("us-west-1", 37.77, -122.42),
+    ("us-west-2", 45.84, -119.70),
+];
+
+pub struct Home {
+    // "Europe/Berlin"
+    pub zone: String,
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+pub fn data_center(code: &str) -> Option<(f64, f64)> {
+    DATA_CENTERS
+        .iter()
+        .find(|(known, _, _)| *known == code)
+        .map(|(_, latitude, longitude)| (*latitude, *longitude))
+}
+
+// TZ first, then the zone /etc/localtime links to, then Debian's /etc/timezone
+fn time_zone() -> Option<String> {
+    if let Some(zone) = std::env::var("TZ").ok().filter(|zone| !zone.is_empty()) {
+        return Some(zone.trim_start_matches(':').to_string());
+    }
+    if let Ok(target) = fs::read_link("/etc/localtime") {
+        let target = target.to_string_lossy().to_string();
+        if let Some((_, zone)) = target.split_once("zoneinfo/") {
+            return Some(zone.to_string());
+        }
+    }
+    fs::read_to_string("/etc/timezone")
+        .ok()
+        .map(|zone| zone.trim().to_string())
+        .filter(|zone| !zone.is_empty())
+}
+
+// ISO 6709 as tzdata writes it: ±DDMM±DDDMM or ±DDMMSS±DDDMMSS
+fn parse_coordinates(value: &str) -> Option<(f64, f64)> {
+    let split = value.get(1..)?.find(['+', '-'])? + 1;
+    let (latitude, longitude) = value.split_at(split);
+    Some((parse_angle(latitude, 2)?, parse_angle(longitude, 3)?))
+}
+
+fn parse_angle(value: &str, degree_digits: usize) -> Option<f64> {
+    let (sign, digits) = value.split_at(1);
+    if !digits.bytes().all(|b| b.is_ascii_digit()) || digits.len() < degree_digits + 2 {
+        return None;
+    }
+    let part = |range: std::ops::Range<usize>| digits.get(range).and_then(|part| part.parse::<f64>().ok());
+    let degrees = part(0..degree_digits)?;
+    let minutes = part(degree_digits..degree_digits + 2)?;
+    let seconds = part(degree_digits + 2..degree_digits + 4).unwrap_or(0.0);
+    let angle = degrees + minutes / 60.0 + seconds / 3600.0;
+    Some(if sign == "-" { -angle } else { angle })
+}
+
+// None in UTC and zones tzdata has no city for
+pub fn home() -> Option<Home> {
+    let zone = time_zone()?;
+    ZONE_TABLES
+        .iter()
+        .filter_map(|path| fs::read_to_string(path).ok())
+        .find_map(|table| {
+            table.lines().filter(|line| !line.starts_with('#')).find_map(|line| {
+                let fields: Vec<&str> = line.split('\t').collect();
+                let (latitude, longitude) = fields.get(1).and_then(|value| parse_coordinates(value))?;
+                (fields.get(2) == Some(&zone.as_str())).then(|| Home {
+                    zone: zone.clone(),
+                    latitude,
+                    longitude,
+                })
+            })
+        })
+}
+
+// Great-circle distance
+pub fn distance_km(from: (f64, f64), to: (f64, f64)) -> f64 {
+    let (lat1, lon1) = (from.0.to_radians(), from.1.to_radians());
+    let (lat2, lon2) = (to.0.to_radians(), to.1.to_radians());
+    let a = ((lat2 - lat1) / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * ((lon2 - lon1) / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS_KM * a.sqrt().asin()
+}
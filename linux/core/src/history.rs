@@ -0,0 +1,127 @@
+use crate::settings::AppliedSelection;
+use crate::N_;
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+
+// Every apply and revert, whoever made it, one JSON object per line and newest last:
+//   {"time": "...", "action": "apply", "applied": {...}, "backend": "hosts", "trigger": "tray"}
+// Kept for tracing back what changed the hosts file and for going back to an earlier
+// selection. Changes made outside the app are not in it.
+
+// Older entries are dropped once the file holds twice as many
+const MAX_ENTRIES: usize = 500;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Action {
+    Apply,
+    Revert,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Trigger {
+    Window,
+    Tray,
+    CommandLine,
+    // A myc:// link or a launcher action
+    Link,
+    // D-Bus, the HTTP API or a plugin
+    Remote,
+    // Applying the active profile when the game started
+    GameLaunch,
+}
+
+impl Trigger {
+    pub fn label(self) -> &'static str {
+        match self {
+            Trigger::Window => N_("Window"),
+            Trigger::Tray => N_("Tray"),
+            Trigger::CommandLine => N_("Command line"),
+            Trigger::Link => N_("Link"),
+            Trigger::Remote => N_("D-Bus, HTTP API or plugin"),
+            Trigger::GameLaunch => N_("Game start"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Entry {
+    pub time: DateTime<Local>,
+    pub action: Action,
+    // What was applied, None for reverts
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub applied: Option<AppliedSelection>,
+    // EnforcementBackend::id of the backend that made the change
+    pub backend: String,
+    pub trigger: Trigger,
+}
+
+fn history_file() -> PathBuf {
+    crate::paths::state_dir().join("apply-history.jsonl")
+}
+
+// Oldest first, lines that can't be read are skipped
+pub fn load() -> Vec<Entry> {
+    fs::read_to_string(history_file())
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect()
+}
+
+pub fn record_apply(applied: &AppliedSelection, backend: &str, trigger: Trigger) {
+    record(Entry {
+        time: Local::now(),
+        action: Action::Apply,
+        applied: Some(applied.clone()),
+        backend: backend.to_string(),
+        trigger,
+    });
+}
+
+pub fn record_revert(backend: &str, trigger: Trigger) {
+    record(Entry {
+        time: Local::now(),
+        action: Action::Revert,
+        applied: None,
+        backend: backend.to_string(),
+        trigger,
+    });
+}
+
+// Failing to keep the history never stops a change, it is only logged
+fn record(entry: Entry) {
+    let path = history_file();
+    let written = (|| -> anyhow::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let mut file = OpenOptions::new().create(true).append(true).open(&path)?;
+        writeln!(file, "{}", serde_json::to_string(&entry)?)?;
+        drop(file);
+
+        let entries = load();
+        if entries.len() > 2 * MAX_ENTRIES {
+            let kept: Vec<String> = entries[entries.len() - MAX_ENTRIES..]
+                .iter()
+                .filter_map(|entry| serde_json::to_string(entry).ok())
+                .collect();
+            fs::write(&path, kept.join("\n") + "\n")?;
+        }
+        Ok(())
+    })();
+    if let Err(e) = written {
+        log_warn!("history", "Failed to write {}: {:#}", path.display(), e);
+    }
+}
+
+pub fn clear() -> anyhow::Result<()> {
+    match fs::remove_file(history_file()) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+        _ => Ok(()),
+    }
+}
@@ -0,0 +1,681 @@
+use anyhow::{Context, Result, bail};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tokio::process::Command;
+use crate::backend::{ActiveConfiguration, BoxFuture, EnforcementBackend, Progress};
+use crate::errors::{self, ErrorKind};
+use crate::region::{ApplyMode, BlockMode, RegionInfo, get_group_name};
+use crate::settings::AppliedSelection;
+
+const SECTION_MARKER: &str = "# --+ Make Your Choice +--";
+pub const DEFAULT_HOSTS_PATH: &str = "/etc/hosts";
+const NSSWITCH_PATH: &str = "/etc/nsswitch.conf";
+// What Restore Default writes
+#[cfg(not(target_os = "macos"))]
+pub const DEFAULT_HOSTS: &str = "# Static table lookup for hostnames.
+# See hosts(5) for details.
+127.0.0.1        localhost
+::1              localhost
+";
+#[cfg(target_os = "macos")]
+pub const DEFAULT_HOSTS: &str = "##
+# Host Database
+#
+# localhost is used to configure the loopback interface
+# when the system is booting.  Do not change this entry.
+##
+127.0.0.1	localhost
+255.255.255.255	broadcasthost
+::1             localhost
+";
+// Whichever resolver cache is running, so the change applies without a reboot
+#[cfg(not(target_os = "macos"))]
+const FLUSH_DNS_CACHES: &str = "systemd-resolve --flush-caches 2>/dev/null || resolvectl flush-caches 2>/dev/null || nscd -i hosts 2>/dev/null || true";
+#[cfg(target_os = "macos")]
+const FLUSH_DNS_CACHES: &str = "dscacheutil -flushcache; killall -HUP mDNSResponder 2>/dev/null || true";
+
+// /etc/hosts unless another file was given with --hosts-file or MYC_HOSTS_FILE
+pub fn hosts_path() -> PathBuf {
+    crate::overrides::get()
+        .hosts_file
+        .clone()
+        .unwrap_or_else(|| PathBuf::from(DEFAULT_HOSTS_PATH))
+}
+
+// How a hosts file line relates to Make Your Choice, used by the hosts file viewer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HostsLineKind {
+    Other,
+    Managed,
+    Conflict,
+}
+
+// The app holds file capabilities that let it write any file, so a link planted in
+// place of the backup, a --hosts-file or the demo's hosts file is never followed. Only
+// /etc/hosts itself may be one, e.g. into /etc/static on NixOS, and only root can
+// replace it.
+async fn write_no_follow(path: &Path, content: &[u8]) -> std::io::Result<()> {
+    use tokio::io::AsyncWriteExt;
+    let mut options = tokio::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    if path != Path::new(DEFAULT_HOSTS_PATH) {
+        options.custom_flags(libc::O_NOFOLLOW);
+    }
+    let mut file = options.open(path).await?;
+    file.write_all(content).await?;
+    file.flush().await
+}
+
+#[derive(Clone)]
+pub struct HostsManager {
+    discord_url: String,
+}
+
+impl HostsManager {
+    pub fn new(discord_url: String) -> Self {
+        Self { discord_url }
+    }
+
+    // For the status and the viewer, which only read one small local file
+    fn read_hosts(&self) -> Result<String> {
+        fs::read_to_string(hosts_path())
+            .or_else(|_| Ok(String::new()))
+    }
+
+    async fn load_hosts(&self) -> Result<String> {
+        Ok(tokio::fs::read_to_string(hosts_path()).await.unwrap_or_default())
+    }
+
+    // Runs as a task of its own: a caller that gives up once writing has started can't
+    // leave a half written file behind
+    async fn write_hosts(&self, content: String) -> Result<()> {
+        tokio::spawn(async move {
+            // Backup current hosts (best effort)
+            let backup = crate::paths::hosts_backup_file();
+            if let Some(dir) = backup.parent() {
+                let _ = tokio::fs::create_dir_all(dir).await;
+            }
+            if let Ok(current) = tokio::fs::read(hosts_path()).await {
+                let _ = write_no_follow(&backup, &current).await;
+            }
+
+            let path = hosts_path();
+            write_no_follow(&path, content.as_bytes())
+                .await
+                .with_context(|| format!("Failed to write to {}", path.display()))?;
+
+            // Resolvers only cache what they read from /etc/hosts
+            if path == Path::new(DEFAULT_HOSTS_PATH) {
+                let _ = Command::new("sh").arg("-c").arg(FLUSH_DNS_CACHES).status().await;
+            }
+
+            Ok(())
+        })
+        .await?
+    }
+
+    async fn write_wrapped_section(&self, inner_content: &str) -> Result<()> {
+        let original = self.load_hosts().await?;
+
+        // Find existing markers
+        let first = original.find(SECTION_MARKER);
+        let last = if let Some(pos) = first {
+            original[pos + SECTION_MARKER.len()..].find(SECTION_MARKER)
+                .map(|p| p + pos + SECTION_MARKER.len())
+        } else {
+            None
+        };
+
+        // Build new wrapped block
+        let wrapped = if inner_content.is_empty() {
+            String::new()
+        } else {
+            let mut content = inner_content.to_string();
+            if !content.ends_with('\n') {
+                content.push('\n');
+            }
+            format!("{}\n{}{}\n", SECTION_MARKER, content, SECTION_MARKER)
+        };
+
+        let new_content = match (first, last) {
+            (Some(f), Some(l)) if wrapped.is_empty() => {
+                // Removing: drop the blank line that separated the section too, so the
+                // file ends up as it was before the first apply
+                let before = original[..f].trim_end_matches('\n');
+                let after = original[l + SECTION_MARKER.len()..].trim_start_matches('\n');
+                if before.is_empty() {
+                    after.to_string()
+                } else {
+                    format!("{}\n{}", before, after)
+                }
+            }
+            (Some(f), Some(l)) => {
+                // Replace everything between markers; the new block brings its own line end
+                let after = &original[l + SECTION_MARKER.len()..];
+                format!("{}{}{}", &original[..f], wrapped, after.strip_prefix('\n').unwrap_or(after))
+            }
+            (Some(f), None) => {
+                // Corrupt state: replace from first marker to end
+                format!("{}{}", &original[..f], wrapped)
+            }
+            (None, _) => {
+                // No markers: append
+                let suffix = if original.ends_with('\n') { "\n" } else { "\n\n" };
+                format!("{}{}{}", original, suffix, wrapped)
+            }
+        };
+
+        self.write_hosts(new_content).await
+    }
+
+    // Content between the section markers, if a complete section exists
+    fn read_managed_section(&self) -> Option<String> {
+        let original = self.read_hosts().ok()?;
+
+        let first = original.find(SECTION_MARKER)?;
+        let last = original[first + SECTION_MARKER.len()..].find(SECTION_MARKER)
+            .map(|p| p + first + SECTION_MARKER.len())?;
+
+        Some(original[first + SECTION_MARKER.len()..last].to_string())
+    }
+
+    pub fn get_blocked_hostnames(&self) -> HashSet<String> {
+        let mut blocked = HashSet::new();
+        let Some(inner) = self.read_managed_section() else { return blocked; };
+
+        for raw_line in inner.lines() {
+            let line = raw_line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            if parts.len() < 2 { continue; }
+            if parts[0] != "0.0.0.0" { continue; }
+
+            for host in parts.iter().skip(1) {
+                blocked.insert(host.to_lowercase());
+            }
+        }
+
+        blocked
+    }
+
+    fn active_configuration(&self, regions: &HashMap<String, RegionInfo>) -> ActiveConfiguration {
+        let Some(inner) = self.read_managed_section() else { return ActiveConfiguration::Inactive; };
+
+        if inner.contains("Universal Redirect mode") {
+            let target_ip = inner.lines()
+                .map(str::trim)
+                .find(|line| !line.is_empty() && !line.starts_with('#'))
+                .and_then(|line| line.split_whitespace().next())
+                .unwrap_or("unknown")
+                .to_string();
+            return ActiveConfiguration::UniversalRedirect { target_ip };
+        }
+
+        let blocked = self.get_blocked_hostnames();
+        if blocked.is_empty() {
+            return ActiveConfiguration::Inactive;
+        }
+
+        let mut allowed: Vec<String> = regions.iter()
+            .filter(|(_, info)| !info.hosts.iter().any(|h| blocked.contains(&h.to_lowercase())))
+            .map(|(name, _)| name.clone())
+            .collect();
+        allowed.sort();
+
+        ActiveConfiguration::Gatekeep { allowed }
+    }
+
+    // When the hosts file was last written, used as the time the configuration was applied
+    pub fn last_modified(&self) -> Option<std::time::SystemTime> {
+        fs::metadata(hosts_path()).and_then(|meta| meta.modified()).ok()
+    }
+
+    fn gatekeep_section(
+        &self,
+        regions: &HashMap<String, RegionInfo>,
+        blocked_regions: &HashMap<String, RegionInfo>,
+        selected: &HashSet<String>,
+        block_mode: BlockMode,
+        merge_unstable: bool,
+    ) -> Result<String> {
+        if selected.is_empty() {
+            return Err(errors::error(ErrorKind::InvalidSelection, "Please select at least one server to allow."));
+        }
+
+        let allowed_set = gatekeep_allowed_regions(regions, selected, merge_unstable);
+
+        // Build hosts content
+        let mut content = String::new();
+        content.push_str("# Edited by Make Your Choice (DbD Server Selector)\n");
+        content.push_str("# Unselected servers are blocked (Gatekeep Mode); selected servers are commented out.\n");
+        content.push_str(&format!("# Need help? Discord: {}\n", self.discord_url));
+        content.push_str("\n");
+
+        for (region_key, region_info) in sorted(regions) {
+            let allow = allowed_set.contains(region_key);
+            for host in &region_info.hosts {
+                let is_ping = host.to_lowercase().contains("ping");
+                let include = match block_mode {
+                    BlockMode::Both => true,
+                    BlockMode::OnlyPing => is_ping,
+                    BlockMode::OnlyService => !is_ping,
+                };
+
+                if include {
+                    let prefix = if allow { "#" } else { "0.0.0.0" };
+                    content.push_str(&format!("{:9} {}\n", prefix, host));
+                }
+            }
+            content.push_str("\n");
+        }
+
+        for (_region_key, region_info) in sorted(blocked_regions) {
+            for host in &region_info.hosts {
+                content.push_str(&format!("{:9} {}\n", "0.0.0.0", host));
+            }
+            content.push_str("\n");
+        }
+
+        Ok(content)
+    }
+
+    async fn redirect_section(
+        &self,
+        regions: &HashMap<String, RegionInfo>,
+        blocked_regions: &HashMap<String, RegionInfo>,
+        selected_region: &str,
+        progress: &(dyn Fn(Progress) + Sync),
+    ) -> Result<String> {
+        let region_info = regions.get(selected_region)
+            .context("Selected region not found")?;
+
+        let service_host = &region_info.hosts[0];
+        let ping_host = if region_info.hosts.len() > 1 {
+            &region_info.hosts[1]
+        } else {
+            &region_info.hosts[0]
+        };
+
+        // Resolve IP addresses
+        progress(Progress::Resolving(service_host.clone()));
+        let service_ip = crate::dns::resolve_ipv4_fresh(service_host).await?.to_string();
+        progress(Progress::Resolving(ping_host.clone()));
+        let ping_ip = crate::dns::resolve_ipv4_fresh(ping_host).await?.to_string();
+
+        // Build hosts content
+        let mut content = String::new();
+        content.push_str("# Edited by Make Your Choice (DbD Server Selector)\n");
+        content.push_str("# Universal Redirect mode: redirect all GameLift endpoints to selected region\n");
+        content.push_str(&format!("# Need help? Discord: {}\n", self.discord_url));
+        content.push_str("\n");
+
+        for (_, region_info) in sorted(regions) {
+            for host in &region_info.hosts {
+                let is_ping = host.to_lowercase().contains("ping");
+                let ip = if is_ping { &ping_ip } else { &service_ip };
+                content.push_str(&format!("{} {}\n", ip, host));
+            }
+            content.push_str("\n");
+        }
+
+        for (_, region_info) in sorted(blocked_regions) {
+            for host in &region_info.hosts {
+                content.push_str(&format!("{} {}\n", "0.0.0.0", host));
+            }
+            content.push_str("\n");
+        }
+
+        Ok(content)
+    }
+
+    // Section content for a selection with the method it is applied with
+    async fn section(
+        &self,
+        regions: &HashMap<String, RegionInfo>,
+        blocked_regions: &HashMap<String, RegionInfo>,
+        applied: &AppliedSelection,
+        progress: &(dyn Fn(Progress) + Sync),
+    ) -> Result<String> {
+        let selected: HashSet<String> = applied.selection.iter().cloned().collect();
+        match applied.apply_mode {
+            ApplyMode::Gatekeep => self.gatekeep_section(
+                regions,
+                blocked_regions,
+                &selected,
+                applied.block_mode,
+                applied.merge_unstable,
+            ),
+            ApplyMode::UniversalRedirect => {
+                if selected.len() != 1 {
+                    return Err(errors::error(ErrorKind::InvalidSelection, "Please select only one server when using Universal Redirect mode."));
+                }
+                self.redirect_section(regions, blocked_regions, &applied.selection[0], progress).await
+            }
+        }
+    }
+
+    // Resolves the Universal Redirect target again and rewrites the section when it
+    // moved. Returns the previous and current address, or None without an active
+    // redirect. `last` is the selection the redirect was applied with.
+    pub async fn refresh_redirect(
+        &self,
+        regions: &HashMap<String, RegionInfo>,
+        blocked_regions: &HashMap<String, RegionInfo>,
+        last: Option<&AppliedSelection>,
+    ) -> Result<Option<(String, String)>> {
+        let ActiveConfiguration::UniversalRedirect { target_ip: previous } = self.active_configuration(regions) else {
+            return Ok(None);
+        };
+        let Some(last) = last.filter(|last| last.apply_mode == ApplyMode::UniversalRedirect) else {
+            bail!("The redirect target is unknown, apply Universal Redirect once more");
+        };
+        let region = last.selection.first().context("Selected region not found")?;
+        let info = regions.get(region).context("Selected region not found")?;
+        let current = crate::dns::resolve_ipv4_fresh(&info.hosts[0]).await?.to_string();
+        if current != previous {
+            self.apply(regions, blocked_regions, last, &|_| {}).await?;
+        }
+        Ok(Some((previous, current)))
+    }
+
+    pub async fn restore_default(&self) -> Result<()> {
+        self.write_hosts(DEFAULT_HOSTS.to_string()).await
+    }
+
+    // A POSIX shell script that writes the current section into a hosts file, for
+    // routers, DNS servers and machines without the app. It replaces an existing
+    // section the same way the app does, and removes it again with --revert.
+    pub fn export_script(&self, regions: &HashMap<String, RegionInfo>) -> Result<String> {
+        let Some(inner) = self.read_managed_section() else {
+            bail!("Nothing is applied, there is no configuration to export");
+        };
+        let description = match self.active_configuration(regions) {
+            ActiveConfiguration::Gatekeep { allowed } => format!("Gatekeep, allowing: {}", allowed.join(", ")),
+            ActiveConfiguration::UniversalRedirect { target_ip } => format!("Universal Redirect to {}", target_ip),
+            ActiveConfiguration::Inactive => "no servers blocked".to_string(),
+        };
+
+        Ok(format!(
+            r#"#!/bin/sh
+# Make Your Choice hosts entries: {description}
+# Generated by Make Your Choice {version} on {date}.
+#
+# Usage: sudo sh {{this script}} [HOSTS_FILE]            apply (default {default_path})
+#        sudo sh {{this script}} --revert [HOSTS_FILE]   remove the entries again
+set -eu
+
+MARKER='{marker}'
+REVERT=0
+if [ "${{1:-}}" = "--revert" ]; then
+    REVERT=1
+    shift
+fi
+HOSTS="${{1:-{default_path}}}"
+
+TMP=$(mktemp)
+trap 'rm -f "$TMP"' EXIT
+
+# Everything except an earlier section
+awk -v marker="$MARKER" '$0 == marker {{ skip = !skip; next }} !skip' "$HOSTS" > "$TMP"
+
+if [ "$REVERT" -eq 0 ]; then
+    if [ -n "$(tail -n 1 "$TMP")" ]; then
+        echo >> "$TMP"
+    fi
+    cat >> "$TMP" <<'MYC_HOSTS'
+{marker}{inner}{marker}
+MYC_HOSTS
+fi
+
+# Written in place, keeps the owner and permissions of the file
+cat "$TMP" > "$HOSTS"
+{flush}
+"#,
+            description = description,
+            version = env!("CARGO_PKG_VERSION"),
+            date = chrono::Local::now().format("%Y-%m-%d %H:%M"),
+            default_path = DEFAULT_HOSTS_PATH,
+            marker = SECTION_MARKER,
+            inner = inner,
+            flush = FLUSH_DNS_CACHES,
+        ))
+    }
+
+    pub fn get_all_managed_hostnames(&self, regions: &HashMap<String, RegionInfo>) -> HashSet<String> {
+        let mut hostnames = HashSet::new();
+        for region_info in regions.values() {
+            for host in &region_info.hosts {
+                hostnames.insert(host.to_lowercase());
+            }
+        }
+        hostnames
+    }
+
+    fn detect_conflicting_entries(&self, regions: &HashMap<String, RegionInfo>) -> Result<Vec<String>> {
+        let mut conflicts = Vec::new();
+        let managed_hosts = self.get_all_managed_hostnames(regions);
+
+        let original = self.read_hosts()?;
+
+        // Find the section markers
+        let first = original.find(SECTION_MARKER);
+        let last = if let Some(pos) = first {
+            original[pos + SECTION_MARKER.len()..].find(SECTION_MARKER)
+                .map(|p| p + pos + SECTION_MARKER.len())
+        } else {
+            None
+        };
+
+        // Get content outside markers
+        let outside_content = match (first, last) {
+            (Some(f), Some(l)) => {
+                // Content before first marker + content after second marker
+                let before = &original[..f];
+                let after = &original[l + SECTION_MARKER.len()..];
+                format!("{}{}", before, after)
+            }
+            (Some(f), None) => {
+                // Only first marker found, take content before it
+                original[..f].to_string()
+            }
+            (None, _) => {
+                // No markers, all content is outside
+                original.clone()
+            }
+        };
+
+        // Parse lines and check for conflicts
+        for line in outside_content.lines() {
+            let trimmed = line.trim();
+
+            // Skip empty lines and commented lines (lines starting with #)
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+
+            // Parse host entry (format: IP hostname)
+            let parts: Vec<&str> = trimmed.split_whitespace().collect();
+            if parts.len() >= 2 {
+                // Check if hostname matches any managed host
+                let hostname = parts[1].to_lowercase();
+                if managed_hosts.contains(&hostname) && !conflicts.contains(&trimmed.to_string()) {
+                    conflicts.push(trimmed.to_string());
+                }
+            }
+        }
+
+        Ok(conflicts)
+    }
+
+    // Every line of the hosts file, tagged as part of the managed section (markers
+    // included) or as a conflicting entry outside it
+    pub fn annotated_lines(&self, regions: &HashMap<String, RegionInfo>) -> Result<Vec<(String, HostsLineKind)>> {
+        let content = self.read_hosts()?;
+        let conflicts: HashSet<String> = self.detect_conflicting_entries(regions)?.into_iter().collect();
+
+        let mut markers_seen = 0;
+        let lines = content
+            .lines()
+            .map(|line| {
+                let is_marker = line.contains(SECTION_MARKER);
+                if is_marker {
+                    markers_seen += 1;
+                }
+                let kind = if is_marker || markers_seen == 1 {
+                    HostsLineKind::Managed
+                } else if conflicts.contains(line.trim()) {
+                    HostsLineKind::Conflict
+                } else {
+                    HostsLineKind::Other
+                };
+                (line.to_string(), kind)
+            })
+            .collect();
+
+        Ok(lines)
+    }
+
+    pub async fn clear_conflicting_entries(&self, conflicts: &[String]) -> Result<()> {
+        let original = self.load_hosts().await?;
+        let conflict_set: HashSet<String> = conflicts.iter().map(|s| s.trim().to_string()).collect();
+
+        // Filter out conflicting lines
+        let cleaned: String = original
+            .lines()
+            .filter(|line| !conflict_set.contains(&line.trim().to_string()))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        // Add trailing newline if original had one
+        let cleaned = if original.ends_with('\n') {
+            format!("{}\n", cleaned)
+        } else {
+            cleaned
+        };
+
+        self.write_hosts(cleaned).await
+    }
+}
+
+impl EnforcementBackend for HostsManager {
+    fn id(&self) -> &'static str {
+        "hosts"
+    }
+
+    // The lines of the managed section
+    fn plan<'a>(
+        &'a self,
+        regions: &'a HashMap<String, RegionInfo>,
+        blocked_regions: &'a HashMap<String, RegionInfo>,
+        applied: &'a AppliedSelection,
+    ) -> BoxFuture<'a, Result<Vec<String>>> {
+        Box::pin(async move {
+            let section = self.section(regions, blocked_regions, applied, &|_| {}).await?;
+            Ok(section.lines().map(str::to_string).collect())
+        })
+    }
+
+    fn apply<'a>(
+        &'a self,
+        regions: &'a HashMap<String, RegionInfo>,
+        blocked_regions: &'a HashMap<String, RegionInfo>,
+        applied: &'a AppliedSelection,
+        progress: &'a (dyn Fn(Progress) + Sync),
+    ) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            check_resolver().await?;
+            let section = self.section(regions, blocked_regions, applied, progress).await?;
+            progress(Progress::Writing);
+            self.write_wrapped_section(&section).await
+        })
+    }
+
+    fn revert(&self) -> BoxFuture<'_, Result<()>> {
+        Box::pin(self.write_wrapped_section(""))
+    }
+
+    fn status(&self, regions: &HashMap<String, RegionInfo>) -> ActiveConfiguration {
+        self.active_configuration(regions)
+    }
+
+    fn detect_conflicts(&self, regions: &HashMap<String, RegionInfo>) -> Result<Vec<String>> {
+        self.detect_conflicting_entries(regions)
+    }
+
+    // The whole hosts file
+    fn snapshot(&self) -> BoxFuture<'_, Result<String>> {
+        Box::pin(self.load_hosts())
+    }
+
+    fn restore(&self, snapshot: String) -> BoxFuture<'_, Result<()>> {
+        Box::pin(self.write_hosts(snapshot))
+    }
+}
+
+// Regions Gatekeep lets through: the selection, plus a stable alternative in the same
+// group for each unstable pick when only unstable servers were chosen and merging is on
+pub fn gatekeep_allowed_regions(
+    regions: &HashMap<String, RegionInfo>,
+    selected: &HashSet<String>,
+    merge_unstable: bool,
+) -> HashSet<String> {
+    // Check if any stable servers are selected
+    let any_stable_selected = selected.iter()
+        .any(|r| regions.get(r).map(|info| info.stable).unwrap_or(false));
+
+    // Merge unstable servers with stable alternatives if needed
+    let mut allowed_set = selected.clone();
+    if merge_unstable && !any_stable_selected {
+        for region in selected.iter() {
+            if let Some(info) = regions.get(region) {
+                if !info.stable {
+                    let group = get_group_name(region);
+                    // Find a stable alternative in the same group
+                    if let Some((alt_region, _)) = regions.iter()
+                        .find(|(r, i)| get_group_name(r) == group && i.stable)
+                    {
+                        allowed_set.insert(alt_region.clone());
+                    }
+                }
+            }
+        }
+    }
+
+    allowed_set
+}
+
+// By name, so the same selection always writes the same file
+fn sorted(regions: &HashMap<String, RegionInfo>) -> Vec<(&String, &RegionInfo)> {
+    let mut sorted: Vec<_> = regions.iter().collect();
+    sorted.sort_by(|a, b| a.0.cmp(b.0));
+    sorted
+}
+
+// Programs only read /etc/hosts when nsswitch.conf lists "files" for hosts, or "resolve"
+// since systemd-resolved reads it itself. Without either the entries would be written
+// and silently ignored. Other files given with --hosts-file aren't checked, and macOS
+// has no nsswitch.conf.
+async fn check_resolver() -> Result<()> {
+    if hosts_path() != std::path::Path::new(DEFAULT_HOSTS_PATH) {
+        return Ok(());
+    }
+    let Ok(conf) = tokio::fs::read_to_string(NSSWITCH_PATH).await else {
+        return Ok(());
+    };
+    let sources = conf
+        .lines()
+        .rev()
+        .find_map(|line| line.split('#').next()?.trim().strip_prefix("hosts:"));
+    match sources {
+        Some(sources) if !sources.split_whitespace().any(|source| source == "files" || source == "resolve") => {
+            Err(errors::error(
+                ErrorKind::ResolverBypass,
+                format!("{} doesn't list \"files\" for hosts, the hosts file would be ignored", NSSWITCH_PATH),
+            ))
+        }
+        _ => Ok(()),
+    }
+}
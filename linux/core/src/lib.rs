@@ -0,0 +1,29 @@
+// Everything that doesn't need GTK: the servers and their hostnames, the hosts file and
+// its conflicts, pings, AWS address ranges and the settings file. The window, the
+// command line and the background service in the make-your-choice binary build on it.
+#[macro_use]
+pub mod logging;
+pub mod aws_ranges;
+pub mod backend;
+pub mod community;
+pub mod custom_hosts;
+pub mod demo;
+pub mod dns;
+pub mod errors;
+pub mod geo;
+pub mod history;
+pub mod hosts;
+pub mod matches;
+pub mod net;
+pub mod overrides;
+pub mod paths;
+pub mod ping;
+pub mod region;
+pub mod settings;
+
+// Marks a string for the translation template without translating it; the app passes it
+// through tr() where it is shown. xgettext picks it up with --keyword=N_.
+#[allow(non_snake_case)]
+pub fn N_(text: &'static str) -> &'static str {
+    text
+}
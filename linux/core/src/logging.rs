@@ -0,0 +1,139 @@
+use chrono::{DateTime, Local};
+use std::collections::VecDeque;
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+// Entries kept in memory for the log viewer; the file keeps the full history
+const MAX_ENTRIES: usize = 2000;
+// The log is started over once it grows past this size, keeping one old copy
+const MAX_FILE_SIZE: u64 = 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Info,
+    Warn,
+    Error,
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Level::Info => "INFO",
+            Level::Warn => "WARN",
+            Level::Error => "ERROR",
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Entry {
+    pub time: DateTime<Local>,
+    pub level: Level,
+    // Part of the app that logged it, e.g. "hosts" or "tray"
+    pub source: &'static str,
+    pub message: String,
+}
+
+impl fmt::Display for Entry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {:<5} {}: {}",
+            self.time.format("%Y-%m-%d %H:%M:%S"),
+            self.level,
+            self.source,
+            self.message
+        )
+    }
+}
+
+struct Log {
+    entries: VecDeque<Entry>,
+    // Total number of entries ever recorded, lets the viewer spot new ones
+    recorded: u64,
+}
+
+static LOG: Mutex<Log> = Mutex::new(Log {
+    entries: VecDeque::new(),
+    recorded: 0,
+});
+
+pub fn log_file() -> PathBuf {
+    crate::paths::state_dir().join("app.log")
+}
+
+// Moves an oversized log aside so the file doesn't grow forever
+pub fn init() {
+    let path = log_file();
+    if let Some(dir) = path.parent() {
+        let _ = fs::create_dir_all(dir);
+    }
+    if fs::metadata(&path).is_ok_and(|meta| meta.len() > MAX_FILE_SIZE) {
+        let _ = fs::rename(&path, path.with_extension("log.1"));
+    }
+}
+
+pub fn record(level: Level, source: &'static str, message: String) {
+    let entry = Entry {
+        time: Local::now(),
+        level,
+        source,
+        message,
+    };
+
+    eprintln!("{}", entry);
+    if let Ok(mut file) = OpenOptions::new().create(true).append(true).open(log_file()) {
+        let _ = writeln!(file, "{}", entry);
+    }
+
+    let mut log = LOG.lock().unwrap();
+    if log.entries.len() == MAX_ENTRIES {
+        log.entries.pop_front();
+    }
+    log.entries.push_back(entry);
+    log.recorded += 1;
+}
+
+// Snapshot of the in-memory entries with the running total
+pub fn entries() -> (u64, Vec<Entry>) {
+    let log = LOG.lock().unwrap();
+    (log.recorded, log.entries.iter().cloned().collect())
+}
+
+pub fn recorded() -> u64 {
+    LOG.lock().unwrap().recorded
+}
+
+// Most recent error of this session, for bug reports
+pub fn last_error() -> Option<Entry> {
+    let log = LOG.lock().unwrap();
+    log.entries
+        .iter()
+        .rev()
+        .find(|entry| entry.level == Level::Error)
+        .cloned()
+}
+
+#[macro_export]
+macro_rules! log_info {
+    ($source:expr, $($arg:tt)*) => {
+        $crate::logging::record($crate::logging::Level::Info, $source, format!($($arg)*))
+    };
+}
+
+#[macro_export]
+macro_rules! log_warn {
+    ($source:expr, $($arg:tt)*) => {
+        $crate::logging::record($crate::logging::Level::Warn, $source, format!($($arg)*))
+    };
+}
+
+#[macro_export]
+macro_rules! log_error {
+    ($source:expr, $($arg:tt)*) => {
+        $crate::logging::record($crate::logging::Level::Error, $source, format!($($arg)*))
+    };
+}
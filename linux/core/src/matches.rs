@@ -0,0 +1,49 @@
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+// Matches played per server, kept across runs for comparing servers. Counted from what
+// the match monitor saw, see community::match_ended; matches on unknown servers and
+// before this file existed are missing.
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RegionMatches {
+    pub matches: u64,
+    // Matches that ended within the first minutes
+    pub disconnects: u64,
+    pub last: Option<DateTime<Local>>,
+}
+
+fn history_file() -> PathBuf {
+    crate::paths::data_dir().join("match-history.json")
+}
+
+// By server name, empty when nothing was recorded yet
+pub fn load() -> HashMap<String, RegionMatches> {
+    std::fs::read_to_string(history_file())
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+pub fn record(region: &str, disconnect: bool) {
+    let mut history = load();
+    let entry = history.entry(region.to_string()).or_default();
+    if disconnect {
+        entry.disconnects += 1;
+    } else {
+        entry.matches += 1;
+    }
+    entry.last = Some(Local::now());
+
+    let path = history_file();
+    let written = path
+        .parent()
+        .map_or(Ok(()), std::fs::create_dir_all)
+        .and_then(|_| std::fs::write(&path, serde_json::to_string_pretty(&history).unwrap_or_default()));
+    if let Err(e) = written {
+        log_warn!("matches", "Failed to write {}: {}", path.display(), e);
+    }
+}
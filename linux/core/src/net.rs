@@ -0,0 +1,52 @@
+use anyhow::{Context, Result};
+use std::sync::Mutex;
+use std::time::Duration;
+
+// Every HTTP request is made with a client from here, so the proxy applies to the
+// update checker, the GitHub identity, AWS ip-ranges and downloads alike. Without an
+// explicit proxy reqwest follows http_proxy, https_proxy, all_proxy and no_proxy.
+
+#[derive(Debug, Clone, Default)]
+struct Proxy {
+    url: String,
+    username: String,
+    password: String,
+}
+
+static PROXY: Mutex<Option<Proxy>> = Mutex::new(None);
+
+// Sets the proxy from the settings, an empty URL goes back to the environment
+pub fn set_proxy(url: &str, username: &str, password: &str) {
+    let proxy = (!url.trim().is_empty()).then(|| Proxy {
+        url: url.trim().to_string(),
+        username: username.to_string(),
+        password: password.to_string(),
+    });
+    *PROXY.lock().unwrap() = proxy;
+}
+
+// Whether `url` can be used as a proxy, for validating the settings field
+pub fn check_proxy_url(url: &str) -> Result<()> {
+    reqwest::Proxy::all(url.trim()).with_context(|| format!("Invalid proxy address {}", url))?;
+    Ok(())
+}
+
+pub fn client(timeout: Option<Duration>) -> Result<reqwest::Client> {
+    let mut builder = reqwest::Client::builder().user_agent("make-your-choice");
+    if let Some(timeout) = timeout {
+        builder = builder.timeout(timeout);
+    }
+
+    let proxy = PROXY.lock().unwrap().clone();
+    if let Some(proxy) = proxy {
+        let mut all = reqwest::Proxy::all(&proxy.url)
+            .with_context(|| format!("Invalid proxy address {}", proxy.url))?
+            .no_proxy(reqwest::NoProxy::from_env());
+        if !proxy.username.is_empty() {
+            all = all.basic_auth(&proxy.username, &proxy.password);
+        }
+        builder = builder.proxy(all);
+    }
+
+    builder.build().context("Failed to create the HTTP client")
+}
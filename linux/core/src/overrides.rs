@@ -0,0 +1,163 @@
+use crate::region::ApplyMode;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+// Settings that can be changed for a single run through the environment or the command
+// line, for packagers, testing and scripted setups. They are never written to config.yaml.
+// Command line flags win over environment variables.
+//
+//   --hosts-file=PATH   MYC_HOSTS_FILE   edit this file instead of /etc/hosts
+//   --config-dir=PATH   MYC_CONFIG_DIR   read and write config.yaml in this directory
+//   --apply-mode=MODE   MYC_APPLY_MODE   "gatekeep" or "universal-redirect"
+//   --offline           MYC_OFFLINE=1    skip the GitHub, update and AWS requests
+//   --dns-server=ADDR   MYC_DNS_SERVER   look names up here, "IP" or "IP:PORT" (dns.rs)
+//   --demo              MYC_DEMO=1       made-up pings and matches, hosts file and settings in
+//                                        a private scratch directory, no network (demo.rs)
+//
+// The two paths are ignored while the process holds file capabilities, which let it
+// write any file: otherwise any user could point them at e.g. /etc/shadow. The demo
+// needs no rights, so it gives the capabilities up and is refused when that fails.
+#[derive(Debug, Default)]
+pub struct Overrides {
+    pub hosts_file: Option<PathBuf>,
+    pub config_dir: Option<PathBuf>,
+    pub apply_mode: Option<ApplyMode>,
+    pub offline: bool,
+    pub dns_server: Option<SocketAddr>,
+    pub demo: bool,
+}
+
+pub const HOSTS_FILE_OPTION: &str = "hosts-file";
+pub const CONFIG_DIR_OPTION: &str = "config-dir";
+pub const APPLY_MODE_OPTION: &str = "apply-mode";
+pub const OFFLINE_OPTION: &str = "offline";
+pub const DNS_SERVER_OPTION: &str = "dns-server";
+pub const DEMO_OPTION: &str = "demo";
+
+static OVERRIDES: OnceLock<Overrides> = OnceLock::new();
+
+pub fn get() -> &'static Overrides {
+    OVERRIDES.get_or_init(|| {
+        let args: Vec<String> = std::env::args().skip(1).collect();
+        let privileged = is_privileged();
+        let mut demo = flag(&args, DEMO_OPTION, "MYC_DEMO");
+        if demo && privileged && !drop_capabilities() {
+            log_warn!("overrides", "Ignoring --{}, the file capabilities could not be dropped", DEMO_OPTION);
+            demo = false;
+        }
+        // The demo stays away from the real files unless told otherwise
+        let demo_dir = demo.then(crate::demo::dir).flatten();
+        if demo && demo_dir.is_none() {
+            log_warn!("overrides", "Ignoring --{} without a private scratch directory", DEMO_OPTION);
+            demo = false;
+        }
+        let demo_dir = |name: &str| demo_dir.map(|dir| dir.join(name));
+        let path = |name: &str, env: &str| {
+            let path = value(&args, name, env).map(PathBuf::from);
+            if path.is_some() && privileged {
+                log_warn!("overrides", "Ignoring --{} while running with file capabilities", name);
+                return None;
+            }
+            path
+        };
+        let overrides = Overrides {
+            hosts_file: path(HOSTS_FILE_OPTION, "MYC_HOSTS_FILE").or_else(|| demo_dir("hosts")),
+            config_dir: path(CONFIG_DIR_OPTION, "MYC_CONFIG_DIR").or_else(|| demo_dir("config")),
+            apply_mode: value(&args, APPLY_MODE_OPTION, "MYC_APPLY_MODE")
+                .and_then(|mode| parse_apply_mode(&mode)),
+            offline: demo || flag(&args, OFFLINE_OPTION, "MYC_OFFLINE"),
+            dns_server: value(&args, DNS_SERVER_OPTION, "MYC_DNS_SERVER").and_then(|server| {
+                crate::dns::parse_server(&server)
+                    .map_err(|e| log_warn!("overrides", "Ignoring {:#}", e))
+                    .ok()
+            }),
+            demo,
+        };
+        if overrides.hosts_file.is_some()
+            || overrides.config_dir.is_some()
+            || overrides.apply_mode.is_some()
+            || overrides.offline
+            || overrides.dns_server.is_some()
+        {
+            log_info!("overrides", "Active for this run: {:?}", overrides);
+        }
+        overrides
+    })
+}
+
+// The kernel sets AT_SECURE for processes that gained privileges when they started, file
+// capabilities included. macOS has no file capabilities, there the commands run with sudo.
+#[cfg(target_os = "linux")]
+fn is_privileged() -> bool {
+    unsafe { libc::getauxval(libc::AT_SECURE) != 0 }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn is_privileged() -> bool {
+    false
+}
+
+// Clears the effective, permitted and inheritable sets. Capabilities belong to a thread,
+// this covers the process because the first get() comes from logging::init at the top
+// of main, before any other thread is started.
+#[cfg(target_os = "linux")]
+fn drop_capabilities() -> bool {
+    #[repr(C)]
+    struct Header {
+        version: u32,
+        pid: libc::c_int,
+    }
+    #[repr(C)]
+    #[derive(Clone, Copy, Default)]
+    struct Data {
+        effective: u32,
+        permitted: u32,
+        inheritable: u32,
+    }
+    // _LINUX_CAPABILITY_VERSION_3, which takes two Data for the 64 capability bits
+    let header = Header { version: 0x2008_0522, pid: 0 };
+    let data = [Data::default(); 2];
+    unsafe { libc::syscall(libc::SYS_capset, &header, data.as_ptr()) == 0 }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn drop_capabilities() -> bool {
+    false
+}
+
+// "--name", then the environment variable set to anything but "" or "0"
+fn flag(args: &[String], name: &str, env: &str) -> bool {
+    args.iter().any(|arg| *arg == format!("--{}", name))
+        || std::env::var(env).is_ok_and(|v| !v.is_empty() && v != "0")
+}
+
+// "--name=value", "--name value", then the environment variable
+fn value(args: &[String], name: &str, env: &str) -> Option<String> {
+    let flag = format!("--{}", name);
+    let prefix = format!("--{}=", name);
+    args.iter()
+        .enumerate()
+        .find_map(|(i, arg)| {
+            if let Some(value) = arg.strip_prefix(&prefix) {
+                Some(value.to_string())
+            } else if *arg == flag {
+                args.get(i + 1).cloned()
+            } else {
+                None
+            }
+        })
+        .or_else(|| std::env::var(env).ok())
+        .filter(|value| !value.is_empty())
+}
+
+fn parse_apply_mode(mode: &str) -> Option<ApplyMode> {
+    match mode.to_ascii_lowercase().as_str() {
+        "gatekeep" => Some(ApplyMode::Gatekeep),
+        "universal-redirect" | "universalredirect" | "redirect" => Some(ApplyMode::UniversalRedirect),
+        other => {
+            log_warn!("overrides", "Ignoring unknown apply mode {:?}", other);
+            None
+        }
+    }
+}
@@ -0,0 +1,132 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// Every file the app keeps lives in one "make-your-choice" directory per XDG base
+// directory: settings, hook scripts and plugins in XDG_CONFIG_HOME, hosts backups in
+// XDG_DATA_HOME, the log in XDG_STATE_HOME and cached GitHub responses in
+// XDG_CACHE_HOME. The only exceptions are the autostart entry, the systemd unit and
+// the icon, which have to sit where the desktop and systemd look for them. On macOS
+// the same directories sit under ~/Library, the log in ~/Library/Logs.
+const APP_DIR: &str = "make-your-choice";
+
+fn home() -> PathBuf {
+    dirs::home_dir().unwrap_or_else(|| PathBuf::from("."))
+}
+
+pub fn config_dir() -> PathBuf {
+    if let Some(dir) = &crate::overrides::get().config_dir {
+        return dir.clone();
+    }
+    dirs::config_dir()
+        .unwrap_or_else(|| home().join(".config"))
+        .join(APP_DIR)
+}
+
+// One subdirectory per event, see hooks.rs
+pub fn hooks_dir() -> PathBuf {
+    config_dir().join("hooks")
+}
+
+// Rhai scripts loaded with the app, see plugins.rs
+pub fn plugins_dir() -> PathBuf {
+    config_dir().join("plugins")
+}
+
+pub fn data_dir() -> PathBuf {
+    data_home().join(APP_DIR)
+}
+
+pub fn state_dir() -> PathBuf {
+    // macOS has no state directory
+    let fallback = if cfg!(target_os = "macos") {
+        home().join("Library").join("Logs")
+    } else {
+        home().join(".local").join("state")
+    };
+    dirs::state_dir().unwrap_or(fallback).join(APP_DIR)
+}
+
+// Only holds what can be downloaded again
+pub fn cache_dir() -> PathBuf {
+    dirs::cache_dir()
+        .unwrap_or_else(|| home().join(".cache"))
+        .join(APP_DIR)
+}
+
+fn data_home() -> PathBuf {
+    dirs::data_dir().unwrap_or_else(|| home().join(".local").join("share"))
+}
+
+pub fn autostart_file() -> PathBuf {
+    dirs::config_dir()
+        .unwrap_or_else(|| home().join(".config"))
+        .join("autostart")
+        .join("make-your-choice.desktop")
+}
+
+pub fn systemd_unit_file() -> PathBuf {
+    dirs::config_dir()
+        .unwrap_or_else(|| home().join(".config"))
+        .join("systemd/user")
+        .join("make-your-choice.service")
+}
+
+// A system daemon, see service.rs in the app
+pub fn launchd_plist_file() -> PathBuf {
+    PathBuf::from("/Library/LaunchDaemons/dev.lawliet.makeyourchoice.daemon.plist")
+}
+
+pub fn icon_file() -> PathBuf {
+    data_home()
+        .join("icons/hicolor/256x256/apps")
+        .join("make-your-choice.png")
+}
+
+// Copy of the hosts file taken before every change
+pub fn hosts_backup_file() -> PathBuf {
+    data_dir().join("hosts.bak")
+}
+
+// Places earlier versions wrote to, with their new location and whether the old file
+// goes away. Nothing under /etc is ever removed, it is only copied.
+fn legacy_files() -> Vec<(PathBuf, PathBuf, bool)> {
+    vec![
+        // The backup used to sit next to the hosts file itself
+        (PathBuf::from("/etc/hosts.bak"), hosts_backup_file(), false),
+        // The log fell back to the cache directory without a state directory
+        (
+            cache_dir().join("app.log"),
+            state_dir().join("app.log"),
+            true,
+        ),
+    ]
+}
+
+// Moves or copies files from older locations once. Files already at the new location win.
+pub fn migrate_legacy() {
+    for (legacy, current, remove) in legacy_files() {
+        if legacy == current || !legacy.exists() || current.exists() {
+            continue;
+        }
+        match migrate_file(&legacy, &current, remove) {
+            Ok(()) if remove => log_info!("paths", "Moved {:?} to {:?}", legacy, current),
+            Ok(()) => log_info!("paths", "Copied {:?} to {:?}", legacy, current),
+            Err(e) => log_warn!("paths", "Failed to move {:?} to {:?}: {}", legacy, current, e),
+        }
+    }
+}
+
+fn migrate_file(from: &Path, to: &Path, remove: bool) -> std::io::Result<()> {
+    if let Some(dir) = to.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    if !remove {
+        return fs::copy(from, to).map(|_| ());
+    }
+    // Renaming fails across file systems, e.g. into a separate /home
+    if fs::rename(from, to).is_err() {
+        fs::copy(from, to)?;
+        fs::remove_file(from)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,195 @@
+use crate::region::RegionInfo;
+use std::collections::{HashMap, VecDeque};
+use std::net::Ipv4Addr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::net::TcpStream;
+use tokio::time::timeout;
+
+// Probe interval bounds. The loop starts at the minimum and backs off towards the
+// maximum while nobody is looking at the window and the numbers aren't moving.
+const MIN_PING_INTERVAL: Duration = Duration::from_secs(5);
+const MAX_PING_INTERVAL: Duration = Duration::from_secs(60);
+const IDLE_THRESHOLD: Duration = Duration::from_secs(60);
+const STABLE_DELTA_MS: i64 = 15;
+const PORTS: [u16; 2] = [443, 80];
+// Rounds kept per region for the statistics, an hour at the fastest interval
+const HISTORY_ROUNDS: usize = 720;
+
+// The port that answered last per address, tried first so a round doesn't spend a
+// failed connect on the other one every time. The handshake itself is what is timed,
+// so every probe needs a socket of its own; addresses come from the DNS cache.
+static OPEN_PORTS: Mutex<Option<HashMap<Ipv4Addr, u16>>> = Mutex::new(None);
+
+// Region name and the host probed for it, built once so a round copies nothing
+pub fn targets(regions: &HashMap<String, RegionInfo>) -> Arc<[(String, String)]> {
+    regions
+        .iter()
+        .filter_map(|(name, info)| Some((name.clone(), info.hosts.first()?.clone())))
+        .collect()
+}
+
+// One round over all targets, one after another
+pub async fn ping_all(targets: &[(String, String)]) -> HashMap<String, i64> {
+    let mut results = HashMap::with_capacity(targets.len());
+    for (name, host) in targets {
+        results.insert(name.clone(), ping_host(host).await);
+    }
+    results
+}
+
+pub async fn ping_host(hostname: &str) -> i64 {
+    if crate::demo::enabled() {
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        return crate::demo::latency(hostname);
+    }
+    // Through the shared resolver: the system one answers from the hosts file, where
+    // blocked servers point at 0.0.0.0 and redirected ones at the redirect target
+    let Ok(ip) = crate::dns::resolve_ipv4(hostname).await else {
+        return -1;
+    };
+    let known = OPEN_PORTS.lock().unwrap().get_or_insert_with(HashMap::new).get(&ip).copied();
+    let mut ports = PORTS;
+    if known == Some(PORTS[1]) {
+        ports.reverse();
+    }
+
+    for port in ports {
+        let start = Instant::now();
+
+        // Try to establish TCP connection with 2 second timeout
+        match timeout(Duration::from_secs(2), TcpStream::connect((ip, port))).await {
+            Ok(Ok(_)) => {
+                // Connection successful, return latency
+                let latency = start.elapsed().as_millis() as i64;
+                if known != Some(port) {
+                    OPEN_PORTS.lock().unwrap().get_or_insert_with(HashMap::new).insert(ip, port);
+                }
+                return latency;
+            }
+            Ok(Err(_)) => {
+                // Connection failed, try next port
+                continue;
+            }
+            Err(_) => {
+                // Timeout, try next port
+                continue;
+            }
+        }
+    }
+
+    // All connection attempts failed
+    -1
+}
+
+pub struct PingScheduler {
+    interval: Duration,
+    last_interaction: Instant,
+    last_results: HashMap<String, i64>,
+}
+
+impl Default for PingScheduler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PingScheduler {
+    pub fn new() -> Self {
+        Self {
+            interval: MIN_PING_INTERVAL,
+            last_interaction: Instant::now(),
+            last_results: HashMap::new(),
+        }
+    }
+
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// Snaps back to the fastest probe rate. Called on user input and whenever
+    /// something (e.g. the match monitor) wants fresh numbers.
+    /// Returns true if the interval had been lengthened before.
+    pub fn notify_interaction(&mut self) -> bool {
+        self.last_interaction = Instant::now();
+        let was_relaxed = self.interval > MIN_PING_INTERVAL;
+        self.interval = MIN_PING_INTERVAL;
+        was_relaxed
+    }
+
+    /// Feeds the results of a finished probe round and returns the delay until the next one.
+    pub fn record_results(&mut self, results: &HashMap<String, i64>) -> Duration {
+        let stable = !self.last_results.is_empty()
+            && results.iter().all(|(region, &latency)| {
+                self.last_results.get(region).is_some_and(|&previous| {
+                    (previous < 0) == (latency < 0) && (previous - latency).abs() <= STABLE_DELTA_MS
+                })
+            });
+        let idle = self.last_interaction.elapsed() >= IDLE_THRESHOLD;
+
+        self.interval = if stable && idle {
+            (self.interval * 2).min(MAX_PING_INTERVAL)
+        } else {
+            MIN_PING_INTERVAL
+        };
+        self.last_results = results.clone();
+        self.interval
+    }
+}
+
+// Recent pings per region, for comparing servers on more than the latest number
+#[derive(Default)]
+pub struct PingHistory {
+    rounds: HashMap<String, VecDeque<i64>>,
+}
+
+pub struct PingStats {
+    pub samples: usize,
+    // Latest answered ping, None when the last one was lost
+    pub latest: Option<i64>,
+    pub min: i64,
+    pub max: i64,
+    pub average: f64,
+    // Mean difference between consecutive answered pings
+    pub jitter: f64,
+    // Share of pings without an answer, 0 to 1
+    pub loss: f64,
+}
+
+impl PingHistory {
+    // One round of pings, -1 for servers that did not answer
+    pub fn record(&mut self, results: &HashMap<String, i64>) {
+        for (name, &ms) in results {
+            let samples = self.rounds.entry(name.clone()).or_default();
+            samples.push_back(ms);
+            if samples.len() > HISTORY_ROUNDS {
+                samples.pop_front();
+            }
+        }
+    }
+
+    // None until the region was pinged, min and max are -1 while nothing answered
+    pub fn stats(&self, name: &str) -> Option<PingStats> {
+        let samples = self.rounds.get(name).filter(|samples| !samples.is_empty())?;
+        let answered: Vec<i64> = samples.iter().copied().filter(|ms| *ms >= 0).collect();
+        let average = match answered.len() {
+            0 => 0.0,
+            count => answered.iter().sum::<i64>() as f64 / count as f64,
+        };
+        let jitter = match answered.len() {
+            0 | 1 => 0.0,
+            count => {
+                answered.windows(2).map(|pair| (pair[1] - pair[0]).abs()).sum::<i64>() as f64 / (count - 1) as f64
+            }
+        };
+        Some(PingStats {
+            samples: samples.len(),
+            latest: samples.back().copied().filter(|ms| *ms >= 0),
+            min: answered.iter().copied().min().unwrap_or(-1),
+            max: answered.iter().copied().max().unwrap_or(-1),
+            average,
+            jitter,
+            loss: (samples.len() - answered.len()) as f64 / samples.len() as f64,
+        })
+    }
+}
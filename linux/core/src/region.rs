@@ -4,6 +4,8 @@ use std::collections::HashMap;
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RegionInfo {
     pub hosts: Vec<String>,
+    // ISO 3166 code of the country hosting the data center, keys the flag icons
+    pub country: String,
     pub stable: bool,
 }
 
@@ -13,6 +15,24 @@ pub enum ApplyMode {
     UniversalRedirect,
 }
 
+impl ApplyMode {
+    // Name used on the command line and over D-Bus
+    pub fn id(self) -> &'static str {
+        match self {
+            ApplyMode::Gatekeep => "gatekeep",
+            ApplyMode::UniversalRedirect => "universal-redirect",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        match id.to_ascii_lowercase().as_str() {
+            "gatekeep" => Some(ApplyMode::Gatekeep),
+            "universal-redirect" | "redirect" => Some(ApplyMode::UniversalRedirect),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum BlockMode {
     Both,
@@ -20,7 +40,23 @@ pub enum BlockMode {
     OnlyService,
 }
 
+// The built-in list with the user's custom hostnames and the community stable marks
 pub fn get_selectable_regions() -> HashMap<String, RegionInfo> {
+    let mut regions = builtin_selectable_regions();
+    crate::custom_hosts::apply(&mut regions, &crate::custom_hosts::load());
+    // The stable marks below are the built-in guess, reports from other players win
+    crate::community::apply_cached(&mut regions);
+    regions
+}
+
+pub fn get_blocked_regions() -> HashMap<String, RegionInfo> {
+    let mut regions = builtin_blocked_regions();
+    crate::custom_hosts::apply(&mut regions, &crate::custom_hosts::load());
+    regions
+}
+
+// As shipped, the hostname editor offers these back
+pub fn builtin_selectable_regions() -> HashMap<String, RegionInfo> {
     let mut regions = HashMap::new();
 
     // Europe
@@ -31,6 +67,7 @@ pub fn get_selectable_regions() -> HashMap<String, RegionInfo> {
                 "gamelift.eu-west-2.amazonaws.com".to_string(),
                 "gamelift-ping.eu-west-2.api.aws".to_string(),
             ],
+            country: "gb".to_string(),
             stable: false,
         },
     );
@@ -41,6 +78,7 @@ pub fn get_selectable_regions() -> HashMap<String, RegionInfo> {
                 "gamelift.eu-west-1.amazonaws.com".to_string(),
                 "gamelift-ping.eu-west-1.api.aws".to_string(),
             ],
+            country: "ie".to_string(),
             stable: true,
         },
     );
@@ -51,6 +89,7 @@ pub fn get_selectable_regions() -> HashMap<String, RegionInfo> {
                 "gamelift.eu-central-1.amazonaws.com".to_string(),
                 "gamelift-ping.eu-central-1.api.aws".to_string(),
             ],
+            country: "de".to_string(),
             stable: true,
         },
     );
@@ -63,6 +102,7 @@ pub fn get_selectable_regions() -> HashMap<String, RegionInfo> {
                 "gamelift.us-east-1.amazonaws.com".to_string(),
                 "gamelift-ping.us-east-1.api.aws".to_string(),
             ],
+            country: "us".to_string(),
             stable: true,
         },
     );
@@ -73,6 +113,7 @@ pub fn get_selectable_regions() -> HashMap<String, RegionInfo> {
                 "gamelift.us-east-2.amazonaws.com".to_string(),
                 "gamelift-ping.us-east-2.api.aws".to_string(),
             ],
+            country: "us".to_string(),
             stable: false,
         },
     );
@@ -83,6 +124,7 @@ pub fn get_selectable_regions() -> HashMap<String, RegionInfo> {
                 "gamelift.us-west-1.amazonaws.com".to_string(),
                 "gamelift-ping.us-west-1.api.aws".to_string(),
             ],
+            country: "us".to_string(),
             stable: true,
         },
     );
@@ -93,6 +135,7 @@ pub fn get_selectable_regions() -> HashMap<String, RegionInfo> {
                 "gamelift.us-west-2.amazonaws.com".to_string(),
                 "gamelift-ping.us-west-2.api.aws".to_string(),
             ],
+            country: "us".to_string(),
             stable: true,
         },
     );
@@ -103,6 +146,7 @@ pub fn get_selectable_regions() -> HashMap<String, RegionInfo> {
                 "gamelift.ca-central-1.amazonaws.com".to_string(),
                 "gamelift-ping.ca-central-1.api.aws".to_string(),
             ],
+            country: "ca".to_string(),
             stable: false,
         },
     );
@@ -113,6 +157,7 @@ pub fn get_selectable_regions() -> HashMap<String, RegionInfo> {
                 "gamelift.sa-east-1.amazonaws.com".to_string(),
                 "gamelift-ping.sa-east-1.api.aws".to_string(),
             ],
+            country: "br".to_string(),
             stable: true,
         },
     );
@@ -125,6 +170,7 @@ pub fn get_selectable_regions() -> HashMap<String, RegionInfo> {
                 "gamelift.ap-northeast-1.amazonaws.com".to_string(),
                 "gamelift-ping.ap-northeast-1.api.aws".to_string(),
             ],
+            country: "jp".to_string(),
             stable: true,
         },
     );
@@ -135,6 +181,7 @@ pub fn get_selectable_regions() -> HashMap<String, RegionInfo> {
                 "gamelift.ap-northeast-2.amazonaws.com".to_string(),
                 "gamelift-ping.ap-northeast-2.api.aws".to_string(),
             ],
+            country: "kr".to_string(),
             stable: true,
         },
     );
@@ -145,6 +192,7 @@ pub fn get_selectable_regions() -> HashMap<String, RegionInfo> {
                 "gamelift.ap-south-1.amazonaws.com".to_string(),
                 "gamelift-ping.ap-south-1.api.aws".to_string(),
             ],
+            country: "in".to_string(),
             stable: true,
         },
     );
@@ -155,6 +203,7 @@ pub fn get_selectable_regions() -> HashMap<String, RegionInfo> {
                 "gamelift.ap-southeast-1.amazonaws.com".to_string(),
                 "gamelift-ping.ap-southeast-1.api.aws".to_string(),
             ],
+            country: "sg".to_string(),
             stable: true,
         },
     );
@@ -165,6 +214,7 @@ pub fn get_selectable_regions() -> HashMap<String, RegionInfo> {
                 "ec2.ap-east-1.amazonaws.com".to_string(),
                 "gamelift-ping.ap-east-1.api.aws".to_string(),
             ],
+            country: "hk".to_string(),
             stable: true,
         },
     );
@@ -177,6 +227,7 @@ pub fn get_selectable_regions() -> HashMap<String, RegionInfo> {
                 "gamelift.ap-southeast-2.amazonaws.com".to_string(),
                 "gamelift-ping.ap-southeast-2.api.aws".to_string(),
             ],
+            country: "au".to_string(),
             stable: true,
         },
     );
@@ -185,7 +236,7 @@ pub fn get_selectable_regions() -> HashMap<String, RegionInfo> {
 }
 
 // These regions are always blocked regardless of user choice. DbD doesn't use them so they're not shown in the UI. They are just blocked for stability purposes.
-pub fn get_blocked_regions() -> HashMap<String, RegionInfo> {
+pub fn builtin_blocked_regions() -> HashMap<String, RegionInfo> {
     let mut regions = HashMap::new();
 
     regions.insert(
@@ -195,6 +246,7 @@ pub fn get_blocked_regions() -> HashMap<String, RegionInfo> {
                 "gamelift.af-south-1.amazonaws.com".to_string(),
                 "gamelift-ping.af-south-1.api.aws".to_string(),
             ],
+            country: "za".to_string(),
             stable: true,
         },
     );
@@ -205,6 +257,7 @@ pub fn get_blocked_regions() -> HashMap<String, RegionInfo> {
                 "gamelift.ap-northeast-3.amazonaws.com".to_string(),
                 "gamelift-ping.ap-northeast-3.api.aws".to_string(),
             ],
+            country: "jp".to_string(),
             stable: true,
         },
     );
@@ -215,6 +268,7 @@ pub fn get_blocked_regions() -> HashMap<String, RegionInfo> {
                 "gamelift.eu-north-1.amazonaws.com".to_string(),
                 "gamelift-ping.eu-north-1.api.aws".to_string(),
             ],
+            country: "se".to_string(),
             stable: true,
         },
     );
@@ -225,6 +279,7 @@ pub fn get_blocked_regions() -> HashMap<String, RegionInfo> {
                 "gamelift.eu-west-3.amazonaws.com".to_string(),
                 "gamelift-ping.eu-west-3.api.aws".to_string(),
             ],
+            country: "fr".to_string(),
             stable: true,
         },
     );
@@ -235,6 +290,7 @@ pub fn get_blocked_regions() -> HashMap<String, RegionInfo> {
                 "gamelift.eu-south-1.amazonaws.com".to_string(),
                 "gamelift-ping.eu-south-1.api.aws".to_string(),
             ],
+            country: "it".to_string(),
             stable: true,
         },
     );
@@ -245,6 +301,7 @@ pub fn get_blocked_regions() -> HashMap<String, RegionInfo> {
                 "gamelift.me-south-1.amazonaws.com".to_string(),
                 "gamelift-ping.me-south-1.api.aws".to_string(),
             ],
+            country: "bh".to_string(),
             stable: true,
         },
     );
@@ -255,6 +312,7 @@ pub fn get_blocked_regions() -> HashMap<String, RegionInfo> {
                 "gamelift.ap-southeast-5.amazonaws.com".to_string(),
                 "gamelift-ping.ap-southeast-5.api.aws".to_string(),
             ],
+            country: "my".to_string(),
             stable: true,
         },
     );
@@ -265,6 +323,7 @@ pub fn get_blocked_regions() -> HashMap<String, RegionInfo> {
                 "gamelift.ap-southeast-7.amazonaws.com".to_string(),
                 "gamelift-ping.ap-southeast-7.api.aws".to_string(),
             ],
+            country: "th".to_string(),
             stable: true,
         },
     );
@@ -275,6 +334,7 @@ pub fn get_blocked_regions() -> HashMap<String, RegionInfo> {
                 "gamelift.cn-north-1.amazonaws.com.cn".to_string(),
                 "gamelift-ping.cn-north-1.api.aws".to_string(),
             ],
+            country: "cn".to_string(),
             stable: true,
         },
     );
@@ -285,6 +345,7 @@ pub fn get_blocked_regions() -> HashMap<String, RegionInfo> {
                 "gamelift.cn-northwest-1.amazonaws.com.cn".to_string(),
                 "gamelift-ping.cn-northwest-1.api.aws".to_string(),
             ],
+            country: "cn".to_string(),
             stable: true,
         },
     );
@@ -305,3 +366,22 @@ pub fn get_group_name(region: &str) -> &'static str {
         "Asia"
     }
 }
+
+// AWS region code of a server, e.g. "eu-central-1" for "gamelift.eu-central-1.amazonaws.com"
+pub fn aws_region_code(info: &RegionInfo) -> Option<&str> {
+    info.hosts
+        .iter()
+        .find_map(|host| host.strip_prefix("gamelift.")?.strip_suffix(".amazonaws.com"))
+}
+
+// Looks a server up by its display name or AWS region code, ignoring case
+pub fn find_region(regions: &HashMap<String, RegionInfo>, query: &str) -> Option<String> {
+    let query = query.trim();
+    regions
+        .iter()
+        .find(|(name, info)| {
+            name.eq_ignore_ascii_case(query)
+                || aws_region_code(info).is_some_and(|code| code.eq_ignore_ascii_case(query))
+        })
+        .map(|(name, _)| name.clone())
+}
@@ -0,0 +1,823 @@
+use crate::N_;
+use crate::region::{ApplyMode, BlockMode};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone};
+use serde::{Deserialize, Serialize};
+use serde_yaml::{Mapping, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// Which GitHub releases are offered. Pre-releases are beta builds, e.g. ones that
+// already follow endpoint changes on the game's public test build.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum UpdateChannel {
+    #[default]
+    Stable,
+    PreRelease,
+}
+
+// Version of the config.yaml layout. Bump it together with a new entry in MIGRATIONS
+// whenever a field is renamed, moved or changes meaning.
+pub const SETTINGS_VERSION: u32 = 2;
+
+// Older snapshots are deleted once there are more than this
+const MAX_SNAPSHOTS: usize = 10;
+const SNAPSHOT_TIME_FORMAT: &str = "%Y%m%d-%H%M%S";
+
+// MIGRATIONS[n] upgrades a version n file to version n + 1
+const MIGRATIONS: &[fn(&mut Mapping)] = &[
+    // 0 -> 1: files written before versioning. Nothing was renamed, missing
+    // fields are filled in by fill_missing_fields.
+    |_| {},
+    // 1 -> 2: the single game_path became a list of named installs
+    |settings| {
+        let path = settings
+            .remove("game_path")
+            .and_then(|path| path.as_str().map(str::to_string))
+            .unwrap_or_default();
+        if path.trim().is_empty() {
+            return;
+        }
+        let mut install = Mapping::new();
+        install.insert("name".into(), "Dead by Daylight".into());
+        install.insert("path".into(), path.trim().into());
+        settings.insert("game_installs".into(), Value::Sequence(vec![install.into()]));
+        settings.insert("last_game_install".into(), "Dead by Daylight".into());
+    },
+];
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ColorScheme {
+    #[default]
+    System,
+    Light,
+    Dark,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Density {
+    #[default]
+    Comfortable,
+    Compact,
+}
+
+// Latency colors, the alternatives keep the states apart for color vision deficiencies
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LatencyPalette {
+    #[default]
+    Standard,
+    Deuteranopia,
+    Protanopia,
+    Tritanopia,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DeckMode {
+    #[default]
+    Auto,
+    On,
+    Off,
+}
+
+// Main window size and list state from the previous session. GTK 4 leaves window
+// placement to the compositor, so only the size is remembered, not the position.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WindowState {
+    pub width: i32,
+    pub height: i32,
+    pub maximized: bool,
+    pub filter_text: String,
+    pub scroll_position: f64,
+}
+
+impl Default for WindowState {
+    fn default() -> Self {
+        Self {
+            width: 405,
+            height: 585,
+            maximized: false,
+            filter_text: String::new(),
+            scroll_position: 0.0,
+        }
+    }
+}
+
+// An explicit HTTP(S) proxy, an empty URL uses the environment
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProxySettings {
+    pub url: String,
+    pub username: String,
+    pub password: String,
+}
+
+impl ProxySettings {
+    // Passes these settings on to every HTTP client created from now on
+    pub fn activate(&self) {
+        crate::net::set_proxy(&self.url, &self.username, &self.password);
+    }
+}
+
+// Where dns.rs looks names up, never through the hosts file
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DnsUpstream {
+    #[default]
+    System,
+    Udp,
+    Https,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DnsSettings {
+    pub upstream: DnsUpstream,
+    // "IP" or "IP:PORT" for Udp
+    pub server: String,
+    pub https_url: String,
+}
+
+impl DnsSettings {
+    // Passes these settings on to the shared resolver, answers cached so far are dropped
+    pub fn activate(&self) {
+        crate::dns::configure(self);
+    }
+}
+
+// Opt-in HTTP endpoint on 127.0.0.1, see api.rs. The token is created when it is
+// first turned on.
+pub const DEFAULT_API_PORT: u16 = 47821;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HttpApiSettings {
+    pub enabled: bool,
+    pub port: u16,
+    pub token: String,
+}
+
+impl Default for HttpApiSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: DEFAULT_API_PORT,
+            token: String::new(),
+        }
+    }
+}
+
+// Prometheus endpoint of the background service, see metrics.rs. Other machines can
+// only scrape it with an address other than 127.0.0.1.
+pub const DEFAULT_METRICS_PORT: u16 = 47822;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MetricsSettings {
+    pub enabled: bool,
+    pub address: String,
+    pub port: u16,
+}
+
+impl Default for MetricsSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            address: "127.0.0.1".to_string(),
+            port: DEFAULT_METRICS_PORT,
+        }
+    }
+}
+
+// What a desktop notification is about, each can be turned off on its own
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotificationKind {
+    // The region of a new match while the window is hidden
+    Match,
+    // The server of the current match got slower than the threshold
+    LatencyAlert,
+    // Applies and reverts not started from the focused window
+    ApplyResult,
+    // Their failures, always shown: nothing else would tell about them
+    Error,
+    Update,
+    // Plugins, links and the app staying in the tray; only quiet hours apply
+    Other,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotificationSettings {
+    pub match_region: bool,
+    pub latency_alerts: bool,
+    pub latency_threshold_ms: u32,
+    pub apply_results: bool,
+    pub updates: bool,
+    // Ask for the notification sound, most notification daemons are silent otherwise
+    pub sound: bool,
+    // Nothing is shown from quiet_start until quiet_end, hours of the local day
+    pub quiet_hours: bool,
+    pub quiet_start: u32,
+    pub quiet_end: u32,
+}
+
+impl Default for NotificationSettings {
+    fn default() -> Self {
+        Self {
+            match_region: true,
+            latency_alerts: true,
+            latency_threshold_ms: 150,
+            apply_results: true,
+            updates: true,
+            sound: false,
+            quiet_hours: false,
+            quiet_start: 23,
+            quiet_end: 8,
+        }
+    }
+}
+
+impl NotificationSettings {
+    // Whether a notification of this kind is shown at this hour of the day
+    pub fn allows(&self, kind: NotificationKind, hour: u32) -> bool {
+        if kind == NotificationKind::Error {
+            return true;
+        }
+        let enabled = match kind {
+            NotificationKind::Match => self.match_region,
+            NotificationKind::LatencyAlert => self.latency_alerts,
+            NotificationKind::ApplyResult => self.apply_results,
+            NotificationKind::Update => self.updates,
+            NotificationKind::Error | NotificationKind::Other => true,
+        };
+        enabled && !self.is_quiet(hour)
+    }
+
+    // Quiet hours may wrap around midnight, 23 to 8 covers the night
+    fn is_quiet(&self, hour: u32) -> bool {
+        let (start, end) = (self.quiet_start, self.quiet_end);
+        self.quiet_hours
+            && start != end
+            && if start < end {
+                (start..end).contains(&hour)
+            } else {
+                hour >= start || hour < end
+            }
+    }
+
+    pub fn allows_now(&self, kind: NotificationKind) -> bool {
+        use chrono::Timelike;
+        self.allows(kind, chrono::Local::now().hour())
+    }
+}
+
+// Opt-in sharing of anonymous server outcomes, see community.rs. Nothing is sent
+// without a server to send to.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CommunitySettings {
+    pub enabled: bool,
+    // Base URL of the stability service
+    pub endpoint: String,
+}
+
+// A game folder features like custom splash art and startup movies work on
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GameInstall {
+    pub name: String,
+    pub path: String,
+    // Wine/Proton prefix the game runs in, when it was detected
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prefix: Option<String>,
+    // What the splash art and movie options left in the folder, to notice a game
+    // update putting the originals back
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub changes: Option<GameChanges>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GameChanges {
+    // Steam build the changes were made on, unknown for the other launchers
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub build_id: Option<String>,
+    // A copy of the custom splash screen is kept in the data folder
+    #[serde(default)]
+    pub custom_splash: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub skipped_movies: Vec<String>,
+}
+
+impl GameChanges {
+    pub fn is_empty(&self) -> bool {
+        !self.custom_splash && self.skipped_movies.is_empty()
+    }
+}
+
+// A saved selection with the method it is meant to be applied with
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Profile {
+    pub name: String,
+    pub selection: Vec<String>,
+    pub apply_mode: ApplyMode,
+    pub block_mode: BlockMode,
+    pub merge_unstable: bool,
+    // The slot of the Killer/Survivor switch this profile fills, one profile per role
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub role: Option<Role>,
+}
+
+// Killers care about their own ping, survivors often follow the region of whoever hosts
+// the group, so each gets a profile of its own
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    Killer,
+    Survivor,
+}
+
+impl Role {
+    pub const ALL: [Role; 2] = [Role::Killer, Role::Survivor];
+
+    // Untranslated, for tr()
+    pub fn label(self) -> &'static str {
+        match self {
+            Role::Killer => N_("Killer"),
+            Role::Survivor => N_("Survivor"),
+        }
+    }
+}
+
+// A selection together with the method it was applied with
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AppliedSelection {
+    pub selection: Vec<String>,
+    pub apply_mode: ApplyMode,
+    pub block_mode: BlockMode,
+    pub merge_unstable: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserSettings {
+    // Layout version of the file, files without one are version 0
+    #[serde(default)]
+    pub version: u32,
+    pub apply_mode: ApplyMode,
+    pub block_mode: BlockMode,
+    pub merge_unstable: bool,
+    pub last_launched_version: String,
+    // Steam, Heroic, SD card... features ask which one to use when there are several
+    #[serde(default)]
+    pub game_installs: Vec<GameInstall>,
+    // Name of the install used last, preselected next time
+    #[serde(default)]
+    pub last_game_install: Option<String>,
+    pub auto_update_check_paused_until: Option<String>,
+    // Look for a new release in the background on every start
+    #[serde(default = "default_true")]
+    pub check_updates_on_start: bool,
+    // Show what's new in a dialog after an update, a banner is used otherwise
+    #[serde(default = "default_true")]
+    pub patch_notes_popup: bool,
+    #[serde(default)]
+    pub update_channel: UpdateChannel,
+    // Re-check for updates every this many hours while running, 0 turns it off
+    #[serde(default)]
+    pub update_check_interval_hours: u32,
+    // Release list asked when GitHub is blocked: a Codeberg/Gitea, GitLab or JSON URL
+    #[serde(default)]
+    pub update_mirror: String,
+    // Version the user chose to skip, automatic checks stay quiet about it
+    #[serde(default)]
+    pub skipped_version: Option<String>,
+    // Used for every network request instead of the *_proxy environment variables
+    #[serde(default)]
+    pub proxy: ProxySettings,
+    #[serde(default)]
+    pub dns: DnsSettings,
+    #[serde(default)]
+    pub http_api: HttpApiSettings,
+    #[serde(default)]
+    pub metrics: MetricsSettings,
+    #[serde(default)]
+    pub community: CommunitySettings,
+    #[serde(default)]
+    pub notifications: NotificationSettings,
+    #[serde(default)]
+    pub color_scheme: ColorScheme,
+    #[serde(default = "default_true")]
+    pub close_to_tray: bool,
+    #[serde(default)]
+    pub start_minimized: bool,
+    #[serde(default = "default_true")]
+    pub confirm_apply: bool,
+    #[serde(default)]
+    pub density: Density,
+    #[serde(default = "default_font_scale")]
+    pub font_scale: f64,
+    // Bolder, stronger colors with the latency severity spelled out
+    #[serde(default)]
+    pub high_contrast: bool,
+    #[serde(default)]
+    pub latency_palette: LatencyPalette,
+    #[serde(default)]
+    pub deck_mode: DeckMode,
+    // Translation code such as "de", empty to follow the system locale
+    #[serde(default)]
+    pub language: String,
+    #[serde(default)]
+    pub window_state: WindowState,
+    // Column with what each server's hostnames resolve to, for checking the blocking
+    #[serde(default)]
+    pub show_addresses: bool,
+    #[serde(default)]
+    pub profiles: Vec<Profile>,
+    // Name of the profile last loaded or saved, shown in the window and the tray
+    #[serde(default)]
+    pub active_profile: Option<String>,
+    // Apply the active profile whenever the game starts
+    #[serde(default)]
+    pub apply_on_game_launch: bool,
+    // What was written to the hosts file last, kept after a revert so it can be re-applied
+    #[serde(default)]
+    pub last_applied: Option<AppliedSelection>,
+    // Options this version doesn't know, e.g. from a newer one, written back unchanged so
+    // saving here doesn't lose them
+    #[serde(flatten)]
+    pub unknown: Mapping,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_font_scale() -> f64 {
+    1.0
+}
+
+impl Default for UserSettings {
+    fn default() -> Self {
+        Self {
+            version: SETTINGS_VERSION,
+            apply_mode: ApplyMode::Gatekeep,
+            block_mode: BlockMode::Both,
+            merge_unstable: true,
+            last_launched_version: String::new(),
+            game_installs: Vec::new(),
+            last_game_install: None,
+            auto_update_check_paused_until: None,
+            color_scheme: ColorScheme::System,
+            check_updates_on_start: true,
+            patch_notes_popup: true,
+            update_channel: UpdateChannel::Stable,
+            update_check_interval_hours: 0,
+            update_mirror: String::new(),
+            skipped_version: None,
+            proxy: ProxySettings::default(),
+            dns: DnsSettings::default(),
+            http_api: HttpApiSettings::default(),
+            metrics: MetricsSettings::default(),
+            community: CommunitySettings::default(),
+            notifications: NotificationSettings::default(),
+            close_to_tray: true,
+            start_minimized: false,
+            confirm_apply: true,
+            density: Density::Comfortable,
+            font_scale: 1.0,
+            high_contrast: false,
+            latency_palette: LatencyPalette::Standard,
+            deck_mode: DeckMode::Auto,
+            language: String::new(),
+            window_state: WindowState::default(),
+            show_addresses: false,
+            profiles: Vec::new(),
+            active_profile: None,
+            apply_on_game_launch: false,
+            last_applied: None,
+            unknown: Mapping::new(),
+        }
+    }
+}
+
+impl UserSettings {
+    // Apply mode for this run, a --apply-mode or MYC_APPLY_MODE override wins
+    pub fn is_version_skipped(&self, version: &str) -> bool {
+        self.skipped_version
+            .as_deref()
+            .is_some_and(|skipped| skipped.eq_ignore_ascii_case(version))
+    }
+
+    pub fn update_mirror(&self) -> Option<String> {
+        let mirror = self.update_mirror.trim();
+        (!mirror.is_empty()).then(|| mirror.to_string())
+    }
+
+    pub fn effective_apply_mode(&self) -> ApplyMode {
+        crate::overrides::get().apply_mode.unwrap_or(self.apply_mode)
+    }
+
+    pub fn profile(&self, name: &str) -> Option<&Profile> {
+        self.profiles.iter().find(|p| p.name == name)
+    }
+
+    // Adds the profile or replaces the one with the same name, which keeps its role
+    // unless the new one has a role of its own
+    pub fn save_profile(&mut self, mut profile: Profile) {
+        if let Some(role) = profile.role {
+            self.clear_role(role);
+        }
+        match self.profiles.iter_mut().find(|p| p.name == profile.name) {
+            Some(existing) => {
+                profile.role = profile.role.or(existing.role);
+                *existing = profile;
+            }
+            None => self.profiles.push(profile),
+        }
+    }
+
+    pub fn role_profile(&self, role: Role) -> Option<&Profile> {
+        self.profiles.iter().find(|p| p.role == Some(role))
+    }
+
+    // Makes the profile fill the role's slot, taking it from the profile that had it.
+    // None takes the profile's role away.
+    pub fn set_profile_role(&mut self, name: &str, role: Option<Role>) {
+        if let Some(role) = role {
+            self.clear_role(role);
+        }
+        if let Some(profile) = self.profiles.iter_mut().find(|p| p.name == name) {
+            profile.role = role;
+        }
+    }
+
+    fn clear_role(&mut self, role: Role) {
+        for profile in self.profiles.iter_mut().filter(|p| p.role == Some(role)) {
+            profile.role = None;
+        }
+    }
+
+    pub fn rename_profile(&mut self, name: &str, new_name: &str) -> Result<()> {
+        let new_name = new_name.trim();
+        if new_name.is_empty() {
+            anyhow::bail!("The profile name can't be empty.");
+        }
+        if new_name != name && self.profile(new_name).is_some() {
+            anyhow::bail!("A profile named \"{}\" already exists.", new_name);
+        }
+        let profile = self
+            .profiles
+            .iter_mut()
+            .find(|p| p.name == name)
+            .with_context(|| format!("No profile named \"{}\".", name))?;
+        profile.name = new_name.to_string();
+        if self.active_profile.as_deref() == Some(name) {
+            self.active_profile = Some(new_name.to_string());
+        }
+        Ok(())
+    }
+
+    pub fn delete_profile(&mut self, name: &str) {
+        self.profiles.retain(|p| p.name != name);
+        if self.active_profile.as_deref() == Some(name) {
+            self.active_profile = None;
+        }
+    }
+
+    // Copies the profile under a free "<name> (copy)" name and returns that name
+    pub fn duplicate_profile(&mut self, name: &str) -> Option<String> {
+        let mut copy = self.profile(name)?.clone();
+        copy.role = None;
+        copy.name = (1..)
+            .map(|n| match n {
+                1 => format!("{} (copy)", name),
+                n => format!("{} (copy {})", name, n),
+            })
+            .find(|candidate| self.profile(candidate).is_none())?;
+        let copy_name = copy.name.clone();
+        self.profiles.push(copy);
+        Some(copy_name)
+    }
+
+    pub fn config_dir() -> PathBuf {
+        crate::paths::config_dir()
+    }
+
+    pub fn config_file() -> PathBuf {
+        Self::config_dir().join("config.yaml")
+    }
+
+    pub fn load() -> Result<Self> {
+        let path = Self::config_file();
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read settings from {:?}", path))?;
+
+        let parsed = serde_yaml::from_str::<Value>(&content)
+            .map_err(anyhow::Error::from)
+            .and_then(|mut value| {
+                let from_version = migrate(&mut value)?;
+                let settings: UserSettings = serde_yaml::from_value(value)?;
+                Ok((from_version, settings))
+            });
+
+        let (from_version, settings) = match parsed {
+            Ok(parsed) => parsed,
+            Err(e) => {
+                // Keep the unreadable file around, the defaults are saved over it later
+                let _ = fs::copy(&path, path.with_extension("yaml.invalid"));
+                log_error!("settings", "Failed to parse {:?}, using defaults: {:#}", path, e);
+                return Err(e.context("Failed to parse settings YAML"));
+            }
+        };
+
+        if from_version < SETTINGS_VERSION {
+            if let Err(e) = take_snapshot(SnapshotReason::Migration) {
+                log_warn!("settings", "Failed to snapshot settings before upgrading: {:#}", e);
+            }
+            log_info!(
+                "settings",
+                "Upgraded settings from version {} to {}",
+                from_version,
+                SETTINGS_VERSION
+            );
+            let _ = settings.save();
+        } else if from_version > SETTINGS_VERSION {
+            log_warn!(
+                "settings",
+                "Settings were written by a newer version ({}), unknown options are kept but not used",
+                from_version
+            );
+        }
+
+        Ok(settings)
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let dir = Self::config_dir();
+        if !dir.exists() {
+            fs::create_dir_all(&dir)
+                .with_context(|| format!("Failed to create config directory {:?}", dir))?;
+        }
+
+        let path = Self::config_file();
+        let yaml = serde_yaml::to_string(self)
+            .with_context(|| "Failed to serialize settings to YAML")?;
+
+        write_private(&path, &yaml).with_context(|| format!("Failed to write settings to {:?}", path))
+    }
+
+    // Whether the file on disk is exactly what `save` would write, i.e. it was not
+    // edited since
+    pub fn matches_file(&self) -> bool {
+        let on_disk = fs::read_to_string(Self::config_file()).ok();
+        let ours = serde_yaml::to_string(self).ok();
+        on_disk.is_some() && on_disk == ours
+    }
+}
+
+// Why a snapshot of config.yaml was taken
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnapshotReason {
+    Reset,
+    Migration,
+    Import,
+    Restore,
+}
+
+impl SnapshotReason {
+    const ALL: [SnapshotReason; 4] = [Self::Reset, Self::Migration, Self::Import, Self::Restore];
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Reset => "reset",
+            Self::Migration => "migration",
+            Self::Import => "import",
+            Self::Restore => "restore",
+        }
+    }
+}
+
+// A copy of config.yaml taken right before it was overwritten
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    pub path: PathBuf,
+    pub taken: DateTime<Local>,
+    pub reason: SnapshotReason,
+}
+
+fn snapshots_dir() -> PathBuf {
+    UserSettings::config_dir().join("snapshots")
+}
+
+// Copies the current settings file into the snapshots folder before a destructive
+// change. Nothing is copied when there is no file yet.
+pub fn take_snapshot(reason: SnapshotReason) -> Result<()> {
+    let source = UserSettings::config_file();
+    if !source.exists() {
+        return Ok(());
+    }
+
+    let dir = snapshots_dir();
+    fs::create_dir_all(&dir)
+        .with_context(|| format!("Failed to create snapshot directory {:?}", dir))?;
+    let name = format!(
+        "{}-{}.yaml",
+        Local::now().format(SNAPSHOT_TIME_FORMAT),
+        reason.as_str()
+    );
+    fs::copy(&source, dir.join(&name))
+        .with_context(|| format!("Failed to snapshot settings to {:?}", name))?;
+
+    for old in snapshots().into_iter().skip(MAX_SNAPSHOTS) {
+        let _ = fs::remove_file(old.path);
+    }
+    Ok(())
+}
+
+// Snapshots on disk, newest first
+pub fn snapshots() -> Vec<Snapshot> {
+    let Ok(entries) = fs::read_dir(snapshots_dir()) else {
+        return Vec::new();
+    };
+    let mut snapshots: Vec<Snapshot> = entries
+        .flatten()
+        .filter_map(|entry| parse_snapshot_name(&entry.path()))
+        .collect();
+    snapshots.sort_by_key(|snapshot| std::cmp::Reverse(snapshot.taken));
+    snapshots
+}
+
+// "20261016-142233-reset.yaml"
+fn parse_snapshot_name(path: &Path) -> Option<Snapshot> {
+    let stem = path.file_stem()?.to_str()?;
+    let (time, reason) = stem.rsplit_once('-')?;
+    let reason = SnapshotReason::ALL.into_iter().find(|r| r.as_str() == reason)?;
+    let taken = NaiveDateTime::parse_from_str(time, SNAPSHOT_TIME_FORMAT).ok()?;
+    let taken = Local.from_local_datetime(&taken).earliest()?;
+    Some(Snapshot {
+        path: path.to_path_buf(),
+        taken,
+        reason,
+    })
+}
+
+// Puts a snapshot back as config.yaml and loads it. The settings being replaced are
+// snapshotted first, so a restore can be undone the same way.
+pub fn restore_snapshot(snapshot: &Snapshot) -> Result<UserSettings> {
+    // Read first, taking the new snapshot may prune the one being restored
+    let content = fs::read_to_string(&snapshot.path)
+        .with_context(|| format!("Failed to read snapshot {:?}", snapshot.path))?;
+    take_snapshot(SnapshotReason::Restore)?;
+    let target = UserSettings::config_file();
+    write_private(&target, &content).with_context(|| format!("Failed to write settings to {:?}", target))?;
+    UserSettings::load()
+}
+
+// Readable by the user only, the settings hold the HTTP API token and the proxy password.
+// Files written before this keep their mode when opened, so it is set again. A link in
+// place of the file is not followed, the app's file capabilities would write through it.
+fn write_private(path: &Path, content: &str) -> std::io::Result<()> {
+    use std::io::Write;
+    use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+    let mut file = fs::OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(0o600)
+        .custom_flags(libc::O_NOFOLLOW)
+        .open(path)?;
+    file.set_permissions(fs::Permissions::from_mode(0o600))?;
+    file.write_all(content.as_bytes())
+}
+
+// Runs the migrations a file still needs and returns the version it was written with
+fn migrate(value: &mut Value) -> Result<u32> {
+    let mapping = value
+        .as_mapping_mut()
+        .context("Settings file is not a YAML mapping")?;
+    let from_version = mapping
+        .get("version")
+        .and_then(Value::as_u64)
+        .map_or(0, |v| v as u32);
+
+    for (version, migration) in MIGRATIONS.iter().enumerate().skip(from_version as usize) {
+        migration(mapping);
+        mapping.insert("version".into(), Value::from(version as u32 + 1));
+    }
+    fill_missing_fields(mapping)?;
+    Ok(from_version)
+}
+
+// Fields added after a file was written take their default instead of failing the load
+fn fill_missing_fields(mapping: &mut Mapping) -> Result<()> {
+    let defaults = serde_yaml::to_value(UserSettings::default())?;
+    if let Value::Mapping(defaults) = defaults {
+        for (key, value) in defaults {
+            if !mapping.contains_key(&key) {
+                mapping.insert(key, value);
+            }
+        }
+    }
+    Ok(())
+}
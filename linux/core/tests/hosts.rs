@@ -0,0 +1,359 @@
+// The hosts file backend against a scratch hosts file and a local DNS server that
+// answers every A query with TARGET_IP. Overrides are read once per process, so all
+// tests share one file and take turns through LOCK.
+use myc_core::backend::{ActiveConfiguration, EnforcementBackend, Progress};
+use myc_core::hosts::HostsManager;
+use myc_core::region::{self, ApplyMode, BlockMode, RegionInfo};
+use myc_core::settings::AppliedSelection;
+use std::collections::HashMap;
+use std::net::{Ipv4Addr, UdpSocket};
+use std::path::PathBuf;
+use std::sync::{Mutex, MutexGuard, OnceLock};
+
+const MARKER: &str = "# --+ Make Your Choice +--";
+const ORIGINAL: &str = "127.0.0.1 localhost\n::1 localhost\n192.168.1.10 nas.lan\n";
+
+static LOCK: Mutex<()> = Mutex::new(());
+static HOSTS_FILE: OnceLock<PathBuf> = OnceLock::new();
+static TARGET_IP: Mutex<Ipv4Addr> = Mutex::new(Ipv4Addr::new(203, 0, 113, 7));
+
+struct Fixture {
+    _turn: MutexGuard<'static, ()>,
+    hosts: HostsManager,
+    regions: HashMap<String, RegionInfo>,
+    blocked: HashMap<String, RegionInfo>,
+}
+
+impl Fixture {
+    // Starts from a hosts file with the given content
+    fn new(content: &str) -> Self {
+        let turn = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let path = HOSTS_FILE.get_or_init(setup);
+        std::fs::write(path, content).unwrap();
+        *TARGET_IP.lock().unwrap() = Ipv4Addr::new(203, 0, 113, 7);
+        Self {
+            _turn: turn,
+            hosts: HostsManager::new("https://discord.gg/example".to_string()),
+            regions: region::get_selectable_regions(),
+            blocked: region::get_blocked_regions(),
+        }
+    }
+
+    fn content(&self) -> String {
+        std::fs::read_to_string(HOSTS_FILE.get().unwrap()).unwrap()
+    }
+
+    fn all_regions(&self) -> HashMap<String, RegionInfo> {
+        let mut all = self.regions.clone();
+        all.extend(self.blocked.clone());
+        all
+    }
+
+    async fn apply(&self, applied: &AppliedSelection) -> anyhow::Result<()> {
+        self.hosts.apply(&self.regions, &self.blocked, applied, &|_| {}).await
+    }
+}
+
+fn setup() -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("myc-core-tests-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let hosts_file = dir.join("hosts");
+    let dns = start_dns_server();
+    std::env::set_var("MYC_HOSTS_FILE", &hosts_file);
+    std::env::set_var("MYC_CONFIG_DIR", dir.join("config"));
+    std::env::set_var("MYC_DNS_SERVER", dns.to_string());
+    std::env::set_var("XDG_DATA_HOME", dir.join("data"));
+    hosts_file
+}
+
+// Answers every question with one A record for TARGET_IP
+fn start_dns_server() -> std::net::SocketAddr {
+    let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let addr = socket.local_addr().unwrap();
+    std::thread::spawn(move || {
+        let mut buf = [0u8; 512];
+        while let Ok((len, peer)) = socket.recv_from(&mut buf) {
+            let query = &buf[..len];
+            // Header and the question as asked
+            let mut end = 12;
+            while end < len && query[end] != 0 {
+                end += 1 + query[end] as usize;
+            }
+            let question = &query[12..(end + 5).min(len)];
+
+            let mut answer = Vec::with_capacity(64);
+            answer.extend_from_slice(&query[..2]);
+            answer.extend_from_slice(&[0x81, 0x80, 0, 1, 0, 1, 0, 0, 0, 0]);
+            answer.extend_from_slice(question);
+            answer.extend_from_slice(&[0xc0, 0x0c, 0, 1, 0, 1, 0, 0, 0, 60, 0, 4]);
+            answer.extend_from_slice(&TARGET_IP.lock().unwrap().octets());
+            let _ = socket.send_to(&answer, peer);
+        }
+    });
+    addr
+}
+
+fn selection(names: &[&str], apply_mode: ApplyMode) -> AppliedSelection {
+    AppliedSelection {
+        selection: names.iter().map(|name| name.to_string()).collect(),
+        apply_mode,
+        block_mode: BlockMode::Both,
+        merge_unstable: false,
+    }
+}
+
+fn marker_count(content: &str) -> usize {
+    content.matches(MARKER).count()
+}
+
+#[tokio::test]
+async fn gatekeep_allows_only_the_selection() {
+    let fixture = Fixture::new(ORIGINAL);
+    fixture
+        .apply(&selection(&["Europe (Ireland)"], ApplyMode::Gatekeep))
+        .await
+        .unwrap();
+
+    let content = fixture.content();
+    assert!(content.starts_with(ORIGINAL));
+    assert_eq!(marker_count(&content), 2);
+    match fixture.hosts.status(&fixture.regions) {
+        ActiveConfiguration::Gatekeep { allowed } => assert_eq!(allowed, vec!["Europe (Ireland)".to_string()]),
+        _ => panic!("Gatekeep should be active"),
+    }
+    // The hostnames of the allowed server are never blocked
+    let ireland = &fixture.regions["Europe (Ireland)"];
+    let blocked = fixture.hosts.get_blocked_hostnames();
+    assert!(ireland.hosts.iter().all(|host| !blocked.contains(&host.to_lowercase())));
+}
+
+#[tokio::test]
+async fn applying_twice_gives_the_same_file() {
+    let fixture = Fixture::new(ORIGINAL);
+    let applied = selection(&["Europe (Ireland)", "Europe (Frankfurt am Main)"], ApplyMode::Gatekeep);
+    fixture.apply(&applied).await.unwrap();
+    let first = fixture.content();
+    fixture.apply(&applied).await.unwrap();
+    assert_eq!(fixture.content(), first);
+}
+
+#[tokio::test]
+async fn changing_the_selection_replaces_the_section() {
+    let fixture = Fixture::new(ORIGINAL);
+    fixture
+        .apply(&selection(&["Europe (Ireland)"], ApplyMode::Gatekeep))
+        .await
+        .unwrap();
+    fixture
+        .apply(&selection(&["Europe (London)"], ApplyMode::Gatekeep))
+        .await
+        .unwrap();
+
+    let content = fixture.content();
+    assert_eq!(marker_count(&content), 2);
+    match fixture.hosts.status(&fixture.regions) {
+        ActiveConfiguration::Gatekeep { allowed } => assert_eq!(allowed, vec!["Europe (London)".to_string()]),
+        _ => panic!("Gatekeep should be active"),
+    }
+}
+
+#[tokio::test]
+async fn revert_restores_the_original_file() {
+    let fixture = Fixture::new(ORIGINAL);
+    for _ in 0..3 {
+        fixture
+            .apply(&selection(&["Europe (Ireland)"], ApplyMode::Gatekeep))
+            .await
+            .unwrap();
+        fixture.hosts.revert().await.unwrap();
+        assert_eq!(fixture.content(), ORIGINAL);
+    }
+    assert!(matches!(fixture.hosts.status(&fixture.regions), ActiveConfiguration::Inactive));
+}
+
+#[tokio::test]
+async fn revert_without_a_section_changes_nothing() {
+    let fixture = Fixture::new(ORIGINAL);
+    fixture.hosts.revert().await.unwrap();
+    assert_eq!(fixture.content().trim_end(), ORIGINAL.trim_end());
+}
+
+#[tokio::test]
+async fn universal_redirect_points_at_the_resolved_address() {
+    let fixture = Fixture::new(ORIGINAL);
+    let steps = Mutex::new(Vec::new());
+    let report = |step: Progress| {
+        steps.lock().unwrap().push(match step {
+            Progress::Resolving(host) => host,
+            Progress::Writing => "writing".to_string(),
+        })
+    };
+    fixture
+        .hosts
+        .apply(
+            &fixture.regions,
+            &fixture.blocked,
+            &selection(&["Europe (Ireland)"], ApplyMode::UniversalRedirect),
+            &report,
+        )
+        .await
+        .unwrap();
+
+    match fixture.hosts.status(&fixture.regions) {
+        ActiveConfiguration::UniversalRedirect { target_ip } => assert_eq!(target_ip, "203.0.113.7"),
+        _ => panic!("Universal Redirect should be active"),
+    }
+    let steps = steps.into_inner().unwrap();
+    assert_eq!(steps.last().map(String::as_str), Some("writing"));
+    assert!(steps.len() >= 2);
+}
+
+#[tokio::test]
+async fn universal_redirect_needs_exactly_one_server() {
+    let fixture = Fixture::new(ORIGINAL);
+    let result = fixture
+        .apply(&selection(&["Europe (Ireland)", "Europe (London)"], ApplyMode::UniversalRedirect))
+        .await;
+    let error = result.unwrap_err();
+    assert_eq!(myc_core::errors::classify(&error), myc_core::errors::ErrorKind::InvalidSelection);
+    assert_eq!(fixture.content(), ORIGINAL);
+}
+
+#[tokio::test]
+async fn refresh_follows_a_moved_target() {
+    let fixture = Fixture::new(ORIGINAL);
+    let applied = selection(&["Europe (Ireland)"], ApplyMode::UniversalRedirect);
+    fixture.apply(&applied).await.unwrap();
+
+    let unchanged = fixture
+        .hosts
+        .refresh_redirect(&fixture.regions, &fixture.blocked, Some(&applied))
+        .await
+        .unwrap();
+    assert_eq!(unchanged, Some(("203.0.113.7".to_string(), "203.0.113.7".to_string())));
+
+    *TARGET_IP.lock().unwrap() = Ipv4Addr::new(198, 51, 100, 23);
+    let moved = fixture
+        .hosts
+        .refresh_redirect(&fixture.regions, &fixture.blocked, Some(&applied))
+        .await
+        .unwrap();
+    assert_eq!(moved, Some(("203.0.113.7".to_string(), "198.51.100.23".to_string())));
+    match fixture.hosts.status(&fixture.regions) {
+        ActiveConfiguration::UniversalRedirect { target_ip } => assert_eq!(target_ip, "198.51.100.23"),
+        _ => panic!("Universal Redirect should still be active"),
+    }
+}
+
+#[tokio::test]
+async fn refresh_ignores_gatekeep() {
+    let fixture = Fixture::new(ORIGINAL);
+    let applied = selection(&["Europe (Ireland)"], ApplyMode::Gatekeep);
+    fixture.apply(&applied).await.unwrap();
+    let before = fixture.content();
+    let refreshed = fixture
+        .hosts
+        .refresh_redirect(&fixture.regions, &fixture.blocked, Some(&applied))
+        .await
+        .unwrap();
+    assert_eq!(refreshed, None);
+    assert_eq!(fixture.content(), before);
+}
+
+#[tokio::test]
+async fn conflicts_outside_the_section_are_found_and_cleared() {
+    let ireland_host = region::get_selectable_regions()["Europe (Ireland)"].hosts[0].clone();
+    let conflict = format!("10.0.0.1 {}", ireland_host);
+    let fixture = Fixture::new(&format!("{}{}\n", ORIGINAL, conflict));
+    fixture
+        .apply(&selection(&["Europe (Ireland)"], ApplyMode::Gatekeep))
+        .await
+        .unwrap();
+
+    let conflicts = fixture.hosts.detect_conflicts(&fixture.all_regions()).unwrap();
+    assert_eq!(conflicts, vec![conflict.clone()]);
+
+    fixture.hosts.clear_conflicting_entries(&conflicts).await.unwrap();
+    let content = fixture.content();
+    assert!(!content.contains(&conflict));
+    assert!(content.starts_with(ORIGINAL));
+    assert_eq!(marker_count(&content), 2);
+    assert!(fixture.hosts.detect_conflicts(&fixture.all_regions()).unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn own_entries_are_not_conflicts() {
+    let fixture = Fixture::new(ORIGINAL);
+    fixture
+        .apply(&selection(&["Europe (London)"], ApplyMode::Gatekeep))
+        .await
+        .unwrap();
+    assert!(fixture.hosts.detect_conflicts(&fixture.all_regions()).unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn an_unterminated_section_is_replaced() {
+    // A section whose closing marker was lost, e.g. by a hand edit
+    let fixture = Fixture::new(&format!("{}\n{}\n0.0.0.0 stale.example\n", ORIGINAL, MARKER));
+    assert!(matches!(fixture.hosts.status(&fixture.regions), ActiveConfiguration::Inactive));
+
+    fixture
+        .apply(&selection(&["Europe (Ireland)"], ApplyMode::Gatekeep))
+        .await
+        .unwrap();
+    let content = fixture.content();
+    assert!(content.starts_with(ORIGINAL));
+    assert_eq!(marker_count(&content), 2);
+    assert!(!content.contains("stale.example"));
+
+    fixture.hosts.revert().await.unwrap();
+    assert_eq!(fixture.content(), ORIGINAL);
+}
+
+#[tokio::test]
+async fn entries_after_the_section_are_kept() {
+    let fixture = Fixture::new(ORIGINAL);
+    fixture
+        .apply(&selection(&["Europe (Ireland)"], ApplyMode::Gatekeep))
+        .await
+        .unwrap();
+    let with_later_entry = format!("{}10.1.1.1 printer.lan\n", fixture.content());
+    std::fs::write(HOSTS_FILE.get().unwrap(), &with_later_entry).unwrap();
+
+    fixture
+        .apply(&selection(&["Europe (London)"], ApplyMode::Gatekeep))
+        .await
+        .unwrap();
+    assert!(fixture.content().ends_with("10.1.1.1 printer.lan\n"));
+
+    fixture.hosts.revert().await.unwrap();
+    assert_eq!(fixture.content(), format!("{}10.1.1.1 printer.lan\n", ORIGINAL));
+}
+
+#[tokio::test]
+async fn plan_writes_nothing() {
+    let fixture = Fixture::new(ORIGINAL);
+    let plan = fixture
+        .hosts
+        .plan(
+            &fixture.regions,
+            &fixture.blocked,
+            &selection(&["Europe (Ireland)"], ApplyMode::Gatekeep),
+        )
+        .await
+        .unwrap();
+    assert!(!plan.is_empty());
+    assert_eq!(fixture.content(), ORIGINAL);
+}
+
+#[tokio::test]
+async fn snapshot_and_restore_round_trip() {
+    let fixture = Fixture::new(ORIGINAL);
+    let snapshot = fixture.hosts.snapshot().await.unwrap();
+    fixture
+        .apply(&selection(&["Europe (Ireland)"], ApplyMode::Gatekeep))
+        .await
+        .unwrap();
+    fixture.hosts.restore(snapshot).await.unwrap();
+    assert_eq!(fixture.content(), ORIGINAL);
+}
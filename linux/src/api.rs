@@ -0,0 +1,247 @@
+use crate::control::{self, Command, Requests};
+use crate::errors::ErrorKind;
+use anyhow::{bail, Context, Result};
+use serde_json::{json, Value};
+use std::net::Ipv4Addr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::runtime::Runtime;
+use tokio::sync::oneshot;
+
+// Opt-in HTTP endpoint on 127.0.0.1 for Stream Deck plugins, scripts and dashboards.
+// Every request needs the token from the settings as "Authorization: Bearer <token>",
+// never in the URL where logs and history keep it. There are no CORS headers, so web
+// pages open in a browser can't call it:
+//
+//   GET  /status      {"mode": "gatekeep", "allowed": [...], "target_ip": null}
+//   GET  /latencies   {"latencies": {"Europe (Ireland)": 31, ...}}, null when unreachable
+//   GET  /match       {"ip": "...", "region": "..."} of the last match server
+//   POST /apply       {"regions": ["eu-central-1"], "mode": "gatekeep"} or {"profile": "EU"}
+//   POST /revert
+//
+// Failures answer {"error": "...", "kind": "..."} with a kind from errors.rs
+
+const MAX_HEAD: usize = 16 * 1024;
+const MAX_BODY: usize = 64 * 1024;
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+// Pause after a failed accept, e.g. out of file descriptors, instead of spinning
+const ACCEPT_RETRY_DELAY: Duration = Duration::from_millis(250);
+
+pub struct ApiServer {
+    port: u16,
+    token: Arc<Mutex<String>>,
+    task: tokio::task::JoinHandle<()>,
+}
+
+impl ApiServer {
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    // Takes effect for the next request, without reopening the port
+    pub fn set_token(&self, token: &str) {
+        *self.token.lock().unwrap() = token.to_string();
+    }
+}
+
+impl Drop for ApiServer {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+// A random token for a new installation or when the user asks for a new one
+pub fn new_token() -> String {
+    glib::uuid_string_random().replace('-', "")
+}
+
+pub fn start(runtime: &Runtime, port: u16, token: &str, requests: Requests) -> Result<ApiServer> {
+    // Bound here so a port in use is reported right away
+    let listener = std::net::TcpListener::bind((Ipv4Addr::LOCALHOST, port))
+        .with_context(|| format!("Failed to listen on 127.0.0.1:{}", port))?;
+    listener.set_nonblocking(true)?;
+    let listener = {
+        let _guard = runtime.enter();
+        TcpListener::from_std(listener)?
+    };
+
+    let token = Arc::new(Mutex::new(token.to_string()));
+    let task_token = token.clone();
+    let task = runtime.spawn(async move {
+        loop {
+            let stream = match listener.accept().await {
+                Ok((stream, _)) => stream,
+                Err(e) => {
+                    log_warn!("api", "Failed to accept a connection: {}", e);
+                    tokio::time::sleep(ACCEPT_RETRY_DELAY).await;
+                    continue;
+                }
+            };
+            let token = task_token.lock().unwrap().clone();
+            let requests = requests.clone();
+            tokio::spawn(async move {
+                if let Err(e) = serve(stream, &token, &requests).await {
+                    log_warn!("api", "{:#}", e);
+                }
+            });
+        }
+    });
+
+    log_info!("api", "Listening on http://127.0.0.1:{}", port);
+    Ok(ApiServer { port, token, task })
+}
+
+struct Request {
+    method: String,
+    path: String,
+    authorization: Option<String>,
+    body: Vec<u8>,
+}
+
+async fn serve(mut stream: TcpStream, token: &str, requests: &Requests) -> Result<()> {
+    let request = tokio::time::timeout(READ_TIMEOUT, read_request(&mut stream))
+        .await
+        .context("Timed out reading a request")??;
+    let (status, body) = respond(&request, token, requests).await;
+    write_response(&mut stream, status, &body).await
+}
+
+async fn read_request(stream: &mut TcpStream) -> Result<Request> {
+    let mut buffer = Vec::new();
+    let mut chunk = [0u8; 4096];
+    let head_end = loop {
+        if let Some(pos) = buffer.windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos;
+        }
+        if buffer.len() > MAX_HEAD {
+            bail!("Request head too large");
+        }
+        let read = stream.read(&mut chunk).await?;
+        if read == 0 {
+            bail!("Connection closed before the request was complete");
+        }
+        buffer.extend_from_slice(&chunk[..read]);
+    };
+
+    let head = String::from_utf8_lossy(&buffer[..head_end]).into_owned();
+    let mut lines = head.split("\r\n");
+    let mut request_line = lines.next().unwrap_or_default().split_whitespace();
+    let method = request_line.next().unwrap_or_default().to_string();
+    let target = request_line.next().unwrap_or_default();
+    let path = target.split_once('?').map_or(target, |(path, _)| path);
+
+    let mut content_length = 0;
+    let mut authorization = None;
+    for line in lines {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+        if name.eq_ignore_ascii_case("content-length") {
+            content_length = value.parse().context("Invalid Content-Length")?;
+        } else if name.eq_ignore_ascii_case("authorization") {
+            authorization = value.strip_prefix("Bearer ").map(|t| t.trim().to_string());
+        }
+    }
+    if content_length > MAX_BODY {
+        bail!("Request body too large");
+    }
+
+    let mut body = buffer[head_end + 4..].to_vec();
+    while body.len() < content_length {
+        let read = stream.read(&mut chunk).await?;
+        if read == 0 {
+            break;
+        }
+        body.extend_from_slice(&chunk[..read]);
+    }
+    body.truncate(content_length);
+
+    Ok(Request {
+        method,
+        path: path.to_string(),
+        authorization,
+        body,
+    })
+}
+
+// Compares in constant time so the token can't be guessed byte by byte
+fn token_matches(given: &str, token: &str) -> bool {
+    !token.is_empty()
+        && given.len() == token.len()
+        && given.bytes().zip(token.bytes()).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+async fn respond(request: &Request, token: &str, requests: &Requests) -> (u16, Value) {
+    if !request.authorization.as_deref().is_some_and(|given| token_matches(given, token)) {
+        return (401, json!({ "error": "Missing or wrong token" }));
+    }
+
+    let command = match (request.method.as_str(), request.path.as_str()) {
+        ("GET", "/status") => Command::GetStatus,
+        ("GET", "/latencies") => Command::GetLatencies,
+        ("GET", "/match") => Command::GetCurrentServer,
+        ("POST", "/revert") => Command::Revert,
+        ("POST", "/apply") => match apply_command(&request.body) {
+            Ok(command) => command,
+            Err(message) => return (400, control::error_json(&control::Error::new(ErrorKind::Usage, message))),
+        },
+        (_, "/status" | "/latencies" | "/match" | "/revert" | "/apply") => {
+            return (405, json!({ "error": "Method not allowed" }));
+        }
+        _ => return (404, json!({ "error": "Not found" })),
+    };
+
+    let (reply_tx, reply_rx) = oneshot::channel();
+    if requests.send((command, reply_tx)).is_err() {
+        return (503, json!({ "error": "The application is shutting down" }));
+    }
+    match reply_rx.await {
+        Ok(Ok(reply)) => (200, control::reply_json(reply)),
+        Ok(Err(error)) => (400, control::error_json(&error)),
+        Err(_) => (503, json!({ "error": "The application is shutting down" })),
+    }
+}
+
+fn apply_command(body: &[u8]) -> std::result::Result<Command, String> {
+    let body: Value = serde_json::from_slice(body).map_err(|e| format!("Invalid JSON body: {}", e))?;
+    if let Some(profile) = body.get("profile").and_then(Value::as_str) {
+        return Ok(Command::ApplyProfile(profile.to_string()));
+    }
+    let regions: Vec<String> = body
+        .get("regions")
+        .and_then(Value::as_array)
+        .ok_or("Expected \"regions\" or \"profile\"")?
+        .iter()
+        .filter_map(|region| region.as_str().map(str::to_string))
+        .collect();
+    let mode = body.get("mode").and_then(Value::as_str).unwrap_or_default();
+    Ok(Command::ApplySelection(regions, mode.to_string()))
+}
+
+async fn write_response(stream: &mut TcpStream, status: u16, body: &Value) -> Result<()> {
+    let reason = match status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        _ => "Service Unavailable",
+    };
+    let body = body.to_string();
+    let response = format!(
+        "HTTP/1.1 {} {}\r\n\
+         Content-Type: application/json\r\n\
+         Content-Length: {}\r\n\
+         Connection: close\r\n\r\n{}",
+        status,
+        reason,
+        body.len(),
+        body
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await?;
+    Ok(())
+}
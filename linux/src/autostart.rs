@@ -0,0 +1,54 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::PathBuf;
+
+// Command line option passed by the autostart entry so the app comes up hidden in the tray
+pub const MINIMIZED_OPTION: &str = "minimized";
+
+pub fn autostart_file() -> PathBuf {
+    crate::paths::autostart_file()
+}
+
+pub fn is_enabled() -> bool {
+    autostart_file().exists()
+}
+
+// Writes (or rewrites) the login entry pointing at the running executable
+pub fn enable(start_minimized: bool) -> Result<()> {
+    let exe = std::env::current_exe().context("Failed to get executable path")?;
+    let mut exec = format!("\"{}\"", exe.to_string_lossy().replace('"', "\\\""));
+    if start_minimized {
+        exec.push_str(&format!(" --{}", MINIMIZED_OPTION));
+    }
+
+    let entry = format!(
+        "[Desktop Entry]\n\
+         Type=Application\n\
+         Name=Make Your Choice\n\
+         Comment=DbD Server Region Selector\n\
+         Exec={}\n\
+         Icon=make-your-choice\n\
+         Terminal=false\n\
+         X-GNOME-Autostart-enabled=true\n",
+        exec
+    );
+
+    let path = autostart_file();
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create autostart directory {:?}", dir))?;
+    }
+    fs::write(&path, entry)
+        .with_context(|| format!("Failed to write autostart entry {:?}", path))?;
+
+    Ok(())
+}
+
+pub fn disable() -> Result<()> {
+    let path = autostart_file();
+    if path.exists() {
+        fs::remove_file(&path)
+            .with_context(|| format!("Failed to remove autostart entry {:?}", path))?;
+    }
+    Ok(())
+}
@@ -0,0 +1,914 @@
+use crate::backend::{ActiveConfiguration, EnforcementBackend};
+use crate::completions;
+use crate::errors::{self, ErrorKind};
+use crate::history::{self, Trigger};
+use crate::hooks;
+use crate::hosts::{self, HostsManager};
+use crate::i18n::{tr, trf};
+use crate::region::{self, ApplyMode, BlockMode, RegionInfo};
+use crate::service;
+use crate::vpn;
+use crate::settings::{AppliedSelection, UserSettings};
+use anyhow::{Context, Result};
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+// Headless subcommands, e.g. `make-your-choice apply --regions eu-central-1,eu-west-1`.
+// They run without GTK and exit with 0 on success, otherwise with the exit code of the
+// error kind (errors.rs), 2 when the command line was wrong. With --json the result is
+// printed as one JSON object on stdout instead, failures as {"error": "...", "kind": "..."},
+// for status bars and other tools.
+pub const COMMANDS: &[&str] = &[
+    "apply",
+    "revert",
+    "refresh",
+    "status",
+    "ping",
+    "list-regions",
+    "hostnames",
+    "history",
+    "export-script",
+    "completions",
+    "wrap",
+    "service",
+    "help",
+];
+
+// Name of a symlink to the binary that behaves like `make-your-choice wrap`, short
+// enough for Steam's launch options: `myc-wrap --profile EU %command%`
+pub const WRAP_PROGRAM: &str = "myc-wrap";
+
+// Run-wide options from overrides.rs, accepted anywhere on the command line
+const OVERRIDE_OPTIONS: &[(&str, bool)] = &[
+    (crate::overrides::HOSTS_FILE_OPTION, true),
+    (crate::overrides::CONFIG_DIR_OPTION, true),
+    (crate::overrides::APPLY_MODE_OPTION, true),
+    (crate::overrides::OFFLINE_OPTION, false),
+    (crate::overrides::DNS_SERVER_OPTION, true),
+    (crate::overrides::DEMO_OPTION, false),
+];
+
+// The subcommand and its arguments, when the binary wasn't started to open the window
+pub fn command(args: &[String]) -> Option<(&str, &[String])> {
+    let invoked_as = args
+        .first()
+        .and_then(|arg| std::path::Path::new(arg).file_name())
+        .and_then(|name| name.to_str());
+    if invoked_as == Some(WRAP_PROGRAM) {
+        return Some(("wrap", &args[1..]));
+    }
+    let command = args.get(1).filter(|arg| COMMANDS.contains(&arg.as_str()))?;
+    Some((command.as_str(), &args[2..]))
+}
+
+struct Usage(String);
+
+#[derive(Default)]
+struct Options {
+    regions: Vec<String>,
+    mode: Option<ApplyMode>,
+    block: Option<BlockMode>,
+    merge_unstable: Option<bool>,
+    profile: Option<String>,
+    // Refuse to apply while other entries point the game's servers elsewhere
+    fail_on_conflict: bool,
+    // Print what apply would write instead of writing it
+    dry_run: bool,
+    // hostnames prints the current addresses instead of the names
+    resolve: bool,
+    json: bool,
+    shell: Option<String>,
+    // enable, disable or status for the background service
+    action: Option<String>,
+    // Program and arguments started by wrap
+    command_line: Vec<String>,
+}
+
+pub fn run(command: &str, args: &[String]) -> i32 {
+    let options = match parse(command, args) {
+        Ok(options) => options,
+        Err(Usage(message)) => {
+            eprintln!("{}", message);
+            eprintln!("{}", trf("Run \"{} help\" for usage.", &[&program()]));
+            return ErrorKind::Usage.exit_code();
+        }
+    };
+
+    if command == "wrap" {
+        return wrap(&options);
+    }
+
+    let json = options.json;
+    let result = match command {
+        "apply" => apply(&options),
+        "revert" => revert(json),
+        "refresh" => refresh(json),
+        "status" => status(json),
+        "ping" => ping(json),
+        "list-regions" => list_regions(json),
+        "hostnames" => hostnames(&options.regions, options.resolve, json),
+        "history" => history(json),
+        "export-script" => export_script(json),
+        "completions" => completions(options.shell.as_deref().unwrap_or_default()),
+        "service" => service(options.action.as_deref().unwrap_or_default(), json),
+        _ => {
+            print_help();
+            Ok(())
+        }
+    };
+
+    match result {
+        Ok(()) => 0,
+        Err(e) => {
+            let kind = errors::classify(&e);
+            let hint = kind.remediation().map(tr);
+            if json {
+                print_json(&json!({
+                    "error": format!("{:#}", e),
+                    "kind": kind.id(),
+                    "summary": tr(kind.summary()),
+                    "hint": hint,
+                }));
+            } else if kind == ErrorKind::Failed {
+                eprintln!("{} {:#}", tr("Error:"), e);
+            } else {
+                // The message is the details, the summary and the hint are translated
+                eprintln!("{} {}", tr("Error:"), tr(kind.summary()));
+                eprintln!("  {:#}", e);
+                if let Some(hint) = hint {
+                    eprintln!("{} {}", tr("Hint:"), hint);
+                }
+            }
+            kind.exit_code()
+        }
+    }
+}
+
+fn parse(command: &str, args: &[String]) -> Result<Options, Usage> {
+    let mut options = Options::default();
+    let mut args = args.iter();
+
+    while let Some(arg) = args.next() {
+        // Everything from the game's command on belongs to the game
+        if command == "wrap" && (arg == "--" || !arg.starts_with("--")) {
+            if arg != "--" {
+                options.command_line.push(arg.clone());
+            }
+            options.command_line.extend(args.cloned());
+            break;
+        }
+        let Some(flag) = arg.strip_prefix("--") else {
+            if command == "completions" && options.shell.is_none() {
+                options.shell = Some(arg.clone());
+                continue;
+            }
+            if command == "service" && options.action.is_none() {
+                options.action = Some(arg.clone());
+                continue;
+            }
+            return Err(Usage(trf("Unexpected argument \"{}\"", &[arg])));
+        };
+        let (name, inline) = match flag.split_once('=') {
+            Some((name, value)) => (name, Some(value.to_string())),
+            None => (flag, None),
+        };
+
+        if let Some((_, takes_value)) = OVERRIDE_OPTIONS.iter().find(|(option, _)| *option == name) {
+            if *takes_value && inline.is_none() {
+                args.next();
+            }
+            continue;
+        }
+
+        let mut value = || {
+            inline
+                .clone()
+                .or_else(|| args.next().cloned())
+                .ok_or_else(|| Usage(trf("--{} needs a value", &[&name])))
+        };
+
+        let applies = command == "apply" || command == "wrap";
+        match (applies, name) {
+            (_, "json") if command != "wrap" => options.json = true,
+            (_, "regions") if applies || command == "hostnames" => {
+                options.regions = value()?
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|region| !region.is_empty())
+                    .map(str::to_string)
+                    .collect();
+            }
+            (_, "resolve") if command == "hostnames" => options.resolve = true,
+            (true, "profile") => options.profile = Some(value()?),
+            (true, "mode") => options.mode = Some(parse_mode(&value()?)?),
+            (true, "block") => options.block = Some(parse_block(&value()?)?),
+            (true, "merge-unstable") => options.merge_unstable = Some(true),
+            (true, "no-merge-unstable") => options.merge_unstable = Some(false),
+            (true, "fail-on-conflict") => options.fail_on_conflict = true,
+            (_, "dry-run") if command == "apply" => options.dry_run = true,
+            _ => return Err(Usage(trf("Unknown option \"{}\" for {}", &[arg, &command]))),
+        }
+    }
+
+    if (command == "apply" || command == "wrap") && options.regions.is_empty() && options.profile.is_none() {
+        return Err(Usage(trf("{} needs --regions or --profile", &[&command])));
+    }
+    if command == "wrap" && options.command_line.is_empty() {
+        return Err(Usage(tr("wrap needs the command that starts the game, e.g. %command% in Steam")));
+    }
+    if command == "completions"
+        && !options.shell.as_deref().is_some_and(|shell| completions::SHELLS.contains(&shell))
+    {
+        return Err(Usage(trf("completions needs a shell: {}", &[&completions::SHELLS.join(", ")])));
+    }
+    if command == "service"
+        && !options.action.as_deref().is_some_and(|action| service::ACTIONS.contains(&action))
+    {
+        return Err(Usage(trf("service needs one of: {}", &[&service::ACTIONS.join(", ")])));
+    }
+    Ok(options)
+}
+
+fn parse_mode(mode: &str) -> Result<ApplyMode, Usage> {
+    ApplyMode::from_id(mode)
+        .ok_or_else(|| Usage(trf("Unknown mode \"{}\", use gatekeep or universal-redirect", &[&mode])))
+}
+
+fn parse_block(block: &str) -> Result<BlockMode, Usage> {
+    match block.to_ascii_lowercase().as_str() {
+        "both" => Ok(BlockMode::Both),
+        "ping" => Ok(BlockMode::OnlyPing),
+        "service" => Ok(BlockMode::OnlyService),
+        _ => Err(Usage(trf("Unknown block mode \"{}\", use both, ping or service", &[&block]))),
+    }
+}
+
+fn block_id(block: BlockMode) -> &'static str {
+    match block {
+        BlockMode::Both => "both",
+        BlockMode::OnlyPing => "ping",
+        BlockMode::OnlyService => "service",
+    }
+}
+
+fn print_json(value: &Value) {
+    println!("{}", value);
+}
+
+// {"name": ..., "code": ...} for a server
+fn region_json(name: &str, regions: &HashMap<String, RegionInfo>) -> Value {
+    json!({
+        "name": name,
+        "code": regions.get(name).and_then(region::aws_region_code),
+    })
+}
+
+// Applying, reverting and status go through the backend, hosts file details like
+// export-script need the hosts file itself
+fn backend() -> Arc<dyn EnforcementBackend> {
+    crate::backend::current(crate::DISCORD_URL)
+}
+
+fn hosts_manager() -> HostsManager {
+    HostsManager::new(crate::DISCORD_URL.to_string())
+}
+
+// The backend is async, one command runs one operation at a time
+fn block_on<T>(future: impl std::future::Future<Output = Result<T>>) -> Result<T> {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .context("Failed to create tokio runtime")?
+        .block_on(future)
+}
+
+// What apply_selection wrote, for the messages and --json
+struct Applied {
+    selection: AppliedSelection,
+    // Stable servers allowed in addition because only unstable ones were picked
+    merged: Vec<String>,
+    conflicts: Vec<String>,
+    // What --dry-run would have put in place; nothing was changed
+    plan: Option<Vec<String>>,
+}
+
+// Same steps as the Apply button, without the conflict prompt: conflicting entries are
+// reported and left alone. A profile supplies the servers and methods, flags override them.
+fn apply_selection(options: &Options, regions: &HashMap<String, RegionInfo>) -> Result<Applied> {
+    let blocked_regions = region::get_blocked_regions();
+    let mut settings = UserSettings::load().unwrap_or_default();
+
+    let profile = match &options.profile {
+        Some(name) => Some(
+            settings
+                .profile(name)
+                .cloned()
+                .ok_or_else(|| {
+                    errors::error(ErrorKind::InvalidSelection, trf("There is no profile named \"{}\"", &[name]))
+                })?,
+        ),
+        None => None,
+    };
+
+    let mut selected: HashSet<String> = profile
+        .iter()
+        .flat_map(|profile| profile.selection.iter())
+        .filter(|name| regions.contains_key(*name))
+        .cloned()
+        .collect();
+    for query in &options.regions {
+        let name = region::find_region(regions, query).ok_or_else(|| {
+            errors::error(ErrorKind::InvalidSelection, trf("Unknown server \"{}\", see list-regions", &[query]))
+        })?;
+        selected.insert(name);
+    }
+
+    let apply_mode = options
+        .mode
+        .or(profile.as_ref().map(|p| p.apply_mode))
+        .unwrap_or_else(|| settings.effective_apply_mode());
+    let block_mode = options
+        .block
+        .or(profile.as_ref().map(|p| p.block_mode))
+        .unwrap_or(settings.block_mode);
+    let merge_unstable = options
+        .merge_unstable
+        .or(profile.as_ref().map(|p| p.merge_unstable))
+        .unwrap_or(settings.merge_unstable);
+
+    let backend = backend();
+    let mut all_regions = regions.clone();
+    all_regions.extend(blocked_regions.clone());
+    let conflicts = backend.detect_conflicts(&all_regions).unwrap_or_default();
+    if options.fail_on_conflict && !conflicts.is_empty() {
+        return Err(errors::error(
+            ErrorKind::ConflictDetected,
+            trf(
+                "{} entries outside the Make Your Choice section point game servers elsewhere:\n{}",
+                &[&conflicts.len(), &conflicts.join("\n")],
+            ),
+        ));
+    }
+
+    if apply_mode == ApplyMode::UniversalRedirect && selected.len() != 1 {
+        return Err(errors::error(
+            ErrorKind::InvalidSelection,
+            tr("Please select only one server when using Universal Redirect mode."),
+        ));
+    }
+    let mut selection: Vec<String> = selected.iter().cloned().collect();
+    selection.sort();
+    let selection = AppliedSelection {
+        selection,
+        apply_mode,
+        block_mode,
+        merge_unstable,
+    };
+    let mut merged: Vec<String> = Vec::new();
+    if apply_mode == ApplyMode::Gatekeep {
+        let allowed = hosts::gatekeep_allowed_regions(regions, &selected, merge_unstable);
+        merged = allowed.difference(&selected).cloned().collect();
+        merged.sort();
+    }
+    if options.dry_run {
+        let plan = block_on(backend.plan(regions, &blocked_regions, &selection))?;
+        return Ok(Applied {
+            selection,
+            merged,
+            conflicts,
+            plan: Some(plan),
+        });
+    }
+
+    let hook_data = hooks::apply_data(apply_mode.id(), &selection.selection);
+    hooks::run(hooks::Event::PreApply, hook_data.clone());
+    block_on(backend.apply(regions, &blocked_regions, &selection, &|_| {}))?;
+    hooks::run(hooks::Event::PostApply, hook_data);
+    history::record_apply(&selection, backend.id(), Trigger::CommandLine);
+
+    settings.last_applied = Some(selection.clone());
+    if let Err(e) = settings.save() {
+        log_warn!("cli", "Failed to remember the applied selection: {:#}", e);
+    }
+
+    Ok(Applied {
+        selection,
+        merged,
+        conflicts,
+        plan: None,
+    })
+}
+
+fn apply(options: &Options) -> Result<()> {
+    let regions = region::get_selectable_regions();
+    let applied = apply_selection(options, &regions)?;
+    let AppliedSelection {
+        apply_mode,
+        block_mode,
+        merge_unstable,
+        ..
+    } = applied.selection;
+
+    if let Some(plan) = &applied.plan {
+        if options.json {
+            print_json(&json!({
+                "dry_run": true,
+                "mode": apply_mode.id(),
+                "selected": applied.selection.selection,
+                "merged": applied.merged,
+                "conflicts": applied.conflicts,
+                "plan": plan,
+            }));
+        } else {
+            for line in plan {
+                println!("{}", line);
+            }
+        }
+        return Ok(());
+    }
+
+    if options.json {
+        let mut blocked_hosts: Vec<String> = hosts_manager().get_blocked_hostnames().into_iter().collect();
+        blocked_hosts.sort();
+        let target_ip = match backend().status(&regions) {
+            ActiveConfiguration::UniversalRedirect { target_ip } => Some(target_ip),
+            _ => None,
+        };
+        let names = |names: &[String]| -> Vec<Value> {
+            names.iter().map(|name| region_json(name, &regions)).collect()
+        };
+        print_json(&json!({
+            "mode": apply_mode.id(),
+            "block_mode": block_id(block_mode),
+            "merge_unstable": merge_unstable,
+            "selected": names(&applied.selection.selection),
+            "merged": names(&applied.merged),
+            "blocked_hosts": blocked_hosts,
+            "target_ip": target_ip,
+            "conflicts": applied.conflicts,
+        }));
+        return Ok(());
+    }
+
+    if !applied.conflicts.is_empty() {
+        eprintln!(
+            "{}",
+            trf(
+                "Warning: {} entries outside the Make Your Choice section also point game servers elsewhere and may override the selection.",
+                &[&applied.conflicts.len()],
+            )
+        );
+    }
+    for region in &applied.merged {
+        println!("{}", trf("Also allowed as a stable alternative: {}", &[region]));
+    }
+    println!(
+        "{}",
+        trf(
+            "Hosts file updated ({} mode). Restart the game for changes to take effect.",
+            &[&format!("{:?}", apply_mode)],
+        )
+    );
+    Ok(())
+}
+
+// Applies, runs the game and puts the hosts file back once it exits, for Steam launch
+// options. The game starts even when applying failed, and the wrapper exits with the
+// game's status.
+fn wrap(options: &Options) -> i32 {
+    let regions = region::get_selectable_regions();
+    let backend = backend();
+    // Like Undo in the window: a selection applied before the launch comes back
+    // afterwards, only without one the section is simply removed
+    let previous = match backend.status(&regions) {
+        ActiveConfiguration::Inactive => None,
+        _ => match block_on(backend.snapshot()) {
+            Ok(snapshot) => Some((snapshot, UserSettings::load().ok().and_then(|s| s.last_applied))),
+            Err(e) => {
+                log_warn!("wrap", "Failed to save the hosts file, it is reverted afterwards: {:#}", e);
+                None
+            }
+        },
+    };
+    let applied = match apply_selection(options, &regions) {
+        Ok(applied) => {
+            log_info!("wrap", "Applied {} for this launch", applied.selection.selection.join(", "));
+            true
+        }
+        Err(e) => {
+            log_error!("wrap", "Starting the game without changing servers: {:#}", e);
+            false
+        }
+    };
+
+    // Stopping the game from Steam signals the whole group, stay alive to clean up
+    ignore_termination_signals();
+
+    let (program, arguments) = options.command_line.split_first().unwrap();
+    let code = match std::process::Command::new(program).args(arguments).status() {
+        Ok(status) => status.code().unwrap_or(1),
+        Err(e) => {
+            log_error!("wrap", "Failed to start {}: {}", program, e);
+            127
+        }
+    };
+
+    if !applied {
+        return code;
+    }
+    match previous {
+        Some((snapshot, previous_applied)) => match block_on(backend.restore(snapshot)) {
+            Ok(()) => {
+                log_info!("wrap", "Game exited, previous hosts file restored");
+                if let Some(previous_applied) = previous_applied {
+                    let hook_data = hooks::apply_data(previous_applied.apply_mode.id(), &previous_applied.selection);
+                    hooks::run(hooks::Event::PostApply, hook_data);
+                    history::record_apply(&previous_applied, backend.id(), Trigger::CommandLine);
+                    let mut settings = UserSettings::load().unwrap_or_default();
+                    settings.last_applied = Some(previous_applied);
+                    if let Err(e) = settings.save() {
+                        log_warn!("wrap", "Failed to remember the restored selection: {:#}", e);
+                    }
+                }
+            }
+            Err(e) => log_error!("wrap", "Failed to restore the hosts file: {:#}", e),
+        },
+        None => match block_on(backend.revert()) {
+            Ok(()) => {
+                log_info!("wrap", "Game exited, hosts file reverted");
+                hooks::run(hooks::Event::Revert, json!({}));
+                history::record_revert(backend.id(), Trigger::CommandLine);
+            }
+            Err(e) => log_error!("wrap", "Failed to revert the hosts file: {:#}", e),
+        },
+    }
+    code
+}
+
+// Handled rather than ignored, so the game itself still gets the default behaviour
+fn ignore_termination_signals() {
+    extern "C" fn noop(_: libc::c_int) {}
+    for signal in [libc::SIGINT, libc::SIGTERM, libc::SIGHUP] {
+        unsafe {
+            libc::signal(signal, noop as extern "C" fn(libc::c_int) as libc::sighandler_t);
+        }
+    }
+}
+
+// Nothing to do is a success too, so a timer can run it whatever is applied
+fn refresh(json: bool) -> Result<()> {
+    let regions = region::get_selectable_regions();
+    let blocked_regions = region::get_blocked_regions();
+    let last = UserSettings::load()?.last_applied;
+    let refreshed = block_on(hosts_manager().refresh_redirect(&regions, &blocked_regions, last.as_ref()))?;
+
+    if json {
+        let (previous, current) = refreshed.clone().unzip();
+        print_json(&json!({
+            "redirect": refreshed.is_some(),
+            "changed": previous != current,
+            "previous_ip": previous,
+            "target_ip": current,
+        }));
+        return Ok(());
+    }
+    match refreshed {
+        None => println!("{}", tr("Universal Redirect is not active, nothing to refresh.")),
+        Some((previous, current)) if previous == current => {
+            println!("{}", trf("The redirect target is still {}.", &[&current]))
+        }
+        Some((previous, current)) => {
+            println!("{}", trf("Redirect moved from {} to {}.", &[&previous, &current]))
+        }
+    }
+    Ok(())
+}
+
+// Printed only: the binary holds cap_dac_override, writing a path it was given would let
+// anyone overwrite any file
+fn export_script(json: bool) -> Result<()> {
+    let regions = region::get_selectable_regions();
+    let script = hosts_manager().export_script(&regions)?;
+    if json {
+        print_json(&json!({ "script": script }));
+    } else {
+        print!("{}", script);
+    }
+    Ok(())
+}
+
+fn revert(json: bool) -> Result<()> {
+    let backend = backend();
+    block_on(backend.revert())?;
+    hooks::run(hooks::Event::Revert, json!({}));
+    history::record_revert(backend.id(), Trigger::CommandLine);
+    if json {
+        print_json(&json!({ "reverted": true }));
+        return Ok(());
+    }
+    println!("{}", tr("Make Your Choice entries removed from the hosts file."));
+    Ok(())
+}
+
+// Oldest first, like a log
+fn history(json: bool) -> Result<()> {
+    let entries = history::load();
+    if json {
+        print_json(&json!({ "entries": entries }));
+        return Ok(());
+    }
+    if entries.is_empty() {
+        println!("{}", tr("Nothing has been applied yet."));
+        return Ok(());
+    }
+    for entry in entries {
+        let change = match &entry.applied {
+            Some(applied) => format!("{} {}", applied.apply_mode.id(), applied.selection.join(", ")),
+            None => "revert".to_string(),
+        };
+        println!(
+            "{}  {:<13} {:<10} {}",
+            entry.time.format("%Y-%m-%d %H:%M"),
+            tr(entry.trigger.label()),
+            entry.backend,
+            change
+        );
+    }
+    Ok(())
+}
+
+fn status(json: bool) -> Result<()> {
+    let regions = region::get_selectable_regions();
+    let active = backend().status(&regions);
+
+    if json {
+        let mode = active.mode_id();
+        let (allowed, target_ip) = match active {
+            ActiveConfiguration::Inactive => (Vec::new(), None),
+            ActiveConfiguration::Gatekeep { allowed } => (allowed, None),
+            ActiveConfiguration::UniversalRedirect { target_ip } => (Vec::new(), Some(target_ip)),
+        };
+        print_json(&json!({
+            "hosts_file": hosts::hosts_path(),
+            "mode": mode,
+            "allowed": allowed.iter().map(|name| region_json(name, &regions)).collect::<Vec<_>>(),
+            "target_ip": target_ip,
+            "vpn": vpn::routed(),
+        }));
+        return Ok(());
+    }
+
+    println!("{}", trf("Hosts file: {}", &[&hosts::hosts_path().display()]));
+    match active {
+        ActiveConfiguration::Inactive => println!("{}", tr("No servers are blocked.")),
+        ActiveConfiguration::Gatekeep { allowed } => {
+            println!("{}", tr("Gatekeep active, allowed servers:"));
+            for name in allowed {
+                println!("  {}", describe(&name, &regions));
+            }
+        }
+        ActiveConfiguration::UniversalRedirect { target_ip } => {
+            println!("{}", trf("Universal Redirect active, all servers point to {}", &[&target_ip]));
+        }
+    }
+    if let Some(interface) = vpn::routed() {
+        println!("{}", trf("Internet traffic goes through the VPN {}, the game picks its server from the VPN's location.", &[&interface]));
+    }
+    Ok(())
+}
+
+fn ping(json: bool) -> Result<()> {
+    let regions = region::get_selectable_regions();
+    let runtime = tokio::runtime::Runtime::new().context("Failed to create tokio runtime")?;
+
+    // Probed concurrently, printed in the same order as list-regions
+    let results: HashMap<String, i64> = runtime.block_on(async {
+        let tasks: Vec<_> = regions
+            .iter()
+            .filter_map(|(name, info)| {
+                let name = name.clone();
+                let host = info.hosts.first()?.clone();
+                Some(tokio::spawn(async move { (name, crate::ping::ping_host(&host).await) }))
+            })
+            .collect();
+        let mut results = HashMap::new();
+        for task in tasks {
+            if let Ok((name, latency)) = task.await {
+                results.insert(name, latency);
+            }
+        }
+        results
+    });
+
+    if json {
+        let latencies: Vec<Value> = sorted_names(&regions)
+            .iter()
+            .map(|name| {
+                let mut entry = region_json(name, &regions);
+                // null when the server could not be reached
+                entry["latency_ms"] = json!(results.get(name).filter(|ms| **ms >= 0));
+                entry
+            })
+            .collect();
+        print_json(&json!({ "latencies": latencies, "vpn": vpn::routed() }));
+        return Ok(());
+    }
+
+    for name in sorted_names(&regions) {
+        let latency = match results.get(&name) {
+            Some(ms) if *ms >= 0 => format!("{} ms", ms),
+            _ => tr("disconnected"),
+        };
+        println!("{:<40} {}", describe(&name, &regions), latency);
+    }
+    if let Some(interface) = vpn::routed() {
+        println!();
+        println!("{}", trf("Measured through the VPN {}, from the VPN server rather than from you.", &[&interface]));
+    }
+    Ok(())
+}
+
+// Hostnames of the given servers or of all of them, one per line to paste into router
+// configs, DNS blocklists or support threads; --resolve prints their current addresses.
+// Those are looked up past the hosts file, so blocked servers show their real ones.
+fn hostnames(queries: &[String], resolve: bool, json: bool) -> Result<()> {
+    let regions = region::get_selectable_regions();
+    let names = if queries.is_empty() {
+        sorted_names(&regions)
+    } else {
+        queries
+            .iter()
+            .map(|query| {
+                region::find_region(&regions, query).ok_or_else(|| {
+                    errors::error(ErrorKind::InvalidSelection, trf("Unknown server \"{}\", see list-regions", &[query]))
+                })
+            })
+            .collect::<Result<Vec<_>>>()?
+    };
+    let hostnames: Vec<String> = names.iter().flat_map(|name| regions[name].hosts.clone()).collect();
+    let addresses: HashMap<String, String> = if resolve {
+        block_on(async { Ok(crate::dns::resolve_all(&hostnames).await) })?
+            .into_iter()
+            .map(|(hostname, ip)| (hostname, ip.to_string()))
+            .collect()
+    } else {
+        HashMap::new()
+    };
+
+    if json {
+        let list: Vec<Value> = names
+            .iter()
+            .map(|name| {
+                let mut entry = region_json(name, &regions);
+                entry["hosts"] = regions[name]
+                    .hosts
+                    .iter()
+                    .map(|host| match resolve {
+                        // null when the name could not be resolved
+                        true => json!({ "hostname": host, "ip": addresses.get(host) }),
+                        false => json!({ "hostname": host }),
+                    })
+                    .collect();
+                entry
+            })
+            .collect();
+        print_json(&json!({ "regions": list }));
+        return Ok(());
+    }
+
+    let mut printed = HashSet::new();
+    for hostname in &hostnames {
+        let line = if resolve { addresses.get(hostname) } else { Some(hostname) };
+        if let Some(line) = line.filter(|line| printed.insert(line.as_str())) {
+            println!("{}", line);
+        }
+    }
+    if resolve && addresses.len() < hostnames.len() {
+        eprintln!("{}", trf("{} of {} hostnames could not be resolved.", &[&(hostnames.len() - addresses.len()), &hostnames.len()]));
+    }
+    Ok(())
+}
+
+fn list_regions(json: bool) -> Result<()> {
+    let regions = region::get_selectable_regions();
+    if json {
+        let list: Vec<Value> = sorted_names(&regions)
+            .iter()
+            .map(|name| {
+                let info = &regions[name];
+                let mut entry = region_json(name, &regions);
+                entry["group"] = json!(region::get_group_name(name));
+                entry["country"] = json!(info.country);
+                entry["stable"] = json!(info.stable);
+                entry["hosts"] = json!(info.hosts);
+                entry
+            })
+            .collect();
+        print_json(&json!({ "regions": list }));
+        return Ok(());
+    }
+
+    for name in sorted_names(&regions) {
+        let info = &regions[&name];
+        let stability = if info.stable { "" } else { " (unstable)" };
+        println!("{}{}", describe(&name, &regions), stability);
+    }
+    Ok(())
+}
+
+fn completions(shell: &str) -> Result<()> {
+    let script = completions::script(shell).with_context(|| trf("Unknown shell \"{}\"", &[&shell]))?;
+    print!("{}", script);
+    Ok(())
+}
+
+// The same switch as in the program settings, for builds and systems without the window
+fn service(action: &str, json: bool) -> Result<()> {
+    match action {
+        "enable" => service::enable()?,
+        "disable" => service::disable()?,
+        _ => {}
+    }
+    let enabled = service::is_enabled();
+    if json {
+        print_json(&json!({ "enabled": enabled, "unit": service::unit_file() }));
+    } else if enabled {
+        println!("{}", trf("The background service is set up and starts automatically: {}", &[&service::unit_file().display()]));
+    } else {
+        println!("{}", tr("The background service is not set up."));
+    }
+    Ok(())
+}
+
+// Grouped like the main window, then by name
+fn sorted_names(regions: &HashMap<String, RegionInfo>) -> Vec<String> {
+    let mut names: Vec<String> = regions.keys().cloned().collect();
+    names.sort_by(|a, b| {
+        region::get_group_name(a)
+            .cmp(region::get_group_name(b))
+            .then_with(|| a.cmp(b))
+    });
+    names
+}
+
+// "eu-central-1  Europe (Frankfurt am Main)"
+fn describe(name: &str, regions: &HashMap<String, RegionInfo>) -> String {
+    let code = regions
+        .get(name)
+        .and_then(region::aws_region_code)
+        .unwrap_or("-");
+    format!("{:<16} {}", code, name)
+}
+
+pub fn program() -> String {
+    std::env::args()
+        .next()
+        .and_then(|arg| {
+            std::path::Path::new(&arg)
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+        })
+        .unwrap_or_else(|| "make-your-choice".to_string())
+}
+
+fn print_help() {
+    let program = program();
+    println!("{}", trf("Usage: {} <command> [options]", &[&program]));
+    println!();
+    println!("{}", tr("Commands:"));
+    for (command, description) in [
+        ("apply --regions LIST", tr("Allow only these servers (names or AWS codes, comma separated)")),
+        ("      --mode MODE", tr("gatekeep (default) or universal-redirect")),
+        ("      --block WHAT", tr("both (default), ping or service")),
+        ("      --[no-]merge-unstable", tr("Also allow a stable server when only unstable ones are picked")),
+        ("      --profile NAME", tr("Use the servers and methods of a saved profile")),
+        ("      --fail-on-conflict", tr("Don't apply while other hosts entries point game servers elsewhere")),
+        ("      --dry-run", tr("Print the entries apply would write without changing anything")),
+        ("revert", tr("Remove the Make Your Choice entries from the hosts file")),
+        ("refresh", tr("Resolve the Universal Redirect target again, for cron and timers")),
+        ("status", tr("Show which servers are currently allowed")),
+        ("ping", tr("Measure the latency to every server")),
+        ("list-regions", tr("List the servers and their AWS codes")),
+        ("hostnames [--regions LIST]", tr("Print the hostnames of these servers, or of all")),
+        ("      --resolve", tr("Print their current IP addresses instead")),
+        ("history", tr("List every apply and revert with when and from where it was made")),
+        ("export-script", tr("Print a shell script that applies the current entries without the app")),
+        ("completions SHELL", tr("Print a completion script for bash, zsh or fish")),
+        ("wrap OPTIONS -- COMMAND", tr("Apply like apply, run the game and revert when it exits")),
+        ("service enable|disable|status", tr("Set up or remove the background service")),
+        ("help", tr("Show this help")),
+    ] {
+        println!("  {:<28} {}", command, description);
+    }
+    println!();
+    println!("{}", tr("Add --json to any command for machine-readable output."));
+    println!();
+    println!("{}", tr("Exit codes:"));
+    println!("  {:<28} {}", 0, tr("success"));
+    for kind in errors::ALL {
+        println!("  {:<28} {}", kind.exit_code(), kind.id());
+    }
+    println!();
+    println!("{}", tr("Without a command the window opens."));
+}
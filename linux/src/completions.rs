@@ -0,0 +1,292 @@
+use crate::region;
+use crate::service;
+
+// Completion scripts for `make-your-choice completions bash|zsh|fish`, for packagers to
+// install. The AWS codes of all servers are written into the script, so --regions
+// completes them one after another in a comma separated list.
+pub const SHELLS: &[&str] = &["bash", "zsh", "fish"];
+
+const PROGRAM: &str = "make-your-choice";
+
+const COMMANDS: &[(&str, &str)] = &[
+    ("apply", "Allow only these servers"),
+    ("revert", "Remove the Make Your Choice entries"),
+    ("refresh", "Resolve the redirect target again"),
+    ("status", "Show which servers are allowed"),
+    ("ping", "Measure the latency to every server"),
+    ("list-regions", "List the servers and their AWS codes"),
+    ("hostnames", "Print the hostnames or addresses of servers"),
+    ("history", "List every apply and revert"),
+    ("export-script", "Print a shell script with the current entries"),
+    ("completions", "Print a shell completion script"),
+    ("wrap", "Apply, run a game and revert when it exits"),
+    ("service", "Set up or remove the background service"),
+    ("help", "Show the command line help"),
+];
+
+const APPLY_OPTIONS: &[(&str, &str)] = &[
+    ("regions", "Servers to allow, comma separated"),
+    ("mode", "Method used to apply"),
+    ("block", "Endpoints to block"),
+    ("merge-unstable", "Also allow a stable server for unstable picks"),
+    ("no-merge-unstable", "Only allow the listed servers"),
+    ("profile", "Use a saved profile"),
+    ("fail-on-conflict", "Fail when other entries point game servers elsewhere"),
+];
+
+// Valid on every command, see cli.rs and overrides.rs
+const COMMON_OPTIONS: &[(&str, &str)] = &[
+    ("json", "Print the result as JSON"),
+    ("hosts-file", "Edit this file instead of /etc/hosts"),
+    ("config-dir", "Keep the settings in this directory"),
+    ("offline", "Skip network requests"),
+];
+
+const MODES: &str = "gatekeep universal-redirect";
+const BLOCK_MODES: &str = "both ping service";
+
+pub fn script(shell: &str) -> Option<String> {
+    let mut codes: Vec<&str> = Vec::new();
+    let regions = region::get_selectable_regions();
+    for info in regions.values() {
+        if let Some(code) = region::aws_region_code(info) {
+            codes.push(code);
+        }
+    }
+    codes.sort();
+    let codes = codes.join(" ");
+
+    match shell {
+        "bash" => Some(bash(&codes)),
+        "zsh" => Some(zsh(&codes)),
+        "fish" => Some(fish(&codes)),
+        _ => None,
+    }
+}
+
+fn names(options: &[(&str, &str)], prefix: &str) -> String {
+    options
+        .iter()
+        .map(|(name, _)| format!("{}{}", prefix, name))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn bash(codes: &str) -> String {
+    format!(
+        r#"# bash completion for {program}
+_make_your_choice() {{
+    local cur="${{COMP_WORDS[COMP_CWORD]}}"
+    local prev="${{COMP_WORDS[COMP_CWORD-1]}}"
+
+    if [[ $COMP_CWORD -eq 1 ]]; then
+        COMPREPLY=($(compgen -W "{commands}" -- "$cur"))
+        return
+    fi
+
+    case "$prev" in
+        --regions)
+            # Complete the part after the last comma, keep what was typed before it
+            local typed="" last="$cur"
+            if [[ "$cur" == *,* ]]; then
+                typed="${{cur%,*}},"
+                last="${{cur##*,}}"
+            fi
+            COMPREPLY=($(compgen -P "$typed" -W "{codes}" -- "$last"))
+            compopt -o nospace 2>/dev/null
+            return
+            ;;
+        --mode)
+            COMPREPLY=($(compgen -W "{modes}" -- "$cur"))
+            return
+            ;;
+        --block)
+            COMPREPLY=($(compgen -W "{block_modes}" -- "$cur"))
+            return
+            ;;
+        --profile)
+            return
+            ;;
+        --hosts-file|--config-dir)
+            COMPREPLY=($(compgen -f -- "$cur"))
+            return
+            ;;
+    esac
+
+    local options="{common}"
+    case "${{COMP_WORDS[1]}}" in
+        apply) options="{apply} --dry-run $options" ;;
+        wrap) options="{apply} $options" ;;
+        hostnames) options="--regions --resolve $options" ;;
+        completions) options="{shells}" ;;
+        service) options="{actions} $options" ;;
+    esac
+    COMPREPLY=($(compgen -W "$options" -- "$cur"))
+}}
+complete -F _make_your_choice {program}
+"#,
+        program = PROGRAM,
+        commands = names(COMMANDS, ""),
+        codes = codes,
+        modes = MODES,
+        block_modes = BLOCK_MODES,
+        common = names(COMMON_OPTIONS, "--"),
+        apply = names(APPLY_OPTIONS, "--"),
+        shells = SHELLS.join(" "),
+        actions = service::ACTIONS.join(" "),
+    )
+}
+
+fn zsh(codes: &str) -> String {
+    let commands: Vec<String> = COMMANDS
+        .iter()
+        .map(|(name, description)| format!("        '{}:{}'", name, description))
+        .collect();
+    let option = |(name, description): &(&str, &str)| -> String {
+        let argument = match *name {
+            "regions" => ":servers:_sequence compadd - $regions".to_string(),
+            "mode" => format!(":mode:({})", MODES),
+            "profile" => ":profile:".to_string(),
+            "block" => format!(":endpoints:({})", BLOCK_MODES),
+            "hosts-file" | "config-dir" => ":path:_files".to_string(),
+            _ => String::new(),
+        };
+        format!("'--{}[{}]{}'", name, description, argument)
+    };
+    let common: Vec<String> = COMMON_OPTIONS.iter().map(|o| format!("        {}", option(o))).collect();
+    let apply: Vec<String> = APPLY_OPTIONS.iter().map(|o| format!("                {}", option(o))).collect();
+
+    format!(
+        r#"#compdef {program}
+
+_make_your_choice() {{
+    local -a commands regions common
+    commands=(
+{commands}
+    )
+    regions=({codes})
+    common=(
+{common}
+    )
+
+    if (( CURRENT == 2 )); then
+        _describe 'command' commands
+        return
+    fi
+
+    # Complete the options of the command as if it were run on its own
+    local command=$words[2]
+    shift words
+    (( CURRENT-- ))
+    case $command in
+        apply)
+            _arguments $common \
+                '--dry-run[Print the entries without applying them]' \
+{apply}
+            ;;
+        wrap)
+            _arguments $common \
+{apply}
+            ;;
+        hostnames)
+            _arguments $common \
+                '--regions[Servers to list, comma separated]:servers:_sequence compadd - $regions' \
+                '--resolve[Print the current IP addresses]'
+            ;;
+        completions)
+            _arguments '1:shell:({shells})'
+            ;;
+        service)
+            _arguments $common '1:action:({actions})'
+            ;;
+        *)
+            _arguments $common
+            ;;
+    esac
+}}
+
+_make_your_choice "$@"
+"#,
+        program = PROGRAM,
+        commands = commands.join("\n"),
+        codes = codes,
+        common = common.join("\n"),
+        apply = apply.join(" \\\n"),
+        shells = SHELLS.join(" "),
+        actions = service::ACTIONS.join(" "),
+    )
+}
+
+fn fish(codes: &str) -> String {
+    let mut out = format!(
+        r#"# fish completion for {program}
+function __make_your_choice_regions
+    # Complete the part after the last comma, keep what was typed before it
+    set -l typed (string replace -r '[^,]*$' '' -- (commandline -ct))
+    for code in {codes}
+        echo $typed$code
+    end
+end
+
+set -l commands {commands}
+complete -c {program} -f
+"#,
+        program = PROGRAM,
+        codes = codes,
+        commands = names(COMMANDS, ""),
+    );
+
+    for (name, description) in COMMANDS {
+        out.push_str(&format!(
+            "complete -c {} -n \"not __fish_seen_subcommand_from $commands\" -a {} -d '{}'\n",
+            PROGRAM, name, description
+        ));
+    }
+    for (name, description) in APPLY_OPTIONS {
+        let argument = match *name {
+            "regions" => " -x -a '(__make_your_choice_regions)'".to_string(),
+            "mode" => format!(" -x -a '{}'", MODES),
+            "block" => format!(" -x -a '{}'", BLOCK_MODES),
+            "profile" => " -x".to_string(),
+            _ => String::new(),
+        };
+        out.push_str(&format!(
+            "complete -c {} -n '__fish_seen_subcommand_from {}' -l {} -d '{}'{}\n",
+            PROGRAM,
+            // --regions also picks the servers of hostnames
+            if *name == "regions" { "apply wrap hostnames" } else { "apply wrap" },
+            name,
+            description,
+            argument
+        ));
+    }
+    for (name, description) in COMMON_OPTIONS {
+        let argument = match *name {
+            "hosts-file" | "config-dir" => " -r -F",
+            _ => "",
+        };
+        out.push_str(&format!(
+            "complete -c {} -n \"__fish_seen_subcommand_from $commands\" -l {} -d '{}'{}\n",
+            PROGRAM, name, description, argument
+        ));
+    }
+    out.push_str(&format!(
+        "complete -c {} -n '__fish_seen_subcommand_from apply' -l dry-run -d 'Print the entries without applying them'\n",
+        PROGRAM
+    ));
+    out.push_str(&format!(
+        "complete -c {} -n '__fish_seen_subcommand_from hostnames' -l resolve -d 'Print the current IP addresses'\n",
+        PROGRAM
+    ));
+    out.push_str(&format!(
+        "complete -c {} -n '__fish_seen_subcommand_from completions' -a '{}'\n",
+        PROGRAM,
+        SHELLS.join(" ")
+    ));
+    out.push_str(&format!(
+        "complete -c {} -n '__fish_seen_subcommand_from service' -a '{}'\n",
+        PROGRAM,
+        service::ACTIONS.join(" ")
+    ));
+    out
+}
@@ -0,0 +1,93 @@
+use crate::errors::ErrorKind;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use tokio::sync::{mpsc, oneshot};
+
+// Requests other programs can send, over D-Bus (dbus.rs), the local HTTP API (api.rs) or
+// from a plugin (plugins.rs). main.rs answers them on the main thread, the same way for all.
+pub enum Command {
+    ApplyProfile(String),
+    // Server names or AWS codes, and "gatekeep", "universal-redirect" or "" for the
+    // current method
+    ApplySelection(Vec<String>, String),
+    Revert,
+    GetStatus,
+    GetLatencies,
+    GetCurrentServer,
+    // Desktop notification with a title and body, only plugins send it
+    Notify(String, String),
+}
+
+// Commands travel to the main thread with a channel for the answer
+pub type ControlRequest = (Command, oneshot::Sender<Result>);
+pub type Requests = mpsc::UnboundedSender<ControlRequest>;
+
+pub enum Reply {
+    // Apply and revert only start the change, the outcome is reported like any other
+    Done,
+    // Mode is "inactive" or an apply mode id
+    Status {
+        mode: &'static str,
+        allowed: Vec<String>,
+        target_ip: Option<String>,
+    },
+    // Latest round in ms, -1 for servers that could not be reached
+    Latencies(HashMap<String, i64>),
+    // Address and region (None when unknown) of the last match server the game used
+    CurrentServer(Option<(String, Option<String>)>),
+}
+
+// The message is shown to the caller as is, the kind lets it react without parsing it
+pub struct Error {
+    pub kind: ErrorKind,
+    pub message: String,
+}
+
+impl Error {
+    pub fn new(kind: ErrorKind, message: impl Into<String>) -> Self {
+        Self {
+            kind,
+            message: message.into(),
+        }
+    }
+}
+
+impl From<anyhow::Error> for Error {
+    fn from(error: anyhow::Error) -> Self {
+        Self::new(crate::errors::classify(&error), format!("{:#}", error))
+    }
+}
+
+pub type Result = std::result::Result<Reply, Error>;
+
+// JSON shapes of the HTTP API (api.rs), which plugin calls share
+pub fn error_json(error: &Error) -> Value {
+    json!({
+        "error": error.message,
+        "kind": error.kind.id(),
+        "summary": crate::i18n::tr(error.kind.summary()),
+        "hint": error.kind.remediation().map(crate::i18n::tr),
+    })
+}
+
+pub fn reply_json(reply: Reply) -> Value {
+    match reply {
+        Reply::Done => json!({ "ok": true }),
+        Reply::Status { mode, allowed, target_ip } => json!({
+            "mode": mode,
+            "allowed": allowed,
+            "target_ip": target_ip,
+        }),
+        Reply::Latencies(latencies) => {
+            let latencies: serde_json::Map<String, Value> = latencies
+                .into_iter()
+                .map(|(name, ms)| (name, if ms >= 0 { json!(ms) } else { Value::Null }))
+                .collect();
+            json!({ "latencies": latencies })
+        }
+        Reply::CurrentServer(server) => {
+            let (ip, region) = server.map_or((None, None), |(ip, region)| (Some(ip), region));
+            json!({ "ip": ip, "region": region })
+        }
+    }
+}
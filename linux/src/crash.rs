@@ -0,0 +1,106 @@
+use crate::diagnostics;
+use crate::logging;
+use crate::settings::UserSettings;
+use std::backtrace::Backtrace;
+use std::fs;
+use std::panic::PanicHookInfo;
+use std::path::PathBuf;
+
+// Log lines kept in the report, the full log stays in app.log
+const LOG_LINES: usize = 40;
+// GitHub rejects longer new-issue links, the rest of the report is left to the file
+#[cfg(feature = "gui")]
+const MAX_ISSUE_REPORT: usize = 4000;
+
+fn pending_file() -> PathBuf {
+    crate::paths::state_dir().join("crash.md")
+}
+
+// Where the report goes once it was offered, so it can still be found afterwards
+#[cfg(feature = "gui")]
+pub fn last_file() -> PathBuf {
+    crate::paths::state_dir().join("last-crash.md")
+}
+
+// Writes a report for the next start before the default hook prints the panic
+pub fn install(version: String) {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let report = report(&version, info);
+        let path = pending_file();
+        if let Some(dir) = path.parent() {
+            let _ = fs::create_dir_all(dir);
+        }
+        // Only the first panic of a crash is worth keeping
+        if !path.exists() {
+            let _ = fs::write(&path, report);
+        }
+        default_hook(info);
+    }));
+}
+
+fn report(version: &str, info: &PanicHookInfo) -> String {
+    let message = info
+        .payload()
+        .downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| info.payload().downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "Unknown".to_string());
+    let location = info
+        .location()
+        .map(|location| location.to_string())
+        .unwrap_or_else(|| "Unknown".to_string());
+    let thread = std::thread::current().name().unwrap_or("unnamed").to_string();
+
+    let (_, entries) = logging::entries();
+    let log = entries
+        .iter()
+        .skip(entries.len().saturating_sub(LOG_LINES))
+        .map(|entry| entry.to_string())
+        .collect::<Vec<_>>()
+        .join("\n");
+    // Loaded again, the copy in the window may be locked by the panicking thread
+    let settings = UserSettings::load().unwrap_or_default();
+
+    format!(
+        "### Crash\n\
+         - Message: {}\n\
+         - Location: {}\n\
+         - Thread: {}\n\n\
+         {}\n\
+         ### Last log lines\n\
+         ```text\n{}\n```\n\n\
+         ### Backtrace\n\
+         ```text\n{}\n```\n",
+        message,
+        location,
+        thread,
+        diagnostics::report(version, &settings, "Not checked"),
+        log,
+        Backtrace::force_capture(),
+    )
+}
+
+// The report of a crash in the previous run, offered once
+#[cfg(feature = "gui")]
+pub fn take_pending() -> Option<String> {
+    let report = fs::read_to_string(pending_file()).ok()?;
+    let _ = fs::rename(pending_file(), last_file());
+    Some(report)
+}
+
+// The start of the report for the issue link, the backtrace is cut first
+#[cfg(feature = "gui")]
+pub fn for_issue(report: &str) -> String {
+    if report.len() <= MAX_ISSUE_REPORT {
+        return report.to_string();
+    }
+    let mut end = MAX_ISSUE_REPORT;
+    while !report.is_char_boundary(end) {
+        end -= 1;
+    }
+    format!(
+        "{}\n```\n\n(Cut short, please attach last-crash.md from the log folder for the rest)\n",
+        &report[..end]
+    )
+}
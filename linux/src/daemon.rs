@@ -0,0 +1,555 @@
+use crate::aws_ranges::AwsIpService;
+use crate::backend::{self, ActiveConfiguration, EnforcementBackend};
+use crate::community;
+use crate::control::{self, Command, Reply};
+#[cfg(feature = "dbus")]
+use crate::dbus;
+use crate::errors::ErrorKind;
+use crate::gamewatch::{self, LaunchWatch};
+use crate::history::{self, Trigger};
+use crate::hooks::{self, Event};
+use crate::hosts::HostsManager;
+use crate::i18n::{tr, trf};
+use crate::metrics::{self, Metrics};
+use crate::region::{self, ApplyMode, RegionInfo};
+use crate::settings::{AppliedSelection, NotificationKind, UserSettings};
+use crate::sniff::TrafficSniffer;
+#[cfg(feature = "dbus")]
+use gio::prelude::*;
+#[cfg(feature = "dbus")]
+use std::cell::{Cell, RefCell};
+use std::future::Future;
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::runtime::Runtime;
+
+// Headless background service, started with --daemon by the systemd user unit from
+// service.rs. Without any window it
+// - applies the remembered selection again every 30 minutes and after network changes
+//   while Universal Redirect is active, so the resolved addresses stay current
+// - watches game traffic, logs the match servers the game connects to and runs the
+//   match hooks (hooks.rs)
+// - pings every server once a minute
+// - applies the active profile when the game starts, if that option is on
+// - serves Prometheus metrics when turned on (metrics.rs)
+// - runs the user's plugins (plugins.rs)
+// - answers the D-Bus Control interface (dbus.rs) under its own bus name, next to the
+//   window's dev.lawliet.makeyourchoice
+// D-Bus needs the GLib main loop. Built without the dbus feature the service runs on
+// tokio instead, without the interface and without refreshing after network changes.
+#[cfg(feature = "dbus")]
+pub const BUS_NAME: &str = "dev.lawliet.makeyourchoice.Daemon";
+
+const REFRESH_INTERVAL: Duration = Duration::from_secs(30 * 60);
+const PING_INTERVAL: Duration = Duration::from_secs(60);
+// Network changes arrive in bursts, refresh once things settled
+#[cfg(feature = "dbus")]
+const NETWORK_SETTLE: Duration = Duration::from_secs(5);
+// Same as the window: a match is over once its server stays quiet this long
+const MATCH_IDLE: Duration = Duration::from_secs(5);
+
+struct Daemon {
+    regions: HashMap<String, RegionInfo>,
+    blocked_regions: HashMap<String, RegionInfo>,
+    backend: Arc<dyn EnforcementBackend>,
+    // Only for refreshing the Universal Redirect target, which is a hosts file matter
+    hosts: HostsManager,
+    latencies: Arc<Mutex<HashMap<String, i64>>>,
+    last_seen: crate::LastSeen,
+    last_packet: Arc<Mutex<Option<Instant>>>,
+    metrics: Option<Arc<Metrics>>,
+    #[cfg(feature = "dbus")]
+    connection: RefCell<Option<gio::DBusConnection>>,
+    #[cfg(feature = "dbus")]
+    refresh_pending: Cell<bool>,
+    // The backend's futures need tokio, the main loop only waits for them
+    runtime: Arc<Runtime>,
+    // D-Bus calls overlap now, changes to the hosts file still happen one at a time
+    changing: tokio::sync::Mutex<()>,
+}
+
+pub fn run() -> i32 {
+    let runtime = match Runtime::new() {
+        Ok(runtime) => Arc::new(runtime),
+        Err(e) => {
+            log_error!("daemon", "Failed to create tokio runtime: {}", e);
+            return 1;
+        }
+    };
+    log_info!("daemon", "Started, hosts file {}", crate::hosts::hosts_path().display());
+
+    let regions = region::get_selectable_regions();
+    let backend = backend::current(crate::DISCORD_URL);
+    let metrics_settings = UserSettings::load().unwrap_or_default().metrics;
+    let metrics = metrics_settings
+        .enabled
+        .then(|| Arc::new(Metrics::new(regions.clone(), backend.clone())));
+    let _metrics_server = metrics.as_ref().and_then(|metrics| {
+        metrics::start(&runtime, &metrics_settings.address, metrics_settings.port, metrics.clone())
+            .inspect_err(|e| log_warn!("daemon", "{:#}", e))
+            .ok()
+    });
+
+    let daemon = Rc::new(Daemon {
+        regions,
+        blocked_regions: region::get_blocked_regions(),
+        backend,
+        hosts: HostsManager::new(crate::DISCORD_URL.to_string()),
+        latencies: Arc::new(Mutex::new(HashMap::new())),
+        last_seen: Arc::new(Mutex::new(None)),
+        last_packet: Arc::new(Mutex::new(None)),
+        metrics,
+        #[cfg(feature = "dbus")]
+        connection: RefCell::new(None),
+        #[cfg(feature = "dbus")]
+        refresh_pending: Cell::new(false),
+        runtime: runtime.clone(),
+        changing: tokio::sync::Mutex::new(()),
+    });
+
+    start_pinging(&daemon, &runtime);
+    community::start(&runtime);
+
+    let mut sniffer = None;
+    run_until_stopped(&runtime, || {
+        sniffer = Some(watch_matches(&daemon, &runtime));
+
+        let daemon_clone = daemon.clone();
+        every(REFRESH_INTERVAL, move || daemon_clone.refresh("periodic refresh"));
+        watch_game_launch(&daemon);
+
+        // Plugin requests, answered on this thread like D-Bus calls
+        let (requests, mut requests_rx) = tokio::sync::mpsc::unbounded_channel::<control::ControlRequest>();
+        let daemon_clone = daemon.clone();
+        spawn_local(async move {
+            while let Some((command, reply)) = requests_rx.recv().await {
+                let _ = reply.send(daemon_clone.handle(command, Trigger::Remote).await);
+            }
+        });
+        crate::plugins::start(&requests);
+
+        #[cfg(feature = "dbus")]
+        export_dbus(&daemon);
+    });
+
+    if let Some(sniffer) = sniffer {
+        sniffer.stop();
+    }
+    crate::plugins::stop();
+    log_info!("daemon", "Stopped");
+    0
+}
+
+// Refreshes once the network settled after a change, and answers on the session bus
+#[cfg(feature = "dbus")]
+fn export_dbus(daemon: &Rc<Daemon>) {
+    let daemon_clone = daemon.clone();
+    gio::NetworkMonitor::default().connect_network_changed(move |_, available| {
+        if available && !daemon_clone.refresh_pending.replace(true) {
+            let daemon = daemon_clone.clone();
+            glib::timeout_add_local_once(NETWORK_SETTLE, move || {
+                daemon.refresh_pending.set(false);
+                daemon.refresh("network change");
+            });
+        }
+    });
+
+    let daemon_clone = daemon.clone();
+    // Kept until the process exits
+    gio::bus_own_name(
+        gio::BusType::Session,
+        BUS_NAME,
+        gio::BusNameOwnerFlags::NONE,
+        move |connection, _| {
+            let daemon = daemon_clone.clone();
+            let exported = dbus::export(&connection, move |command| {
+                let daemon = daemon.clone();
+                async move { daemon.handle(command, Trigger::Remote).await }
+            });
+            match exported {
+                Ok(()) => *daemon_clone.connection.borrow_mut() = Some(connection),
+                Err(e) => log_warn!("daemon", "{:#}", e),
+            }
+        },
+        |_, name| log_info!("daemon", "Listening on the session bus as {}", name),
+        |_, name| log_warn!("daemon", "Could not own {} on the session bus, is another instance running?", name),
+    );
+}
+
+// Calls `start` on the main loop, then runs it until SIGTERM or SIGINT
+#[cfg(feature = "dbus")]
+fn run_until_stopped(_runtime: &Runtime, start: impl FnOnce()) {
+    start();
+    let main_loop = glib::MainLoop::new(None, false);
+    for signal in [libc::SIGTERM, libc::SIGINT] {
+        let main_loop = main_loop.clone();
+        glib::unix_signal_add_local(signal, move || {
+            main_loop.quit();
+            glib::ControlFlow::Break
+        });
+    }
+    main_loop.run();
+}
+
+#[cfg(not(feature = "dbus"))]
+fn run_until_stopped(runtime: &Runtime, start: impl FnOnce()) {
+    use tokio::signal::unix::{signal, SignalKind};
+    tokio::task::LocalSet::new().block_on(runtime, async {
+        start();
+        let (Ok(mut terminate), Ok(mut interrupt)) = (signal(SignalKind::terminate()), signal(SignalKind::interrupt())) else {
+            log_error!("daemon", "Failed to listen for signals");
+            return;
+        };
+        tokio::select! {
+            _ = terminate.recv() => {}
+            _ = interrupt.recv() => {}
+        }
+    });
+}
+
+// Runs on the main thread, where the daemon lives
+#[cfg(feature = "dbus")]
+fn spawn_local(task: impl Future<Output = ()> + 'static) {
+    glib::spawn_future_local(task);
+}
+
+#[cfg(not(feature = "dbus"))]
+fn spawn_local(task: impl Future<Output = ()> + 'static) {
+    tokio::task::spawn_local(task);
+}
+
+#[cfg(feature = "dbus")]
+fn every(period: Duration, mut tick: impl FnMut() + 'static) {
+    glib::timeout_add_local(period, move || {
+        tick();
+        glib::ControlFlow::Continue
+    });
+}
+
+#[cfg(not(feature = "dbus"))]
+fn every(period: Duration, mut tick: impl FnMut() + 'static) {
+    spawn_local(async move {
+        let mut interval = tokio::time::interval_at(tokio::time::Instant::now() + period, period);
+        loop {
+            interval.tick().await;
+            tick();
+        }
+    });
+}
+
+// Looks up the region of every new remote address the game talks to
+fn watch_matches(daemon: &Rc<Daemon>, runtime: &Arc<Runtime>) -> TrafficSniffer {
+    let (match_tx, mut match_rx) = tokio::sync::mpsc::unbounded_channel::<(String, Option<String>)>();
+    let aws_service = Arc::new(AwsIpService::new());
+    let last_seen = daemon.last_seen.clone();
+    let last_packet = daemon.last_packet.clone();
+    let runtime = runtime.clone();
+
+    let sniffer = TrafficSniffer::new(move |remote_ip, _port| {
+        *last_packet.lock().unwrap() = Some(Instant::now());
+        if last_seen.lock().unwrap().as_ref().is_some_and(|(ip, _)| *ip == remote_ip) {
+            return;
+        }
+        let aws = aws_service.clone();
+        let match_tx = match_tx.clone();
+        runtime.spawn(async move {
+            let region = aws.get_region(&remote_ip).await;
+            let _ = match_tx.send((remote_ip, region));
+        });
+    });
+
+    let daemon_clone = daemon.clone();
+    spawn_local(async move {
+        while let Some((ip, region)) = match_rx.recv().await {
+            log_info!("daemon", "Game connected to {} ({})", ip, region.as_deref().unwrap_or("unknown region"));
+            #[cfg(feature = "dbus")]
+            if let Some(connection) = daemon_clone.connection.borrow().as_ref() {
+                dbus::emit_server_connected(connection, &ip, region.as_deref());
+            }
+            hooks::spawn(Event::MatchDetected, hooks::match_data(&ip, region.as_deref()));
+            community::match_ended();
+            community::match_started(region.as_deref());
+            if let Some(metrics) = &daemon_clone.metrics {
+                metrics.record_match(region.as_deref());
+            }
+            *daemon_clone.last_seen.lock().unwrap() = Some((ip, region));
+        }
+    });
+
+    let daemon = daemon.clone();
+    every(Duration::from_secs(1), move || {
+        let idle = daemon.last_packet.lock().unwrap().is_none_or(|at| at.elapsed() >= MATCH_IDLE);
+        if idle {
+            if let Some((ip, region)) = daemon.last_seen.lock().unwrap().take() {
+                log_info!("daemon", "Match on {} ended", ip);
+                hooks::spawn(Event::MatchEnded, hooks::match_data(&ip, region.as_deref()));
+                community::match_ended();
+            }
+        }
+    });
+
+    sniffer
+}
+
+// The selection is in place before the first match even when nobody opened the window
+fn watch_game_launch(daemon: &Rc<Daemon>) {
+    let daemon = daemon.clone();
+    let mut watch = LaunchWatch::default();
+    every(gamewatch::POLL_INTERVAL, move || {
+        if !watch.started() {
+            return;
+        }
+        let settings = UserSettings::load().unwrap_or_default();
+        let Some(profile) = settings.active_profile.filter(|_| settings.apply_on_game_launch) else {
+            return;
+        };
+        log_info!("daemon", "Game started, applying profile {}", profile);
+        let daemon = daemon.clone();
+        spawn_local(async move {
+            match daemon.handle(Command::ApplyProfile(profile.clone()), Trigger::GameLaunch).await {
+                Ok(_) => daemon.notify(
+                    NotificationKind::ApplyResult,
+                    &tr("Profile applied"),
+                    &trf("Dead by Daylight started, \"{}\" is applied.", &[&profile]),
+                ),
+                Err(e) => {
+                    log_warn!("daemon", "Failed to apply profile {} at game start: {}", profile, e.message);
+                    daemon.notify(NotificationKind::Error, &tr("Failed to update the hosts file"), &e.message);
+                }
+            }
+        });
+    });
+}
+
+fn start_pinging(daemon: &Rc<Daemon>, runtime: &Arc<Runtime>) {
+    let targets = crate::ping::targets(&daemon.regions);
+    let latencies = daemon.latencies.clone();
+    let metrics = daemon.metrics.clone();
+    runtime.spawn(async move {
+        loop {
+            let results = crate::ping::ping_all(&targets).await;
+            if let Some(metrics) = &metrics {
+                metrics.record_pings(&results);
+            }
+            crate::plugins::broadcast_latencies(&results);
+            community::record_pings(&results);
+            *latencies.lock().unwrap() = results;
+            tokio::time::sleep(PING_INTERVAL).await;
+        }
+    });
+}
+
+impl Daemon {
+    // Universal Redirect points at addresses resolved when it was applied, those can
+    // change. Gatekeep blocks by name and needs nothing; a reverted file stays reverted.
+    fn refresh(self: &Rc<Self>, reason: &str) {
+        let daemon = self.clone();
+        let reason = reason.to_string();
+        spawn_local(async move {
+            let hosts = daemon.hosts.clone();
+            let (regions, blocked_regions) = (daemon.regions.clone(), daemon.blocked_regions.clone());
+            let last = UserSettings::load().ok().and_then(|settings| settings.last_applied);
+            let refreshed = daemon
+                .change(async move { hosts.refresh_redirect(&regions, &blocked_regions, last.as_ref()).await })
+                .await;
+            match refreshed {
+                Ok(Some((previous, current))) if previous != current => {
+                    log_info!("daemon", "Redirect moved from {} to {} after a {}", previous, current, reason)
+                }
+                Ok(_) => {}
+                Err(e) => log_warn!("daemon", "Failed to refresh the redirect after a {}: {:#}", reason, e),
+            }
+        });
+    }
+
+    // Runs a change to the hosts file on the tokio runtime, after the one before it
+    async fn change<T: Send + 'static>(
+        &self,
+        task: impl std::future::Future<Output = anyhow::Result<T>> + Send + 'static,
+    ) -> anyhow::Result<T> {
+        let _changing = self.changing.lock().await;
+        self.runtime.spawn(task).await?
+    }
+
+    async fn apply(&self, applied: AppliedSelection, trigger: Trigger) -> control::Result {
+        let hook_data = hooks::apply_data(applied.apply_mode.id(), &applied.selection);
+        let backend = self.backend.clone();
+        let (regions, blocked_regions) = (self.regions.clone(), self.blocked_regions.clone());
+        let (pre_apply, selection) = (hook_data.clone(), applied.clone());
+        self.change(async move {
+            tokio::task::spawn_blocking(move || hooks::run(Event::PreApply, pre_apply)).await?;
+            backend.apply(&regions, &blocked_regions, &selection, &|_| {}).await
+        })
+        .await?;
+        hooks::spawn(Event::PostApply, hook_data);
+        log_info!("daemon", "Applied {} ({})", applied.selection.join(", "), applied.apply_mode.id());
+        self.emit_hosts_changed(&applied.selection);
+        history::record_apply(&applied, self.backend.id(), trigger);
+
+        let mut settings = UserSettings::load().unwrap_or_default();
+        settings.last_applied = Some(applied);
+        if let Err(e) = settings.save() {
+            log_warn!("daemon", "Failed to remember the applied selection: {:#}", e);
+        }
+        Ok(Reply::Done)
+    }
+
+    // There is no GApplication to send notifications with, ask the notification daemon.
+    // The preferences are read each time, the window may have changed them.
+    #[cfg(feature = "dbus")]
+    fn notify(&self, kind: NotificationKind, title: &str, body: &str) {
+        let Some(connection) = self.connection.borrow().clone() else {
+            return;
+        };
+        let preferences = UserSettings::load().unwrap_or_default().notifications;
+        if !preferences.allows_now(kind) {
+            return;
+        }
+        let mut hints: HashMap<String, glib::Variant> = HashMap::new();
+        if preferences.sound {
+            hints.insert("sound-name".to_string(), "message-new-instant".to_variant());
+        } else {
+            hints.insert("suppress-sound".to_string(), true.to_variant());
+        }
+        let parameters = (
+            "Make Your Choice",
+            0u32,
+            "make-your-choice",
+            title,
+            body,
+            Vec::<String>::new(),
+            hints,
+            -1i32,
+        )
+            .to_variant();
+        connection.call(
+            Some("org.freedesktop.Notifications"),
+            "/org/freedesktop/Notifications",
+            "org.freedesktop.Notifications",
+            "Notify",
+            Some(&parameters),
+            None,
+            gio::DBusCallFlags::NONE,
+            -1,
+            gio::Cancellable::NONE,
+            |result| {
+                if let Err(e) = result {
+                    log_warn!("daemon", "Failed to show a notification: {}", e);
+                }
+            },
+        );
+    }
+
+    // Without D-Bus there is nobody to show it, the journal keeps it
+    #[cfg(not(feature = "dbus"))]
+    fn notify(&self, _kind: NotificationKind, title: &str, body: &str) {
+        log_info!("daemon", "{}: {}", title, body);
+    }
+
+    #[cfg(feature = "dbus")]
+    fn emit_hosts_changed(&self, applied: &[String]) {
+        if let Some(connection) = self.connection.borrow().as_ref() {
+            dbus::emit_hosts_changed(connection, applied);
+        }
+    }
+
+    #[cfg(not(feature = "dbus"))]
+    fn emit_hosts_changed(&self, _applied: &[String]) {}
+
+    // Same requests as the window answers, applied right away since there is nothing to
+    // confirm without one
+    async fn handle(&self, command: Command, trigger: Trigger) -> control::Result {
+        match command {
+            Command::ApplyProfile(name) => {
+                let settings = UserSettings::load().unwrap_or_default();
+                let profile = settings
+                    .profile(&name)
+                    .ok_or_else(|| {
+                        control::Error::new(
+                            ErrorKind::InvalidSelection,
+                            trf("There is no profile named \"{}\"", &[&name]),
+                        )
+                    })?;
+                let mut selection: Vec<String> = profile
+                    .selection
+                    .iter()
+                    .filter(|name| self.regions.contains_key(*name))
+                    .cloned()
+                    .collect();
+                selection.sort();
+                self.apply(
+                    AppliedSelection {
+                        selection,
+                        apply_mode: profile.apply_mode,
+                        block_mode: profile.block_mode,
+                        merge_unstable: profile.merge_unstable,
+                    },
+                    trigger,
+                )
+                .await
+            }
+            Command::ApplySelection(queries, mode) => {
+                let settings = UserSettings::load().unwrap_or_default();
+                let apply_mode = match mode.as_str() {
+                    "" => settings.effective_apply_mode(),
+                    id => ApplyMode::from_id(id).ok_or_else(|| {
+                        control::Error::new(ErrorKind::InvalidSelection, trf("Unknown mode \"{}\"", &[&id]))
+                    })?,
+                };
+                let mut selection = Vec::new();
+                for query in &queries {
+                    let name = region::find_region(&self.regions, query).ok_or_else(|| {
+                        control::Error::new(ErrorKind::InvalidSelection, trf("Unknown server \"{}\"", &[query]))
+                    })?;
+                    if !selection.contains(&name) {
+                        selection.push(name);
+                    }
+                }
+                if selection.is_empty() {
+                    return Err(control::Error::new(
+                        ErrorKind::InvalidSelection,
+                        tr("Please select at least one server to allow."),
+                    ));
+                }
+                selection.sort();
+                self.apply(
+                    AppliedSelection {
+                        selection,
+                        apply_mode,
+                        block_mode: settings.block_mode,
+                        merge_unstable: settings.merge_unstable,
+                    },
+                    trigger,
+                )
+                .await
+            }
+            Command::Revert => {
+                let backend = self.backend.clone();
+                self.change(async move { backend.revert().await }).await?;
+                log_info!("daemon", "Reverted");
+                hooks::spawn(Event::Revert, serde_json::json!({}));
+                history::record_revert(self.backend.id(), trigger);
+                self.emit_hosts_changed(&[]);
+                Ok(Reply::Done)
+            }
+            Command::GetStatus => {
+                let active = self.backend.status(&self.regions);
+                let mode = active.mode_id();
+                let (allowed, target_ip) = match active {
+                    ActiveConfiguration::Inactive => (Vec::new(), None),
+                    ActiveConfiguration::Gatekeep { allowed } => (allowed, None),
+                    ActiveConfiguration::UniversalRedirect { target_ip } => (Vec::new(), Some(target_ip)),
+                };
+                Ok(Reply::Status { mode, allowed, target_ip })
+            }
+            Command::GetLatencies => Ok(Reply::Latencies(self.latencies.lock().unwrap().clone())),
+            Command::GetCurrentServer => Ok(Reply::CurrentServer(self.last_seen.lock().unwrap().clone())),
+            Command::Notify(title, body) => {
+                self.notify(NotificationKind::Other, &title, &body);
+                Ok(Reply::Done)
+            }
+        }
+    }
+}
@@ -0,0 +1,128 @@
+use crate::control::{self, Command, Reply};
+use anyhow::{anyhow, Context, Result};
+use gio::prelude::*;
+use glib::Variant;
+use std::future::Future;
+
+// Session bus interface for hotkey daemons, shell extensions and Stream Deck plugins.
+// It is exported on the application's own connection, next to org.gtk.Application:
+//
+//   gdbus call --session --dest dev.lawliet.makeyourchoice \
+//     --object-path /dev/lawliet/makeyourchoice \
+//     --method dev.lawliet.makeyourchoice.Control.GetStatus
+pub const OBJECT_PATH: &str = "/dev/lawliet/makeyourchoice";
+pub const INTERFACE: &str = "dev.lawliet.makeyourchoice.Control";
+
+const INTROSPECTION: &str = r#"
+<node>
+  <interface name="dev.lawliet.makeyourchoice.Control">
+    <method name="ApplyProfile">
+      <arg name="name" type="s" direction="in"/>
+    </method>
+    <method name="ApplySelection">
+      <arg name="regions" type="as" direction="in"/>
+      <arg name="mode" type="s" direction="in"/>
+    </method>
+    <method name="Revert"/>
+    <method name="GetStatus">
+      <arg name="mode" type="s" direction="out"/>
+      <arg name="allowed" type="as" direction="out"/>
+      <arg name="target_ip" type="s" direction="out"/>
+    </method>
+    <method name="GetLatencies">
+      <arg name="latencies" type="a{sx}" direction="out"/>
+    </method>
+    <method name="GetCurrentServer">
+      <arg name="ip" type="s" direction="out"/>
+      <arg name="region" type="s" direction="out"/>
+    </method>
+    <signal name="HostsChanged">
+      <arg name="applied" type="as"/>
+    </signal>
+    <signal name="ServerConnected">
+      <arg name="ip" type="s"/>
+      <arg name="region" type="s"/>
+    </signal>
+  </interface>
+</node>
+"#;
+
+// Reply arguments, empty strings stand in for missing values
+fn reply_arguments(reply: Reply) -> Option<Variant> {
+    match reply {
+        Reply::Done => None,
+        Reply::Status { mode, allowed, target_ip } => {
+            Some((mode, allowed, target_ip.unwrap_or_default()).to_variant())
+        }
+        Reply::Latencies(latencies) => Some((latencies,).to_variant()),
+        Reply::CurrentServer(server) => {
+            let (ip, region) = server.unwrap_or_default();
+            Some((ip, region.unwrap_or_default()).to_variant())
+        }
+    }
+}
+
+// Registers the interface. `handle` runs on the main thread and answers each call, or
+// returns an error the caller receives as a named D-Bus error (errors.rs). The reply is
+// sent once its future finishes, other calls are served meanwhile.
+pub fn export<F, Fut>(connection: &gio::DBusConnection, handle: F) -> Result<()>
+where
+    F: Fn(Command) -> Fut + 'static,
+    Fut: Future<Output = control::Result> + 'static,
+{
+    let node = gio::DBusNodeInfo::for_xml(INTROSPECTION).context("Invalid introspection data")?;
+    let interface = node
+        .lookup_interface(INTERFACE)
+        .ok_or_else(|| anyhow!("Interface {} missing from the introspection data", INTERFACE))?;
+
+    connection
+        .register_object(OBJECT_PATH, &interface)
+        .method_call(move |_, sender, _, _, method, parameters, invocation| {
+            log_info!("dbus", "{} called by {}", method, sender.unwrap_or("unknown"));
+            let command = match method {
+                "ApplyProfile" => parameters.get::<(String,)>().map(|(name,)| Command::ApplyProfile(name)),
+                "ApplySelection" => parameters
+                    .get::<(Vec<String>, String)>()
+                    .map(|(regions, mode)| Command::ApplySelection(regions, mode)),
+                "Revert" => Some(Command::Revert),
+                "GetStatus" => Some(Command::GetStatus),
+                "GetLatencies" => Some(Command::GetLatencies),
+                "GetCurrentServer" => Some(Command::GetCurrentServer),
+                _ => None,
+            };
+            let Some(command) = command else {
+                invocation.return_dbus_error(
+                    "org.freedesktop.DBus.Error.UnknownMethod",
+                    &format!("Unknown method {}", method),
+                );
+                return;
+            };
+            let reply = handle(command);
+            glib::spawn_future_local(async move {
+                match reply.await {
+                    Ok(reply) => invocation.return_value(reply_arguments(reply).as_ref()),
+                    Err(error) => invocation.return_dbus_error(&error.kind.dbus_name(), &error.message),
+                }
+            });
+        })
+        .build()
+        .context("Failed to register the D-Bus interface")?;
+
+    Ok(())
+}
+
+fn emit(connection: &gio::DBusConnection, signal: &str, parameters: Variant) {
+    if let Err(e) = connection.emit_signal(None, OBJECT_PATH, INTERFACE, signal, Some(&parameters)) {
+        log_warn!("dbus", "Failed to emit {}: {}", signal, e);
+    }
+}
+
+// After every apply, revert or undo, with the servers now allowed (empty when reverted)
+pub fn emit_hosts_changed(connection: &gio::DBusConnection, applied: &[String]) {
+    emit(connection, "HostsChanged", (applied.to_vec(),).to_variant());
+}
+
+// The game connected to a match server; `region` is empty when it isn't a known region
+pub fn emit_server_connected(connection: &gio::DBusConnection, ip: &str, region: Option<&str>) {
+    emit(connection, "ServerConnected", (ip, region.unwrap_or("")).to_variant());
+}
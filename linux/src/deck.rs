@@ -0,0 +1,27 @@
+use crate::settings::DeckMode;
+use std::fs;
+
+// Steam Deck hardware reports Valve as board vendor; Jupiter is the LCD model, Galileo the OLED
+fn is_steam_deck_hardware() -> bool {
+    let read = |name: &str| {
+        fs::read_to_string(format!("/sys/devices/virtual/dmi/id/{}", name))
+            .map(|value| value.trim().to_string())
+            .unwrap_or_default()
+    };
+    read("board_vendor") == "Valve" && matches!(read("product_name").as_str(), "Jupiter" | "Galileo")
+}
+
+// Game Mode on SteamOS (and other gamescope sessions) sets these for every app it launches
+fn is_gamescope_session() -> bool {
+    std::env::var_os("SteamDeck").is_some_and(|value| value == "1")
+        || std::env::var_os("GAMESCOPE_WAYLAND_DISPLAY").is_some()
+        || std::env::var("XDG_CURRENT_DESKTOP").is_ok_and(|desktop| desktop.eq_ignore_ascii_case("gamescope"))
+}
+
+pub fn is_active(mode: DeckMode) -> bool {
+    match mode {
+        DeckMode::Auto => is_steam_deck_hardware() || is_gamescope_session(),
+        DeckMode::On => true,
+        DeckMode::Off => false,
+    }
+}
@@ -0,0 +1,109 @@
+use crate::logging;
+use crate::settings::UserSettings;
+use std::fs;
+
+// "Fedora Linux 40 (Workstation Edition)" from os-release, or "Unknown"
+pub fn distro() -> String {
+    if cfg!(target_os = "macos") {
+        return std::process::Command::new("sw_vers")
+            .arg("-productVersion")
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .map(|output| format!("macOS {}", String::from_utf8_lossy(&output.stdout).trim()))
+            .unwrap_or_else(|| "macOS".to_string());
+    }
+    ["/etc/os-release", "/usr/lib/os-release"]
+        .iter()
+        .filter_map(|path| fs::read_to_string(path).ok())
+        .find_map(|content| {
+            content.lines().find_map(|line| {
+                line.strip_prefix("PRETTY_NAME=")
+                    .map(|value| value.trim_matches('"').to_string())
+            })
+        })
+        .unwrap_or_else(|| "Unknown".to_string())
+}
+
+// Settings with anything personal taken out: the game paths and the search text
+fn sanitized_settings(settings: &UserSettings) -> String {
+    let mut settings = settings.clone();
+    for install in &mut settings.game_installs {
+        install.path = "<set>".to_string();
+        if install.prefix.is_some() {
+            install.prefix = Some("<set>".to_string());
+        }
+    }
+    settings.window_state.filter_text.clear();
+    if !settings.proxy.url.is_empty() {
+        settings.proxy.url = "<set>".to_string();
+    }
+    settings.proxy.username.clear();
+    settings.proxy.password.clear();
+    settings.http_api.token.clear();
+    serde_yaml::to_string(&settings).unwrap_or_else(|e| format!("<unavailable: {}>", e))
+}
+
+// "wg0 (default route), tailscale0" or "None"
+fn vpn() -> String {
+    let tunnels: Vec<String> = crate::vpn::detect()
+        .iter()
+        .map(|tunnel| match tunnel.default_route {
+            true => format!("{} (default route)", tunnel.interface),
+            false => tunnel.interface.clone(),
+        })
+        .collect();
+    if tunnels.is_empty() {
+        "None".to_string()
+    } else {
+        tunnels.join(", ")
+    }
+}
+
+// Markdown block pasted into bug reports. Kept in English so maintainers can read it.
+pub fn report(version: &str, settings: &UserSettings, hosts_status: &str) -> String {
+    let last_error = logging::last_error()
+        .map(|entry| entry.to_string())
+        .unwrap_or_else(|| "None".to_string());
+    let desktop = std::env::var("XDG_CURRENT_DESKTOP").unwrap_or_else(|_| "Unknown".to_string());
+    let session = std::env::var("XDG_SESSION_TYPE").unwrap_or_else(|_| "Unknown".to_string());
+
+    format!(
+        "### Environment\n\
+         - Version: {}\n\
+         - Installed as: {:?}\n\
+         - Distribution: {}\n\
+         - Desktop: {} ({})\n\
+         - Method: {:?} / {:?}\n\
+         - Hosts section: {}\n\
+         - VPN: {}\n\
+         - Last error: {}\n\n\
+         ### Settings\n\
+         ```yaml\n{}```\n",
+        version,
+        crate::packaging::install_kind(),
+        distro(),
+        desktop,
+        session,
+        settings.apply_mode,
+        settings.block_mode,
+        hosts_status,
+        vpn(),
+        last_error,
+        sanitized_settings(settings),
+    )
+}
+
+// New-issue link with the report filled in below a space for the user's description
+#[cfg(feature = "gui")]
+pub fn issue_url(repo_url: &str, report: &str) -> String {
+    let body = format!(
+        "**What happened?**\n\n\n**What did you expect?**\n\n\n{}",
+        report
+    );
+    format!(
+        "{}/issues/new?body={}",
+        repo_url,
+        glib::Uri::escape_string(&body, None, true)
+    )
+}
@@ -0,0 +1,46 @@
+use gtk4::gdk;
+use gtk4::gdk_pixbuf::PixbufLoader;
+use gtk4::prelude::*;
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+// Flags of the countries hosting the selectable servers, keyed by RegionInfo::country
+const FLAGS: &[(&str, &[u8])] = &[
+    ("au", include_bytes!("../flags/au.svg")),
+    ("br", include_bytes!("../flags/br.svg")),
+    ("ca", include_bytes!("../flags/ca.svg")),
+    ("de", include_bytes!("../flags/de.svg")),
+    ("gb", include_bytes!("../flags/gb.svg")),
+    ("hk", include_bytes!("../flags/hk.svg")),
+    ("ie", include_bytes!("../flags/ie.svg")),
+    ("in", include_bytes!("../flags/in.svg")),
+    ("jp", include_bytes!("../flags/jp.svg")),
+    ("kr", include_bytes!("../flags/kr.svg")),
+    ("sg", include_bytes!("../flags/sg.svg")),
+    ("us", include_bytes!("../flags/us.svg")),
+];
+
+thread_local! {
+    static TEXTURES: RefCell<HashMap<String, Option<gdk::Texture>>> = RefCell::new(HashMap::new());
+}
+
+// Rendered once per country and shared by every row showing it
+pub fn texture(country: &str) -> Option<gdk::Texture> {
+    TEXTURES.with(|textures| {
+        textures
+            .borrow_mut()
+            .entry(country.to_string())
+            .or_insert_with(|| render(country))
+            .clone()
+    })
+}
+
+fn render(country: &str) -> Option<gdk::Texture> {
+    let (_, svg) = FLAGS.iter().find(|(code, _)| *code == country)?;
+    let loader = PixbufLoader::new();
+    // Twice the displayed size so the flags stay sharp on HiDPI screens
+    loader.set_size(40, 26);
+    loader.write(svg).ok()?;
+    loader.close().ok()?;
+    loader.pixbuf().map(|pixbuf| gdk::Texture::for_pixbuf(&pixbuf))
+}
@@ -0,0 +1,89 @@
+use evdev::{AbsoluteAxisType, InputEventKind, Key};
+use std::sync::mpsc::Sender;
+
+// Controller input translated into UI navigation, handled on the GTK main thread
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GamepadInput {
+    Up,
+    Down,
+    Left,
+    Right,
+    Accept,
+    Back,
+    Start,
+    Select,
+    North,
+    West,
+}
+
+fn map_key(key: Key) -> Option<GamepadInput> {
+    Some(match key {
+        Key::BTN_DPAD_UP => GamepadInput::Up,
+        Key::BTN_DPAD_DOWN => GamepadInput::Down,
+        Key::BTN_DPAD_LEFT => GamepadInput::Left,
+        Key::BTN_DPAD_RIGHT => GamepadInput::Right,
+        Key::BTN_SOUTH => GamepadInput::Accept,
+        Key::BTN_EAST => GamepadInput::Back,
+        Key::BTN_START => GamepadInput::Start,
+        Key::BTN_SELECT => GamepadInput::Select,
+        Key::BTN_NORTH => GamepadInput::North,
+        Key::BTN_WEST => GamepadInput::West,
+        _ => return None,
+    })
+}
+
+// Most pads (including Steam's virtual controller) report the D-pad as a hat axis
+fn map_hat(axis: AbsoluteAxisType, value: i32) -> Option<GamepadInput> {
+    match (axis, value) {
+        (AbsoluteAxisType::ABS_HAT0Y, -1) => Some(GamepadInput::Up),
+        (AbsoluteAxisType::ABS_HAT0Y, 1) => Some(GamepadInput::Down),
+        (AbsoluteAxisType::ABS_HAT0X, -1) => Some(GamepadInput::Left),
+        (AbsoluteAxisType::ABS_HAT0X, 1) => Some(GamepadInput::Right),
+        _ => None,
+    }
+}
+
+// Starts a reader thread per connected gamepad. Returns how many were found;
+// devices the user may not read are skipped.
+pub fn spawn_listeners(inputs: Sender<GamepadInput>) -> usize {
+    let mut count = 0;
+
+    for (path, mut device) in evdev::enumerate() {
+        let is_gamepad = device
+            .supported_keys()
+            .is_some_and(|keys| keys.contains(Key::BTN_SOUTH));
+        if !is_gamepad {
+            continue;
+        }
+
+        let inputs = inputs.clone();
+        let spawned = std::thread::Builder::new()
+            .name("gamepad".to_string())
+            .spawn(move || loop {
+                let events = match device.fetch_events() {
+                    Ok(events) => events,
+                    Err(e) => {
+                        log_warn!("gamepad", "Stopped reading {:?}: {}", path, e);
+                        return;
+                    }
+                };
+                for event in events {
+                    let input = match event.kind() {
+                        InputEventKind::Key(key) if event.value() == 1 => map_key(key),
+                        InputEventKind::AbsAxis(axis) => map_hat(axis, event.value()),
+                        _ => None,
+                    };
+                    if let Some(input) = input {
+                        if inputs.send(input).is_err() {
+                            return;
+                        }
+                    }
+                }
+            });
+        if spawned.is_ok() {
+            count += 1;
+        }
+    }
+
+    count
+}
@@ -0,0 +1,255 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const GAME_TITLE: &str = "Dead by Daylight";
+// Steam names the folder after the title, the Epic version uses the project name
+const FOLDER_NAMES: &[&str] = &["Dead by Daylight", "DeadByDaylight"];
+const STEAM_APP_ID: &str = "381210";
+// Unreal's project folder inside the game folder, the same for Steam and Epic
+const PROJECT_DIR: &str = "DeadByDaylight";
+// The Epic version ships the Epic Online Services variant of the anti-cheat
+const ANTI_CHEAT_DIRS: &[&str] = &["EasyAntiCheat", "EasyAntiCheat_EOS"];
+
+// A game folder found on disk, with the Wine/Proton prefix it runs in if known
+pub struct DetectedInstall {
+    pub launcher: &'static str,
+    pub path: PathBuf,
+    pub prefix: Option<PathBuf>,
+}
+
+// Name offered for a newly added game folder, based on where it lives
+pub fn suggest_install_name(path: &Path) -> String {
+    let path = path.to_string_lossy().to_lowercase();
+    if path.starts_with("/run/media/") || path.starts_with("/media/") {
+        "SD card".to_string()
+    } else if path.contains("heroic") {
+        "Heroic".to_string()
+    } else if path.contains("lutris") {
+        "Lutris".to_string()
+    } else if path.contains("steamapps") {
+        "Steam".to_string()
+    } else {
+        GAME_TITLE.to_string()
+    }
+}
+
+pub fn is_game_folder_name(name: &str) -> bool {
+    FOLDER_NAMES.contains(&name)
+}
+
+// The folder holding the project folder and the anti-cheat files. On Epic the project
+// folder is named DeadByDaylight too and easily selected instead, and Heroic or
+// Legendary installs can be nested one folder deeper; both are probed so the splash
+// and movie options find the files whichever folder was set.
+pub fn game_root(path: &Path) -> PathBuf {
+    let has_project = |dir: &Path| dir.join(PROJECT_DIR).join("Content").is_dir();
+    if has_project(path) {
+        return path.to_path_buf();
+    }
+    if path.join("Content").is_dir() {
+        if let Some(parent) = path.parent().filter(|parent| has_project(parent)) {
+            return parent.to_path_buf();
+        }
+    }
+    FOLDER_NAMES
+        .iter()
+        .map(|name| path.join(name))
+        .find(|dir| has_project(dir))
+        .unwrap_or_else(|| path.to_path_buf())
+}
+
+pub fn content_dir(path: &Path) -> PathBuf {
+    game_root(path).join(PROJECT_DIR).join("Content")
+}
+
+// Where EasyAntiCheat's splash screen lives, the Steam folder name when there is none yet
+pub fn anti_cheat_dir(path: &Path) -> PathBuf {
+    let root = game_root(path);
+    ANTI_CHEAT_DIRS
+        .iter()
+        .map(|name| root.join(name))
+        .find(|dir| dir.is_dir())
+        .unwrap_or_else(|| root.join(ANTI_CHEAT_DIRS[0]))
+}
+
+// Looks for the game in the Steam libraries and in Epic installs managed by Heroic or
+// Lutris, including their Flatpak variants. Each folder is listed once.
+pub fn detect() -> Vec<DetectedInstall> {
+    let mut found: Vec<DetectedInstall> = Vec::new();
+    for install in detect_steam()
+        .into_iter()
+        .chain(detect_heroic())
+        .chain(detect_lutris())
+    {
+        let path = install.path.canonicalize().unwrap_or(install.path.clone());
+        if !found.iter().any(|f| f.path == path) {
+            found.push(DetectedInstall { path, ..install });
+        }
+    }
+    found
+}
+
+fn home() -> PathBuf {
+    dirs::home_dir().unwrap_or_else(|| PathBuf::from("."))
+}
+
+fn detect_steam() -> Vec<DetectedInstall> {
+    let home = home();
+    let roots = [
+        home.join(".local/share/Steam"),
+        home.join(".steam/steam"),
+        home.join(".var/app/com.valvesoftware.Steam/.local/share/Steam"),
+    ];
+
+    let mut libraries: Vec<PathBuf> = Vec::new();
+    for root in roots.iter().filter(|root| root.is_dir()) {
+        libraries.push(root.clone());
+        let vdf = root.join("steamapps").join("libraryfolders.vdf");
+        if let Ok(content) = fs::read_to_string(vdf) {
+            libraries.extend(library_paths(&content));
+        }
+    }
+
+    libraries
+        .into_iter()
+        .filter_map(|library| {
+            let steamapps = library.join("steamapps");
+            let path = steamapps.join("common").join(GAME_TITLE);
+            let prefix = steamapps.join("compatdata").join(STEAM_APP_ID).join("pfx");
+            path.is_dir().then(|| DetectedInstall {
+                launcher: "Steam",
+                path,
+                prefix: prefix.is_dir().then_some(prefix),
+            })
+        })
+        .collect()
+}
+
+// `"path"		"/run/media/deck/sdcard"` lines from libraryfolders.vdf
+fn library_paths(vdf: &str) -> Vec<PathBuf> {
+    vdf_values(vdf, "path")
+        .map(|path| PathBuf::from(path.replace("\\\\", "\\")))
+        .collect()
+}
+
+// Values of `"key"		"value"` lines in Steam's .vdf and .acf files
+fn vdf_values<'a>(vdf: &'a str, key: &'a str) -> impl Iterator<Item = &'a str> {
+    vdf.lines().filter_map(move |line| {
+        let mut parts = line.split('"').filter(|part| !part.trim().is_empty());
+        match (parts.next(), parts.next()) {
+            (Some(name), Some(value)) if name == key => Some(value),
+            _ => None,
+        }
+    })
+}
+
+// The Steam build of a game folder, which changes with every game update. Steam keeps it
+// in steamapps/appmanifest_381210.acf next to the common folder; Epic installs have none.
+pub fn build_id(game_path: &Path) -> Option<String> {
+    let steamapps = game_path.parent()?.parent()?;
+    let manifest = steamapps.join(format!("appmanifest_{}.acf", STEAM_APP_ID));
+    let content = fs::read_to_string(manifest).ok()?;
+    let build_id = vdf_values(&content, "buildid").next().map(str::to_string);
+    build_id
+}
+
+fn detect_heroic() -> Vec<DetectedInstall> {
+    let home = home();
+    let config_dirs = [
+        home.join(".config/heroic"),
+        home.join(".var/app/com.heroicgameslauncher.hgl/config/heroic"),
+    ];
+
+    let mut found = Vec::new();
+    for config_dir in config_dirs.iter().filter(|dir| dir.is_dir()) {
+        let installed = config_dir.join("legendaryConfig/legendary/installed.json");
+        let Ok(content) = fs::read_to_string(installed) else {
+            continue;
+        };
+        let Ok(serde_json::Value::Object(games)) = serde_json::from_str(&content) else {
+            continue;
+        };
+
+        for (app_name, game) in games {
+            if game.get("title").and_then(|t| t.as_str()) != Some(GAME_TITLE) {
+                continue;
+            }
+            let Some(install_path) = game.get("install_path").and_then(|p| p.as_str()) else {
+                continue;
+            };
+            found.push(DetectedInstall {
+                launcher: "Heroic",
+                path: PathBuf::from(install_path),
+                prefix: heroic_prefix(config_dir, &app_name),
+            });
+        }
+    }
+    found
+}
+
+// GamesConfig/<app>.json holds the per-game Wine settings, keyed by the app name again
+fn heroic_prefix(config_dir: &Path, app_name: &str) -> Option<PathBuf> {
+    let file = config_dir.join("GamesConfig").join(format!("{}.json", app_name));
+    let content = fs::read_to_string(file).ok()?;
+    let config: serde_json::Value = serde_json::from_str(&content).ok()?;
+    let prefix = config.get(app_name)?.get("winePrefix")?.as_str()?;
+    Some(PathBuf::from(prefix))
+}
+
+// Lutris installs the Epic Games Store inside a Wine prefix, so the game folder is
+// searched for in the prefix of every configured game
+fn detect_lutris() -> Vec<DetectedInstall> {
+    let home = home();
+    let game_dirs = [
+        home.join(".local/share/lutris/games"),
+        home.join(".config/lutris/games"),
+        home.join(".var/app/net.lutris.Lutris/data/lutris/games"),
+        home.join(".var/app/net.lutris.Lutris/config/lutris/games"),
+    ];
+
+    let mut found = Vec::new();
+    for entry in game_dirs
+        .iter()
+        .filter_map(|dir| fs::read_dir(dir).ok())
+        .flatten()
+        .flatten()
+    {
+        let Ok(content) = fs::read_to_string(entry.path()) else {
+            continue;
+        };
+        let Ok(config) = serde_yaml::from_str::<serde_yaml::Value>(&content) else {
+            continue;
+        };
+        let game = &config["game"];
+
+        // A game entry pointing straight into the game folder
+        if let Some(exe) = game["exe"].as_str() {
+            if let Some(path) = Path::new(exe)
+                .ancestors()
+                .find(|dir| dir.file_name().and_then(|n| n.to_str()).is_some_and(is_game_folder_name))
+            {
+                found.push(DetectedInstall {
+                    launcher: "Lutris",
+                    path: path.to_path_buf(),
+                    prefix: game["prefix"].as_str().map(PathBuf::from),
+                });
+                continue;
+            }
+        }
+
+        if let Some(prefix) = game["prefix"].as_str().map(PathBuf::from) {
+            let epic_dir = prefix.join("drive_c/Program Files/Epic Games");
+            for name in FOLDER_NAMES {
+                let path = epic_dir.join(name);
+                if path.is_dir() {
+                    found.push(DetectedInstall {
+                        launcher: "Lutris",
+                        path,
+                        prefix: Some(prefix.clone()),
+                    });
+                }
+            }
+        }
+    }
+    found
+}
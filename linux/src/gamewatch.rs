@@ -0,0 +1,53 @@
+use std::fs;
+use std::time::Duration;
+
+// Notices the game starting, for applying the active profile at launch. The game runs
+// under Proton or Wine, so it shows up as a process whose command line names the
+// shipping executable: DeadByDaylight-Win64-Shipping.exe on Steam and
+// DeadByDaylight-EGS-Shipping.exe on Epic. The short process name is cut to 15
+// characters and can't tell the two apart from the launchers, so the command line is read.
+
+pub const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+const EXECUTABLE_PREFIX: &str = "DeadByDaylight-";
+const EXECUTABLE_SUFFIX: &str = "-Shipping.exe";
+
+// Whether any process of the user runs the game. There is no /proc on macOS, where the
+// game doesn't run either.
+pub fn is_running() -> bool {
+    let Ok(entries) = fs::read_dir("/proc") else {
+        return false;
+    };
+    entries
+        .flatten()
+        .filter(|entry| entry.file_name().to_string_lossy().bytes().all(|b| b.is_ascii_digit()))
+        .any(|entry| {
+            fs::read(entry.path().join("cmdline")).is_ok_and(|cmdline| {
+                cmdline
+                    .split(|b| *b == 0)
+                    .map(String::from_utf8_lossy)
+                    .any(|arg| is_game_executable(&arg))
+            })
+        })
+}
+
+fn is_game_executable(arg: &str) -> bool {
+    let name = arg.rsplit(['/', '\\']).next().unwrap_or(arg);
+    name.starts_with(EXECUTABLE_PREFIX) && name.ends_with(EXECUTABLE_SUFFIX)
+}
+
+// Remembers whether the game ran at the last poll
+#[derive(Default)]
+pub struct LaunchWatch {
+    running: bool,
+}
+
+impl LaunchWatch {
+    // True once each time the game was started since the last poll
+    pub fn started(&mut self) -> bool {
+        let running = is_running();
+        let started = running && !self.running;
+        self.running = running;
+        started
+    }
+}
@@ -0,0 +1,208 @@
+use anyhow::{bail, Context, Result};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+// GitHub allows 60 unauthenticated API requests an hour per IP address, which a shared
+// campus or CGNAT address can use up. Responses are cached with their ETag so repeated
+// checks are answered with "304 Not Modified", which doesn't count against the limit.
+// While limited, or when GitHub can't be reached, the cached response is used instead.
+
+// Short, the identity is still fetched while the window is being built
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Serialize, Deserialize)]
+struct CachedResponse {
+    etag: Option<String>,
+    body: String,
+}
+
+fn cache_dir() -> PathBuf {
+    crate::paths::cache_dir().join("github")
+}
+
+// "https://api.github.com/repos/a/b/releases" -> "repos_a_b_releases.json"
+fn cache_file(url: &str) -> PathBuf {
+    let path = url.trim_start_matches("https://api.github.com/");
+    let name: String = path
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    cache_dir().join(format!("{}.json", name))
+}
+
+fn rate_limit_file() -> PathBuf {
+    cache_dir().join("rate_limit_reset")
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+fn read_cache(url: &str) -> Option<CachedResponse> {
+    let content = fs::read_to_string(cache_file(url)).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+fn write_cache(url: &str, response: &CachedResponse) {
+    let path = cache_file(url);
+    let result = fs::create_dir_all(cache_dir())
+        .and_then(|_| fs::write(&path, serde_json::to_string(response).unwrap_or_default()));
+    if let Err(e) = result {
+        log_warn!("github", "Failed to cache {}: {}", url, e);
+    }
+}
+
+// Unix time at which the exhausted rate limit resets, if it hasn't yet
+fn rate_limited_until() -> Option<u64> {
+    let reset: u64 = fs::read_to_string(rate_limit_file()).ok()?.trim().parse().ok()?;
+    (reset > now_secs()).then_some(reset)
+}
+
+fn set_rate_limited_until(reset: u64) {
+    let _ = fs::create_dir_all(cache_dir());
+    let _ = fs::write(rate_limit_file(), reset.to_string());
+}
+
+// 1s, 2s, 4s... plus up to half of that again, so clients that failed together don't
+// retry together
+fn backoff(attempt: u32) -> Duration {
+    let base = Duration::from_secs(1 << attempt.min(5));
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.subsec_nanos());
+    base + base.mul_f64(f64::from(nanos % 1000) / 2000.0)
+}
+
+async fn send_with_retries(
+    client: &reqwest::Client,
+    url: &str,
+    etag: Option<&str>,
+    retries: u32,
+) -> reqwest::Result<reqwest::Response> {
+    let mut attempt = 0;
+    loop {
+        let mut request = client
+            .get(url)
+            .header("Accept", "application/vnd.github+json");
+        if let Some(etag) = etag {
+            request = request.header("If-None-Match", etag);
+        }
+
+        let result = request.send().await;
+        let retryable = match &result {
+            Ok(response) => response.status().is_server_error(),
+            Err(e) => e.is_connect() || e.is_timeout() || e.is_request(),
+        };
+        if !retryable || attempt >= retries {
+            return result;
+        }
+
+        let delay = backoff(attempt);
+        attempt += 1;
+        log_info!("github", "Request to {} failed, retry {} of {} in {:?}", url, attempt, retries, delay);
+        tokio::time::sleep(delay).await;
+    }
+}
+
+fn header_u64(response: &reqwest::Response, name: &str) -> Option<u64> {
+    response.headers().get(name)?.to_str().ok()?.parse().ok()
+}
+
+// The last response cached for `url`, without any request
+pub fn cached_json<T: serde::de::DeserializeOwned>(url: &str) -> Option<T> {
+    serde_json::from_str(&read_cache(url)?.body).ok()
+}
+
+// GET a GitHub API URL and parse the JSON body, going through the cache described above.
+// Connection errors and server errors are retried up to `retries` times with a growing,
+// jittered delay before falling back to the cache.
+pub async fn get_json<T: serde::de::DeserializeOwned>(url: &str, retries: u32) -> Result<T> {
+    let body = get(url, retries, true).await?;
+    serde_json::from_str(&body).with_context(|| format!("Failed to parse the response of {}", url))
+}
+
+// Like get_json, but fails instead of answering with an outdated cached response, for
+// callers that have another source to try first
+pub async fn get_json_fresh<T: serde::de::DeserializeOwned>(url: &str, retries: u32) -> Result<T> {
+    let body = get(url, retries, false).await?;
+    serde_json::from_str(&body).with_context(|| format!("Failed to parse the response of {}", url))
+}
+
+async fn get(url: &str, retries: u32, allow_stale: bool) -> Result<String> {
+    let cached = read_cache(url);
+
+    if let Some(reset) = rate_limited_until() {
+        return match cached.filter(|_| allow_stale) {
+            Some(cached) => Ok(cached.body),
+            None => bail!(
+                "GitHub rate limit reached, try again in {} minutes",
+                (reset - now_secs()).div_ceil(60)
+            ),
+        };
+    }
+
+    let client = crate::net::client(Some(TIMEOUT))?;
+    let etag = cached.as_ref().and_then(|c| c.etag.clone());
+    let response = match send_with_retries(&client, url, etag.as_deref(), retries).await {
+        Ok(response) => response,
+        Err(e) => {
+            return match cached.filter(|_| allow_stale) {
+                Some(cached) => {
+                    log_warn!("github", "{} unreachable, using the cached response: {}", url, e);
+                    Ok(cached.body)
+                }
+                None => Err(e).with_context(|| format!("Failed to fetch {}", url)),
+            };
+        }
+    };
+
+    let status = response.status();
+    if status == StatusCode::NOT_MODIFIED {
+        if let Some(cached) = cached {
+            return Ok(cached.body);
+        }
+    }
+
+    let exhausted = header_u64(&response, "x-ratelimit-remaining") == Some(0);
+    if status == StatusCode::TOO_MANY_REQUESTS || (status == StatusCode::FORBIDDEN && exhausted) {
+        let reset = header_u64(&response, "x-ratelimit-reset")
+            .or_else(|| header_u64(&response, "retry-after").map(|secs| now_secs() + secs))
+            .unwrap_or_else(|| now_secs() + 60);
+        set_rate_limited_until(reset);
+        log_warn!("github", "Rate limited until {}, using cached responses", reset);
+        return match cached.filter(|_| allow_stale) {
+            Some(cached) => Ok(cached.body),
+            None => bail!(
+                "GitHub rate limit reached, try again in {} minutes",
+                reset.saturating_sub(now_secs()).div_ceil(60)
+            ),
+        };
+    }
+
+    if status.is_server_error() {
+        if let Some(cached) = cached.filter(|_| allow_stale) {
+            log_warn!("github", "GitHub answered {} for {}, using the cached response", status, url);
+            return Ok(cached.body);
+        }
+    }
+    if !status.is_success() {
+        bail!("GitHub answered {} for {}", status, url);
+    }
+
+    let etag = response
+        .headers()
+        .get("etag")
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string);
+    let body = response
+        .text()
+        .await
+        .with_context(|| format!("Failed to read the response of {}", url))?;
+    write_cache(url, &CachedResponse { etag, body: body.clone() });
+    Ok(body)
+}
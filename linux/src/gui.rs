@@ -0,0 +1,7594 @@
+use crate::{
+    autostart, deck, diagnostics, flags, games, github, help, i18n, integrity, launcher, movies, plugins, region_row,
+    selfupdate, service, splash, theme, tray, update, api, control, crash, dbus, hooks, gamepad, gamewatch, packaging, sniff, uri, vpn,
+};
+use crate::{has_required_caps, load_versinf, LastSeen, APP_ID, DISCORD_URL};
+use crate::gamewatch::LaunchWatch;
+use myc_core::{aws_ranges, backend, community, custom_hosts, dns, errors, geo, history, hosts, logging, matches, net, overrides, paths, ping, region, settings};
+
+use gio::{Menu, SimpleAction};
+use libadwaita as adw;
+use adw::prelude::*;
+use adw::{Application, ApplicationWindow};
+use gtk4::{
+    gio, glib, Box as GtkBox, Button, ButtonsType,
+    CheckButton, ColumnView, ColumnViewColumn, ComboBoxText, Dialog, FileChooserAction,
+    FileChooserNative, FileFilter, Image, Label, ListItem, MenuButton, MessageDialog,
+    MessageType, Orientation, PolicyType, ResponseType, ScrolledWindow, Separator,
+    SignalListItemFactory,
+};
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+use anyhow::Context as _;
+use chrono::{DateTime, Local};
+use std::time::{Duration, Instant};
+use tokio::runtime::Runtime;
+
+use errors::ErrorKind;
+use backend::{ActiveConfiguration, EnforcementBackend, Progress};
+use hosts::{HostsLineKind, HostsManager};
+use ping::{PingHistory, PingScheduler};
+use history::Trigger;
+use region::*;
+use region_row::RegionRow;
+use settings::{
+    AppliedSelection, ColorScheme, DeckMode, Density, DnsUpstream, GameInstall, LatencyPalette, NotificationKind,
+    NotificationSettings, Profile, Role, SnapshotReason, UpdateChannel, UserSettings, WindowState,
+};
+use update::{Release, UpdateChecker};
+use sniff::TrafficSniffer;
+use aws_ranges::AwsIpService;
+use tray::{TrayCommand, TrayHandle};
+use gamepad::GamepadInput;
+use i18n::{tr, trf};
+
+// Command line options that re-apply the last applied selection or revert, in the
+// running instance if there is one. Named like the app actions they activate.
+const APPLY_LAST_OPTION: &str = "apply-last";
+const REVERT_OPTION: &str = "revert";
+
+#[derive(Clone)]
+struct AppConfig {
+    current_version: String,
+    // GitHub login of the developer, from the cache until refresh_git_identity is done
+    developer: Rc<RefCell<Option<String>>>,
+    repo: String,
+    update_message: String,
+    discord_url: String,
+}
+
+impl AppConfig {
+    fn developer(&self) -> Option<String> {
+        self.developer.borrow().clone()
+    }
+
+    fn repo_url(&self) -> Option<String> {
+        self.developer().map(|dev| format!("https://github.com/{}/{}", dev, self.repo))
+    }
+}
+
+#[allow(dead_code)]
+struct AppState {
+    config: AppConfig,
+    regions: HashMap<String, RegionInfo>,
+        blocked_regions: HashMap<String, RegionInfo>,
+    settings: Arc<Mutex<UserSettings>>,
+    // Applies, reverts and reports the selection
+    backend: Arc<dyn EnforcementBackend>,
+    // Hosts file details: the viewer, export, undoing a restore and the tray's blocked list
+    hosts_manager: HostsManager,
+    // Replaced once the developer identity arrives
+    update_checker: RefCell<UpdateChecker>,
+    selected_regions: RefCell<HashSet<String>>,
+    list_store: gio::ListStore,
+    tokio_runtime: Arc<Runtime>,
+    sniffer: Arc<TrafficSniffer>,
+    aws_service: Arc<AwsIpService>,
+    connected_to_label: Label,
+    connection_dot: Label,
+    ping_scheduler: Rc<RefCell<PingScheduler>>,
+    ping_source: RefCell<Option<glib::SourceId>>,
+    ping_in_flight: Cell<bool>,
+    // Latest round of pings in ms (-1 unreachable), served over D-Bus and HTTP
+    latencies: RefCell<HashMap<String, i64>>,
+    // This session's pings per region, for the comparison dialog
+    ping_history: RefCell<PingHistory>,
+    // What each ping round probes, see ping::targets
+    ping_targets: Arc<[(String, String)]>,
+    last_seen: LastSeen,
+    // Sender for the local HTTP API, answered by handle_control_command
+    control_requests: control::Requests,
+    api_server: RefCell<Option<api::ApiServer>>,
+    toast_overlay: adw::ToastOverlay,
+    tray: Option<TrayHandle>,
+    // Regions the hosts file currently lets through, as far as this session knows
+    applied_selection: RefCell<HashSet<String>>,
+    window_title: adw::WindowTitle,
+    active_banner: adw::Banner,
+    status_bar: StatusBar,
+    apply_button: Button,
+    launch_button: Button,
+    best_button: Button,
+    // Entries of the profile switcher next to Apply, rebuilt when profiles change
+    profile_menu: Menu,
+    // app.profile, its state is the active profile name ("" for none)
+    profile_action: SimpleAction,
+    // Killer/Survivor switch in the header bar, pressed for the role of the active profile
+    role_buttons: Vec<(Role, gtk4::ToggleButton)>,
+    // Set by Apply & Launch, the game starts once the hosts file was written
+    launch_after_apply: Cell<bool>,
+    revert_button: Button,
+    hosts_spinner: gtk4::Spinner,
+    hosts_busy: Cell<bool>,
+    // Stops the running hosts task until it starts writing
+    hosts_cancel: Button,
+    hosts_task: RefCell<Option<tokio::task::AbortHandle>>,
+    gamepad_hints: Label,
+    gamepad_tx: std::sync::mpsc::Sender<GamepadInput>,
+    gamepad_started: Cell<bool>,
+    search_entry: gtk4::SearchEntry,
+    region_scroll: ScrolledWindow,
+    // Hidden unless View → Show resolved addresses is on
+    addresses_column: ColumnViewColumn,
+    // Kept alive so edits to config.yaml made outside the app are picked up
+    settings_monitor: RefCell<Option<gio::FileMonitor>>,
+    // When updates were last looked for, the periodic check counts from here
+    last_update_check: Cell<Option<Instant>>,
+    // Newest version already announced by the periodic check this session
+    announced_version: RefCell<Option<String>>,
+    // Region of the match the latency alert was shown for, once per match
+    latency_alerted: RefCell<Option<String>>,
+}
+
+// Slim bar under the list: managed hosts section, conflicts, last apply and the ping loop
+struct StatusBar {
+    container: GtkBox,
+    section: Label,
+    conflicts: Button,
+    applied: Label,
+    pings: Label,
+}
+
+impl StatusBar {
+    fn new() -> Self {
+        let section = Label::new(None);
+        let conflicts = Button::builder()
+            .visible(false)
+            .css_classes(["flat", "warning"])
+            .tooltip_text(tr("Review and remove conflicting entries"))
+            .build();
+        let applied = Label::new(None);
+        let pings = Label::builder()
+            .hexpand(true)
+            .halign(gtk4::Align::End)
+            .build();
+        for label in [&section, &applied, &pings] {
+            label.add_css_class("dim-label");
+        }
+
+        let container = GtkBox::new(Orientation::Horizontal, 12);
+        container.add_css_class("status-bar");
+        container.append(&section);
+        container.append(&conflicts);
+        container.append(&applied);
+        container.append(&pings);
+
+        Self {
+            container,
+            section,
+            conflicts,
+            applied,
+            pings,
+        }
+    }
+}
+
+fn get_severity_for_latency(ms: i64) -> &'static str {
+    if ms < 0 {
+        return "latency-none";
+    }
+    if ms < 80 {
+        return "latency-good";
+    }
+    if ms < 130 {
+        return "latency-fair";
+    }
+    if ms < 250 {
+        return "latency-poor";
+    }
+    "latency-severe"
+}
+
+// Applies density, text size and Steam Deck mode from the settings to the main window
+fn apply_layout_settings(app_state: &AppState, window: &ApplicationWindow) {
+    let settings = app_state.settings.lock().unwrap();
+    let deck = deck::is_active(settings.deck_mode);
+    theme::apply_layout(window, settings.density, settings.font_scale, deck);
+    app_state.gamepad_hints.set_visible(deck);
+
+    if deck && !app_state.gamepad_started.replace(true) {
+        let count = gamepad::spawn_listeners(app_state.gamepad_tx.clone());
+        if count == 0 {
+            log_warn!("gamepad", "No readable controllers found.");
+        }
+    }
+}
+
+// Controller buttons drive focus like the keyboard does: the D-pad moves between widgets
+// (and between regions inside the list), A activates, B closes dialogs
+fn handle_gamepad_input(
+    app_state: &AppState,
+    app: &Application,
+    main_window: &ApplicationWindow,
+    column_view: &ColumnView,
+    selection: &gtk4::SingleSelection,
+    input: GamepadInput,
+) {
+    let Some(window) = app.active_window() else { return; };
+    if !window.is_visible() {
+        return;
+    }
+    let focus = gtk4::prelude::GtkWindowExt::focus(&window);
+    let is_main = window == *main_window.upcast_ref::<gtk4::Window>();
+    let in_list = is_main && focus.as_ref().is_some_and(|focus| focus.is_ancestor(column_view));
+
+    let direction = match input {
+        GamepadInput::Up => Some(gtk4::DirectionType::Up),
+        GamepadInput::Down => Some(gtk4::DirectionType::Down),
+        GamepadInput::Left => Some(gtk4::DirectionType::Left),
+        GamepadInput::Right => Some(gtk4::DirectionType::Right),
+        _ => None,
+    };
+
+    match input {
+        GamepadInput::Up | GamepadInput::Down if in_list => {
+            let key = if input == GamepadInput::Up {
+                gtk4::gdk::Key::Up
+            } else {
+                gtk4::gdk::Key::Down
+            };
+            if let Some(target) = next_region_position(selection, key) {
+                let _ = column_view.activate_action(
+                    "list.select-item",
+                    Some(&(target, false, false).to_variant()),
+                );
+                let _ = column_view.activate_action("list.scroll-to-item", Some(&target.to_variant()));
+            }
+        }
+        GamepadInput::Up | GamepadInput::Down | GamepadInput::Left | GamepadInput::Right => {
+            if let Some(direction) = direction {
+                window.child_focus(direction);
+            }
+        }
+        GamepadInput::Accept if in_list => toggle_region_at(selection, selection.selected()),
+        GamepadInput::Accept => match focus {
+            Some(focus) => {
+                focus.activate();
+            }
+            None => {
+                window.activate_default();
+            }
+        },
+        GamepadInput::Back => {
+            if !is_main {
+                window.close();
+            }
+        }
+        // The apply summary asks already when it is on
+        GamepadInput::Start if app_state.settings.lock().unwrap().confirm_apply => app.activate_action("apply", None),
+        GamepadInput::Start => {
+            confirm_gamepad_action(app, main_window, &tr("Apply the checked servers?"), &tr("Apply"), "apply")
+        }
+        GamepadInput::West => confirm_gamepad_action(
+            app,
+            main_window,
+            &tr("Remove the Make Your Choice entries from the hosts file?"),
+            &tr("Revert"),
+            "revert",
+        ),
+        GamepadInput::North => app.activate_action("refresh-pings", None),
+        GamepadInput::Select => app.activate_action("settings", None),
+    }
+}
+
+// A stray press on the pad shouldn't rewrite the hosts file, so its shortcuts ask first
+// with large buttons: A confirms, B cancels
+fn confirm_gamepad_action(app: &Application, window: &ApplicationWindow, question: &str, accept_label: &str, action: &str) {
+    let dialog = MessageDialog::new(
+        Some(window),
+        gtk4::DialogFlags::MODAL,
+        MessageType::Question,
+        ButtonsType::None,
+        question,
+    );
+    dialog.add_css_class("deck");
+    dialog.add_button(&tr("Cancel"), ResponseType::Cancel);
+    dialog.add_button(accept_label, ResponseType::Accept);
+    dialog.set_default_response(ResponseType::Accept);
+    if let Some(button) = dialog.widget_for_response(ResponseType::Accept) {
+        button.grab_focus();
+    }
+
+    let app = app.clone();
+    let action = action.to_string();
+    dialog.run_async(move |dialog, response| {
+        dialog.close();
+        if response == ResponseType::Accept {
+            app.activate_action(&action, None);
+        }
+    });
+}
+
+fn toggle_region_at(selection: &gtk4::SingleSelection, position: u32) {
+    if let Some(row) = selection.item(position).and_downcast::<RegionRow>() {
+        if !row.is_divider() {
+            row.set_selected(!row.selected());
+        }
+    }
+}
+
+// Position of the region row an arrow/Home/End press should move to, skipping dividers
+fn next_region_position(selection: &gtk4::SingleSelection, key: gtk4::gdk::Key) -> Option<u32> {
+    use gtk4::gdk::Key;
+    let count = selection.n_items();
+    let current = selection.selected();
+    let has_current = current != gtk4::INVALID_LIST_POSITION;
+    let is_region = |position: &u32| {
+        selection
+            .item(*position)
+            .and_downcast::<RegionRow>()
+            .is_some_and(|row| !row.is_divider())
+    };
+
+    match key {
+        Key::Down => {
+            let start = if has_current { current + 1 } else { 0 };
+            (start..count).find(is_region)
+        }
+        Key::Up => {
+            let end = if has_current { current } else { count };
+            (0..end).rev().find(is_region)
+        }
+        Key::Home => (0..count).find(is_region),
+        Key::End => (0..count).rev().find(is_region),
+        _ => None,
+    }
+}
+
+// Iterates the region rows of the list, skipping group dividers
+fn region_rows(list_store: &gio::ListStore) -> impl Iterator<Item = RegionRow> + '_ {
+    list_store
+        .iter::<RegionRow>()
+        .filter_map(Result::ok)
+        .filter(|row| !row.is_divider())
+}
+
+// Re-renders the cells after high contrast or the palette changed, here or in the system
+fn refresh_spelled_out(list_store: &gio::ListStore) {
+    let spell_out = theme::spells_out();
+    for row in region_rows(list_store) {
+        row.set_spell_out(spell_out);
+    }
+}
+
+fn refresh_warning_symbols(list_store: &gio::ListStore, merge_unstable: bool) {
+    for row in region_rows(list_store) {
+        row.refresh_warning(merge_unstable);
+    }
+}
+
+fn git_identity_url() -> String {
+    const UID: &str = "109703063"; // Changing this, or the final result of this functionality may break license compliance
+    format!("https://api.github.com/user/{}", UID)
+}
+
+fn identity_login(json: &serde_json::Value) -> Option<String> {
+    json.get("login").and_then(|v| v.as_str()).map(str::to_string)
+}
+
+// The identity from the last successful fetch, without touching the network
+fn cached_git_identity() -> Option<String> {
+    github::cached_json::<serde_json::Value>(&git_identity_url()).and_then(|json| identity_login(&json))
+}
+
+async fn fetch_git_identity() -> Option<String> {
+    // No retries, the next start tries again
+    match github::get_json::<serde_json::Value>(&git_identity_url(), 0).await {
+        Ok(json) => identity_login(&json),
+        Err(e) => {
+            log_warn!("github", "Failed to fetch the developer identity: {:#}", e);
+            None
+        }
+    }
+}
+
+// The window, after main() ruled out the command line and the service
+pub fn run() -> glib::ExitCode {
+    // The demo never touches the system, so it needs no capabilities
+    if !overrides::get().demo {
+        ensure_capabilities_or_exit();
+    }
+    // After the capability check, moving /etc/hosts.bak needs write access to /etc
+    paths::migrate_legacy();
+
+    // Picked once per run, strings are translated as the UI is built
+    i18n::init(&UserSettings::load().unwrap_or_default().language);
+
+    let app = Application::builder()
+        .application_id(APP_ID)
+        .flags(gio::ApplicationFlags::HANDLES_OPEN)
+        .build();
+    app.add_main_option(
+        autostart::MINIMIZED_OPTION,
+        glib::Char::from(b'm'),
+        glib::OptionFlags::NONE,
+        glib::OptionArg::None,
+        &tr("Start hidden in the tray"),
+        None,
+    );
+    app.add_main_option(
+        APPLY_LAST_OPTION,
+        glib::Char::from(b'\0'),
+        glib::OptionFlags::NONE,
+        glib::OptionArg::None,
+        &tr("Apply the last applied selection again"),
+        None,
+    );
+    app.add_main_option(
+        REVERT_OPTION,
+        glib::Char::from(b'\0'),
+        glib::OptionFlags::NONE,
+        glib::OptionArg::None,
+        &tr("Remove the Make Your Choice entries from the hosts file"),
+        None,
+    );
+
+    // Parsed by overrides::get() before GTK starts, registered so GLib accepts them
+    for (name, arg, description, value) in [
+        (overrides::HOSTS_FILE_OPTION, glib::OptionArg::Filename, tr("Edit this file instead of /etc/hosts"), Some("PATH")),
+        (overrides::CONFIG_DIR_OPTION, glib::OptionArg::Filename, tr("Keep the settings in this directory"), Some("PATH")),
+        (overrides::APPLY_MODE_OPTION, glib::OptionArg::String, tr("Use this method for this session"), Some("gatekeep|universal-redirect")),
+        (overrides::OFFLINE_OPTION, glib::OptionArg::None, tr("Skip update checks and other network requests"), None),
+        (overrides::DNS_SERVER_OPTION, glib::OptionArg::String, tr("Look up the redirect target on this DNS server"), Some("IP[:PORT]")),
+        (overrides::DEMO_OPTION, glib::OptionArg::None, tr("Try the app with made-up pings and matches, without changing the system"), None),
+    ] {
+        app.add_main_option(name, glib::Char::from(b'\0'), glib::OptionFlags::NONE, arg, &description, value);
+    }
+
+    let start_minimized = Rc::new(Cell::new(false));
+    let start_minimized_clone = start_minimized.clone();
+    let pending_action = Rc::new(Cell::new(None));
+    let pending_action_clone = pending_action.clone();
+    app.connect_handle_local_options(move |app, options| {
+        start_minimized_clone.set(options.contains(autostart::MINIMIZED_OPTION));
+        if let Some(action) = [APPLY_LAST_OPTION, REVERT_OPTION].into_iter().find(|option| options.contains(option)) {
+            // Already running: let that instance do it and exit
+            if app.register(gio::Cancellable::NONE).is_ok() && app.is_remote() {
+                app.activate_action(action, None);
+                return 0;
+            }
+            pending_action_clone.set(Some(action));
+        }
+        -1 // Continue with the default activation
+    });
+
+    app.connect_activate(move |app| {
+        // Launching again while running (e.g. hidden in the tray) brings the window back
+        if let Some(window) = app.active_window() {
+            window.present();
+            return;
+        }
+        build_ui(app, start_minimized.get());
+        if let Some(action) = pending_action.take() {
+            app.activate_action(action, None);
+        }
+    });
+
+    // myc:// links, handed to the running instance by GApplication
+    app.connect_open(|app, files, _| {
+        if app.active_window().is_none() {
+            build_ui(app, false);
+        }
+        for file in files {
+            app.activate_action("open-uri", Some(&file.uri().to_variant()));
+        }
+    });
+    app.run()
+}
+
+fn ensure_capabilities_or_exit() {
+    let exe = match std::env::current_exe() {
+        Ok(path) => path,
+        Err(e) => {
+            log_error!("startup", "Failed to get executable path: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    if has_required_caps(&exe) {
+        return;
+    }
+
+    let status = std::process::Command::new("pkexec")
+        .arg("setcap")
+        .arg("cap_net_raw,cap_dac_override+ep")
+        .arg(&exe)
+        .status();
+
+    match status {
+        Ok(s) if s.success() => {
+            let _ = std::process::Command::new(&exe)
+                .args(std::env::args_os().skip(1))
+                .spawn();
+            std::process::exit(0);
+        }
+        Ok(_) => {
+            log_error!("startup", "Failed to set required capabilities. Operation cancelled or permission denied.");
+            std::process::exit(1);
+        }
+        Err(e) => {
+            log_error!("startup", "Failed to run pkexec/setcap: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+fn build_ui(app: &Application, start_minimized: bool) {
+    // Create tokio runtime for async operations
+    let tokio_runtime = Arc::new(Runtime::new().expect("Failed to create tokio runtime"));
+
+    // Load settings first
+    let settings = Arc::new(Mutex::new(UserSettings::load().unwrap_or_default()));
+    theme::apply_color_scheme(settings.lock().unwrap().color_scheme);
+    theme::set_high_contrast(settings.lock().unwrap().high_contrast);
+    theme::set_latency_palette(settings.lock().unwrap().latency_palette);
+    settings.lock().unwrap().proxy.activate();
+    settings.lock().unwrap().dns.activate();
+
+    // GTK takes the text direction from the system locale, follow the chosen translation
+    // instead so box packing, columns and glyph placement mirror along with the text
+    gtk4::Widget::set_default_direction(if i18n::is_rtl() {
+        gtk4::TextDirection::Rtl
+    } else {
+        gtk4::TextDirection::Ltr
+    });
+
+    // Load configuration. The git identifier comes from the cache, startup never waits on
+    // GitHub; refresh_git_identity fetches it once the window is up.
+    let last_version = settings.lock().unwrap().last_launched_version.clone();
+    let (current_version, update_message) = load_versinf(&last_version);
+    let config = AppConfig {
+        current_version,
+        developer: Rc::new(RefCell::new(cached_git_identity())),
+        repo: "make-your-choice".to_string(), // Repository name
+        update_message,
+        discord_url: DISCORD_URL.to_string(),
+    };
+
+    let regions = get_selectable_regions();
+        let blocked_regions = get_blocked_regions();
+    let backend = backend::current(&config.discord_url);
+    let hosts_manager = HostsManager::new(config.discord_url.clone());
+    let update_checker = UpdateChecker::new(
+        config.developer().unwrap_or_else(|| "unknown".to_string()),
+        config.repo.clone(),
+        config.current_version.clone(),
+    );
+
+    // Check if the user's previously used version differs from current version and show patch notes
+    let mut patch_notes_banner = false;
+    {
+        let mut settings_lock = settings.lock().unwrap();
+        if settings_lock.last_launched_version != config.current_version
+            && !config.update_message.is_empty()
+        {
+            if settings_lock.patch_notes_popup {
+                show_patch_notes(None, &config);
+            } else {
+                patch_notes_banner = true;
+            }
+
+            settings_lock.last_launched_version = config.current_version.clone();
+            settings_lock.auto_update_check_paused_until = None;
+            let _ = settings_lock.save();
+        }
+    }
+
+    // One RegionRow per region, plus a divider row heading each group
+    let list_store = gio::ListStore::new::<RegionRow>();
+
+    // Group regions by category
+    let mut groups: HashMap<&'static str, Vec<(&String, &RegionInfo)>> = HashMap::new();
+    for (region_name, region_info) in &regions {
+        let group_name = get_group_name(region_name);
+        groups
+            .entry(group_name)
+            .or_insert_with(Vec::new)
+            .push((region_name, region_info));
+    }
+
+    // Define group order and names matching Windows version
+    let group_order = vec![
+        ("Europe", tr("Europe")),
+        ("Americas", tr("The Americas")),
+        ("Asia", tr("Asia (Excl. Cn)")),
+        ("Oceania", tr("Oceania")),
+        ("China", tr("Mainland China")),
+    ];
+
+    // Check merge_unstable setting to determine if we show warning symbols
+    let merge_unstable = settings.lock().unwrap().merge_unstable;
+
+    // Populate list store with dividers and regions
+    for (group_key, group_label) in group_order.iter() {
+        if let Some(group_regions) = groups.get(group_key) {
+            list_store.append(&RegionRow::divider(group_label));
+
+            for (region_name, region_info) in group_regions {
+                let row = RegionRow::new(region_name, &region_info.country, region_info.stable);
+                row.refresh_warning(merge_unstable);
+                row.set_spell_out(theme::spells_out());
+                list_store.append(&row);
+            }
+        }
+    }
+
+    // Server column: checkbox, country flag, name and unstable marker
+    let server_factory = SignalListItemFactory::new();
+    server_factory.connect_setup(|_, item| {
+        let item = item.downcast_ref::<ListItem>().unwrap();
+        let check = CheckButton::new();
+        // Decorative, the region name already says where the server is
+        let flag = Image::builder()
+            .pixel_size(20)
+            .accessible_role(gtk4::AccessibleRole::Presentation)
+            .build();
+        let name_label = Label::new(None);
+        name_label.set_halign(gtk4::Align::Start);
+        let warning_label = Label::new(Some("⚠︎"));
+        warning_label.add_css_class("unstable-warning");
+        warning_label.update_property(&[gtk4::accessible::Property::Label(&tr("Unstable server"))]);
+        check.update_relation(&[gtk4::accessible::Relation::LabelledBy(&[name_label.upcast_ref()])]);
+
+        let cell = GtkBox::new(Orientation::Horizontal, 6);
+        cell.append(&check);
+        cell.append(&flag);
+        cell.append(&name_label);
+        cell.append(&warning_label);
+
+        let row_expression = item.property_expression("item");
+        row_expression
+            .chain_property::<RegionRow>("name")
+            .bind(&name_label, "label", gtk4::Widget::NONE);
+        row_expression
+            .chain_property::<RegionRow>("show-warning")
+            .bind(&warning_label, "visible", gtk4::Widget::NONE);
+        // A thin glyph is easy to miss, high contrast and the other palettes spell it out
+        row_expression
+            .chain_property::<RegionRow>("spell-out")
+            .chain_closure::<String>(glib::closure!(
+                |_: Option<glib::Object>, spell_out: bool| {
+                    if spell_out {
+                        trf("⚠ {}", &[&tr("Unstable")])
+                    } else {
+                        "⚠︎".to_string()
+                    }
+                }
+            ))
+            .bind(&warning_label, "label", gtk4::Widget::NONE);
+        row_expression
+            .chain_property::<RegionRow>("selected")
+            .bind(&check, "active", gtk4::Widget::NONE);
+        row_expression
+            .chain_property::<RegionRow>("tooltip")
+            .chain_closure::<Option<String>>(glib::closure!(
+                |_: Option<glib::Object>, tooltip: &str| {
+                    (!tooltip.is_empty()).then(|| tooltip.to_string())
+                }
+            ))
+            .bind(&cell, "tooltip-text", gtk4::Widget::NONE);
+
+        // Screen readers get the whole row as text: region, latency, severity and stability.
+        // The checked state is announced by the checkbox itself.
+        let summary = gtk4::ClosureExpression::new::<String>(
+            [
+                row_expression.chain_property::<RegionRow>("name"),
+                row_expression.chain_property::<RegionRow>("latency"),
+                row_expression.chain_property::<RegionRow>("severity"),
+                row_expression.chain_property::<RegionRow>("show-warning"),
+            ],
+            glib::closure!(|_: Option<glib::Object>,
+                            name: &str,
+                            latency: &str,
+                            severity: &str,
+                            unstable: bool| {
+                let mut parts = vec![name.to_string()];
+                if !latency.is_empty() && latency != "…" {
+                    parts.push(latency.to_string());
+                }
+                parts.push(theme::severity_description(severity));
+                if unstable {
+                    parts.push(tr("unstable, issues may occur"));
+                }
+                parts.join(", ")
+            }),
+        );
+        let summary_for_watch = summary.clone();
+        let check_for_watch = check.clone();
+        summary.watch(None::<&glib::Object>, move || {
+            if let Some(text) = summary_for_watch.evaluate_as::<String, _>(None::<&glib::Object>) {
+                check_for_watch.update_property(&[gtk4::accessible::Property::Label(&text)]);
+            }
+        });
+
+        // Write user toggles back into the model
+        let item_weak = item.downgrade();
+        check.connect_toggled(move |check| {
+            let row = item_weak
+                .upgrade()
+                .and_then(|item| item.item())
+                .and_downcast::<RegionRow>();
+            if let Some(row) = row {
+                if row.selected() != check.is_active() {
+                    row.set_selected(check.is_active());
+                }
+            }
+        });
+
+        // Context menu with right click or a long press: copy the server's hostnames or
+        // addresses for router configs, DNS blocklists and support threads
+        let open_menu = {
+            let item_weak = item.downgrade();
+            let cell = cell.clone();
+            move |x: f64, y: f64| {
+                let row = item_weak
+                    .upgrade()
+                    .and_then(|item| item.item())
+                    .and_downcast::<RegionRow>();
+                let Some(row) = row.filter(|row| !row.is_divider()) else { return; };
+                let menu = Menu::new();
+                for (label, action) in [
+                    (tr("Copy hostnames"), "app.copy-hostnames"),
+                    (tr("Copy resolved IPs"), "app.copy-addresses"),
+                ] {
+                    let menu_item = gio::MenuItem::new(Some(&label), None);
+                    menu_item.set_action_and_target_value(Some(action), Some(&row.name().to_variant()));
+                    menu.append_item(&menu_item);
+                }
+                let popover = gtk4::PopoverMenu::from_model(Some(&menu));
+                popover.set_parent(&cell);
+                popover.set_has_arrow(false);
+                popover.set_pointing_to(Some(&gtk4::gdk::Rectangle::new(x as i32, y as i32, 1, 1)));
+                popover.connect_closed(|popover| {
+                    let popover = popover.clone();
+                    // Unparented after the activated item has run its action
+                    glib::idle_add_local_once(move || popover.unparent());
+                });
+                popover.popup();
+            }
+        };
+        let right_click = gtk4::GestureClick::builder().button(gtk4::gdk::BUTTON_SECONDARY).build();
+        let open_menu_clone = open_menu.clone();
+        right_click.connect_pressed(move |gesture, _, x, y| {
+            gesture.set_state(gtk4::EventSequenceState::Claimed);
+            open_menu_clone(x, y);
+        });
+        cell.add_controller(right_click);
+        let long_press = gtk4::GestureLongPress::builder().touch_only(true).build();
+        long_press.connect_pressed(move |gesture, x, y| {
+            gesture.set_state(gtk4::EventSequenceState::Claimed);
+            open_menu(x, y);
+        });
+        cell.add_controller(long_press);
+
+        item.set_child(Some(&cell));
+    });
+    server_factory.connect_bind(|_, item| {
+        let item = item.downcast_ref::<ListItem>().unwrap();
+        let Some(row) = item.item().and_downcast::<RegionRow>() else { return; };
+        let Some(cell) = item.child() else { return; };
+
+        // Dividers are plain bold headings: no checkbox, not activatable
+        item.set_activatable(!row.is_divider());
+        item.set_selectable(!row.is_divider());
+        if let Some(check) = cell.first_child() {
+            check.set_visible(!row.is_divider());
+            let flag = check.next_sibling().and_downcast::<Image>();
+            if let Some(flag) = &flag {
+                let texture = flags::texture(&row.country());
+                flag.set_visible(texture.is_some());
+                flag.set_paintable(texture.as_ref());
+            }
+            if let Some(name_label) = flag.and_then(|flag| flag.next_sibling()) {
+                if row.is_divider() {
+                    name_label.add_css_class("divider-label");
+                } else {
+                    name_label.remove_css_class("divider-label");
+                }
+            }
+        }
+    });
+
+    // Latency column: italic text colored by severity
+    let latency_factory = SignalListItemFactory::new();
+    latency_factory.connect_setup(|_, item| {
+        let item = item.downcast_ref::<ListItem>().unwrap();
+        let label = Label::new(None);
+        label.set_halign(gtk4::Align::Start);
+
+        let row_expression = item.property_expression("item");
+        // The severity as a word too, where colors alone are hard to tell apart
+        gtk4::ClosureExpression::new::<String>(
+            [
+                row_expression.chain_property::<RegionRow>("latency"),
+                row_expression.chain_property::<RegionRow>("severity"),
+                row_expression.chain_property::<RegionRow>("spell-out"),
+            ],
+            glib::closure!(|_: Option<glib::Object>, latency: &str, severity: &str, spell_out: bool| {
+                let word = theme::severity_word(severity);
+                if spell_out && !word.is_empty() && latency != "…" {
+                    trf("{} · {}", &[&latency, &word])
+                } else {
+                    latency.to_string()
+                }
+            }),
+        )
+        .bind(&label, "label", gtk4::Widget::NONE);
+        row_expression
+            .chain_property::<RegionRow>("severity")
+            .chain_closure::<Vec<String>>(glib::closure!(
+                |_: Option<glib::Object>, severity: &str| {
+                    vec!["italic-label".to_string(), severity.to_string()]
+                }
+            ))
+            .bind(&label, "css-classes", gtk4::Widget::NONE);
+        row_expression
+            .chain_property::<RegionRow>("severity")
+            .chain_closure::<Option<String>>(glib::closure!(
+                |_: Option<glib::Object>, severity: &str| {
+                    // Divider rows have no severity
+                    (!severity.is_empty()).then(|| theme::severity_description(severity))
+                }
+            ))
+            .bind(&label, "tooltip-text", gtk4::Widget::NONE);
+
+        item.set_child(Some(&label));
+    });
+
+    // Addresses column: what the hostnames resolve to, red when one is blocked
+    let addresses_factory = SignalListItemFactory::new();
+    addresses_factory.connect_setup(|_, item| {
+        let item = item.downcast_ref::<ListItem>().unwrap();
+        let label = Label::new(None);
+        label.set_halign(gtk4::Align::Start);
+        label.add_css_class("numeric");
+
+        let row_expression = item.property_expression("item");
+        row_expression
+            .chain_property::<RegionRow>("addresses")
+            .bind(&label, "label", gtk4::Widget::NONE);
+        row_expression
+            .chain_property::<RegionRow>("addresses-blocked")
+            .chain_closure::<Vec<String>>(glib::closure!(
+                |_: Option<glib::Object>, blocked: bool| {
+                    let mut classes = vec!["numeric".to_string()];
+                    if blocked {
+                        classes.push("error".to_string());
+                    }
+                    classes
+                }
+            ))
+            .bind(&label, "css-classes", gtk4::Widget::NONE);
+        row_expression
+            .chain_property::<RegionRow>("addresses-tooltip")
+            .chain_closure::<Option<String>>(glib::closure!(
+                |_: Option<glib::Object>, tooltip: &str| {
+                    (!tooltip.is_empty()).then(|| tooltip.to_string())
+                }
+            ))
+            .bind(&label, "tooltip-text", gtk4::Widget::NONE);
+
+        item.set_child(Some(&label));
+    });
+
+    // Filter rows by the search text; dividers only make sense for the unfiltered list
+    let search_entry = gtk4::SearchEntry::builder()
+        .placeholder_text(tr("Search servers"))
+        .hexpand(true)
+        .build();
+    let search_filter = {
+        let search_entry = search_entry.clone();
+        gtk4::CustomFilter::new(move |item| {
+            let Some(row) = item.downcast_ref::<RegionRow>() else { return false; };
+            let query = search_entry.text().trim().to_lowercase();
+            if query.is_empty() {
+                return true;
+            }
+            !row.is_divider() && row.name().to_lowercase().contains(&query)
+        })
+    };
+    let filter_model = gtk4::FilterListModel::new(Some(list_store.clone()), Some(search_filter.clone()));
+    search_entry.connect_search_changed(move |_| {
+        search_filter.changed(gtk4::FilterChange::Different);
+    });
+
+    let search_bar = gtk4::SearchBar::builder()
+        .child(&search_entry)
+        .show_close_button(true)
+        .build();
+    search_bar.connect_entry(&search_entry);
+
+    let window_state = settings.lock().unwrap().window_state.clone();
+    if !window_state.filter_text.is_empty() {
+        search_entry.set_text(&window_state.filter_text);
+        search_bar.set_search_mode(true);
+    }
+
+    let selection = gtk4::SingleSelection::builder()
+        .model(&filter_model)
+        .autoselect(false)
+        .can_unselect(true)
+        .build();
+    let column_view = ColumnView::new(Some(selection.clone()));
+    column_view.set_show_row_separators(false);
+
+    // Keyboard operation: arrows, Home and End move between regions, skipping the group
+    // dividers; Space and Enter toggle the highlighted region
+    let nav_keys = gtk4::EventControllerKey::new();
+    nav_keys.set_propagation_phase(gtk4::PropagationPhase::Capture);
+    let selection_clone = selection.clone();
+    let column_view_clone = column_view.clone();
+    nav_keys.connect_key_pressed(move |_, key, _, modifiers| {
+        use gtk4::gdk::Key;
+        if !modifiers.is_empty() {
+            return glib::Propagation::Proceed;
+        }
+        match key {
+            Key::space => {
+                toggle_region_at(&selection_clone, selection_clone.selected());
+                glib::Propagation::Stop
+            }
+            Key::Up | Key::Down | Key::Home | Key::End => {
+                if let Some(target) = next_region_position(&selection_clone, key) {
+                    let _ = column_view_clone.activate_action(
+                        "list.select-item",
+                        Some(&(target, false, false).to_variant()),
+                    );
+                    let _ = column_view_clone
+                        .activate_action("list.scroll-to-item", Some(&target.to_variant()));
+                }
+                glib::Propagation::Stop
+            }
+            _ => glib::Propagation::Proceed,
+        }
+    });
+    column_view.add_controller(nav_keys);
+
+    let selection_clone = selection.clone();
+    column_view.connect_activate(move |_, position| {
+        toggle_region_at(&selection_clone, position);
+    });
+
+    let col_server = ColumnViewColumn::new(Some(&tr("Server")), Some(server_factory));
+    col_server.set_expand(true);
+    column_view.append_column(&col_server);
+
+    col_server.set_resizable(true);
+
+    // Sized by content so the column follows the font size instead of a fixed pixel width
+    let col_latency = ColumnViewColumn::new(Some(&tr("Latency")), Some(latency_factory));
+    col_latency.set_resizable(true);
+    column_view.append_column(&col_latency);
+
+    let col_addresses = ColumnViewColumn::new(Some(&tr("Addresses")), Some(addresses_factory));
+    col_addresses.set_resizable(true);
+    col_addresses.set_visible(settings.lock().unwrap().show_addresses);
+    column_view.append_column(&col_addresses);
+
+    // Create scrolled window for the region list
+    let scrolled = ScrolledWindow::new();
+    scrolled.set_policy(PolicyType::Automatic, PolicyType::Automatic);
+    scrolled.set_child(Some(&column_view));
+    scrolled.set_vexpand(true);
+
+    // Restore the previous scroll offset once the list has been laid out far enough
+    let scroll_position = window_state.scroll_position;
+    let scroll_restored = Cell::new(scroll_position <= 0.0);
+    scrolled.vadjustment().connect_changed(move |adjustment| {
+        let max = adjustment.upper() - adjustment.page_size();
+        if scroll_restored.get() || max <= 0.0 {
+            return;
+        }
+        adjustment.set_value(scroll_position.min(max));
+        if max >= scroll_position {
+            scroll_restored.set(true);
+        }
+    });
+
+    // Create window
+    let window = ApplicationWindow::builder()
+        .application(app)
+        .title(tr("Make Your Choice (DbD Server Selector)"))
+        .default_width(window_state.width)
+        .default_height(window_state.height)
+        .maximized(window_state.maximized)
+        .build();
+
+    // Set window icon from embedded ICO file
+    const ICON_DATA: &[u8] = include_bytes!("../icon.ico");
+    const ICON_NAME: &str = "make-your-choice";
+
+    // Install icon to user's local icon directory (only if not already there)
+    let icon_path = paths::icon_file();
+    if !icon_path.exists() {
+        let loader = gtk4::gdk_pixbuf::PixbufLoader::new();
+        if loader.write(ICON_DATA).is_ok() && loader.close().is_ok() {
+            if let Some(pixbuf) = loader.pixbuf() {
+                if let Some(parent) = icon_path.parent() {
+                    let _ = std::fs::create_dir_all(parent);
+                }
+                let _ = pixbuf.savev(&icon_path, "png", &[]);
+            }
+        }
+    }
+
+    window.set_icon_name(Some(ICON_NAME));
+
+
+    // Connected to display
+    let connected_box = GtkBox::new(Orientation::Horizontal, 5);
+    connected_box.set_margin_start(10);
+    connected_box.set_margin_end(10);
+    connected_box.set_margin_top(8);
+    
+    let connection_dot = Label::builder()
+        .label("⬤")
+        .css_classes(["connection-dot", "waiting"])
+        .build();
+    // Dot colors come from the theme palette
+    const CSS_DOT: &str = "label.connection-dot { font-weight: bold; }";
+    let provider = gtk4::CssProvider::new();
+    provider.load_from_data(CSS_DOT);
+    gtk4::style_context_add_provider_for_display(
+        &gtk4::gdk::Display::default().expect("Could not connect to a display."),
+        &provider,
+        gtk4::STYLE_PROVIDER_PRIORITY_APPLICATION,
+    );
+
+    let connected_title = Label::builder()
+        .label(tr("Connected to: "))
+        .css_classes(["bold-label"])
+        .build();
+        
+    let connected_value = Label::builder()
+        .label(tr("Waiting for match..."))
+        .css_classes(["italic-label"])
+        .build();
+    
+    // Additional CSS for styling
+    const CSS_STYLES: &str = "
+        label.bold-label { font-weight: bold; }
+        label.italic-label { font-style: italic; }
+        label.divider-label { font-weight: bold; }
+        button.kofi-button {
+            background-color: #ff5e5b;
+            color: #ffffff;
+        }
+        button.kofi-button:hover {
+            background-color: #ff726f;
+        }
+        button.kofi-button:active {
+            background-color: #e8514e;
+        }
+    ";
+    let style_provider = gtk4::CssProvider::new();
+    style_provider.load_from_data(CSS_STYLES);
+    gtk4::style_context_add_provider_for_display(
+        &gtk4::gdk::Display::default().expect("Could not connect to a display."),
+        &style_provider,
+        gtk4::STYLE_PROVIDER_PRIORITY_APPLICATION,
+    );
+        
+    connected_box.append(&connection_dot);
+    connected_box.append(&connected_title);
+    connected_box.append(&connected_value);
+
+    // Tip label
+    let tip_label = Label::new(Some(&tr("Tip: You can select multiple servers. The game will decide which one to use based on latency.")));
+    tip_label.set_wrap(true);
+    tip_label.set_max_width_chars(50);
+    tip_label.set_margin_start(10);
+    tip_label.set_margin_end(10);
+    tip_label.set_margin_top(5);
+    tip_label.set_margin_bottom(5);
+
+    // Buttons
+    let button_box = GtkBox::new(Orientation::Horizontal, 10);
+    button_box.set_halign(gtk4::Align::End);
+    button_box.set_margin_start(10);
+    button_box.set_margin_end(10);
+    button_box.set_margin_top(10);
+    button_box.set_margin_bottom(10);
+
+    let btn_revert = Button::with_label(&tr("Revert to Default"));
+    let btn_apply = Button::with_label(&tr("Apply Selection"));
+    btn_apply.add_css_class("suggested-action");
+    let btn_launch = Button::from_icon_name("media-playback-start-symbolic");
+    btn_launch.set_tooltip_text(Some(&tr("Apply & Launch DbD")));
+    btn_launch.update_property(&[gtk4::accessible::Property::Label(&tr("Apply & Launch DbD"))]);
+    let btn_best = Button::with_label(&tr("Best Server"));
+    btn_best.set_tooltip_text(Some(&tr("Allow only the stable server with the lowest latency")));
+
+    // Shown while the hosts file is being written
+    let hosts_spinner = gtk4::Spinner::new();
+    hosts_spinner.set_visible(false);
+    let hosts_cancel = Button::from_icon_name("process-stop-symbolic");
+    hosts_cancel.set_tooltip_text(Some(&tr("Cancel")));
+    hosts_cancel.update_property(&[gtk4::accessible::Property::Label(&tr("Cancel"))]);
+    hosts_cancel.set_visible(false);
+
+    // Quick switcher for saved profiles
+    let profile_menu = Menu::new();
+    let profile_button = gtk4::MenuButton::builder()
+        .icon_name("view-list-bullet-symbolic")
+        .tooltip_text(tr("Profiles"))
+        .menu_model(&profile_menu)
+        .build();
+
+    button_box.append(&hosts_spinner);
+    button_box.append(&hosts_cancel);
+    button_box.append(&profile_button);
+    button_box.append(&btn_best);
+    button_box.append(&btn_revert);
+    button_box.append(&btn_apply);
+    button_box.append(&btn_launch);
+
+    // Initialize AWS service
+    let aws_service = Arc::new(AwsIpService::new());
+
+    let (region_tx, region_rx) = std::sync::mpsc::channel::<(String, Option<String>)>();
+    let last_seen: LastSeen = Arc::new(Mutex::new(None));
+    let ping_scheduler = Rc::new(RefCell::new(PingScheduler::new()));
+    // The match monitor starts before the state exists, it is set once it does
+    let app_state_handle: Rc<RefCell<std::rc::Weak<AppState>>> = Rc::default();
+
+    let (gamepad_tx, gamepad_rx) = std::sync::mpsc::channel::<GamepadInput>();
+
+    // Tray icon with quick actions; menu clicks arrive on tray_rx
+    let (tray_tx, tray_rx) = std::sync::mpsc::channel::<TrayCommand>();
+    let tray = TrayHandle::spawn(tokio_runtime.clone(), tray_tx);
+    {
+        let connected_label = connected_value.clone();
+        let app_state_handle = app_state_handle.clone();
+        let tray = tray.clone();
+        let app = app.clone();
+        let window = window.clone();
+        let mut tray_text = String::new();
+        let connection_dot = connection_dot.clone();
+        let regions_map = regions.clone();
+        let blocked_regions_map = blocked_regions.clone();
+        let hosts_manager = hosts_manager.clone();
+        let last_update = Rc::new(RefCell::new(None::<DateTime<Local>>));
+        let last_update_clone = last_update.clone();
+        let last_seen_for_ui = last_seen.clone();
+        let settings = settings.clone();
+        // Server of the match in progress, every packet from it arrives here
+        let mut current_match: Option<(String, Option<String>)> = None;
+
+        glib::timeout_add_local(std::time::Duration::from_millis(100), move || {
+            let blocked_hosts = hosts_manager.get_blocked_hostnames();
+            while let Ok((ip_string, region_name_opt)) = region_rx.try_recv() {
+                if current_match.as_ref().is_none_or(|(ip, _)| *ip != ip_string) {
+                    if let Some(connection) = app.dbus_connection() {
+                        dbus::emit_server_connected(&connection, &ip_string, region_name_opt.as_deref());
+                    }
+                    hooks::spawn(
+                        hooks::Event::MatchDetected,
+                        hooks::match_data(&ip_string, region_name_opt.as_deref()),
+                    );
+                    community::match_ended();
+                    community::match_started(region_name_opt.as_deref());
+                    current_match = Some((ip_string.clone(), region_name_opt.clone()));
+                }
+                // A match is in progress, keep latencies fresh even after the loop backed off
+                if let Some(app_state) = app_state_handle.borrow().upgrade() {
+                    request_fresh_pings(&app_state);
+                }
+                *last_update_clone.borrow_mut() = Some(Local::now());
+                let (text, is_known, region_key_opt) = if let Some(name) = region_name_opt {
+                    (name.clone(), true, Some(name))
+                } else {
+                    (trf("Unknown Region [{}]", &[&ip_string]), false, None)
+                };
+
+                connected_label.set_text(&text);
+
+                // Determine dot color
+                let mut color_class = "waiting"; // Gray (default)
+
+                if is_known {
+                    if let Some(key) = region_key_opt {
+                        let is_blocked = is_region_blocked_by_hosts(
+                            &key,
+                            &regions_map,
+                            &blocked_regions_map,
+                            &blocked_hosts,
+                        );
+
+                        if !is_blocked {
+                            color_class = "allowed"; // Green
+                        } else {
+                            color_class = "blocked"; // Red
+                        }
+                    }
+                } else {
+                    color_class = "unknown"; // Orange for unknown
+                }
+
+                // Apply CSS class to dot (remove existing classes first)
+                let _ = connection_dot.remove_css_class("waiting");
+                let _ = connection_dot.remove_css_class("good");
+                let _ = connection_dot.remove_css_class("bad");
+                let _ = connection_dot.remove_css_class("warning");
+                let _ = connection_dot.remove_css_class("allowed");
+                let _ = connection_dot.remove_css_class("blocked");
+                let _ = connection_dot.remove_css_class("unknown");
+                connection_dot.add_css_class(color_class);
+
+                // Spelled out as well where the dot's color isn't enough
+                let state = match color_class {
+                    "allowed" => tr("allowed"),
+                    "blocked" => tr("blocked"),
+                    _ => String::new(),
+                };
+                connection_dot.set_tooltip_text((!state.is_empty()).then_some(state.as_str()));
+                if theme::spells_out() && !state.is_empty() {
+                    connected_label.set_text(&trf("{} · {}", &[&text, &state]));
+                }
+            }
+
+            let tooltip = if let Some(ts) = *last_update.borrow() {
+                let elapsed = (Local::now() - ts).num_seconds().max(0);
+                if elapsed >= 5 {
+                    if let Some((ip, region)) = current_match.take() {
+                        hooks::spawn(hooks::Event::MatchEnded, hooks::match_data(&ip, region.as_deref()));
+                        community::match_ended();
+                    }
+                    connected_label.set_text(&tr("Waiting for match..."));
+                    let _ = connection_dot.remove_css_class("waiting");
+                    let _ = connection_dot.remove_css_class("allowed");
+                    let _ = connection_dot.remove_css_class("blocked");
+                    let _ = connection_dot.remove_css_class("unknown");
+                    connection_dot.add_css_class("waiting");
+                    connection_dot.set_tooltip_text(None);
+                    if let Ok(mut last) = last_seen_for_ui.lock() {
+                        *last = None;
+                    }
+                }
+                format_update_tooltip(ts)
+            } else {
+                connected_label.set_text(&tr("Waiting for match..."));
+                let _ = connection_dot.remove_css_class("allowed");
+                let _ = connection_dot.remove_css_class("blocked");
+                let _ = connection_dot.remove_css_class("unknown");
+                connection_dot.add_css_class("waiting");
+                if let Ok(mut last) = last_seen_for_ui.lock() {
+                    *last = None;
+                }
+                tr("Most recent connection: —\n\nThis is the region that Dead by Daylight chose\nwhen connecting you to their game.")
+            };
+            connected_label.set_tooltip_text(Some(&tooltip));
+
+            // Mirror the current match region in the tray menu, and announce new
+            // matches while the window is hidden there
+            if let Some(tray) = &tray {
+                let text = connected_label.text();
+                if tray_text != text.as_str() {
+                    tray_text = text.to_string();
+                    tray.set_match_region(&tray_text);
+
+                    if !window.is_visible() && tray_text != tr("Waiting for match...") {
+                        let preferences = settings.lock().unwrap().notifications.clone();
+                        post_notification(
+                            &app,
+                            &preferences,
+                            NotificationKind::Match,
+                            "match",
+                            &tr("Match found"),
+                            &trf("Connected to: {}", &[&tray_text]),
+                        );
+                    }
+                }
+            }
+
+            glib::ControlFlow::Continue
+        });
+    }
+
+    // Initialize Sniffer
+    let aws_service_clone = aws_service.clone();
+    let runtime_clone = tokio_runtime.clone();
+    let region_tx_clone = region_tx.clone();
+    let last_seen_clone = last_seen.clone();
+
+    let sniffer = Arc::new(TrafficSniffer::new(move |remote_ip, _port| {
+        if let Ok(last) = last_seen_clone.lock() {
+            if let Some((last_ip, last_region)) = &*last {
+                if last_ip == &remote_ip {
+                    let _ = region_tx_clone.send((remote_ip, last_region.clone()));
+                    return;
+                }
+            }
+        }
+        let aws = aws_service_clone.clone();
+        let runtime = runtime_clone.clone();
+        let ip_string = remote_ip.clone();
+        let region_tx = region_tx_clone.clone();
+        let last_seen_update = last_seen_clone.clone();
+
+        runtime.spawn(async move {
+            let region_name_opt = aws.get_region(&ip_string).await;
+            if let Ok(mut last) = last_seen_update.lock() {
+                *last = Some((ip_string.clone(), region_name_opt.clone()));
+            }
+            let _ = region_tx.send((ip_string, region_name_opt));
+        });
+    }));
+    
+    // Latency and dot colors for the current light/dark theme
+    theme::install_palette();
+    let list_store_clone = list_store.clone();
+    adw::StyleManager::default().connect_high_contrast_notify(move |_| refresh_spelled_out(&list_store_clone));
+
+    let (control_requests, control_rx) = tokio::sync::mpsc::unbounded_channel();
+
+    // Create app state
+    let app_state = Rc::new(AppState {
+        config: config.clone(),
+        regions: regions.clone(),
+        blocked_regions: blocked_regions.clone(),
+        settings: settings.clone(),
+        backend,
+        hosts_manager,
+        update_checker: RefCell::new(update_checker),
+        selected_regions: RefCell::new(HashSet::new()),
+        list_store: list_store.clone(),
+        tokio_runtime,
+        sniffer,
+        aws_service,
+        connected_to_label: connected_value,
+        connection_dot: connection_dot, 
+        ping_scheduler,
+        ping_source: RefCell::new(None),
+        ping_in_flight: Cell::new(false),
+        latencies: RefCell::new(HashMap::new()),
+        ping_history: RefCell::new(PingHistory::default()),
+        ping_targets: ping::targets(&regions),
+        last_seen,
+        control_requests,
+        api_server: RefCell::new(None),
+        toast_overlay: adw::ToastOverlay::new(),
+        tray,
+        applied_selection: RefCell::new(HashSet::new()),
+        window_title: adw::WindowTitle::new(&tr("Make Your Choice"), &tr("DbD Server Selector")),
+        active_banner: adw::Banner::builder().revealed(true).build(),
+        status_bar: StatusBar::new(),
+        apply_button: btn_apply.clone(),
+        launch_button: btn_launch.clone(),
+        best_button: btn_best.clone(),
+        profile_menu,
+        profile_action: SimpleAction::new_stateful(
+            "profile",
+            Some(glib::VariantTy::STRING),
+            &String::new().to_variant(),
+        ),
+        role_buttons: Role::ALL
+            .iter()
+            .map(|role| (*role, gtk4::ToggleButton::with_label(&tr(role.label()))))
+            .collect(),
+        launch_after_apply: Cell::new(false),
+        revert_button: btn_revert.clone(),
+        hosts_spinner,
+        hosts_busy: Cell::new(false),
+        hosts_cancel: hosts_cancel.clone(),
+        hosts_task: RefCell::new(None),
+        gamepad_hints: Label::builder()
+            .label(tr("Ⓐ Toggle   Ⓑ Back   Ⓧ Revert   Ⓨ Refresh   ☰ Apply   ⧉ Settings"))
+            .css_classes(["gamepad-hints", "dim-label"])
+            .visible(false)
+            .build(),
+        gamepad_tx,
+        gamepad_started: Cell::new(false),
+        search_entry: search_entry.clone(),
+        region_scroll: scrolled.clone(),
+        addresses_column: col_addresses.clone(),
+        settings_monitor: RefCell::new(None),
+        last_update_check: Cell::new(None),
+        announced_version: RefCell::new(None),
+        latency_alerted: RefCell::new(None),
+    });
+    app_state_handle.replace(Rc::downgrade(&app_state));
+
+    // Header bar with the primary menu
+    let primary_menu = Menu::new();
+    primary_menu.append_section(None, &create_selection_menu());
+    primary_menu.append_section(None, &create_options_menu());
+    primary_menu.append_section(
+        Some(&config.current_version),
+        &create_version_menu(&window, &app_state),
+    );
+    primary_menu.append_section(Some(&tr("Help")), &create_help_menu(&app_state));
+    let menu_btn = MenuButton::builder()
+        .icon_name("open-menu-symbolic")
+        .tooltip_text(tr("Main menu"))
+        .menu_model(&primary_menu)
+        .build();
+
+    // Set up menu actions
+    setup_menu_actions(app, &window, &app_state);
+
+    search_bar.set_key_capture_widget(Some(&window));
+    let action = SimpleAction::new("search", None);
+    let search_bar_clone = search_bar.clone();
+    let search_entry_clone = search_entry.clone();
+    action.connect_activate(move |_, _| {
+        search_bar_clone.set_search_mode(true);
+        search_entry_clone.grab_focus();
+    });
+    app.add_action(&action);
+
+    app.set_accels_for_action("app.apply", &["<Control>Return"]);
+    app.set_accels_for_action("app.apply-launch", &["<Control><Shift>Return"]);
+    app.set_accels_for_action("app.revert", &["<Control>r"]);
+    app.set_accels_for_action("app.search", &["<Control>f"]);
+    app.set_accels_for_action("app.settings", &["<Control>comma"]);
+    app.set_accels_for_action("app.refresh-pings", &["F5"]);
+    app.set_accels_for_action("app.help", &["F1"]);
+
+    const KOFI_SVG: &[u8] = include_bytes!("../../shared/kofi.svg");
+    let kofi_icon = {
+        let loader = gtk4::gdk_pixbuf::PixbufLoader::new();
+        let _ = loader.write(KOFI_SVG);
+        let _ = loader.close();
+        loader.pixbuf().map(|pixbuf| {
+            let texture = gtk4::gdk::Texture::for_pixbuf(&pixbuf);
+            let image = Image::from_paintable(Some(&texture));
+            image.set_pixel_size(16);
+            image
+        })
+    };
+
+    let kofi_label = Label::new(Some("Ko-fi"));
+    let kofi_content = GtkBox::new(Orientation::Horizontal, 6);
+    if let Some(icon) = kofi_icon {
+        kofi_content.append(&icon);
+    }
+    kofi_content.append(&kofi_label);
+
+    let kofi_button = Button::new();
+    kofi_button.add_css_class("kofi-button");
+    kofi_button.set_tooltip_text(Some(&tr("Support on Ko-fi")));
+    kofi_button.set_child(Some(&kofi_content));
+    kofi_button.connect_clicked(|_| {
+        open_url("https://ko-fi.com/kylo");
+    });
+
+    let header_bar = adw::HeaderBar::new();
+    header_bar.set_title_widget(Some(&app_state.window_title));
+    header_bar.pack_start(&kofi_button);
+    let role_box = GtkBox::new(Orientation::Horizontal, 0);
+    role_box.add_css_class("linked");
+    for (role, button) in &app_state.role_buttons {
+        button.set_tooltip_text(Some(&trf("Switch to your {} profile", &[&tr(role.label())])));
+        let app_state_clone = app_state.clone();
+        let window_clone = window.clone();
+        let role = *role;
+        button.connect_clicked(move |_| switch_role(&app_state_clone, &window_clone, role));
+        role_box.append(button);
+    }
+    header_bar.pack_start(&role_box);
+    header_bar.pack_end(&menu_btn);
+    if overrides::get().demo {
+        let demo_label = Label::new(Some(&tr("Demo")));
+        demo_label.add_css_class("warning");
+        demo_label.add_css_class("heading");
+        demo_label.set_tooltip_text(Some(&trf(
+            "Pings and matches are made up, the hosts file is {}",
+            &[&hosts::hosts_path().display()],
+        )));
+        header_bar.pack_end(&demo_label);
+    }
+
+    // Main layout
+    let main_box = GtkBox::new(Orientation::Vertical, 0);
+    main_box.append(&connected_box);
+    main_box.append(&tip_label);
+    main_box.append(&search_bar);
+    main_box.append(&scrolled);
+    main_box.append(&button_box);
+    main_box.append(&app_state.gamepad_hints);
+    apply_layout_settings(&app_state, &window);
+    watch_settings_file(&app_state, &window);
+
+    app_state.toast_overlay.set_child(Some(&main_box));
+
+    let toolbar_view = adw::ToolbarView::new();
+    toolbar_view.add_top_bar(&header_bar);
+    toolbar_view.add_top_bar(&app_state.active_banner);
+    for banner in startup_banners(&app_state, &window, patch_notes_banner) {
+        toolbar_view.add_top_bar(&banner);
+    }
+    toolbar_view.set_content(Some(&app_state.toast_overlay));
+    toolbar_view.add_bottom_bar(&app_state.status_bar.container);
+    window.set_content(Some(&toolbar_view));
+
+    // Keep the selected set in sync with the row checkboxes
+    for row in region_rows(&app_state.list_store) {
+        let app_state_clone = app_state.clone();
+        row.connect_selected_notify(move |row| {
+            {
+                let mut selected = app_state_clone.selected_regions.borrow_mut();
+                if row.selected() {
+                    selected.insert(row.name());
+                } else {
+                    selected.remove(&row.name());
+                }
+            }
+            refresh_unsaved_indicator(&app_state_clone);
+        });
+    }
+    restore_applied_selection(&app_state);
+
+    // Connect button signals
+    let app_state_clone = app_state.clone();
+    let window_clone = window.clone();
+    btn_apply.connect_clicked(move |_| {
+        handle_apply_click(&app_state_clone, &window_clone);
+    });
+
+    let app_state_clone = app_state.clone();
+    let window_clone = window.clone();
+    btn_launch.connect_clicked(move |_| {
+        handle_apply_and_launch_click(&app_state_clone, &window_clone);
+    });
+
+    let app_state_clone = app_state.clone();
+    let window_clone = window.clone();
+    btn_best.connect_clicked(move |_| {
+        apply_best_server(&app_state_clone, &window_clone, Trigger::Window);
+    });
+
+    let app_state_clone = app_state.clone();
+    let window_clone = window.clone();
+    btn_revert.connect_clicked(move |_| {
+        handle_revert_click(&app_state_clone, &window_clone, Trigger::Window);
+    });
+
+    let app_state_clone = app_state.clone();
+    hosts_cancel.connect_clicked(move |_| {
+        if let Some(task) = app_state_clone.hosts_task.take() {
+            task.abort();
+        }
+    });
+
+    // Controller navigation for Steam Deck mode; the readers start once the mode is active
+    let app_state_clone = app_state.clone();
+    let app_clone = app.clone();
+    let window_clone = window.clone();
+    glib::timeout_add_local(Duration::from_millis(30), move || {
+        while let Ok(input) = gamepad_rx.try_recv() {
+            handle_gamepad_input(&app_state_clone, &app_clone, &window_clone, &column_view, &selection, input);
+        }
+        glib::ControlFlow::Continue
+    });
+
+    // Handle tray menu requests
+    let app_state_clone = app_state.clone();
+    let window_clone = window.clone();
+    let app_clone = app.clone();
+    glib::timeout_add_local(Duration::from_millis(200), move || {
+        while let Ok(command) = tray_rx.try_recv() {
+            match command {
+                TrayCommand::ApplyLast => apply_last_selection(&app_state_clone, &window_clone, Trigger::Tray),
+                TrayCommand::ApplyBest => apply_best_server(&app_state_clone, &window_clone, Trigger::Tray),
+                TrayCommand::ApplyProfile(name) => {
+                    load_profile(&app_state_clone, &name);
+                    app_state_clone.launch_after_apply.set(false);
+                    start_apply(&app_state_clone, &window_clone, Trigger::Tray);
+                }
+                TrayCommand::Revert => handle_revert_click(&app_state_clone, &window_clone, Trigger::Tray),
+                TrayCommand::ShowWindow => window_clone.present(),
+                TrayCommand::Quit => {
+                    save_window_state(&app_state_clone, &window_clone);
+                    app_clone.quit();
+                }
+            }
+        }
+        glib::ControlFlow::Continue
+    });
+
+    // Applies the active profile when the game starts, unless the background service
+    // does it already
+    let app_state_clone = app_state.clone();
+    let window_clone = window.clone();
+    let mut launch_watch = LaunchWatch::default();
+    glib::timeout_add_local(gamewatch::POLL_INTERVAL, move || {
+        let enabled = app_state_clone.settings.lock().unwrap().apply_on_game_launch;
+        if enabled && !service::is_enabled() && launch_watch.started() {
+            apply_at_game_launch(&app_state_clone, &window_clone);
+        }
+        glib::ControlFlow::Continue
+    });
+
+    // Keep the active-configuration banner and status bar current, including edits made
+    // outside the app
+    refresh_active_banner(&app_state);
+    refresh_status_bar(&app_state);
+    refresh_ping_status(&app_state);
+    let app_state_clone = app_state.clone();
+    glib::timeout_add_seconds_local(30, move || {
+        refresh_active_banner(&app_state_clone);
+        refresh_status_bar(&app_state_clone);
+        glib::ControlFlow::Continue
+    });
+    let app_state_clone = app_state.clone();
+    let window_clone = window.clone();
+    app_state.status_bar.conflicts.connect_clicked(move |_| {
+        show_resolve_conflicts_dialog(&app_state_clone, &window_clone);
+    });
+    let app_state_clone = app_state.clone();
+    let window_clone = window.clone();
+    app_state.active_banner.connect_button_clicked(move |_| {
+        handle_revert_click(&app_state_clone, &window_clone, Trigger::Window);
+    });
+
+    // Start ping timer
+    start_ping_timer(app_state.clone());
+    // The background service reports instead while it runs, once is enough
+    if !service::is_enabled() {
+        community::start(&app_state.tokio_runtime);
+    }
+
+    // Any input on the window means someone is looking at the numbers
+    let motion = gtk4::EventControllerMotion::new();
+    let app_state_clone = app_state.clone();
+    motion.connect_motion(move |_, _, _| {
+        request_fresh_pings(&app_state_clone);
+    });
+    window.add_controller(motion);
+
+    let keys = gtk4::EventControllerKey::new();
+    let app_state_clone = app_state.clone();
+    keys.connect_key_pressed(move |_, _, _, _| {
+        request_fresh_pings(&app_state_clone);
+        glib::Propagation::Proceed
+    });
+    window.add_controller(keys);
+
+    // Images dropped anywhere on the window open the custom splash dialog
+    let drop_target = gtk4::DropTarget::new(gio::File::static_type(), gtk4::gdk::DragAction::COPY);
+    let app_state_clone = app_state.clone();
+    let window_clone = window.clone();
+    drop_target.connect_drop(move |_, value, _, _| {
+        let Some(path) = value.get::<gio::File>().ok().and_then(|file| file.path()) else {
+            return false;
+        };
+        let is_image = gio::content_type_guess(Some(&path), &[])
+            .0
+            .starts_with("image/");
+        if !is_image {
+            return false;
+        }
+        show_custom_splash_dialog(&app_state_clone, &window_clone, Some(path));
+        true
+    });
+    window.add_controller(drop_target);
+
+    // Closing hides to the tray when possible so monitoring keeps running.
+    // Otherwise ensure helper sniffer exits when the window closes.
+    let app_state_clone = app_state.clone();
+    let app_clone = app.clone();
+    let hidden_once = Cell::new(false);
+    let discard_confirmed = Rc::new(Cell::new(false));
+    window.connect_close_request(move |window| {
+        save_window_state(&app_state_clone, window);
+
+        let close_to_tray = app_state_clone.settings.lock().unwrap().close_to_tray;
+        if close_to_tray && app_state_clone.tray.is_some() {
+            window.set_visible(false);
+            if !hidden_once.replace(true) {
+                let preferences = app_state_clone.settings.lock().unwrap().notifications.clone();
+                post_notification(
+                    &app_clone,
+                    &preferences,
+                    NotificationKind::Other,
+                    "background",
+                    &tr("Make Your Choice is still running"),
+                    &tr("Server monitoring continues in the background. Use Quit from the tray icon to exit."),
+                );
+            }
+            return glib::Propagation::Stop;
+        }
+
+        // Ask before quitting with checkbox changes that were never applied
+        if has_unsaved_changes(&app_state_clone) && !discard_confirmed.get() {
+            let dialog = MessageDialog::new(
+                Some(window),
+                gtk4::DialogFlags::MODAL,
+                MessageType::Warning,
+                ButtonsType::None,
+                tr("Discard unapplied changes?"),
+            );
+            dialog.set_secondary_text(Some(
+                &tr("Your server selection differs from what is currently applied. \
+                Closing now leaves the hosts file unchanged."),
+            ));
+            dialog.add_button(&tr("Cancel"), ResponseType::Cancel);
+            dialog.add_button(&tr("Close Without Applying"), ResponseType::Accept);
+
+            let window = window.clone();
+            let discard_confirmed = discard_confirmed.clone();
+            dialog.run_async(move |dialog, response| {
+                dialog.close();
+                if response == ResponseType::Accept {
+                    discard_confirmed.set(true);
+                    window.close();
+                }
+            });
+            return glib::Propagation::Stop;
+        }
+
+        app_state_clone.sniffer.stop();
+        plugins::stop();
+        glib::Propagation::Proceed
+    });
+
+    // Check for updates silently on launch, on the first start once the identity is there
+    let check_updates = app_state.settings.lock().unwrap().check_updates_on_start;
+    if check_updates && app_state.config.developer().is_some() {
+        check_for_updates_silent(&app_state, &window, 0);
+    }
+    refresh_git_identity(&app_state, &window, &toolbar_view, check_updates);
+    start_periodic_update_checks(&app_state, &window);
+    export_dbus_interface(&app_state, &window);
+    answer_control_requests(&app_state, &window, control_rx);
+    plugins::start(&app_state.control_requests);
+    refresh_http_api(&app_state);
+
+    // Started from the login entry: stay in the tray until the user asks for the window
+    if start_minimized && app_state.tray.is_some() {
+        return;
+    }
+    window.present();
+}
+
+fn create_version_menu(_window: &ApplicationWindow, _app_state: &Rc<AppState>) -> Menu {
+    let menu = Menu::new();
+    menu.append(Some(&tr("Check for updates")), Some("app.check-updates"));
+    menu.append(Some(&tr("Repository (⭐)")), Some("app.repository"));
+    menu.append(Some(&tr("About")), Some("app.about"));
+    menu.append(Some(&tr("View hosts file")), Some("app.view-hosts"));
+    menu.append(Some(&tr("Open hosts file location")), Some("app.open-hosts"));
+    menu.append(Some(&tr("Export as shell script…")), Some("app.export-script"));
+    menu.append(Some(&tr("Reset hosts file")), Some("app.reset-hosts"));
+    menu
+}
+
+fn create_selection_menu() -> Menu {
+    let menu = Menu::new();
+    menu.append(Some(&tr("Apply selection")), Some("app.apply"));
+    menu.append(Some(&tr("Apply and launch game")), Some("app.apply-launch"));
+    menu.append(Some(&tr("Re-apply last selection")), Some("app.apply-last"));
+    menu.append(Some(&tr("Best server only")), Some("app.apply-best"));
+    menu.append(Some(&tr("Apply history")), Some("app.apply-history"));
+    menu.append(Some(&tr("Revert to default")), Some("app.revert"));
+    menu.append(Some(&tr("Find server")), Some("app.search"));
+    menu.append(Some(&tr("Refresh latency")), Some("app.refresh-pings"));
+    menu.append(Some(&tr("Compare servers")), Some("app.compare"));
+    menu.append(Some(&tr("Show resolved addresses")), Some("app.show-addresses"));
+    menu
+}
+
+fn create_options_menu() -> Menu {
+    let menu = Menu::new();
+    menu.append(Some(&tr("Program settings")), Some("app.settings"));
+    menu.append(Some(&tr("Custom splash art")), Some("app.custom-splash"));
+    menu.append(Some(&tr("Startup movies")), Some("app.startup-movies"));
+    menu.append(Some(&tr("Verify game changes")), Some("app.verify-game"));
+    menu.append(Some(&tr("Restore all game files")), Some("app.restore-game"));
+    menu.append(Some(&tr("Edit server hostnames")), Some("app.edit-hostnames"));
+    menu
+}
+
+fn create_help_menu(_app_state: &Rc<AppState>) -> Menu {
+    let menu = Menu::new();
+    menu.append(Some(&tr("Help and FAQ")), Some("app.help"));
+    menu.append(Some(&tr("Discord (Get support)")), Some("app.discord"));
+    menu.append(Some(&tr("View log")), Some("app.view-log"));
+    menu.append(Some(&tr("Report an issue")), Some("app.report-issue"));
+    menu
+}
+
+fn setup_menu_actions(app: &Application, window: &ApplicationWindow, app_state: &Rc<AppState>) {
+    // Apply selection action
+    let action = SimpleAction::new("apply", None);
+    let app_state_clone = app_state.clone();
+    let window_clone = window.clone();
+    action.connect_activate(move |_, _| {
+        handle_apply_click(&app_state_clone, &window_clone);
+    });
+    app.add_action(&action);
+
+    // Profile actions, the parameter is the profile name
+    let app_state_clone = app_state.clone();
+    app_state.profile_action.connect_activate(move |_, parameter| {
+        if let Some(name) = parameter.and_then(|p| p.get::<String>()) {
+            load_profile(&app_state_clone, &name);
+        }
+    });
+    app.add_action(&app_state.profile_action);
+
+    let action = SimpleAction::new("save-profile", None);
+    let app_state_clone = app_state.clone();
+    let window_clone = window.clone();
+    action.connect_activate(move |_, _| {
+        show_save_profile_dialog(&app_state_clone, &window_clone);
+    });
+    app.add_action(&action);
+
+    let action = SimpleAction::new("manage-profiles", None);
+    let app_state_clone = app_state.clone();
+    let window_clone = window.clone();
+    action.connect_activate(move |_, _| {
+        show_manage_profiles_dialog(&app_state_clone, &window_clone);
+    });
+    app.add_action(&action);
+    refresh_profiles(app_state);
+
+    // Apply and launch action
+    let action = SimpleAction::new("apply-launch", None);
+    let app_state_clone = app_state.clone();
+    let window_clone = window.clone();
+    action.connect_activate(move |_, _| {
+        handle_apply_and_launch_click(&app_state_clone, &window_clone);
+    });
+    app.add_action(&action);
+
+    // Re-apply the last applied selection, also used by --apply-last
+    let action = SimpleAction::new("apply-last", None);
+    let app_state_clone = app_state.clone();
+    let window_clone = window.clone();
+    action.connect_activate(move |_, _| {
+        if !app_state_clone.hosts_busy.get() {
+            apply_last_selection(&app_state_clone, &window_clone, Trigger::Window);
+        }
+    });
+    app.add_action(&action);
+
+    let action = SimpleAction::new("apply-best", None);
+    let app_state_clone = app_state.clone();
+    let window_clone = window.clone();
+    action.connect_activate(move |_, _| {
+        apply_best_server(&app_state_clone, &window_clone, Trigger::Window);
+    });
+    app.add_action(&action);
+
+    let action = SimpleAction::new("apply-history", None);
+    let app_state_clone = app_state.clone();
+    let window_clone = window.clone();
+    action.connect_activate(move |_, _| {
+        show_apply_history_dialog(&app_state_clone, &window_clone);
+    });
+    app.add_action(&action);
+
+    // myc:// links, see uri.rs
+    let action = SimpleAction::new("open-uri", Some(glib::VariantTy::STRING));
+    let app_state_clone = app_state.clone();
+    let window_clone = window.clone();
+    action.connect_activate(move |_, parameter| {
+        if let Some(link) = parameter.and_then(|parameter| parameter.str()) {
+            open_link(&app_state_clone, &window_clone, link);
+        }
+    });
+    app.add_action(&action);
+
+    let action = SimpleAction::new("compare", None);
+    let app_state_clone = app_state.clone();
+    let window_clone = window.clone();
+    action.connect_activate(move |_, _| {
+        show_compare_dialog(&app_state_clone, &window_clone);
+    });
+    app.add_action(&action);
+
+    // Toggles the addresses column, looked up again each time it is shown
+    let show_addresses = app_state.settings.lock().unwrap().show_addresses;
+    let action = SimpleAction::new_stateful("show-addresses", None, &show_addresses.to_variant());
+    let app_state_clone = app_state.clone();
+    action.connect_activate(move |action, _| {
+        let show = !action.state().and_then(|state| state.get::<bool>()).unwrap_or(false);
+        action.set_state(&show.to_variant());
+        update_settings(&app_state_clone, |settings| settings.show_addresses = show);
+        app_state_clone.addresses_column.set_visible(show);
+        refresh_region_addresses(&app_state_clone);
+    });
+    app.add_action(&action);
+    refresh_region_addresses(app_state);
+
+    // Context menu of a server row, the parameter is the region name
+    let action = SimpleAction::new("copy-hostnames", Some(glib::VariantTy::STRING));
+    let app_state_clone = app_state.clone();
+    action.connect_activate(move |_, parameter| {
+        if let Some(name) = parameter.and_then(|parameter| parameter.str()) {
+            copy_region_hostnames(&app_state_clone, name);
+        }
+    });
+    app.add_action(&action);
+
+    let action = SimpleAction::new("copy-addresses", Some(glib::VariantTy::STRING));
+    let app_state_clone = app_state.clone();
+    action.connect_activate(move |_, parameter| {
+        if let Some(name) = parameter.and_then(|parameter| parameter.str()) {
+            copy_region_addresses(&app_state_clone, name);
+        }
+    });
+    app.add_action(&action);
+
+    // Revert to default action, also used by --revert
+    let action = SimpleAction::new("revert", None);
+    let app_state_clone = app_state.clone();
+    let window_clone = window.clone();
+    action.connect_activate(move |_, _| {
+        handle_revert_click(&app_state_clone, &window_clone, Trigger::Window);
+    });
+    app.add_action(&action);
+
+    // Refresh latency action
+    let action = SimpleAction::new("refresh-pings", None);
+    let app_state_clone = app_state.clone();
+    action.connect_activate(move |_, _| {
+        app_state_clone.ping_scheduler.borrow_mut().notify_interaction();
+        if !app_state_clone.ping_in_flight.get() {
+            schedule_ping_round(app_state_clone.clone(), Duration::ZERO);
+        }
+        refresh_region_addresses(&app_state_clone);
+    });
+    app.add_action(&action);
+
+    // Check for updates action
+    let action = SimpleAction::new("check-updates", None);
+    let app_state_clone = app_state.clone();
+    let window_clone = window.clone();
+    action.connect_activate(move |_, _| {
+        check_for_updates_action(&app_state_clone, &window_clone);
+    });
+    app.add_action(&action);
+
+    // Repository action
+    let action = SimpleAction::new("repository", None);
+    let app_state_clone = app_state.clone();
+    let window_clone = window.clone();
+    action.connect_activate(move |_, _| {
+        if let Some(url) = app_state_clone.config.repo_url() {
+            let dialog = MessageDialog::new(
+                Some(&window_clone),
+                gtk4::DialogFlags::MODAL,
+                MessageType::Info,
+                ButtonsType::OkCancel,
+                tr("Repository"),
+            );
+            dialog.set_secondary_text(Some(
+                &tr("Pressing \"Continue\" will open the project's public repository.\n\nPlease star the repository if you are able to do so as it increases awareness of the project! <3")
+            ));
+
+            // Change button labels
+            if let Some(widget) = dialog.widget_for_response(ResponseType::Ok) {
+                if let Some(button) = widget.downcast_ref::<Button>() {
+                    button.set_label(&tr("Continue"));
+                }
+            }
+
+            dialog.run_async(move |dialog, response| {
+                if response == ResponseType::Ok {
+                    open_url(&url);
+                }
+                dialog.close();
+            });
+        } else {
+            show_error_dialog(
+                &window_clone,
+                &tr("Repository"),
+                &tr("Unable to open repository.\n\nThe application was unable to fetch the git identity and therefore couldn't determine the repository URL.\n\nThis may be due to network issues or GitHub API issues.\nAn update to fix this issue has most likely been released, please check manually by joining the Discord server or doing a web search.")
+            );
+        }
+    });
+    app.add_action(&action);
+
+    // About action
+    let action = SimpleAction::new("about", None);
+    let app_state_clone = app_state.clone();
+    let window_clone = window.clone();
+    action.connect_activate(move |_, _| {
+        show_about_dialog(&app_state_clone, &window_clone);
+    });
+    app.add_action(&action);
+
+    // Hosts file viewer with the managed section and conflicts marked
+    let action = SimpleAction::new("view-hosts", None);
+    let app_state_clone = app_state.clone();
+    let window_clone = window.clone();
+    action.connect_activate(move |_, _| {
+        show_hosts_viewer(&app_state_clone, &window_clone);
+    });
+    app.add_action(&action);
+
+    let action = SimpleAction::new("view-log", None);
+    let window_clone = window.clone();
+    action.connect_activate(move |_, _| {
+        show_log_viewer(&window_clone);
+    });
+    app.add_action(&action);
+
+    let action = SimpleAction::new("report-issue", None);
+    let app_state_clone = app_state.clone();
+    let window_clone = window.clone();
+    action.connect_activate(move |_, _| {
+        show_report_issue_dialog(&app_state_clone, &window_clone);
+    });
+    app.add_action(&action);
+
+    // Open hosts location action
+    let action = SimpleAction::new("open-hosts", None);
+    action.connect_activate(move |_, _| {
+        // Open the hosts file's directory (/etc) in the file manager
+        let hosts_path = hosts::hosts_path();
+        let dir = hosts_path.parent().unwrap_or(std::path::Path::new("/etc"));
+        let _ = std::process::Command::new("xdg-open")
+            .arg(dir)
+            .spawn();
+    });
+    app.add_action(&action);
+
+    let action = SimpleAction::new("export-script", None);
+    let app_state_clone = app_state.clone();
+    let window_clone = window.clone();
+    action.connect_activate(move |_, _| {
+        export_hosts_script(&app_state_clone, &window_clone);
+    });
+    app.add_action(&action);
+
+    // Reset hosts action
+    let action = SimpleAction::new("reset-hosts", None);
+    let app_state_clone = app_state.clone();
+    let window_clone = window.clone();
+    action.connect_activate(move |_, _| {
+        reset_hosts_action(&app_state_clone, &window_clone);
+    });
+    app.add_action(&action);
+
+    // Program settings action
+    let action = SimpleAction::new("settings", None);
+    let app_state_clone = app_state.clone();
+    let window_clone = window.clone();
+    action.connect_activate(move |_, _| {
+        show_settings_dialog(&app_state_clone, &window_clone);
+    });
+    app.add_action(&action);
+
+    // Help and FAQ action
+    let action = SimpleAction::new("help", None);
+    let app_state_clone = app_state.clone();
+    let window_clone = window.clone();
+    action.connect_activate(move |_, _| {
+        show_help_window(&app_state_clone, &window_clone);
+    });
+    app.add_action(&action);
+
+    // Discord action
+    let action = SimpleAction::new("discord", None);
+    let discord_url = app_state.config.discord_url.clone();
+    action.connect_activate(move |_, _| {
+        open_url(&discord_url);
+    });
+    app.add_action(&action);
+
+    // Custom splash art action
+    let action = SimpleAction::new("edit-hostnames", None);
+    let window_clone = window.clone();
+    action.connect_activate(move |_, _| {
+        show_custom_hostnames_dialog(&window_clone);
+    });
+    app.add_action(&action);
+
+    let action = SimpleAction::new("custom-splash", None);
+    let window_clone = window.clone();
+    let app_state_clone = app_state.clone();
+    action.connect_activate(move |_, _| {
+        show_custom_splash_dialog(&app_state_clone, &window_clone, None);
+    });
+    app.add_action(&action);
+
+    // Startup movies action
+    let action = SimpleAction::new("startup-movies", None);
+    let window_clone = window.clone();
+    let app_state_clone = app_state.clone();
+    action.connect_activate(move |_, _| {
+        show_startup_movies_dialog(&app_state_clone, &window_clone);
+    });
+    app.add_action(&action);
+
+    // Verify game changes action
+    let action = SimpleAction::new("verify-game", None);
+    let window_clone = window.clone();
+    let app_state_clone = app_state.clone();
+    action.connect_activate(move |_, _| {
+        let window = window_clone.clone();
+        let app_state = app_state_clone.clone();
+        with_game_install(&app_state_clone, &window_clone, move |game_path| {
+            show_game_changes_dialog(&app_state, &window, game_path);
+        });
+    });
+    app.add_action(&action);
+
+    // Restore all game files action
+    let action = SimpleAction::new("restore-game", None);
+    let window_clone = window.clone();
+    let app_state_clone = app_state.clone();
+    action.connect_activate(move |_, _| {
+        let window = window_clone.clone();
+        let app_state = app_state_clone.clone();
+        with_game_install(&app_state_clone, &window_clone, move |game_path| {
+            confirm_restore_game_files(&app_state, &window, game_path);
+        });
+    });
+    app.add_action(&action);
+}
+
+// `image` preselects a file, e.g. one dropped onto the main window
+fn show_custom_splash_dialog(
+    app_state: &Rc<AppState>,
+    window: &ApplicationWindow,
+    image: Option<std::path::PathBuf>,
+) {
+    let app_state_clone = app_state.clone();
+    let window_clone = window.clone();
+    with_game_install(app_state, window, move |game_path| {
+        open_custom_splash_dialog(&app_state_clone, &window_clone, game_path, image);
+    });
+}
+
+fn open_custom_splash_dialog(
+    app_state: &Rc<AppState>,
+    window: &ApplicationWindow,
+    game_path: std::path::PathBuf,
+    image: Option<std::path::PathBuf>,
+) {
+    let dialog = Dialog::with_buttons(
+        Some(&tr("Custom splash art")),
+        Some(window),
+        gtk4::DialogFlags::MODAL,
+        &[
+            (&tr("Apply"), ResponseType::Accept),
+            (&tr("Revert to default"), ResponseType::Reject),
+            (&tr("Cancel"), ResponseType::Cancel),
+        ],
+    );
+
+    dialog.set_default_width(460);
+    dialog.set_response_sensitive(ResponseType::Accept, false);
+
+    if let Some(action_area) = dialog.child().and_then(|c| c.last_child()) {
+        action_area.set_margin_start(15);
+        action_area.set_margin_end(15);
+        action_area.set_margin_top(10);
+        action_area.set_margin_bottom(15);
+    }
+
+    let content = dialog.content_area();
+    content.set_margin_start(15);
+    content.set_margin_end(15);
+    content.set_margin_top(10);
+    content.set_margin_bottom(10);
+    content.set_spacing(10);
+
+    let description = Label::new(Some(
+        &tr("This lets you use custom artwork for the EAC splash screen that pops up when you launch the game."),
+    ));
+    description.set_halign(gtk4::Align::Start);
+    description.set_wrap(true);
+    description.set_margin_top(5);
+    content.append(&description);
+
+    // Preview at half the splash size, showing exactly what will be written
+    let preview = gtk4::Picture::builder()
+        .width_request(splash::WIDTH / 2)
+        .height_request(splash::HEIGHT / 2)
+        .keep_aspect_ratio(true)
+        .halign(gtk4::Align::Center)
+        .css_classes(["card"])
+        .build();
+    content.append(&preview);
+
+    let info = Label::new(Some(&tr(
+        "Any PNG or JPEG works. Images that aren't 800 x 450 pixels are resized, and everything is saved as PNG.",
+    )));
+    info.set_halign(gtk4::Align::Start);
+    info.set_wrap(true);
+    info.add_css_class("dim-label");
+    content.append(&info);
+
+    let fit_dropdown = gtk4::DropDown::from_strings(&[
+        &tr("Crop to fill"),
+        &tr("Fit with black bars"),
+    ]);
+    fit_dropdown.set_tooltip_text(Some(&tr("How images with a different aspect ratio are resized")));
+    let choose_button = Button::with_label(&tr("Choose image…"));
+    let controls = GtkBox::new(Orientation::Horizontal, 10);
+    controls.append(&choose_button);
+    controls.append(&fit_dropdown);
+    content.append(&controls);
+
+    let gallery_label = Label::new(Some(&tr("Or pick one of the included designs:")));
+    gallery_label.set_halign(gtk4::Align::Start);
+    content.append(&gallery_label);
+    let gallery = gtk4::FlowBox::builder()
+        .selection_mode(gtk4::SelectionMode::Single)
+        .max_children_per_line(4)
+        .min_children_per_line(2)
+        .column_spacing(6)
+        .row_spacing(6)
+        .homogeneous(true)
+        .build();
+    let mut presets = Vec::new();
+    for (name, svg) in splash::PRESETS {
+        let Ok(pixbuf) = splash::render_preset(svg) else {
+            continue;
+        };
+        let thumbnail = gtk4::Picture::for_paintable(&gtk4::gdk::Texture::for_pixbuf(&pixbuf));
+        thumbnail.set_size_request(96, 54);
+        thumbnail.set_tooltip_text(Some(&tr(name)));
+        thumbnail.update_property(&[gtk4::accessible::Property::Label(&tr(name))]);
+        gallery.insert(&thumbnail, -1);
+        presets.push(pixbuf);
+    }
+    content.append(&gallery);
+
+    // The image to fit, either chosen from disk or one of the presets
+    let selected: Rc<RefCell<Option<gtk4::gdk_pixbuf::Pixbuf>>> = Rc::new(RefCell::new(None));
+    let prepared: Rc<RefCell<Option<gtk4::gdk_pixbuf::Pixbuf>>> = Rc::new(RefCell::new(None));
+
+    let update_preview = {
+        let dialog = dialog.clone();
+        let window = window.clone();
+        let selected = selected.clone();
+        let prepared = prepared.clone();
+        let fit_dropdown = fit_dropdown.clone();
+        let preview = preview.clone();
+        Rc::new(move || {
+            let Some(source) = selected.borrow().clone() else {
+                return;
+            };
+            let mode = match fit_dropdown.selected() {
+                1 => splash::FitMode::Letterbox,
+                _ => splash::FitMode::Fill,
+            };
+            match splash::fit(&source, mode) {
+                Ok(pixbuf) => {
+                    preview.set_paintable(Some(&gtk4::gdk::Texture::for_pixbuf(&pixbuf)));
+                    *prepared.borrow_mut() = Some(pixbuf);
+                    dialog.set_response_sensitive(ResponseType::Accept, true);
+                }
+                Err(err) => {
+                    preview.set_paintable(gtk4::gdk::Paintable::NONE);
+                    *prepared.borrow_mut() = None;
+                    dialog.set_response_sensitive(ResponseType::Accept, false);
+                    show_error_dialog(
+                        &window,
+                        &tr("Custom splash art"),
+                        &trf("Failed to load the image:\n{}", &[&format!("{:#}", err)]),
+                    );
+                }
+            }
+        })
+    };
+
+    let update_preview_clone = update_preview.clone();
+    fit_dropdown.connect_selected_notify(move |_| update_preview_clone());
+
+    let selected_clone = selected.clone();
+    let update_preview_clone = update_preview.clone();
+    gallery.connect_child_activated(move |_, child| {
+        if let Some(pixbuf) = usize::try_from(child.index()).ok().and_then(|i| presets.get(i)) {
+            *selected_clone.borrow_mut() = Some(pixbuf.clone());
+            update_preview_clone();
+        }
+    });
+
+    let select_file = {
+        let window = window.clone();
+        move |image_path: std::path::PathBuf| match splash::load(&image_path) {
+            Ok(pixbuf) => {
+                gallery.unselect_all();
+                *selected.borrow_mut() = Some(pixbuf);
+                update_preview();
+            }
+            Err(err) => show_error_dialog(
+                &window,
+                &tr("Custom splash art"),
+                &trf("Failed to load the image:\n{}", &[&format!("{:#}", err)]),
+            ),
+        }
+    };
+    if let Some(image) = image {
+        select_file(image);
+    }
+
+    let window_clone = window.clone();
+    let select_file = Rc::new(select_file);
+    choose_button.connect_clicked(move |_| {
+        let select_file = select_file.clone();
+        select_image_file(&window_clone, move |image_path| select_file(image_path));
+    });
+
+    let app_state = app_state.clone();
+    let window_clone = window.clone();
+    dialog.connect_response(move |dialog, response| {
+        dialog.close();
+
+        match response {
+            ResponseType::Accept => {
+                let Some(image) = prepared.borrow().clone() else {
+                    return;
+                };
+                let result = splash::apply(&game_path, &image);
+                remember_game_changes(&app_state, &game_path);
+                if let Err(err) = result {
+                    show_error_dialog(
+                        &window_clone,
+                        &tr("Custom splash art"),
+                        &trf("Failed to apply custom splash art:\n{}", &[&err]),
+                    );
+                } else {
+                    show_info_dialog(
+                        &window_clone,
+                        &tr("Custom splash art"),
+                        &tr("Custom splash art applied."),
+                    );
+                }
+            }
+            ResponseType::Reject => {
+                let result = splash::revert(&game_path);
+                remember_game_changes(&app_state, &game_path);
+                match result {
+                    Ok(()) => show_info_dialog(
+                        &window_clone,
+                        &tr("Custom splash art"),
+                        &tr("Reverted to default splash art."),
+                    ),
+                    Err(err) if errors::classify(&err) == ErrorKind::BackupMissing => show_error_dialog(
+                        &window_clone,
+                        &tr("Custom splash art"),
+                        &error_details(&err),
+                    ),
+                    Err(err) => show_error_dialog(
+                        &window_clone,
+                        &tr("Custom splash art"),
+                        &trf("Failed to revert splash art:\n{}", &[&error_details(&err)]),
+                    ),
+                }
+            }
+            _ => {}
+        }
+    });
+
+    dialog.show();
+}
+
+fn show_startup_movies_dialog(app_state: &Rc<AppState>, window: &ApplicationWindow) {
+    let app_state_clone = app_state.clone();
+    let window_clone = window.clone();
+    with_game_install(app_state, window, move |game_path| {
+        open_startup_movies_dialog(&app_state_clone, &window_clone, game_path);
+    });
+}
+
+fn open_startup_movies_dialog(
+    app_state: &Rc<AppState>,
+    window: &ApplicationWindow,
+    game_path: std::path::PathBuf,
+) {
+    let movies = match movies::list(&game_path) {
+        Ok(movies) => movies,
+        Err(err) => {
+            show_error_dialog(
+                window,
+                &tr("Startup movies"),
+                &trf("Failed to list the game's movies:\n{}", &[&format!("{:#}", err)]),
+            );
+            return;
+        }
+    };
+
+    let dialog = Dialog::with_buttons(
+        Some(&tr("Startup movies")),
+        Some(window),
+        gtk4::DialogFlags::MODAL,
+        &[
+            (&tr("Restore all"), ResponseType::Reject),
+            (&tr("Close"), ResponseType::Close),
+        ],
+    );
+    dialog.set_default_width(420);
+
+    if let Some(action_area) = dialog.child().and_then(|c| c.last_child()) {
+        action_area.set_margin_start(15);
+        action_area.set_margin_end(15);
+        action_area.set_margin_top(10);
+        action_area.set_margin_bottom(15);
+    }
+
+    let content = dialog.content_area();
+    content.set_margin_start(15);
+    content.set_margin_end(15);
+    content.set_margin_top(10);
+    content.set_margin_bottom(10);
+    content.set_spacing(10);
+
+    let description = Label::new(Some(
+        &tr("Turn off the movies you don't want to sit through every time you launch the game. Skipped movies are kept as a backup and can be restored at any time."),
+    ));
+    description.set_halign(gtk4::Align::Start);
+    description.set_wrap(true);
+    description.set_margin_top(5);
+    content.append(&description);
+
+    let list = gtk4::ListBox::builder()
+        .selection_mode(gtk4::SelectionMode::None)
+        .css_classes(["boxed-list"])
+        .build();
+    let mut switches = Vec::new();
+    for movie in &movies {
+        let row = adw::SwitchRow::builder()
+            .title(movie.label())
+            .subtitle(movie.file_name.as_str())
+            .active(!movie.skipped)
+            .build();
+
+        let app_state = app_state.clone();
+        let game_path = game_path.clone();
+        let file_name = movie.file_name.clone();
+        let window_clone = window.clone();
+        row.connect_active_notify(move |row| {
+            let result = if row.is_active() {
+                movies::restore(&game_path, &file_name)
+            } else {
+                movies::skip(&game_path, &file_name)
+            };
+            remember_game_changes(&app_state, &game_path);
+            if let Err(err) = result {
+                show_error_dialog(
+                    &window_clone,
+                    &tr("Startup movies"),
+                    &trf("Failed to change {}:\n{}", &[&file_name, &error_details(&err)]),
+                );
+            }
+        });
+        list.append(&row);
+        switches.push(row);
+    }
+    if movies.is_empty() {
+        let empty = Label::new(Some(&tr("No movies found in the game folder.")));
+        empty.add_css_class("dim-label");
+        content.append(&empty);
+    } else {
+        content.append(&list);
+    }
+
+    let window_clone = window.clone();
+    dialog.connect_response(move |dialog, response| {
+        if response != ResponseType::Reject {
+            dialog.close();
+            return;
+        }
+
+        // Turning the switches back on restores each skipped movie
+        for row in &switches {
+            row.set_active(true);
+        }
+        show_info_dialog(
+            &window_clone,
+            &tr("Startup movies"),
+            &tr("All startup movies will play again."),
+        );
+    });
+
+    dialog.show();
+}
+
+// Lists every file the splash art and movie options changed, whether its original is
+// still there, and puts all of them back in one go
+fn show_game_changes_dialog(
+    app_state: &Rc<AppState>,
+    window: &ApplicationWindow,
+    game_path: std::path::PathBuf,
+) {
+    let entries = match integrity::scan(&game_path) {
+        Ok(entries) => entries,
+        Err(err) => {
+            show_error_dialog(
+                window,
+                &tr("Verify game changes"),
+                &trf("Failed to check the game folder:\n{}", &[&format!("{:#}", err)]),
+            );
+            return;
+        }
+    };
+    if entries.is_empty() {
+        show_info_dialog(
+            window,
+            &tr("Verify game changes"),
+            &tr("The game folder is as installed, Make Your Choice has no changed files in it."),
+        );
+        return;
+    }
+
+    let dialog = Dialog::with_buttons(
+        Some(&tr("Verify game changes")),
+        Some(window),
+        gtk4::DialogFlags::MODAL,
+        &[
+            (&tr("Restore everything"), ResponseType::Reject),
+            (&tr("Close"), ResponseType::Close),
+        ],
+    );
+    dialog.set_default_width(480);
+
+    if let Some(action_area) = dialog.child().and_then(|c| c.last_child()) {
+        action_area.set_margin_start(15);
+        action_area.set_margin_end(15);
+        action_area.set_margin_top(10);
+        action_area.set_margin_bottom(15);
+    }
+
+    let content = dialog.content_area();
+    content.set_margin_start(15);
+    content.set_margin_end(15);
+    content.set_margin_top(10);
+    content.set_margin_bottom(10);
+    content.set_spacing(10);
+
+    let description = Label::new(Some(
+        &tr("These are the only files Make Your Choice changed in the game folder. Each original is kept next to it, the game's executables and archives are never touched. Restoring puts every original back, like verifying the game files in Steam would."),
+    ));
+    description.set_halign(gtk4::Align::Start);
+    description.set_wrap(true);
+    description.set_margin_top(5);
+    content.append(&description);
+
+    let list = gtk4::ListBox::builder()
+        .selection_mode(gtk4::SelectionMode::None)
+        .css_classes(["boxed-list"])
+        .build();
+    for entry in &entries {
+        let row = adw::ActionRow::builder()
+            .title(entry.label.as_str())
+            .subtitle(entry.file.display().to_string())
+            .build();
+        let (icon, tooltip) = if entry.backup_ok {
+            ("emblem-ok-symbolic", trf("Original kept as {}", &[&entry.backup.display()]))
+        } else {
+            ("dialog-warning-symbolic", tr("The original is missing or damaged, verify the game files in Steam to get it back"))
+        };
+        let status = gtk4::Image::from_icon_name(icon);
+        status.set_tooltip_text(Some(&tooltip));
+        if !entry.backup_ok {
+            status.add_css_class("warning");
+        }
+        row.add_suffix(&status);
+        list.append(&row);
+    }
+    content.append(&list);
+
+    let app_state = app_state.clone();
+    let window_clone = window.clone();
+    dialog.connect_response(move |dialog, response| {
+        dialog.close();
+        if response == ResponseType::Reject {
+            restore_game_files(&app_state, &window_clone, &game_path);
+        }
+    });
+
+    dialog.show();
+}
+
+fn confirm_restore_game_files(app_state: &Rc<AppState>, window: &ApplicationWindow, game_path: std::path::PathBuf) {
+    let dialog = MessageDialog::new(
+        Some(window),
+        gtk4::DialogFlags::MODAL,
+        MessageType::Question,
+        ButtonsType::None,
+        tr("Restore All Game Files?"),
+    );
+    dialog.set_secondary_text(Some(&tr(
+        "The original splash screen and every skipped movie are put back from their backups, as if Make Your Choice never changed the game folder.",
+    )));
+    dialog.add_button(&tr("Cancel"), ResponseType::Cancel);
+    dialog.add_button(&tr("Restore"), ResponseType::Accept);
+    dialog.set_default_response(ResponseType::Cancel);
+
+    let app_state = app_state.clone();
+    let window = window.clone();
+    dialog.run_async(move |dialog, response| {
+        dialog.close();
+        if response == ResponseType::Accept {
+            restore_game_files(&app_state, &window, &game_path);
+        }
+    });
+}
+
+// Undoes every recorded change in one go, see integrity.rs
+fn restore_game_files(app_state: &Rc<AppState>, window: &ApplicationWindow, game_path: &std::path::Path) {
+    let result = integrity::restore_all(game_path);
+    remember_game_changes(app_state, game_path);
+    match result {
+        Ok(restored) => show_info_dialog(
+            window,
+            &tr("Restore all game files"),
+            &trf("Restored {} files, the game folder is as installed.", &[&restored]),
+        ),
+        Err(err) => show_error_dialog(
+            window,
+            &tr("Restore all game files"),
+            &trf("Some files could not be restored:\n{}", &[&error_details(&err)]),
+        ),
+    }
+}
+
+fn select_game_path<F: FnOnce(std::path::PathBuf) + 'static>(
+    window: &impl IsA<gtk4::Window>,
+    on_selected: F,
+) {
+    let dialog = FileChooserNative::new(
+        Some(&tr("Select game folder")),
+        Some(window),
+        FileChooserAction::SelectFolder,
+        Some(&tr("Select")),
+        Some(&tr("Cancel")),
+    );
+
+    let on_selected = Rc::new(RefCell::new(Some(on_selected)));
+    dialog.run_async(move |dialog, response| {
+        if response == ResponseType::Accept {
+            if let Some(file) = dialog.file() {
+                if let Some(path) = file.path() {
+                    if let Some(callback) = on_selected.borrow_mut().take() {
+                        callback(path);
+                    }
+                }
+            }
+        }
+        dialog.destroy();
+    });
+}
+
+fn select_image_file<F: FnOnce(std::path::PathBuf) + 'static>(
+    window: &ApplicationWindow,
+    on_selected: F,
+) {
+    let dialog = FileChooserNative::new(
+        Some(&tr("Select splash image")),
+        Some(window),
+        FileChooserAction::Open,
+        Some(&tr("Open")),
+        Some(&tr("Cancel")),
+    );
+
+    let filter = FileFilter::new();
+    filter.add_mime_type("image/png");
+    filter.add_mime_type("image/jpeg");
+    filter.add_pattern("*.png");
+    filter.add_pattern("*.jpg");
+    filter.add_pattern("*.jpeg");
+    dialog.add_filter(&filter);
+
+    let on_selected = Rc::new(RefCell::new(Some(on_selected)));
+    dialog.run_async(move |dialog, response| {
+        if response == ResponseType::Accept {
+            if let Some(file) = dialog.file() {
+                if let Some(path) = file.path() {
+                    if let Some(callback) = on_selected.borrow_mut().take() {
+                        callback(path);
+                    }
+                }
+            }
+        }
+        dialog.destroy();
+    });
+}
+
+// Saves a script that reproduces the current entries on a machine without the app
+fn export_hosts_script(app_state: &Rc<AppState>, window: &ApplicationWindow) {
+    let script = match app_state.hosts_manager.export_script(&app_state.regions) {
+        Ok(script) => script,
+        Err(e) => {
+            show_error_dialog(window, &tr("Export as shell script"), &error_details(&e));
+            return;
+        }
+    };
+
+    let dialog = FileChooserNative::new(
+        Some(&tr("Export as shell script")),
+        Some(window),
+        FileChooserAction::Save,
+        Some(&tr("Save")),
+        Some(&tr("Cancel")),
+    );
+    dialog.set_current_name("make-your-choice-hosts.sh");
+
+    let window = window.clone();
+    let toast_overlay = app_state.toast_overlay.clone();
+    dialog.run_async(move |dialog, response| {
+        if response == ResponseType::Accept {
+            if let Some(path) = dialog.file().and_then(|file| file.path()) {
+                let result = std::fs::write(&path, &script).and_then(|_| {
+                    std::fs::set_permissions(&path, std::os::unix::fs::PermissionsExt::from_mode(0o755))
+                });
+                match result {
+                    Ok(()) => toast_overlay.add_toast(adw::Toast::new(&trf(
+                        "Saved to {}. Run it with sudo on the target machine.",
+                        &[&path.display()],
+                    ))),
+                    Err(e) => show_error_dialog(
+                        &window,
+                        &tr("Export as shell script"),
+                        &trf("Failed to save the script:\n{}", &[&e]),
+                    ),
+                }
+            }
+        }
+        dialog.destroy();
+    });
+}
+
+// Reads the hosts file back before launching, so the game never starts unprotected
+fn launch_game_if_applied(app_state: &Rc<AppState>, window: &ApplicationWindow) {
+    if matches!(
+        app_state.backend.status(&app_state.regions),
+        ActiveConfiguration::Inactive
+    ) {
+        show_error_dialog(
+            window,
+            &tr("Apply & Launch DbD"),
+            &tr("The hosts file doesn't contain the applied selection, so the game was not started. Please try applying again."),
+        );
+        return;
+    }
+
+    match launcher::launch() {
+        Ok(()) => log_info!("launcher", "Started Dead by Daylight through Steam"),
+        Err(e) => show_error_dialog(
+            window,
+            &tr("Apply & Launch DbD"),
+            &trf("Failed to start the game:\n{}", &[&format!("{:#}", e)]),
+        ),
+    }
+}
+
+fn open_url(url: &str) {
+    // Use the `open` crate for cross-platform URL opening
+    let _ = open::that(url);
+}
+
+fn get_all_regions_map(
+    selectable: &HashMap<String, RegionInfo>,
+    blocked: &HashMap<String, RegionInfo>,
+) -> HashMap<String, RegionInfo> {
+    let mut all = selectable.clone();
+    for (k, v) in blocked.iter() {
+        all.insert(k.clone(), v.clone());
+    }
+    all
+}
+
+fn check_for_updates_action(app_state: &Rc<AppState>, window: &ApplicationWindow) {
+    let window = window.clone();
+    let update_checker = app_state.update_checker.borrow().clone();
+    let runtime = app_state.tokio_runtime.clone();
+    let repo_url = app_state.config.repo_url();
+    let settings = app_state.settings.clone();
+
+    if overrides::get().offline {
+        show_info_dialog(
+            &window,
+            &tr("Check For Updates"),
+            &tr("Network requests are turned off for this session (--offline)."),
+        );
+        return;
+    }
+
+    // Check if developer identity was fetched
+    if repo_url.is_none() {
+        show_error_dialog(
+            &window,
+            &tr("Check For Updates"),
+            &tr("Unable to check for updates.\n\nThe application was unable to fetch the git identity and therefore couldn't determine the repository URL.\n\nThis may be due to network issues or GitHub API issues.\nAn update to fix this issue has most likely been released, please check manually by joining the Discord server or doing a web search.")
+        );
+        return;
+    }
+
+    let (channel, mirror) = {
+        let settings = settings.lock().unwrap();
+        (settings.update_channel, settings.update_mirror())
+    };
+    let app_state = app_state.clone();
+
+    glib::spawn_future_local(async move {
+        let result = runtime
+            .spawn(async move { update_checker.check_for_updates(channel, mirror).await })
+            .await
+            .unwrap();
+
+        match result {
+            Ok(Some(release)) => {
+                show_update_dialog(&app_state, &window, &release);
+            }
+            Ok(None) => {
+                show_info_dialog(
+                    &window,
+                    &tr("Check For Updates"),
+                    &tr("You're already using the latest release! :D"),
+                );
+            }
+            Err(e) => {
+                log_error!("update", "Update check failed: {:#}", e);
+                show_error_dialog(
+                    &window,
+                    &tr("Error"),
+                    &trf("Error while checking for updates:\n{}", &[&error_details(&e)]),
+                );
+            }
+        }
+    });
+}
+
+// Silent checks that still fail after the checker's own retries are tried again later:
+// after 15 minutes, then 30, then 60
+const SILENT_CHECK_ATTEMPTS: u32 = 3;
+
+fn check_for_updates_silent(app_state: &Rc<AppState>, window: &ApplicationWindow, attempt: u32) {
+    if overrides::get().offline {
+        return;
+    }
+    {
+        let settings = app_state.settings.lock().unwrap();
+        if let Some(paused_until) = &settings.auto_update_check_paused_until {
+            if let Ok(date) = chrono::DateTime::parse_from_rfc3339(paused_until) {
+                if chrono::Local::now() < date {
+                    return;
+                }
+            }
+        }
+    }
+
+    // Without the developer identity there is no repository to check, a banner says so
+    if app_state.config.repo_url().is_none() {
+        return;
+    }
+
+    let window = window.clone();
+    let update_checker = app_state.update_checker.borrow().clone();
+    let runtime = app_state.tokio_runtime.clone();
+    let (channel, mirror) = {
+        let settings = app_state.settings.lock().unwrap();
+        (settings.update_channel, settings.update_mirror())
+    };
+    let app_state = app_state.clone();
+    app_state.last_update_check.set(Some(Instant::now()));
+
+    glib::spawn_future_local(async move {
+        let result = runtime
+            .spawn(async move { update_checker.check_for_updates(channel, mirror).await })
+            .await
+            .unwrap();
+
+        // Only show dialog if there's a new version available
+        match result {
+            Ok(Some(release))
+                if app_state.settings.lock().unwrap().is_version_skipped(&release.tag_name) =>
+            {
+                log_info!("update", "Version {} is available but was skipped", release.tag_name);
+            }
+            Ok(Some(release)) => show_update_dialog(&app_state, &window, &release),
+            Ok(None) => {}
+            Err(e) if attempt < SILENT_CHECK_ATTEMPTS => {
+                let delay = 15 * 60 * (1 << attempt);
+                log_warn!("update", "Update check failed, trying again in {} minutes: {:#}", delay / 60, e);
+                glib::timeout_add_seconds_local_once(delay, move || {
+                    check_for_updates_silent(&app_state, &window, attempt + 1);
+                });
+            }
+            Err(e) => log_warn!("update", "Update check failed, giving up for this session: {:#}", e),
+        }
+    });
+}
+
+// While running, e.g. in the tray, looks for updates every few hours when enabled and
+// announces a new version with a desktop notification and a toast instead of a dialog
+fn start_periodic_update_checks(app_state: &Rc<AppState>, window: &ApplicationWindow) {
+    let app_state_weak = Rc::downgrade(app_state);
+    let window = window.clone();
+    glib::timeout_add_seconds_local(15 * 60, move || {
+        let Some(app_state) = app_state_weak.upgrade() else {
+            return glib::ControlFlow::Break;
+        };
+        let hours = app_state.settings.lock().unwrap().update_check_interval_hours;
+        let due = app_state
+            .last_update_check
+            .get()
+            .is_none_or(|last| last.elapsed() >= Duration::from_secs(u64::from(hours) * 3600));
+        if hours > 0 && due && !overrides::get().offline && app_state.config.repo_url().is_some() {
+            check_for_updates_in_background(&app_state, &window);
+        }
+        glib::ControlFlow::Continue
+    });
+}
+
+fn check_for_updates_in_background(app_state: &Rc<AppState>, window: &ApplicationWindow) {
+    app_state.last_update_check.set(Some(Instant::now()));
+    let update_checker = app_state.update_checker.borrow().clone();
+    let runtime = app_state.tokio_runtime.clone();
+    let (channel, mirror) = {
+        let settings = app_state.settings.lock().unwrap();
+        (settings.update_channel, settings.update_mirror())
+    };
+    let app_state = app_state.clone();
+    let window = window.clone();
+
+    glib::spawn_future_local(async move {
+        let result = runtime
+            .spawn(async move { update_checker.check_for_updates(channel, mirror).await })
+            .await
+            .unwrap();
+
+        let release = match result {
+            Ok(Some(release)) => release,
+            Ok(None) => return,
+            Err(e) => {
+                log_warn!("update", "Periodic update check failed: {:#}", e);
+                return;
+            }
+        };
+        if app_state.announced_version.borrow().as_deref() == Some(release.tag_name.as_str())
+            || app_state.settings.lock().unwrap().is_version_skipped(&release.tag_name)
+        {
+            return;
+        }
+        app_state.announced_version.replace(Some(release.tag_name.clone()));
+        log_info!("update", "Version {} is available", release.tag_name);
+
+        let title = trf("Version {} is available", &[&release.tag_name]);
+        send_desktop_notification(&app_state, &window, NotificationKind::Update, "update", &title, &tr("Open Make Your Choice to update."));
+        let toast = adw::Toast::builder()
+            .title(title.as_str())
+            .button_label(tr("Details"))
+            .timeout(0)
+            .build();
+        let window_clone = window.clone();
+        let app_state_clone = app_state.clone();
+        toast.connect_button_clicked(move |_| {
+            show_update_dialog(&app_state_clone, &window_clone, &release);
+        });
+        app_state.toast_overlay.add_toast(toast);
+    });
+}
+
+// Offers a newer version the way this install is updated: the exact command for
+// package manager installs, self-update for AppImages and the release page otherwise.
+// Every variant can also be postponed.
+fn show_update_dialog(app_state: &Rc<AppState>, window: &ApplicationWindow, release: &Release) {
+    let dialog = MessageDialog::new(
+        Some(window),
+        gtk4::DialogFlags::MODAL,
+        MessageType::Question,
+        ButtonsType::None,
+        tr("Update Available"),
+    );
+    let available = if release.prerelease {
+        trf("A new pre-release is available: {}.", &[&release.tag_name])
+    } else {
+        trf("A new version is available: {}.", &[&release.tag_name])
+    };
+    let current = trf("Your version: {}", &[&app_state.config.current_version]);
+
+    // Running from an AppImage the new one can be swapped in directly
+    let self_update = selfupdate::available(release);
+    let update_command = packaging::update_command(app_state.config.repo_url().as_deref());
+
+    let question = match (&update_command, packaging::install_kind()) {
+        (Some(_), packaging::InstallKind::Flatpak { .. }) => tr("Update it through Flatpak or your software center:"),
+        (Some(_), packaging::InstallKind::Pacman { .. }) => tr("Update it with your AUR helper:"),
+        (Some(_), _) => tr("Update it with:"),
+        (None, _) if self_update.is_some() => tr("Would you like to install it now?"),
+        (None, _) => tr("Would you like to visit the repository?"),
+    };
+    dialog.set_secondary_text(Some(&format!("{}\n{}\n\n{}", available, current, question)));
+
+    // The release's changelog, so the update can be judged before installing it
+    if let Some(body) = release.body.as_deref().filter(|body| !body.trim().is_empty()) {
+        let notes = Label::builder()
+            .label(help::to_pango(body.trim()))
+            .use_markup(true)
+            .wrap(true)
+            .xalign(0.0)
+            .yalign(0.0)
+            .selectable(true)
+            .build();
+        let notes_scroll = ScrolledWindow::builder()
+            .child(&notes)
+            .hscrollbar_policy(PolicyType::Never)
+            .min_content_height(120)
+            .max_content_height(280)
+            .propagate_natural_height(true)
+            .build();
+        notes_scroll.add_css_class("card");
+        notes.set_margin_top(8);
+        notes.set_margin_bottom(8);
+        notes.set_margin_start(8);
+        notes.set_margin_end(8);
+        if let Some(message_area) = dialog.message_area().downcast_ref::<GtkBox>() {
+            message_area.append(&notes_scroll);
+        }
+    }
+
+    if let Some(command) = &update_command {
+        let command_label = Label::builder()
+            .label(command.as_str())
+            .selectable(true)
+            .css_classes(["monospace"])
+            .build();
+        if let Some(message_area) = dialog.message_area().downcast_ref::<GtkBox>() {
+            message_area.append(&command_label);
+        }
+    }
+
+    let combo = ComboBoxText::new();
+    if self_update.is_some() {
+        combo.append(Some("install"), &tr("Download and install now"));
+    }
+    if update_command.is_some() {
+        combo.append(Some("copy"), &tr("Copy the command"));
+    } else {
+        combo.append(Some("now"), &tr("Update now"));
+    }
+    combo.append(Some("3days"), &tr("Ask again in 3 days"));
+    combo.append(Some("14days"), &tr("Ask again in 14 days"));
+    combo.append(Some("21days"), &tr("Ask again in 21 days"));
+    combo.set_active(Some(0));
+    combo.set_margin_top(10);
+    combo.set_margin_bottom(10);
+    combo.set_margin_start(10);
+    combo.set_margin_end(10);
+
+    dialog.content_area().append(&combo);
+    dialog.add_button(&tr("Skip This Version"), ResponseType::Reject);
+    dialog.add_button(&tr("Not now"), ResponseType::Close);
+    dialog.add_button(&tr("Continue"), ResponseType::Ok);
+
+    let release_url = release.html_url.clone();
+    let version = release.tag_name.clone();
+    let settings = app_state.settings.clone();
+    let app_state = app_state.clone();
+    let window = window.clone();
+    dialog.run_async(move |dialog, response| {
+        if response == ResponseType::Reject {
+            log_info!("update", "Skipping version {}", version);
+            update_settings(&app_state, |settings| settings.skipped_version = Some(version.clone()));
+        } else if response == ResponseType::Ok {
+            let active = combo.active_id().map(|s| s.to_string()).unwrap_or_default();
+            if active == "install" {
+                if let Some(update) = self_update.clone() {
+                    install_appimage_update(&app_state, &window, update);
+                }
+            } else if active == "copy" {
+                if let Some(command) = &update_command {
+                    window.clipboard().set_text(command);
+                    app_state
+                        .toast_overlay
+                        .add_toast(adw::Toast::new(&tr("Update command copied")));
+                }
+            } else if active == "now" {
+                open_url(&release_url);
+            } else {
+                let days = match active.as_str() {
+                    "3days" => 3,
+                    "14days" => 14,
+                    "21days" => 21,
+                    _ => 0,
+                };
+                if days > 0 {
+                    let mut settings = settings.lock().unwrap();
+                    let date = chrono::Local::now() + chrono::Duration::days(days);
+                    settings.auto_update_check_paused_until = Some(date.to_rfc3339());
+                    let _ = settings.save();
+                }
+            }
+        }
+        dialog.close();
+    });
+}
+
+// Downloads and verifies the release's AppImage, replaces the running one and offers
+// to restart
+fn install_appimage_update(
+    app_state: &Rc<AppState>,
+    window: &ApplicationWindow,
+    update: selfupdate::SelfUpdate,
+) {
+    app_state
+        .toast_overlay
+        .add_toast(adw::Toast::new(&trf("Downloading {}…", &[&update.asset.name])));
+    log_info!("update", "Downloading {} to replace {:?}", update.asset.name, update.appimage);
+
+    let runtime = app_state.tokio_runtime.clone();
+    let app_state = app_state.clone();
+    let window = window.clone();
+    let appimage = update.appimage.clone();
+    glib::spawn_future_local(async move {
+        let result = runtime
+            .spawn(async move {
+                let downloaded = selfupdate::download(&update).await?;
+                selfupdate::install(&downloaded, &update.appimage)
+            })
+            .await
+            .unwrap();
+
+        if let Err(e) = result {
+            log_error!("update", "Self-update failed: {:#}", e);
+            show_error_dialog(
+                &window,
+                &tr("Update failed"),
+                &trf("The update could not be installed, your current version was kept.\n\n{}", &[&format!("{:#}", e)]),
+            );
+            return;
+        }
+        log_info!("update", "Installed the update to {:?}", appimage);
+
+        let dialog = MessageDialog::new(
+            Some(&window),
+            gtk4::DialogFlags::MODAL,
+            MessageType::Info,
+            ButtonsType::None,
+            tr("Update Installed"),
+        );
+        dialog.set_secondary_text(Some(&tr("Restart Make Your Choice to use the new version.")));
+        dialog.add_button(&tr("Later"), ResponseType::Close);
+        dialog.add_button(&tr("Restart Now"), ResponseType::Accept);
+        dialog.set_default_response(ResponseType::Accept);
+        let window_clone = window.clone();
+        dialog.run_async(move |dialog, response| {
+            dialog.close();
+            if response != ResponseType::Accept {
+                return;
+            }
+            match selfupdate::relaunch(&appimage) {
+                Ok(()) => {
+                    save_window_state(&app_state, &window_clone);
+                    if let Some(app) = window_clone.application() {
+                        app.quit();
+                    }
+                }
+                Err(e) => show_error_dialog(
+                    &window_clone,
+                    &tr("Restart failed"),
+                    &trf("The new version could not be started:\n{}", &[&format!("{:#}", e)]),
+                ),
+            }
+        });
+    });
+}
+
+fn show_patch_notes(parent: Option<&ApplicationWindow>, config: &AppConfig) {
+    let dialog = MessageDialog::new(
+        parent,
+        gtk4::DialogFlags::MODAL,
+        MessageType::Info,
+        ButtonsType::Ok,
+        trf("What's new in {}", &[&config.current_version]),
+    );
+    dialog.set_secondary_text(Some(&config.update_message));
+    dialog.run_async(|dialog, _| dialog.close());
+}
+
+// Notices from startup that don't need an answer: the patch notes when their popup is
+// turned off, and update checks being unavailable because GitHub couldn't be reached
+fn startup_banners(
+    app_state: &Rc<AppState>,
+    window: &ApplicationWindow,
+    patch_notes: bool,
+) -> Vec<adw::Banner> {
+    let mut banners = Vec::new();
+
+    // The previous run panicked, the report was written by the hook in crash.rs
+    if let Some(report) = crash::take_pending() {
+        log_warn!("crash", "The previous run crashed, report kept in {}", crash::last_file().display());
+        let banner = adw::Banner::builder()
+            .title(tr("Make Your Choice closed unexpectedly last time"))
+            .button_label(tr("Report"))
+            .revealed(true)
+            .build();
+        let app_state_clone = app_state.clone();
+        let window = window.clone();
+        banner.connect_button_clicked(move |banner| {
+            banner.set_revealed(false);
+            show_report_dialog(
+                &window,
+                tr("Report the Crash"),
+                tr("The details below show where the app stopped. Nothing personal is included, your game path is left out."),
+                report.clone(),
+                crash::for_issue(&report),
+                app_state_clone.config.repo_url(),
+            );
+        });
+        banners.push(banner);
+    }
+
+    if patch_notes {
+        let banner = adw::Banner::builder()
+            .title(trf("Updated to {}", &[&app_state.config.current_version]))
+            .button_label(tr("What's New"))
+            .revealed(true)
+            .build();
+        let app_state_clone = app_state.clone();
+        let window = window.clone();
+        banner.connect_button_clicked(move |banner| {
+            banner.set_revealed(false);
+            show_patch_notes(Some(&window), &app_state_clone.config);
+        });
+        banners.push(banner);
+    }
+
+    if let Some(banner) = game_update_banner(app_state, window) {
+        banners.push(banner);
+    }
+
+    banners.push(vpn_banner(app_state, window));
+
+    banners
+}
+
+// Rounds and spacing of Measure Again in the comparison
+const COMPARE_ROUNDS: usize = 10;
+const COMPARE_ROUND_DELAY: Duration = Duration::from_secs(1);
+
+// Two or three servers next to each other: this session's pings, the matches played there
+// and the distance, for settling between close candidates with numbers. Checked servers
+// are picked first, then the fastest.
+fn show_compare_dialog(app_state: &Rc<AppState>, window: &ApplicationWindow) {
+    let mut names: Vec<String> = app_state.regions.keys().cloned().collect();
+    names.sort_by(|a, b| get_group_name(a).cmp(get_group_name(b)).then_with(|| a.cmp(b)));
+    let latencies = app_state.latencies.borrow().clone();
+    let latency = |name: &String| latencies.get(name).copied().filter(|ms| *ms >= 0).unwrap_or(i64::MAX);
+    let mut preset: Vec<String> = app_state
+        .selected_regions
+        .borrow()
+        .iter()
+        .filter(|name| app_state.regions.contains_key(*name))
+        .cloned()
+        .collect();
+    preset.sort_by_key(latency);
+    let mut fastest = names.clone();
+    fastest.sort_by_key(latency);
+    for name in fastest {
+        if preset.len() >= 2 {
+            break;
+        }
+        if !preset.contains(&name) {
+            preset.push(name);
+        }
+    }
+
+    let dialog = Dialog::with_buttons(
+        Some(&tr("Compare servers")),
+        Some(window),
+        gtk4::DialogFlags::MODAL,
+        &[
+            (&tr("Measure Again"), ResponseType::Apply),
+            (&tr("Close"), ResponseType::Close),
+        ],
+    );
+    dialog.set_default_width(560);
+
+    if let Some(action_area) = dialog.child().and_then(|c| c.last_child()) {
+        action_area.set_margin_start(15);
+        action_area.set_margin_end(15);
+        action_area.set_margin_top(10);
+        action_area.set_margin_bottom(15);
+    }
+
+    let content = dialog.content_area();
+    content.set_margin_start(15);
+    content.set_margin_end(15);
+    content.set_margin_top(10);
+    content.set_margin_bottom(10);
+    content.set_spacing(10);
+
+    let grid = gtk4::Grid::builder().column_spacing(18).row_spacing(6).build();
+    let mut choices = vec![tr("None")];
+    choices.extend(names.iter().cloned());
+    let choice_refs: Vec<&str> = choices.iter().map(String::as_str).collect();
+    let pickers: Vec<gtk4::DropDown> = (0..3)
+        .map(|column| {
+            let picker = gtk4::DropDown::from_strings(&choice_refs);
+            picker.set_enable_search(true);
+            let position = preset
+                .get(column)
+                .and_then(|name| names.iter().position(|known| known == name))
+                .map_or(0, |position| position as u32 + 1);
+            picker.set_selected(position);
+            picker.update_property(&[gtk4::accessible::Property::Label(&trf("Server {}", &[&(column + 1)]))]);
+            grid.attach(&picker, column as i32 + 1, 0, 1, 1);
+            picker
+        })
+        .collect();
+
+    let metrics = [
+        tr("Latest ping"),
+        tr("Average"),
+        tr("Jitter"),
+        tr("Loss"),
+        tr("Pings measured"),
+        tr("Stability"),
+        tr("Matches played"),
+        tr("Last match"),
+        tr("Distance"),
+    ];
+    for (row, metric) in metrics.iter().enumerate() {
+        let label = Label::builder().label(metric.as_str()).halign(gtk4::Align::Start).css_classes(["dim-label"]).build();
+        grid.attach(&label, 0, row as i32 + 1, 1, 1);
+    }
+    let cells: Vec<Vec<Label>> = (0..pickers.len())
+        .map(|column| {
+            (0..metrics.len())
+                .map(|row| {
+                    let label = Label::builder().halign(gtk4::Align::Start).css_classes(["numeric"]).build();
+                    grid.attach(&label, column as i32 + 1, row as i32 + 1, 1, 1);
+                    label
+                })
+                .collect()
+        })
+        .collect();
+    content.append(&grid);
+
+    let home = geo::home();
+    let note = Label::new(Some(&match &home {
+        Some(home) => trf(
+            "Pings are this session's, press Measure Again for more. Distances are counted from {}, the main city of your time zone.",
+            &[&home.zone],
+        ),
+        None => tr("Pings are this session's, press Measure Again for more. Distances need a time zone other than UTC."),
+    }));
+    note.set_halign(gtk4::Align::Start);
+    note.set_wrap(true);
+    note.add_css_class("dim-label");
+    content.append(&note);
+
+    let history = matches::load();
+    let refresh = {
+        let app_state = app_state.clone();
+        let pickers = pickers.clone();
+        let names = names.clone();
+        Rc::new(move || {
+            let chosen: Vec<Option<&String>> = pickers
+                .iter()
+                .map(|picker| (picker.selected() as usize).checked_sub(1).and_then(|index| names.get(index)))
+                .collect();
+            // Per metric the lowest value among the chosen servers is marked
+            let mut scores: Vec<Vec<Option<f64>>> = vec![vec![None; chosen.len()]; metrics.len()];
+            for (column, name) in chosen.iter().enumerate() {
+                let texts: Vec<String> = match name {
+                    None => vec![String::new(); metrics.len()],
+                    Some(name) => {
+                        let info = &app_state.regions[*name];
+                        let stats = app_state.ping_history.borrow().stats(name);
+                        let played = history.get(*name).cloned().unwrap_or_default();
+                        let distance = home.as_ref().and_then(|home| {
+                            let data_center = aws_region_code(info).and_then(geo::data_center)?;
+                            Some(geo::distance_km((home.latitude, home.longitude), data_center))
+                        });
+                        let answered = stats.as_ref().filter(|stats| stats.min >= 0);
+                        scores[1][column] = answered.map(|stats| stats.average);
+                        scores[2][column] = answered.map(|stats| stats.jitter);
+                        scores[3][column] = stats.as_ref().map(|stats| stats.loss);
+                        scores[8][column] = distance;
+                        vec![
+                            match &stats {
+                                Some(stats) => stats.latest.map_or_else(|| tr("lost"), |ms| trf("{} ms", &[&ms])),
+                                None => "–".to_string(),
+                            },
+                            answered.map_or_else(
+                                || "–".to_string(),
+                                |stats| trf("{} ms ({}–{} ms)", &[&format!("{:.0}", stats.average), &stats.min, &stats.max]),
+                            ),
+                            answered.map_or_else(|| "–".to_string(), |stats| trf("{} ms", &[&format!("{:.1}", stats.jitter)])),
+                            stats.as_ref().map_or_else(|| "–".to_string(), |stats| format!("{:.0} %", stats.loss * 100.0)),
+                            stats.as_ref().map_or(0, |stats| stats.samples).to_string(),
+                            if info.stable { tr("Stable") } else { tr("Unstable, issues may occur") },
+                            match (played.matches, played.disconnects) {
+                                (0, 0) => tr("None yet"),
+                                (matches, 0) => matches.to_string(),
+                                (matches, disconnects) => trf("{} ({} left early)", &[&matches, &disconnects]),
+                            },
+                            played.last.map_or_else(|| "–".to_string(), |last| last.format("%Y-%m-%d").to_string()),
+                            distance.map_or_else(|| "–".to_string(), |km| trf("≈ {} km", &[&format!("{:.0}", km)])),
+                        ]
+                    }
+                };
+                for (row, text) in texts.into_iter().enumerate() {
+                    cells[column][row].set_label(&text);
+                }
+            }
+            for (row, values) in scores.iter().enumerate() {
+                let known: Vec<f64> = values.iter().flatten().copied().collect();
+                let best = known.iter().copied().fold(f64::INFINITY, f64::min);
+                for (column, value) in values.iter().enumerate() {
+                    let marked = known.len() >= 2 && *value == Some(best);
+                    if marked {
+                        cells[column][row].add_css_class("success");
+                    } else {
+                        cells[column][row].remove_css_class("success");
+                    }
+                }
+            }
+        })
+    };
+    refresh();
+    for picker in &pickers {
+        let refresh = refresh.clone();
+        picker.connect_selected_notify(move |_| refresh());
+    }
+
+    // Pings the chosen servers a few times right away instead of waiting for the rounds
+    let open = Rc::new(Cell::new(true));
+    let app_state = app_state.clone();
+    dialog.connect_response(move |dialog, response| {
+        if response != ResponseType::Apply {
+            open.set(false);
+            dialog.close();
+            return;
+        }
+        let targets: Vec<(String, String)> = pickers
+            .iter()
+            .filter_map(|picker| (picker.selected() as usize).checked_sub(1).and_then(|index| names.get(index)))
+            .filter_map(|name| Some((name.clone(), app_state.regions.get(name)?.hosts.first()?.clone())))
+            .collect();
+        if targets.is_empty() {
+            return;
+        }
+        dialog.set_response_sensitive(ResponseType::Apply, false);
+        let dialog = dialog.clone();
+        let app_state = app_state.clone();
+        let refresh = refresh.clone();
+        let open = open.clone();
+        glib::spawn_future_local(async move {
+            let targets: Arc<[(String, String)]> = targets.into();
+            for round in 0..COMPARE_ROUNDS {
+                if round > 0 {
+                    glib::timeout_future(COMPARE_ROUND_DELAY).await;
+                }
+                if !open.get() {
+                    return;
+                }
+                let round_targets = targets.clone();
+                let Ok(results) = app_state
+                    .tokio_runtime
+                    .spawn(async move { ping::ping_all(&round_targets).await })
+                    .await
+                else {
+                    break;
+                };
+                app_state.ping_history.borrow_mut().record(&results);
+                refresh();
+            }
+            dialog.set_response_sensitive(ResponseType::Apply, true);
+        });
+    });
+
+    dialog.show();
+}
+
+// One hostname per line, the same list `make-your-choice hostnames` prints
+fn copy_region_hostnames(app_state: &Rc<AppState>, name: &str) {
+    let Some(info) = app_state.regions.get(name) else { return; };
+    app_state.toast_overlay.clipboard().set_text(&info.hosts.join("\n"));
+    app_state
+        .toast_overlay
+        .add_toast(adw::Toast::new(&trf("Copied the hostnames of {}", &[&name])));
+}
+
+// Current addresses of the server, one per line. They are looked up past the hosts file,
+// so blocked servers show their real addresses rather than 0.0.0.0.
+fn copy_region_addresses(app_state: &Rc<AppState>, name: &str) {
+    let Some(info) = app_state.regions.get(name) else { return; };
+    let hosts = info.hosts.clone();
+    let name = name.to_string();
+    let runtime = app_state.tokio_runtime.clone();
+    let app_state = app_state.clone();
+    glib::spawn_future_local(async move {
+        let addresses = runtime
+            .spawn(async move { dns::resolve_all(&hosts).await })
+            .await
+            .unwrap_or_default();
+        if addresses.is_empty() {
+            app_state
+                .toast_overlay
+                .add_toast(adw::Toast::new(&trf("Could not resolve the hostnames of {}", &[&name])));
+            return;
+        }
+        let mut ips: Vec<String> = Vec::new();
+        for (_, ip) in addresses {
+            if !ips.contains(&ip.to_string()) {
+                ips.push(ip.to_string());
+            }
+        }
+        app_state.toast_overlay.clipboard().set_text(&ips.join("\n"));
+        app_state
+            .toast_overlay
+            .add_toast(adw::Toast::new(&trf("Copied the addresses of {}", &[&name])));
+    });
+}
+
+// A VPN carrying the internet traffic moves both the pings and the game's server choice
+// to the VPN server's location. Checked on start and again whenever the network changes,
+// shown once per tunnel.
+fn vpn_banner(app_state: &Rc<AppState>, window: &ApplicationWindow) -> adw::Banner {
+    let banner = adw::Banner::builder()
+        .button_label(tr("Details"))
+        .revealed(false)
+        .build();
+    let shown_for: Rc<RefCell<Option<String>>> = Rc::new(RefCell::new(None));
+
+    let check = {
+        let banner = banner.clone();
+        let shown_for = shown_for.clone();
+        move || {
+            let routed = vpn::routed();
+            if routed.is_none() {
+                banner.set_revealed(false);
+            } else if routed != *shown_for.borrow() {
+                log_info!("vpn", "Internet traffic goes through {:?}", routed);
+                banner.set_title(&trf(
+                    "Your VPN ({}) carries the game traffic, pings and servers are picked from its location",
+                    &[&routed.as_deref().unwrap_or_default()],
+                ));
+                banner.set_revealed(true);
+            }
+            *shown_for.borrow_mut() = routed;
+        }
+    };
+    check();
+    let check = Rc::new(check);
+    gio::NetworkMonitor::default().connect_network_changed(move |_, _| {
+        // Tunnels come up a moment after the network reports the change
+        let check = check.clone();
+        glib::timeout_add_seconds_local_once(2, move || check());
+    });
+
+    let app_state = app_state.clone();
+    let window = window.clone();
+    banner.connect_button_clicked(move |banner| {
+        banner.set_revealed(false);
+        let apply_mode = app_state.settings.lock().unwrap().effective_apply_mode();
+        let method = match apply_mode {
+            ApplyMode::Gatekeep => tr("Gatekeep blocks the servers you didn't select in /etc/hosts. The game still can't reach them through the tunnel, so it picks among the selected ones, measured from the VPN server. VPN clients with their own DNS protection or DNS-over-HTTPS skip the hosts file, then the game sees every server again; turn that option off in the VPN client or disconnect it for playing."),
+            ApplyMode::UniversalRedirect => tr("Universal Redirect points every server name in /etc/hosts at the selected server. The game connects to it through the tunnel, which adds the way to the VPN server and back to every match. VPN clients with their own DNS protection or DNS-over-HTTPS skip the hosts file, then the redirect has no effect; turn that option off in the VPN client or disconnect it for playing."),
+        };
+        show_info_dialog(
+            &window,
+            &tr("Playing Through a VPN"),
+            &format!(
+                "{}\n\n{}\n\n{}",
+                tr("Your internet traffic goes through a VPN. The pings in the list are measured from the VPN server, not from you, and the game picks its match server from there as well."),
+                method,
+                tr("Some VPN clients also rewrite /etc/hosts when they connect. Check the status after connecting and apply again if the selection is gone."),
+            ),
+        );
+    });
+    banner
+}
+
+// When the game updates, Steam puts the original splash screen and movies back without a
+// word. Installs with remembered changes are checked on every start and the ones that
+// were undone can be made again in one click.
+fn game_update_banner(app_state: &Rc<AppState>, window: &ApplicationWindow) -> Option<adw::Banner> {
+    let installs = app_state.settings.lock().unwrap().game_installs.clone();
+    let mut updated = false;
+    let mut undone = Vec::new();
+    for install in installs {
+        let Some(changes) = install.changes else {
+            continue;
+        };
+        let path = std::path::PathBuf::from(install.path.trim());
+        let changes_undone = integrity::undone(&path, &changes);
+        if changes_undone.is_empty() {
+            continue;
+        }
+        let build_id = games::build_id(&path);
+        updated |= build_id.is_some() && build_id != changes.build_id;
+        log_info!(
+            "games",
+            "{} changes in {} were undone, build {:?} was {:?}",
+            changes_undone.len(),
+            install.name,
+            build_id,
+            changes.build_id
+        );
+        undone.push((path, changes_undone));
+    }
+    if undone.is_empty() {
+        return None;
+    }
+
+    let title = if updated {
+        tr("A game update undid your splash art or skipped movies")
+    } else {
+        tr("Your splash art or skipped movies were undone in the game folder")
+    };
+    let banner = adw::Banner::builder()
+        .title(title)
+        .button_label(tr("Apply Again"))
+        .revealed(true)
+        .build();
+    let app_state = app_state.clone();
+    let window = window.clone();
+    let undone = RefCell::new(undone);
+    banner.connect_button_clicked(move |banner| {
+        banner.set_revealed(false);
+        let mut failures = Vec::new();
+        for (path, changes) in undone.take() {
+            if let Err(e) = integrity::reapply(&path, &changes) {
+                failures.push(format!("{}: {:#}", path.display(), e));
+            }
+            remember_game_changes(&app_state, &path);
+        }
+        if failures.is_empty() {
+            app_state
+                .toast_overlay
+                .add_toast(adw::Toast::new(&tr("Splash art and skipped movies applied again.")));
+        } else {
+            show_error_dialog(
+                &window,
+                &tr("Apply Again"),
+                &trf("Some changes could not be made again:\n{}", &[&failures.join("\n")]),
+            );
+        }
+    });
+    Some(banner)
+}
+
+// Fetches the developer identity after the window opened and keeps it for the next
+// start. The About dialog, the repository and the update checks read it when used, so
+// on the first start they work as soon as it arrives; the check on launch waits for it.
+fn refresh_git_identity(
+    app_state: &Rc<AppState>,
+    window: &ApplicationWindow,
+    toolbar_view: &adw::ToolbarView,
+    check_updates: bool,
+) {
+    if overrides::get().offline {
+        return;
+    }
+    let cached = app_state.config.developer().is_some();
+    let runtime = app_state.tokio_runtime.clone();
+    let app_state = app_state.clone();
+    let window = window.clone();
+    let toolbar_view = toolbar_view.clone();
+    glib::spawn_future_local(async move {
+        let developer = runtime.spawn(fetch_git_identity()).await.ok().flatten();
+        match developer {
+            Some(developer) => {
+                if app_state.config.developer().as_ref() == Some(&developer) {
+                    return;
+                }
+                app_state.update_checker.replace(UpdateChecker::new(
+                    developer.clone(),
+                    app_state.config.repo.clone(),
+                    app_state.config.current_version.clone(),
+                ));
+                app_state.config.developer.replace(Some(developer));
+                if check_updates && !cached {
+                    check_for_updates_silent(&app_state, &window, 0);
+                }
+            }
+            None if check_updates && !cached => {
+                log_warn!("update", "Could not fetch the developer identity, update checks are unavailable");
+                let banner = adw::Banner::builder()
+                    .title(tr("Couldn't reach GitHub, update checks are unavailable this time"))
+                    .button_label(tr("Dismiss"))
+                    .revealed(true)
+                    .build();
+                banner.connect_button_clicked(|banner| banner.set_revealed(false));
+                toolbar_view.add_top_bar(&banner);
+            }
+            None => {}
+        }
+    });
+}
+
+fn show_about_dialog(app_state: &Rc<AppState>, window: &ApplicationWindow) {
+    let dialog = Dialog::with_buttons(
+        Some(&tr("About Make Your Choice")),
+        Some(window),
+        gtk4::DialogFlags::MODAL,
+        &[(&tr("Awesome!"), ResponseType::Ok)],
+    );
+    dialog.set_default_width(480);
+
+    // Add margin to the button area
+    if let Some(action_area) = dialog.child().and_then(|c| c.last_child()) {
+        action_area.set_margin_start(15);
+        action_area.set_margin_end(15);
+        action_area.set_margin_top(10);
+        action_area.set_margin_bottom(15);
+    }
+
+    let content = dialog.content_area();
+    let vbox = GtkBox::new(Orientation::Vertical, 10);
+    vbox.set_margin_start(20);
+    vbox.set_margin_end(20);
+    vbox.set_margin_top(20);
+    vbox.set_margin_bottom(20);
+
+    let title = adw::StatusPage::builder()
+        .icon_name("make-your-choice")
+        .title(tr("Make Your Choice"))
+        .description(tr("DbD Server Selector"))
+        .build();
+    title.add_css_class("compact");
+
+    // Developer label. This must always refer to the original developer. Changing this breaks license compliance.
+    let developer_box = GtkBox::new(Orientation::Horizontal, 5);
+    developer_box.set_halign(gtk4::Align::Start);
+    let developer_label = Label::new(Some(&tr("Developer: ")));
+    developer_box.append(&developer_label);
+
+    if let Some(dev) = &app_state.config.developer() {
+        let developer_link = gtk4::LinkButton::with_label(
+            &format!("https://github.com/{}", dev),
+            dev,
+        );
+        developer_link.set_halign(gtk4::Align::Start);
+        developer_box.append(&developer_link);
+    } else {
+        let unknown_label = Label::new(Some("(unknown)"));
+        unknown_label.set_halign(gtk4::Align::Start);
+        developer_box.append(&unknown_label);
+    }
+
+    let version = Label::new(Some(&trf(
+        "Version {}\nLinux (GTK4)",
+        &[&app_state.config.current_version],
+    )));
+    version.set_halign(gtk4::Align::Start);
+
+    // Copyright notice
+    let copyright = Label::new(Some(&tr("Copyright © 2026")));
+    copyright.set_halign(gtk4::Align::Start);
+
+    // License information
+    let license = Label::new(Some(
+        &tr("This program is free software licensed\n\
+        under the terms of the GNU General Public License.\n\
+        This program is distributed in the hope that it will be useful, but\n\
+        without any warranty. See the GNU General Public License\n\
+        for more details.")
+    ));
+    license.set_halign(gtk4::Align::Start);
+    license.set_wrap(true);
+    license.set_max_width_chars(60);
+
+    vbox.append(&title);
+    vbox.append(&developer_box);
+    vbox.append(&version);
+    vbox.append(&Separator::new(Orientation::Horizontal));
+    vbox.append(&copyright);
+    vbox.append(&license);
+    content.append(&vbox);
+
+    dialog.run_async(|dialog, _| dialog.close());
+    dialog.show();
+}
+
+fn reset_hosts_action(app_state: &Rc<AppState>, window: &ApplicationWindow) {
+    let dialog = MessageDialog::new(
+        Some(window),
+        gtk4::DialogFlags::MODAL,
+        MessageType::Warning,
+        ButtonsType::YesNo,
+        tr("Restore Linux default hosts file"),
+    );
+    dialog.set_secondary_text(Some(
+        &trf("If you are having problems, or the program doesn't seem to work correctly, try resetting your hosts file.\n\n\
+        This will overwrite your entire hosts file with the Linux default.\n\n\
+        A backup will be saved as {}. Continue?", &[&paths::hosts_backup_file().display()])
+    ));
+
+    let app_state = app_state.clone();
+    let window = window.clone();
+    dialog.run_async(move |dialog, response| {
+        if response == ResponseType::Yes {
+            let app_state_clone = app_state.clone();
+            let window = window.clone();
+            let hosts = app_state.hosts_manager.clone();
+            run_hosts_task(
+                &app_state,
+                move |backend, _| async move {
+                    let snapshot = backend.snapshot().await.ok();
+                    hosts.restore_default().await.map(|_| snapshot)
+                },
+                move |result| match result {
+                    Ok(snapshot) => report_hosts_change(
+                        &app_state_clone,
+                        &window,
+                        &tr("Hosts file restored to Linux default template"),
+                        snapshot,
+                        HashSet::new(),
+                    ),
+                    Err(e) => report_hosts_error(&app_state_clone, &window, &e),
+                },
+            );
+        }
+        dialog.close();
+    });
+}
+
+fn show_conflict_dialog(
+    window: &ApplicationWindow,
+    app_state: &Rc<AppState>,
+    selected: &HashSet<String>,
+    apply_mode: ApplyMode,
+    block_mode: BlockMode,
+    merge_unstable: bool,
+    trigger: Trigger,
+) {
+    let dialog = Dialog::with_buttons(
+        Some(&tr("Conflicting Hosts Entries Detected")),
+        Some(window),
+        gtk4::DialogFlags::MODAL,
+        &[
+            (&tr("Cancel"), ResponseType::Cancel),
+            (&tr("Continue"), ResponseType::Ok),
+        ],
+    );
+    dialog.set_default_width(500);
+    dialog.set_default_height(280);
+
+    // Add margin to button area
+    if let Some(action_area) = dialog.child().and_then(|c| c.last_child()) {
+        action_area.set_margin_start(15);
+        action_area.set_margin_end(15);
+        action_area.set_margin_top(10);
+        action_area.set_margin_bottom(15);
+    }
+
+    let content = dialog.content_area();
+    let vbox = GtkBox::new(Orientation::Vertical, 15);
+    vbox.set_margin_start(20);
+    vbox.set_margin_end(20);
+    vbox.set_margin_top(20);
+    vbox.set_margin_bottom(20);
+
+    let message = Label::new(Some(
+        &tr("It seems like there are conflicting entries in your hosts file.\n\n\
+        This is usually caused by another program, or by manual changes.\n\n\
+        It's best to resolve these issues first before applying a new configuration.\n\
+        Would you like to clear out all conflicting entries?")
+    ));
+    message.set_wrap(true);
+    message.set_max_width_chars(60);
+    message.set_halign(gtk4::Align::Start);
+
+    let rb_clear = gtk4::CheckButton::with_label(&tr("Clear out conflicts, and apply selection (recommended)"));
+    rb_clear.set_active(true);
+
+    let rb_keep = gtk4::CheckButton::with_label(&tr("Apply selection without clearing out conflicts"));
+    rb_keep.set_group(Some(&rb_clear));
+
+    vbox.append(&message);
+    vbox.append(&rb_clear);
+    vbox.append(&rb_keep);
+    content.append(&vbox);
+
+    let app_state_clone = app_state.clone();
+    let window_clone = window.clone();
+    let selected_clone = selected.clone();
+
+    dialog.connect_response(move |dialog, response| {
+        if response != ResponseType::Ok {
+            dialog.close();
+            return;
+        }
+
+        let clear_conflicts = rb_clear.is_active();
+
+        if !clear_conflicts {
+            // Show confirmation dialog
+            let confirm_dialog = MessageDialog::new(
+                Some(&window_clone),
+                gtk4::DialogFlags::MODAL,
+                MessageType::Warning,
+                ButtonsType::YesNo,
+                tr("Confirm"),
+            );
+            confirm_dialog.set_secondary_text(Some(
+                &tr("Not clearing out conflicting entries will cause unexpected behavior.\n\n\
+                Are you sure you want to continue?")
+            ));
+
+            let app_state_clone2 = app_state_clone.clone();
+            let window_clone2 = window_clone.clone();
+            let selected_clone2 = selected_clone.clone();
+
+            confirm_dialog.run_async(move |confirm_dialog, confirm_response| {
+                if confirm_response == ResponseType::Yes {
+                    // User confirmed, proceed without clearing conflicts
+                    apply_hosts_changes(&app_state_clone2, &window_clone2, &selected_clone2, apply_mode, block_mode, merge_unstable, trigger);
+                }
+                confirm_dialog.close();
+            });
+
+            dialog.close();
+        } else {
+            // Clear conflicts first, then apply
+            let all_regions = get_all_regions_map(&app_state_clone.regions, &app_state_clone.blocked_regions);
+            let app_state_clone2 = app_state_clone.clone();
+            let window_clone2 = window_clone.clone();
+            let selected_clone2 = selected_clone.clone();
+            let hosts = app_state_clone.hosts_manager.clone();
+            run_hosts_task(
+                &app_state_clone,
+                move |backend, _| async move {
+                    let conflicts = backend
+                        .detect_conflicts(&all_regions)
+                        .context("Failed to check for conflicts")?;
+                    hosts
+                        .clear_conflicting_entries(&conflicts)
+                        .await
+                        .context("Failed to clear conflicting entries")
+                },
+                move |result| match result {
+                    // Conflicts cleared, now apply
+                    Ok(_) => apply_hosts_changes(&app_state_clone2, &window_clone2, &selected_clone2, apply_mode, block_mode, merge_unstable, trigger),
+                    Err(e) => show_error_dialog(&window_clone2, &tr("Error"), &error_details(&e)),
+                },
+            );
+            dialog.close();
+        }
+    });
+
+    dialog.show();
+}
+
+fn apply_hosts_changes(
+    app_state: &Rc<AppState>,
+    window: &ApplicationWindow,
+    selected: &HashSet<String>,
+    apply_mode: ApplyMode,
+    block_mode: BlockMode,
+    merge_unstable: bool,
+    trigger: Trigger,
+) {
+    if apply_mode == ApplyMode::UniversalRedirect && selected.len() != 1 {
+        show_error_dialog(
+            window,
+            &tr("Universal Redirect"),
+            &tr("Please select only one server when using Universal Redirect mode."),
+        );
+        return;
+    }
+
+    let regions = app_state.regions.clone();
+    let blocked_regions = app_state.blocked_regions.clone();
+    let app_state_clone = app_state.clone();
+    let window = window.clone();
+    let mut selection: Vec<String> = selected.iter().cloned().collect();
+    selection.sort();
+    let hook_data = hooks::apply_data(apply_mode.id(), &selection);
+    let hook_data_for_task = hook_data.clone();
+
+    let applied_selection = AppliedSelection {
+        selection,
+        apply_mode,
+        block_mode,
+        merge_unstable,
+    };
+    let applied_for_task = applied_selection.clone();
+
+    // Universal Redirect resolves hostnames, which can take a while on slow DNS
+    run_hosts_task(
+        app_state,
+        move |backend, progress| async move {
+            tokio::task::spawn_blocking(move || hooks::run(hooks::Event::PreApply, hook_data_for_task)).await?;
+            let snapshot = backend.snapshot().await.ok();
+            let report = |step| {
+                let _ = progress.send(step);
+            };
+            backend
+                .apply(&regions, &blocked_regions, &applied_for_task, &report)
+                .await
+                .map(|_| snapshot)
+        },
+        move |result| match result {
+            Ok(snapshot) => {
+                hooks::spawn(hooks::Event::PostApply, hook_data);
+                let applied: HashSet<String> = applied_selection.selection.iter().cloned().collect();
+                history::record_apply(&applied_selection, app_state_clone.backend.id(), trigger);
+                update_settings(&app_state_clone, |settings| {
+                    settings.last_applied = Some(applied_selection);
+                });
+                report_hosts_change(
+                    &app_state_clone,
+                    &window,
+                    &trf(
+                        "Hosts file updated ({} mode). Restart the game for changes to take effect.",
+                        &[&format!("{:?}", apply_mode)],
+                    ),
+                    snapshot,
+                    applied,
+                );
+                if app_state_clone.launch_after_apply.replace(false) {
+                    launch_game_if_applied(&app_state_clone, &window);
+                }
+            }
+            Err(e) => {
+                app_state_clone.launch_after_apply.set(false);
+                report_hosts_error(&app_state_clone, &window, &e);
+            }
+        },
+    );
+}
+
+// Same as Apply, then starts the game once the hosts file is confirmed to be written
+fn handle_apply_and_launch_click(app_state: &Rc<AppState>, window: &ApplicationWindow) {
+    if app_state.hosts_busy.get() {
+        return;
+    }
+    app_state.launch_after_apply.set(true);
+    show_apply_summary(app_state, window);
+}
+
+fn handle_apply_click(app_state: &Rc<AppState>, window: &ApplicationWindow) {
+    if app_state.hosts_busy.get() {
+        return;
+    }
+    app_state.launch_after_apply.set(false);
+    show_apply_summary(app_state, window);
+}
+
+fn show_apply_summary(app_state: &Rc<AppState>, window: &ApplicationWindow) {
+    let selected = app_state.selected_regions.borrow().clone();
+    let (apply_mode, block_mode, merge_unstable, confirm_apply) = {
+        let settings = app_state.settings.lock().unwrap();
+        (settings.effective_apply_mode(), settings.block_mode, settings.merge_unstable, settings.confirm_apply)
+    };
+
+    // Selections that can't be applied go straight to the error reporting in start_apply
+    let valid_selection = match apply_mode {
+        ApplyMode::Gatekeep => !selected.is_empty(),
+        ApplyMode::UniversalRedirect => selected.len() == 1,
+    };
+    if !confirm_apply || !valid_selection {
+        start_apply(app_state, window, Trigger::Window);
+        return;
+    }
+
+    let sorted = |set: &HashSet<String>| {
+        let mut names: Vec<String> = set.iter().cloned().collect();
+        names.sort();
+        names
+    };
+
+    let mut summary = Vec::new();
+    match apply_mode {
+        ApplyMode::Gatekeep => {
+            let allowed = hosts::gatekeep_allowed_regions(&app_state.regions, &selected, merge_unstable);
+            let added: HashSet<String> = allowed.difference(&selected).cloned().collect();
+            let blocked: HashSet<String> = app_state
+                .regions
+                .keys()
+                .filter(|name| !allowed.contains(*name))
+                .cloned()
+                .collect();
+
+            summary.push(trf(
+                "Method: Gatekeep, blocking {}",
+                &[&match block_mode {
+                    BlockMode::Both => tr("ping and service"),
+                    BlockMode::OnlyPing => tr("ping only"),
+                    BlockMode::OnlyService => tr("service only"),
+                }],
+            ));
+            summary.push(trf("Allowed: {}", &[&sorted(&selected).join(", ")]));
+            if !added.is_empty() {
+                summary.push(trf(
+                    "Also allowed as stable alternatives (merge unstable servers): {}",
+                    &[&sorted(&added).join(", ")],
+                ));
+            }
+            summary.push(trf("Blocked ({}): {}", &[&blocked.len(), &sorted(&blocked).join(", ")]));
+        }
+        ApplyMode::UniversalRedirect => {
+            summary.push(tr("Method: Universal Redirect"));
+            summary.push(trf(
+                "All servers will be redirected to {}.",
+                &[&sorted(&selected).join(", ")],
+            ));
+        }
+    }
+
+    let dialog = MessageDialog::new(
+        Some(window),
+        gtk4::DialogFlags::MODAL,
+        MessageType::Question,
+        ButtonsType::None,
+        tr("Apply this selection?"),
+    );
+    dialog.set_secondary_text(Some(&summary.join("\n\n")));
+    dialog.add_button(&tr("Cancel"), ResponseType::Cancel);
+    let apply_label = if app_state.launch_after_apply.get() {
+        tr("Apply & Launch")
+    } else {
+        tr("Apply")
+    };
+    dialog.add_button(&apply_label, ResponseType::Accept);
+    dialog.set_default_response(ResponseType::Accept);
+
+    let dont_ask = CheckButton::with_label(&tr("Don't show this summary again"));
+    if let Some(message_area) = dialog.message_area().downcast_ref::<GtkBox>() {
+        message_area.append(&dont_ask);
+    }
+
+    let app_state = app_state.clone();
+    let window = window.clone();
+    dialog.run_async(move |dialog, response| {
+        dialog.close();
+        if response != ResponseType::Accept {
+            return;
+        }
+        if dont_ask.is_active() {
+            update_settings(&app_state, |settings| settings.confirm_apply = false);
+        }
+        start_apply(&app_state, &window, Trigger::Window);
+    });
+}
+
+// Checks for conflicts and writes the current selection without asking for confirmation
+fn start_apply(app_state: &Rc<AppState>, window: &ApplicationWindow, trigger: Trigger) {
+    let selected = app_state.selected_regions.borrow().clone();
+    let (apply_mode, block_mode, merge_unstable) = {
+        let settings = app_state.settings.lock().unwrap();
+        (settings.effective_apply_mode(), settings.block_mode, settings.merge_unstable)
+    };
+
+    // Check for conflicting entries before proceeding
+    let all_regions = get_all_regions_map(&app_state.regions, &app_state.blocked_regions);
+    let app_state_clone = app_state.clone();
+    let window = window.clone();
+    run_hosts_task(
+        app_state,
+        move |backend, _| async move { backend.detect_conflicts(&all_regions) },
+        move |result| match result {
+            Ok(conflicts) if !conflicts.is_empty() => {
+                // Show conflict dialog and let it handle everything
+                show_conflict_dialog(&window, &app_state_clone, &selected, apply_mode, block_mode, merge_unstable, trigger);
+            }
+            Err(e) => {
+                show_error_dialog(&window, &tr("Error"), &trf("Failed to check for conflicts:\n{}", &[&error_details(&e)]));
+            }
+            // No conflicts, apply directly
+            _ => apply_hosts_changes(&app_state_clone, &window, &selected, apply_mode, block_mode, merge_unstable, trigger),
+        },
+    );
+}
+
+// Loads and applies the active profile, with a notification since the game is in front
+fn apply_at_game_launch(app_state: &Rc<AppState>, window: &ApplicationWindow) {
+    let profile = {
+        let settings = app_state.settings.lock().unwrap();
+        settings
+            .active_profile
+            .clone()
+            .filter(|name| settings.profile(name).is_some())
+    };
+    let Some(profile) = profile else {
+        return;
+    };
+    if app_state.hosts_busy.get() {
+        return;
+    }
+    log_info!("gamewatch", "Game started, applying profile {}", profile);
+    send_desktop_notification(
+        app_state,
+        window,
+        NotificationKind::ApplyResult,
+        "game-launch",
+        &tr("Dead by Daylight started"),
+        &trf("Applying profile \"{}\".", &[&profile]),
+    );
+    load_profile(app_state, &profile);
+    app_state.launch_after_apply.set(false);
+    start_apply(app_state, window, Trigger::GameLaunch);
+}
+
+// Re-checks the most recently applied regions and applies them again with the method
+// used back then. Works after a revert and across restarts.
+fn apply_last_selection(app_state: &Rc<AppState>, window: &ApplicationWindow, trigger: Trigger) {
+    let last = app_state.settings.lock().unwrap().last_applied.clone();
+    let Some(last) = last else {
+        app_state
+            .toast_overlay
+            .add_toast(adw::Toast::new(&tr("Nothing has been applied yet")));
+        return;
+    };
+    apply_again(app_state, window, last, trigger);
+}
+
+// The stable server with the lowest latency that answered, with its latency
+fn best_server(regions: &HashMap<String, RegionInfo>, latencies: &HashMap<String, i64>) -> Option<(String, i64)> {
+    latencies
+        .iter()
+        .filter(|(name, ms)| **ms >= 0 && regions.get(*name).is_some_and(|info| info.stable))
+        .min_by_key(|(name, ms)| (**ms, (*name).clone()))
+        .map(|(name, ms)| (name.clone(), *ms))
+}
+
+// Gatekeep with only the fastest stable server, for getting low ping without choosing.
+// Right after start there are no latencies yet, they are measured first then.
+fn apply_best_server(app_state: &Rc<AppState>, window: &ApplicationWindow, trigger: Trigger) {
+    if app_state.hosts_busy.get() {
+        return;
+    }
+    let latencies = app_state.latencies.borrow().clone();
+    if best_server(&app_state.regions, &latencies).is_some() {
+        apply_best_server_from(app_state, window, &latencies, trigger);
+        return;
+    }
+
+    app_state.toast_overlay.add_toast(adw::Toast::new(&tr("Measuring latency…")));
+    app_state.best_button.set_sensitive(false);
+    let targets = app_state.ping_targets.clone();
+    let runtime = app_state.tokio_runtime.clone();
+    let app_state = app_state.clone();
+    let window = window.clone();
+    glib::spawn_future_local(async move {
+        let latencies = runtime
+            .spawn(async move { ping::ping_all(&targets).await })
+            .await
+            .unwrap_or_default();
+        app_state.best_button.set_sensitive(!app_state.hosts_busy.get());
+        if !app_state.hosts_busy.get() {
+            apply_best_server_from(&app_state, &window, &latencies, trigger);
+        }
+    });
+}
+
+fn apply_best_server_from(
+    app_state: &Rc<AppState>,
+    window: &ApplicationWindow,
+    latencies: &HashMap<String, i64>,
+    trigger: Trigger,
+) {
+    let Some((name, ms)) = best_server(&app_state.regions, latencies) else {
+        show_error_dialog(
+            window,
+            &tr("No Server Answered"),
+            &tr("None of the stable servers answered a ping. Check your connection and try again."),
+        );
+        return;
+    };
+    log_info!("gui", "Best server is {} at {} ms", name, ms);
+    app_state.toast_overlay.add_toast(adw::Toast::new(&trf(
+        "Fastest stable server: {} ({} ms). Allowing only it with Gatekeep.",
+        &[&name, &ms],
+    )));
+    update_settings(app_state, |settings| settings.apply_mode = ApplyMode::Gatekeep);
+    set_checked_regions(app_state, &HashSet::from([name]));
+    app_state.launch_after_apply.set(false);
+    start_apply(app_state, window, trigger);
+}
+
+// Checks the regions of an earlier apply and applies them with its method
+fn apply_again(app_state: &Rc<AppState>, window: &ApplicationWindow, applied: AppliedSelection, trigger: Trigger) {
+    update_settings(app_state, |settings| {
+        settings.apply_mode = applied.apply_mode;
+        settings.block_mode = applied.block_mode;
+        settings.merge_unstable = applied.merge_unstable;
+    });
+    set_checked_regions(app_state, &applied.selection.into_iter().collect());
+    // Re-applying an earlier selection, no need to summarize it again
+    app_state.launch_after_apply.set(false);
+    start_apply(app_state, window, trigger);
+}
+
+// Every apply and revert, newest first, with a button to apply an entry again
+fn show_apply_history_dialog(app_state: &Rc<AppState>, window: &ApplicationWindow) {
+    let prefs = adw::PreferencesWindow::builder()
+        .title(tr("Apply History"))
+        .transient_for(window)
+        .modal(true)
+        .search_enabled(false)
+        .default_width(520)
+        .default_height(520)
+        .build();
+
+    let group = adw::PreferencesGroup::builder()
+        .description(tr("Changes made by the window, the tray, the command line, links, the background service and plugins. Changes to the hosts file made outside the app are not listed."))
+        .build();
+    let clear_button = Button::with_label(&tr("Clear History"));
+    clear_button.set_valign(gtk4::Align::Center);
+    clear_button.add_css_class("flat");
+    group.set_header_suffix(Some(&clear_button));
+    let page = adw::PreferencesPage::new();
+    page.add(&group);
+    prefs.add(&page);
+
+    let entries = history::load();
+    clear_button.set_sensitive(!entries.is_empty());
+    if entries.is_empty() {
+        group.add(&adw::ActionRow::builder().title(tr("Nothing has been applied yet")).build());
+    }
+
+    let mut rows = Vec::new();
+    for entry in entries.into_iter().rev() {
+        let title = match &entry.applied {
+            Some(applied) => {
+                let method = match applied.apply_mode {
+                    ApplyMode::Gatekeep => tr("Gatekeep"),
+                    ApplyMode::UniversalRedirect => tr("Universal Redirect"),
+                };
+                let regions = if applied.selection.len() > 3 {
+                    trf("{} + {} more", &[&applied.selection[..3].join(" + "), &(applied.selection.len() - 3)])
+                } else {
+                    applied.selection.join(" + ")
+                };
+                trf("{}: {}", &[&method, &regions])
+            }
+            None => tr("Reverted to default"),
+        };
+        let row = adw::ActionRow::builder()
+            .title(glib::markup_escape_text(&title).as_str())
+            .subtitle(trf(
+                "{} · {} · {}",
+                &[&entry.time.format("%Y-%m-%d %H:%M"), &tr(entry.trigger.label()), &entry.backend],
+            ))
+            .build();
+        if let Some(applied) = entry.applied {
+            row.set_tooltip_text(Some(&applied.selection.join("\n")));
+            let button = Button::with_label(&tr("Apply Again"));
+            button.set_valign(gtk4::Align::Center);
+            let app_state = app_state.clone();
+            let window = window.clone();
+            let prefs_clone = prefs.clone();
+            button.connect_clicked(move |_| {
+                if app_state.hosts_busy.get() {
+                    return;
+                }
+                prefs_clone.close();
+                apply_again(&app_state, &window, applied.clone(), Trigger::Window);
+            });
+            row.add_suffix(&button);
+        }
+        group.add(&row);
+        rows.push(row);
+    }
+
+    let prefs_clone = prefs.clone();
+    clear_button.connect_clicked(move |button| match history::clear() {
+        Ok(()) => {
+            for row in &rows {
+                group.remove(row);
+            }
+            group.add(&adw::ActionRow::builder().title(tr("Nothing has been applied yet")).build());
+            button.set_sensitive(false);
+        }
+        Err(e) => prefs_clone.add_toast(adw::Toast::new(&format!("{:#}", e))),
+    });
+    prefs.present();
+}
+
+// Brings the switcher menu, window subtitle and tray in line with the saved profiles
+fn refresh_profiles(app_state: &AppState) {
+    let (names, active) = {
+        let settings = app_state.settings.lock().unwrap();
+        let names: Vec<String> = settings.profiles.iter().map(|p| p.name.clone()).collect();
+        (names, settings.active_profile.clone())
+    };
+
+    let menu = &app_state.profile_menu;
+    menu.remove_all();
+    let profiles = Menu::new();
+    for name in &names {
+        let item = gio::MenuItem::new(Some(name), None);
+        item.set_action_and_target_value(Some("app.profile"), Some(&name.to_variant()));
+        profiles.append_item(&item);
+    }
+    menu.append_section(None, &profiles);
+    let actions = Menu::new();
+    actions.append(Some(&tr("Save selection as profile…")), Some("app.save-profile"));
+    actions.append(Some(&tr("Manage profiles…")), Some("app.manage-profiles"));
+    menu.append_section(None, &actions);
+
+    app_state
+        .profile_action
+        .set_state(&active.clone().unwrap_or_default().to_variant());
+    app_state.window_title.set_subtitle(&match &active {
+        Some(name) => trf("Profile: {}", &[name]),
+        None => tr("DbD Server Selector"),
+    });
+    let active_role = {
+        let settings = app_state.settings.lock().unwrap();
+        active.as_deref().and_then(|name| settings.profile(name)).and_then(|profile| profile.role)
+    };
+    for (role, button) in &app_state.role_buttons {
+        button.set_active(active_role == Some(*role));
+    }
+    if let Some(tray) = &app_state.tray {
+        let roles = {
+            let settings = app_state.settings.lock().unwrap();
+            Role::ALL
+                .iter()
+                .filter_map(|role| Some((*role, settings.role_profile(*role)?.name.clone())))
+                .collect()
+        };
+        tray.set_profiles(names, active, roles);
+    }
+}
+
+// One click between the Killer and Survivor profiles. An empty slot is filled with the
+// checked servers, so the first click on each role sets it up.
+fn switch_role(app_state: &Rc<AppState>, window: &ApplicationWindow, role: Role) {
+    let profile = app_state
+        .settings
+        .lock()
+        .unwrap()
+        .role_profile(role)
+        .map(|profile| profile.name.clone());
+    let role_name = tr(role.label());
+    match profile {
+        Some(name) if !app_state.hosts_busy.get() => {
+            load_profile(app_state, &name);
+            app_state.launch_after_apply.set(false);
+            start_apply(app_state, window, Trigger::Window);
+        }
+        Some(_) => refresh_profiles(app_state),
+        None if app_state.selected_regions.borrow().is_empty() => {
+            refresh_profiles(app_state);
+            app_state.toast_overlay.add_toast(adw::Toast::new(&trf(
+                "Check the servers you want as {}, then click {} again to keep them.",
+                &[&role_name, &role_name],
+            )));
+        }
+        None => {
+            let mut selection: Vec<String> = app_state.selected_regions.borrow().iter().cloned().collect();
+            selection.sort();
+            update_settings(app_state, |settings| {
+                let profile = Profile {
+                    name: role_name.clone(),
+                    selection,
+                    apply_mode: settings.apply_mode,
+                    block_mode: settings.block_mode,
+                    merge_unstable: settings.merge_unstable,
+                    role: Some(role),
+                };
+                settings.save_profile(profile);
+                settings.active_profile = Some(role_name.clone());
+            });
+            refresh_profiles(app_state);
+            app_state.toast_overlay.add_toast(adw::Toast::new(&trf(
+                "Saved the checked servers as your {} profile. Click it again any time to switch back.",
+                &[&role_name],
+            )));
+        }
+    }
+}
+
+// Checks the profile's servers, switches to its method and makes it the active profile.
+// Nothing is written to the hosts file until the selection is applied.
+fn load_profile(app_state: &Rc<AppState>, name: &str) {
+    let profile = app_state.settings.lock().unwrap().profile(name).cloned();
+    let Some(profile) = profile else {
+        return;
+    };
+
+    let selection: HashSet<String> = profile
+        .selection
+        .iter()
+        .filter(|name| app_state.regions.contains_key(*name))
+        .cloned()
+        .collect();
+    set_checked_regions(app_state, &selection);
+    update_settings(app_state, |settings| {
+        settings.apply_mode = profile.apply_mode;
+        settings.block_mode = profile.block_mode;
+        settings.merge_unstable = profile.merge_unstable;
+        settings.active_profile = Some(profile.name.clone());
+    });
+    refresh_profiles(app_state);
+
+    app_state.toast_overlay.add_toast(
+        adw::Toast::builder()
+            .title(trf("Loaded profile \"{}\". Apply to use it.", &[&profile.name]))
+            .timeout(4)
+            .build(),
+    );
+}
+
+// Asks for a name, e.g. of a profile, prefilled with `initial`
+fn ask_name(
+    parent: &impl IsA<gtk4::Window>,
+    title: &str,
+    message: &str,
+    initial: &str,
+    placeholder: &str,
+    on_done: impl Fn(String) + 'static,
+) {
+    let dialog = MessageDialog::new(
+        Some(parent),
+        gtk4::DialogFlags::MODAL,
+        MessageType::Question,
+        ButtonsType::None,
+        title,
+    );
+    dialog.set_secondary_text(Some(message));
+    let name_entry = gtk4::Entry::builder()
+        .text(initial)
+        .placeholder_text(placeholder)
+        .activates_default(true)
+        .build();
+    if let Some(message_area) = dialog.message_area().downcast_ref::<GtkBox>() {
+        message_area.append(&name_entry);
+    }
+    dialog.add_button(&tr("Cancel"), ResponseType::Cancel);
+    dialog.add_button(&tr("Save"), ResponseType::Accept);
+    dialog.set_default_response(ResponseType::Accept);
+
+    dialog.run_async(move |dialog, response| {
+        dialog.close();
+        let name = name_entry.text().trim().to_string();
+        if response == ResponseType::Accept && !name.is_empty() {
+            on_done(name);
+        }
+    });
+}
+
+// Advanced: replaces a server's hostnames when the game moved it and the built-in list
+// is behind, see custom_hosts.rs
+fn show_custom_hostnames_dialog(window: &ApplicationWindow) {
+    let prefs = adw::PreferencesWindow::builder()
+        .title(tr("Server Hostnames"))
+        .transient_for(window)
+        .modal(true)
+        .default_width(560)
+        .default_height(600)
+        .build();
+
+    let page = adw::PreferencesPage::new();
+    let servers = adw::PreferencesGroup::builder()
+        .title(tr("Servers"))
+        .description(tr("Correct the hostnames of a server when the game moves it, until an update of Make Your Choice ships the fix. Changes are used after restarting Make Your Choice; apply your selection again then."))
+        .build();
+    let blocked = adw::PreferencesGroup::builder()
+        .title(tr("Always blocked"))
+        .description(tr("Regions the game doesn't use, blocked with every selection."))
+        .build();
+    page.add(&servers);
+    page.add(&blocked);
+    prefs.add(&page);
+
+    fill_hostnames_group(&prefs, &servers, Rc::new(region::builtin_selectable_regions()), Rc::new(RefCell::new(Vec::new())));
+    fill_hostnames_group(&prefs, &blocked, Rc::new(region::builtin_blocked_regions()), Rc::new(RefCell::new(Vec::new())));
+    prefs.present();
+}
+
+// (Re)creates one row per server with edit and reset buttons
+fn fill_hostnames_group(
+    prefs: &adw::PreferencesWindow,
+    group: &adw::PreferencesGroup,
+    builtin: Rc<HashMap<String, RegionInfo>>,
+    rows: Rc<RefCell<Vec<adw::ActionRow>>>,
+) {
+    for row in rows.borrow_mut().drain(..) {
+        group.remove(&row);
+    }
+
+    let custom = custom_hosts::load();
+    let mut names: Vec<&String> = builtin.keys().collect();
+    names.sort_by(|a, b| get_group_name(a).cmp(get_group_name(b)).then_with(|| a.cmp(b)));
+    for name in names {
+        let is_custom = custom.contains_key(name);
+        let hosts = custom.get(name).unwrap_or(&builtin[name].hosts).clone();
+        let row = adw::ActionRow::builder()
+            .title(name.as_str())
+            .subtitle(hosts.join("\n"))
+            .build();
+        if is_custom {
+            row.set_title(&trf("{} (custom)", &[name]));
+        }
+
+        // Writes the changed list, then rebuilds the rows
+        let save = {
+            let prefs = prefs.clone();
+            let group = group.clone();
+            let builtin = builtin.clone();
+            let rows = rows.clone();
+            let name = name.clone();
+            move |hosts: Option<Vec<String>>| {
+                let mut custom = custom_hosts::load();
+                match hosts.filter(|hosts| *hosts != builtin[&name].hosts) {
+                    Some(hosts) => custom.insert(name.clone(), hosts),
+                    None => custom.remove(&name),
+                };
+                match custom_hosts::save(&custom) {
+                    Ok(()) => {
+                        log_info!("regions", "Changed the hostnames of {}", name);
+                        restart_service();
+                        prefs.add_toast(adw::Toast::new(&tr("Restart Make Your Choice to use the changed hostnames.")));
+                        fill_hostnames_group(&prefs, &group, builtin.clone(), rows.clone());
+                    }
+                    Err(e) => prefs.add_toast(adw::Toast::new(&format!("{:#}", e))),
+                }
+            }
+        };
+
+        if is_custom {
+            let reset_button = Button::from_icon_name("edit-undo-symbolic");
+            reset_button.set_tooltip_text(Some(&tr("Use the built-in hostnames")));
+            reset_button.set_valign(gtk4::Align::Center);
+            reset_button.add_css_class("flat");
+            let save = save.clone();
+            reset_button.connect_clicked(move |_| save(None));
+            row.add_suffix(&reset_button);
+        }
+
+        let edit_button = Button::from_icon_name("document-edit-symbolic");
+        edit_button.set_tooltip_text(Some(&tr("Edit")));
+        edit_button.set_valign(gtk4::Align::Center);
+        edit_button.add_css_class("flat");
+        let prefs_clone = prefs.clone();
+        let name = name.clone();
+        edit_button.connect_clicked(move |_| {
+            let save = save.clone();
+            ask_hostnames(&prefs_clone, &name, &hosts, move |hosts| save(Some(hosts)));
+        });
+        row.add_suffix(&edit_button);
+
+        group.add(&row);
+        rows.borrow_mut().push(row);
+    }
+}
+
+// One hostname per line; asks again while a line isn't a hostname
+fn ask_hostnames(
+    parent: &adw::PreferencesWindow,
+    name: &str,
+    hosts: &[String],
+    on_done: impl Fn(Vec<String>) + 'static,
+) {
+    let dialog = MessageDialog::new(
+        Some(parent),
+        gtk4::DialogFlags::MODAL,
+        MessageType::Question,
+        ButtonsType::None,
+        name,
+    );
+    dialog.set_secondary_text(Some(&tr(
+        "One hostname per line, the service endpoint first and then the ping endpoint, e.g. gamelift.ap-east-1.amazonaws.com and gamelift-ping.ap-east-1.api.aws.",
+    )));
+    let buffer = gtk4::TextBuffer::new(None);
+    buffer.set_text(&hosts.join("\n"));
+    let text_view = gtk4::TextView::builder()
+        .buffer(&buffer)
+        .monospace(true)
+        .top_margin(6)
+        .bottom_margin(6)
+        .left_margin(6)
+        .right_margin(6)
+        .build();
+    let scrolled = ScrolledWindow::builder()
+        .child(&text_view)
+        .min_content_height(90)
+        .build();
+    let error_label = Label::builder()
+        .wrap(true)
+        .xalign(0.0)
+        .visible(false)
+        .css_classes(["error"])
+        .build();
+    if let Some(message_area) = dialog.message_area().downcast_ref::<GtkBox>() {
+        message_area.append(&scrolled);
+        message_area.append(&error_label);
+    }
+    dialog.add_button(&tr("Cancel"), ResponseType::Cancel);
+    dialog.add_button(&tr("Save"), ResponseType::Accept);
+    dialog.set_default_response(ResponseType::Accept);
+
+    dialog.connect_response(move |dialog, response| {
+        if response != ResponseType::Accept {
+            dialog.close();
+            return;
+        }
+        let text = buffer.text(&buffer.start_iter(), &buffer.end_iter(), false);
+        let hosts: Vec<String> = text
+            .lines()
+            .map(|line| line.trim().to_lowercase())
+            .filter(|line| !line.is_empty())
+            .collect();
+        let problem = if hosts.is_empty() {
+            Some(tr("Enter at least one hostname."))
+        } else {
+            hosts
+                .iter()
+                .find(|host| !custom_hosts::is_valid_hostname(host))
+                .map(|host| trf("\"{}\" is not a valid hostname.", &[host]))
+        };
+        match problem {
+            Some(problem) => {
+                error_label.set_text(&problem);
+                error_label.set_visible(true);
+            }
+            None => {
+                dialog.close();
+                on_done(hosts);
+            }
+        }
+    });
+    dialog.present();
+}
+
+fn show_save_profile_dialog(app_state: &Rc<AppState>, window: &ApplicationWindow) {
+    let active = app_state.settings.lock().unwrap().active_profile.clone();
+    let app_state = app_state.clone();
+    ask_name(
+        window,
+        &tr("Save Profile"),
+        &tr("Saves the checked servers together with the current method. Using an existing name replaces that profile."),
+        active.as_deref().unwrap_or(""),
+        &tr("e.g. Solo low-ping"),
+        move |name| {
+            let mut selection: Vec<String> =
+                app_state.selected_regions.borrow().iter().cloned().collect();
+            selection.sort();
+            update_settings(&app_state, |settings| {
+                let profile = Profile {
+                    name: name.clone(),
+                    selection,
+                    apply_mode: settings.apply_mode,
+                    block_mode: settings.block_mode,
+                    merge_unstable: settings.merge_unstable,
+                    role: None,
+                };
+                settings.save_profile(profile);
+                settings.active_profile = Some(name.clone());
+            });
+            refresh_profiles(&app_state);
+        },
+    );
+}
+
+fn show_manage_profiles_dialog(app_state: &Rc<AppState>, window: &ApplicationWindow) {
+    let prefs = adw::PreferencesWindow::builder()
+        .title(tr("Profiles"))
+        .transient_for(window)
+        .modal(true)
+        .search_enabled(false)
+        .default_width(420)
+        .default_height(460)
+        .build();
+
+    let group = adw::PreferencesGroup::builder()
+        .description(tr("Profiles remember the checked servers and the method. Load one from the list button next to Apply or from the tray."))
+        .build();
+    let page = adw::PreferencesPage::new();
+    page.add(&group);
+    prefs.add(&page);
+
+    fill_profiles_group(app_state, &prefs, &group, Rc::new(RefCell::new(Vec::new())));
+    prefs.present();
+}
+
+// (Re)creates one row per profile with rename, duplicate and delete buttons
+fn fill_profiles_group(
+    app_state: &Rc<AppState>,
+    prefs: &adw::PreferencesWindow,
+    group: &adw::PreferencesGroup,
+    rows: Rc<RefCell<Vec<adw::ActionRow>>>,
+) {
+    for row in rows.borrow_mut().drain(..) {
+        group.remove(&row);
+    }
+
+    let (profiles, active) = {
+        let settings = app_state.settings.lock().unwrap();
+        (settings.profiles.clone(), settings.active_profile.clone())
+    };
+    if profiles.is_empty() {
+        let row = adw::ActionRow::builder()
+            .title(tr("No profiles yet. Use \"Save selection as profile…\" to create one."))
+            .build();
+        group.add(&row);
+        rows.borrow_mut().push(row);
+        return;
+    }
+
+    for profile in profiles {
+        let method = match profile.apply_mode {
+            ApplyMode::Gatekeep => tr("Gatekeep"),
+            ApplyMode::UniversalRedirect => tr("Universal Redirect"),
+        };
+        let mut subtitle = trf("{} servers · {}", &[&profile.selection.len(), &method]);
+        if let Some(role) = profile.role {
+            subtitle = trf("{} · {}", &[&subtitle, &tr(role.label())]);
+        }
+        if active.as_deref() == Some(profile.name.as_str()) {
+            subtitle = trf("{} · active", &[&subtitle]);
+        }
+        let row = adw::ActionRow::builder()
+            .title(profile.name.as_str())
+            .subtitle(subtitle)
+            .build();
+
+        // Rebuilds the list and the switcher after a change
+        let refresh = {
+            let app_state = app_state.clone();
+            let prefs = prefs.clone();
+            let group = group.clone();
+            let rows = rows.clone();
+            move || {
+                refresh_profiles(&app_state);
+                fill_profiles_group(&app_state, &prefs, &group, rows.clone());
+            }
+        };
+
+        let rename_button = Button::from_icon_name("document-edit-symbolic");
+        rename_button.set_tooltip_text(Some(&tr("Rename")));
+        rename_button.set_valign(gtk4::Align::Center);
+        rename_button.add_css_class("flat");
+        let app_state_clone = app_state.clone();
+        let prefs_clone = prefs.clone();
+        let name = profile.name.clone();
+        let refresh_clone = refresh.clone();
+        rename_button.connect_clicked(move |_| {
+            let app_state = app_state_clone.clone();
+            let prefs = prefs_clone.clone();
+            let name = name.clone();
+            let refresh = refresh_clone.clone();
+            let message = trf("New name for \"{}\":", &[&name]);
+            let initial = name.clone();
+            ask_name(
+                &prefs_clone,
+                &tr("Rename Profile"),
+                &message,
+                &initial,
+                &tr("e.g. Solo low-ping"),
+                move |new_name| {
+                    let result = {
+                        let mut settings = app_state.settings.lock().unwrap();
+                        settings
+                            .rename_profile(&name, &new_name)
+                            .and_then(|_| settings.save())
+                    };
+                    match result {
+                        Ok(()) => refresh(),
+                        Err(e) => prefs.add_toast(adw::Toast::new(&e.to_string())),
+                    }
+                },
+            );
+        });
+
+        let duplicate_button = Button::from_icon_name("edit-copy-symbolic");
+        duplicate_button.set_tooltip_text(Some(&tr("Duplicate")));
+        duplicate_button.set_valign(gtk4::Align::Center);
+        duplicate_button.add_css_class("flat");
+        let app_state_clone = app_state.clone();
+        let name = profile.name.clone();
+        let refresh_clone = refresh.clone();
+        duplicate_button.connect_clicked(move |_| {
+            update_settings(&app_state_clone, |settings| {
+                settings.duplicate_profile(&name);
+            });
+            refresh_clone();
+        });
+
+        // Which slot of the Killer/Survivor switch the profile fills
+        let role_names: Vec<String> = std::iter::once(tr("No role"))
+            .chain(Role::ALL.iter().map(|role| tr(role.label())))
+            .collect();
+        let role_names: Vec<&str> = role_names.iter().map(String::as_str).collect();
+        let role_dropdown = gtk4::DropDown::from_strings(&role_names);
+        role_dropdown.set_tooltip_text(Some(&tr("Role")));
+        role_dropdown.set_valign(gtk4::Align::Center);
+        role_dropdown.set_selected(
+            profile
+                .role
+                .and_then(|role| Role::ALL.iter().position(|r| *r == role))
+                .map_or(0, |i| i as u32 + 1),
+        );
+        let app_state_clone = app_state.clone();
+        let name = profile.name.clone();
+        let refresh_clone = refresh.clone();
+        role_dropdown.connect_selected_notify(move |dropdown| {
+            let role = (dropdown.selected() as usize)
+                .checked_sub(1)
+                .and_then(|i| Role::ALL.get(i).copied());
+            update_settings(&app_state_clone, |settings| settings.set_profile_role(&name, role));
+            // Another profile may have lost the role, rebuilt once this handler returned
+            glib::idle_add_local_once(refresh_clone.clone());
+        });
+
+        let delete_button = Button::from_icon_name("user-trash-symbolic");
+        delete_button.set_tooltip_text(Some(&tr("Delete")));
+        delete_button.set_valign(gtk4::Align::Center);
+        delete_button.add_css_class("flat");
+        let app_state_clone = app_state.clone();
+        let name = profile.name.clone();
+        delete_button.connect_clicked(move |_| {
+            update_settings(&app_state_clone, |settings| settings.delete_profile(&name));
+            refresh();
+        });
+
+        row.add_suffix(&role_dropdown);
+        row.add_suffix(&rename_button);
+        row.add_suffix(&duplicate_button);
+        row.add_suffix(&delete_button);
+        group.add(&row);
+        rows.borrow_mut().push(row);
+    }
+}
+
+fn set_checked_regions(app_state: &Rc<AppState>, selection: &HashSet<String>) {
+    for row in region_rows(&app_state.list_store) {
+        row.set_selected(selection.contains(&row.name()));
+    }
+}
+
+fn handle_revert_click(app_state: &Rc<AppState>, window: &ApplicationWindow, trigger: Trigger) {
+    let app_state_clone = app_state.clone();
+    let window = window.clone();
+    run_hosts_task(
+        app_state,
+        |backend, _| async move {
+            let snapshot = backend.snapshot().await.ok();
+            backend.revert().await.map(|_| snapshot)
+        },
+        move |result| match result {
+            Ok(snapshot) => {
+                hooks::spawn(hooks::Event::Revert, serde_json::json!({}));
+                history::record_revert(app_state_clone.backend.id(), trigger);
+                report_hosts_change(
+                    &app_state_clone,
+                    &window,
+                    &tr("Cleared Make Your Choice entries. Your existing hosts lines were left untouched."),
+                    snapshot,
+                    HashSet::new(),
+                );
+            }
+            Err(e) => {
+                report_hosts_error(&app_state_clone, &window, &e);
+            }
+        },
+    );
+}
+
+// A section written in an earlier session is shown checked, so the unsaved changes dot
+// compares against what the hosts file really holds. The last applied selection is used
+// when it still matches, Gatekeep's allowed servers include the merged alternatives.
+fn restore_applied_selection(app_state: &Rc<AppState>) {
+    let last = app_state.settings.lock().unwrap().last_applied.clone();
+    let applied: HashSet<String> = match app_state.backend.status(&app_state.regions) {
+        ActiveConfiguration::Inactive => return,
+        ActiveConfiguration::Gatekeep { allowed } => match last {
+            Some(last)
+                if last.apply_mode == ApplyMode::Gatekeep
+                    && !last.selection.is_empty()
+                    && last.selection.iter().all(|name| allowed.contains(name)) =>
+            {
+                last.selection.into_iter().collect()
+            }
+            _ => allowed.into_iter().collect(),
+        },
+        ActiveConfiguration::UniversalRedirect { .. } => match last {
+            Some(last) if last.apply_mode == ApplyMode::UniversalRedirect => last.selection.into_iter().collect(),
+            _ => return,
+        },
+    };
+    let applied: HashSet<String> = applied.into_iter().filter(|name| app_state.regions.contains_key(name)).collect();
+    app_state.applied_selection.replace(applied.clone());
+    set_checked_regions(app_state, &applied);
+    refresh_unsaved_indicator(app_state);
+}
+
+fn has_unsaved_changes(app_state: &AppState) -> bool {
+    *app_state.selected_regions.borrow() != *app_state.applied_selection.borrow()
+}
+
+// Flags checkbox changes that haven't been written to the hosts file yet
+fn refresh_unsaved_indicator(app_state: &AppState) {
+    let dirty = has_unsaved_changes(app_state);
+    if dirty {
+        app_state.apply_button.set_label(&tr("Apply Selection •"));
+        app_state
+            .apply_button
+            .set_tooltip_text(Some(&tr("Your selection has changes that haven't been applied yet.")));
+        app_state.window_title.set_title("*Make Your Choice");
+    } else {
+        app_state.apply_button.set_label(&tr("Apply Selection"));
+        app_state.apply_button.set_tooltip_text(None);
+        app_state.window_title.set_title(&tr("Make Your Choice"));
+    }
+}
+
+// Summarizes what the managed hosts section enforces right now
+fn refresh_active_banner(app_state: &AppState) {
+    let banner = &app_state.active_banner;
+    let applied_ago = app_state
+        .hosts_manager
+        .last_modified()
+        .and_then(|modified| modified.elapsed().ok())
+        .map(format_elapsed)
+        .unwrap_or_default();
+
+    match app_state.backend.status(&app_state.regions) {
+        ActiveConfiguration::Inactive => {
+            banner.set_title(&tr("No Make Your Choice entries active"));
+            banner.set_button_label(None);
+        }
+        ActiveConfiguration::Gatekeep { allowed } => {
+            let regions = if allowed.len() > 3 {
+                trf("{} + {} more", &[&allowed[..3].join(" + "), &(allowed.len() - 3)])
+            } else if allowed.is_empty() {
+                tr("all servers blocked")
+            } else {
+                allowed.join(" + ")
+            };
+            let scope = match app_state.settings.lock().unwrap().block_mode {
+                BlockMode::Both => String::new(),
+                BlockMode::OnlyPing => tr(" (ping only)"),
+                BlockMode::OnlyService => tr(" (service only)"),
+            };
+            banner.set_title(&trf("Gatekeep{}: {}, applied {}", &[&scope, &regions, &applied_ago]));
+            banner.set_button_label(Some(&tr("Revert")));
+        }
+        ActiveConfiguration::UniversalRedirect { target_ip } => {
+            banner.set_title(&trf(
+                "Universal Redirect to {}, applied {}",
+                &[&target_ip, &applied_ago],
+            ));
+            banner.set_button_label(Some(&tr("Revert")));
+        }
+    }
+}
+
+fn refresh_status_bar(app_state: &AppState) {
+    let status = &app_state.status_bar;
+    let active = !matches!(
+        app_state.backend.status(&app_state.regions),
+        ActiveConfiguration::Inactive
+    );
+    status.section.set_label(&if active {
+        tr("Hosts section present")
+    } else {
+        tr("No hosts section")
+    });
+
+    // Blocked regions count too, applying refuses to go on with those conflicts as well
+    let conflicts = app_state
+        .backend
+        .detect_conflicts(&get_all_regions_map(&app_state.regions, &app_state.blocked_regions))
+        .unwrap_or_default();
+    status.conflicts.set_visible(!conflicts.is_empty());
+    status.conflicts.set_label(&trf("⚠ {} conflicts", &[&conflicts.len()]));
+
+    let applied_ago = app_state
+        .hosts_manager
+        .last_modified()
+        .and_then(|modified| modified.elapsed().ok())
+        .map(format_elapsed);
+    status.applied.set_label(&match applied_ago {
+        Some(ago) if active => trf("Applied {}", &[&ago]),
+        _ => tr("Not applied"),
+    });
+}
+
+fn refresh_ping_status(app_state: &AppState) {
+    let text = if app_state.ping_in_flight.get() {
+        tr("Pinging servers…")
+    } else {
+        let interval = app_state.ping_scheduler.borrow().interval();
+        trf("Pings every {} s", &[&interval.as_secs()])
+    };
+    app_state.status_bar.pings.set_label(&text);
+}
+
+// Offers to remove lines outside the managed section that override the servers it manages
+fn show_resolve_conflicts_dialog(app_state: &Rc<AppState>, window: &ApplicationWindow) {
+    let all_regions = get_all_regions_map(&app_state.regions, &app_state.blocked_regions);
+    let conflicts = match app_state.backend.detect_conflicts(&all_regions) {
+        Ok(conflicts) if !conflicts.is_empty() => conflicts,
+        Ok(_) => {
+            refresh_status_bar(app_state);
+            return;
+        }
+        Err(e) => {
+            show_error_dialog(window, &tr("Error"), &trf("Failed to check for conflicts:\n{}", &[&error_details(&e)]));
+            return;
+        }
+    };
+
+    let dialog = MessageDialog::new(
+        Some(window),
+        gtk4::DialogFlags::MODAL,
+        MessageType::Warning,
+        ButtonsType::None,
+        tr("Conflicting Hosts Entries Detected"),
+    );
+    dialog.set_secondary_text(Some(&trf(
+        "These lines outside the Make Your Choice section override servers it manages:\n\n{}\n\nRemove them?",
+        &[&conflicts.join("\n")],
+    )));
+    dialog.add_button(&tr("Cancel"), ResponseType::Cancel);
+    dialog.add_button(&tr("Remove Conflicts"), ResponseType::Accept);
+
+    let app_state = app_state.clone();
+    let window = window.clone();
+    dialog.run_async(move |dialog, response| {
+        dialog.close();
+        if response != ResponseType::Accept {
+            return;
+        }
+
+        let app_state_clone = app_state.clone();
+        let window = window.clone();
+        let hosts = app_state.hosts_manager.clone();
+        run_hosts_task(
+            &app_state,
+            move |backend, _| async move {
+                let snapshot = backend.snapshot().await.ok();
+                hosts.clear_conflicting_entries(&conflicts).await.map(|_| snapshot)
+            },
+            move |result| match result {
+                Ok(snapshot) => {
+                    let applied = app_state_clone.applied_selection.borrow().clone();
+                    report_hosts_change(
+                        &app_state_clone,
+                        &window,
+                        &tr("Removed conflicting hosts entries."),
+                        snapshot,
+                        applied,
+                    );
+                }
+                Err(e) => report_hosts_error(&app_state_clone, &window, &e),
+            },
+        );
+    });
+}
+
+fn format_elapsed(elapsed: Duration) -> String {
+    let minutes = elapsed.as_secs() / 60;
+    match minutes {
+        0 => tr("just now"),
+        1..=59 => trf("{} min ago", &[&minutes]),
+        60..=1439 => trf("{} h ago", &[&(minutes / 60)]),
+        _ => trf("{} d ago", &[&(minutes / 1440)]),
+    }
+}
+
+// Runs a hosts file operation on the tokio runtime so slow DNS or disk access doesn't
+// freeze the window. Apply and revert stay disabled until `on_done` receives the result.
+// Runs the task on the tokio runtime; the steps it reports show up next to the spinner.
+// Cancel drops it, on_done is skipped then.
+fn run_hosts_task<T, F>(
+    app_state: &Rc<AppState>,
+    task: impl FnOnce(Arc<dyn EnforcementBackend>, tokio::sync::mpsc::UnboundedSender<Progress>) -> F,
+    on_done: impl FnOnce(anyhow::Result<T>) + 'static,
+) where
+    T: Send + 'static,
+    F: std::future::Future<Output = anyhow::Result<T>> + Send + 'static,
+{
+    if app_state.hosts_busy.replace(true) {
+        return;
+    }
+    set_hosts_busy(app_state, true);
+
+    let (progress, mut steps) = tokio::sync::mpsc::unbounded_channel();
+    let handle = app_state.tokio_runtime.spawn(task(app_state.backend.clone(), progress));
+    app_state.hosts_task.replace(Some(handle.abort_handle()));
+
+    let app_state_clone = app_state.clone();
+    glib::spawn_future_local(async move {
+        while let Some(step) = steps.recv().await {
+            let text = match step {
+                Progress::Resolving(host) => trf("Resolving {}", &[&host]),
+                Progress::Writing => {
+                    // Too late to stop, the file is being replaced
+                    app_state_clone.hosts_task.take();
+                    app_state_clone.hosts_cancel.set_sensitive(false);
+                    tr("Writing the hosts file")
+                }
+            };
+            app_state_clone.hosts_spinner.set_tooltip_text(Some(&text));
+        }
+    });
+
+    let app_state = app_state.clone();
+    glib::spawn_future_local(async move {
+        let result = handle.await;
+        app_state.hosts_task.take();
+        app_state.hosts_busy.set(false);
+        set_hosts_busy(&app_state, false);
+
+        let result = match result {
+            Ok(result) => result,
+            Err(e) if e.is_cancelled() => {
+                log_info!("hosts", "Cancelled");
+                app_state.launch_after_apply.set(false);
+                app_state.toast_overlay.add_toast(adw::Toast::new(&tr("Cancelled, nothing was changed")));
+                return;
+            }
+            Err(e) => Err(anyhow::anyhow!("Hosts file task failed: {}", e)),
+        };
+        if let Err(e) = &result {
+            log_error!("hosts", "{:#}", e);
+        }
+        on_done(result);
+    });
+}
+
+fn set_hosts_busy(app_state: &AppState, busy: bool) {
+    app_state.apply_button.set_sensitive(!busy);
+    app_state.launch_button.set_sensitive(!busy);
+    app_state.best_button.set_sensitive(!busy);
+    app_state.revert_button.set_sensitive(!busy);
+    app_state.hosts_spinner.set_visible(busy);
+    app_state.hosts_spinner.set_spinning(busy);
+    app_state.hosts_spinner.set_tooltip_text(None);
+    app_state.hosts_cancel.set_visible(busy);
+    app_state.hosts_cancel.set_sensitive(busy);
+}
+
+// Records the newly enforced selection and reports the change without blocking;
+// Undo writes the previous file back
+fn report_hosts_change(
+    app_state: &Rc<AppState>,
+    window: &ApplicationWindow,
+    title: &str,
+    snapshot: Option<String>,
+    applied: HashSet<String>,
+) {
+    log_info!("hosts", "{}", title);
+    emit_hosts_changed(window, &applied);
+    let previous_applied = app_state.applied_selection.replace(applied);
+    refresh_unsaved_indicator(app_state);
+    refresh_active_banner(app_state);
+    refresh_status_bar(app_state);
+    refresh_region_addresses(app_state);
+
+    let toast = adw::Toast::builder().title(title).timeout(6).build();
+
+    if let Some(snapshot) = snapshot {
+        toast.set_button_label(Some(&tr("Undo")));
+        let app_state = app_state.clone();
+        let window = window.clone();
+        toast.connect_button_clicked(move |_| {
+            let snapshot = snapshot.clone();
+            let previous_applied = previous_applied.clone();
+            let app_state_clone = app_state.clone();
+            let window = window.clone();
+            run_hosts_task(
+                &app_state,
+                move |backend, _| async move { backend.restore(snapshot).await },
+                move |result| match result {
+                    Ok(_) => {
+                        emit_hosts_changed(&window, &previous_applied);
+                        *app_state_clone.applied_selection.borrow_mut() = previous_applied;
+                        refresh_unsaved_indicator(&app_state_clone);
+                        refresh_active_banner(&app_state_clone);
+                        refresh_status_bar(&app_state_clone);
+                        refresh_region_addresses(&app_state_clone);
+                        app_state_clone
+                            .toast_overlay
+                            .add_toast(adw::Toast::new(&tr("Previous hosts file restored")));
+                    }
+                    Err(e) => show_error_dialog(&window, &tr("Error"), &trf("Failed to undo:\n{}", &[&error_details(&e)])),
+                },
+            );
+        });
+    }
+
+    app_state.toast_overlay.add_toast(toast);
+
+    // Changes made from the tray or while playing would go unnoticed as a toast
+    if !window.is_active() {
+        send_desktop_notification(app_state, window, NotificationKind::ApplyResult, "hosts-change", &tr("Make Your Choice"), title);
+    }
+}
+
+fn emit_hosts_changed(window: &ApplicationWindow, applied: &HashSet<String>) {
+    if let Some(connection) = window.application().and_then(|app| app.dbus_connection()) {
+        let mut applied: Vec<String> = applied.iter().cloned().collect();
+        applied.sort();
+        dbus::emit_hosts_changed(&connection, &applied);
+    }
+}
+
+// Lets other programs on the session bus apply, revert and read the state, see dbus.rs
+fn export_dbus_interface(app_state: &Rc<AppState>, window: &ApplicationWindow) {
+    let Some(connection) = window.application().and_then(|app| app.dbus_connection()) else {
+        return;
+    };
+    let app_state = app_state.clone();
+    let window_clone = window.clone();
+    let result = dbus::export(&connection, move |command| {
+        std::future::ready(handle_control_command(&app_state, &window_clone, command, Trigger::Remote))
+    });
+    if let Err(e) = result {
+        log_warn!("dbus", "{:#}", e);
+    }
+}
+
+// Answers the local HTTP API, whose requests arrive from the tokio runtime
+fn answer_control_requests(
+    app_state: &Rc<AppState>,
+    window: &ApplicationWindow,
+    mut requests: tokio::sync::mpsc::UnboundedReceiver<control::ControlRequest>,
+) {
+    let app_state = app_state.clone();
+    let window = window.clone();
+    glib::spawn_future_local(async move {
+        while let Some((command, reply)) = requests.recv().await {
+            let _ = reply.send(handle_control_command(&app_state, &window, command, Trigger::Remote));
+        }
+    });
+}
+
+// The service reads its settings once, when it starts
+fn restart_service() {
+    if let Err(e) = service::restart() {
+        log_warn!("service", "{:#}", e);
+    }
+}
+
+// Starts, stops or re-keys the local HTTP API to match the settings
+fn refresh_http_api(app_state: &AppState) {
+    let mut config = app_state.settings.lock().unwrap().http_api.clone();
+    let mut server = app_state.api_server.borrow_mut();
+    if !config.enabled {
+        *server = None;
+        return;
+    }
+    if config.token.is_empty() {
+        config.token = api::new_token();
+        let mut settings = app_state.settings.lock().unwrap();
+        settings.http_api.token = config.token.clone();
+        if let Err(e) = settings.save() {
+            log_warn!("api", "Failed to save the new token: {:#}", e);
+        }
+    }
+    if let Some(running) = server.as_ref().filter(|running| running.port() == config.port) {
+        running.set_token(&config.token);
+        return;
+    }
+
+    // Closed first in case it is being restarted on another port
+    *server = None;
+    match api::start(&app_state.tokio_runtime, config.port, &config.token, app_state.control_requests.clone()) {
+        Ok(started) => *server = Some(started),
+        Err(e) => {
+            log_error!("api", "{:#}", e);
+            app_state
+                .toast_overlay
+                .add_toast(adw::Toast::new(&trf("The local HTTP API could not start: {}", &[&format!("{:#}", e)])));
+        }
+    }
+}
+
+// Requests from D-Bus and the local HTTP API. Apply and revert start the same steps as
+// the buttons and return right away, the outcome follows as a HostsChanged signal.
+fn handle_control_command(
+    app_state: &Rc<AppState>,
+    window: &ApplicationWindow,
+    command: control::Command,
+    trigger: Trigger,
+) -> control::Result {
+    use control::{Command, Reply};
+    match command {
+        Command::ApplyProfile(name) => {
+            if app_state.settings.lock().unwrap().profile(&name).is_none() {
+                return Err(control::Error::new(
+                    ErrorKind::InvalidSelection,
+                    trf("There is no profile named \"{}\"", &[&name]),
+                ));
+            }
+            load_profile(app_state, &name);
+            start_apply(app_state, window, trigger);
+            Ok(Reply::Done)
+        }
+        Command::ApplySelection(queries, mode) => {
+            let mode = match mode.as_str() {
+                "" => None,
+                id => Some(ApplyMode::from_id(id).ok_or_else(|| {
+                    control::Error::new(ErrorKind::InvalidSelection, trf("Unknown mode \"{}\"", &[&id]))
+                })?),
+            };
+            let mut selection = HashSet::new();
+            for query in &queries {
+                let name = find_region(&app_state.regions, query).ok_or_else(|| {
+                    control::Error::new(ErrorKind::InvalidSelection, trf("Unknown server \"{}\"", &[query]))
+                })?;
+                selection.insert(name);
+            }
+            if selection.is_empty() {
+                return Err(control::Error::new(
+                    ErrorKind::InvalidSelection,
+                    tr("Please select at least one server to allow."),
+                ));
+            }
+            if let Some(mode) = mode {
+                update_settings(app_state, |settings| settings.apply_mode = mode);
+            }
+            set_checked_regions(app_state, &selection);
+            start_apply(app_state, window, trigger);
+            Ok(Reply::Done)
+        }
+        Command::Revert => {
+            handle_revert_click(app_state, window, trigger);
+            Ok(Reply::Done)
+        }
+        Command::GetStatus => {
+            let active = app_state.backend.status(&app_state.regions);
+            let mode = active.mode_id();
+            let (allowed, target_ip) = match active {
+                ActiveConfiguration::Inactive => (Vec::new(), None),
+                ActiveConfiguration::Gatekeep { allowed } => (allowed, None),
+                ActiveConfiguration::UniversalRedirect { target_ip } => (Vec::new(), Some(target_ip)),
+            };
+            Ok(Reply::Status { mode, allowed, target_ip })
+        }
+        Command::GetLatencies => Ok(Reply::Latencies(app_state.latencies.borrow().clone())),
+        Command::GetCurrentServer => Ok(Reply::CurrentServer(app_state.last_seen.lock().unwrap().clone())),
+        Command::Notify(title, body) => {
+            send_desktop_notification(app_state, window, NotificationKind::Other, "plugin", &title, &body);
+            Ok(Reply::Done)
+        }
+    }
+}
+
+// Changes start like D-Bus requests and report their outcome the same way. A link that
+// can't be used is reported as a notification, the browser or launcher may be in front.
+// Any web page or message can hold a link, so changes are confirmed in the window first.
+fn open_link(app_state: &Rc<AppState>, window: &ApplicationWindow, link: &str) {
+    log_info!("uri", "Opening {}", link);
+    let request = match uri::parse(link) {
+        Ok(uri::Request::Show) => {
+            window.present();
+            return;
+        }
+        Ok(request) => request,
+        Err(message) => {
+            report_link_error(app_state, window, link, &message);
+            return;
+        }
+    };
+
+    let change = match &request {
+        uri::Request::ApplyLast => tr("Apply the last applied servers again."),
+        uri::Request::Control(control::Command::ApplyProfile(name)) => trf("Apply the profile \"{}\".", &[name]),
+        uri::Request::Control(control::Command::ApplySelection(regions, mode)) if mode.is_empty() => {
+            trf("Allow only these servers: {}", &[&regions.join(", ")])
+        }
+        uri::Request::Control(control::Command::ApplySelection(regions, mode)) => {
+            trf("Allow only these servers: {}\nMethod: {}", &[&regions.join(", "), mode])
+        }
+        uri::Request::Control(control::Command::Revert) => tr("Remove the Make Your Choice entries from the hosts file."),
+        _ => String::new(),
+    };
+
+    window.present();
+    let dialog = MessageDialog::new(
+        Some(window),
+        gtk4::DialogFlags::MODAL,
+        MessageType::Question,
+        ButtonsType::None,
+        tr("A link wants to change your hosts file"),
+    );
+    dialog.set_secondary_text(Some(&trf("{}\n\nOnly continue if you opened this link yourself.", &[&change])));
+    dialog.add_button(&tr("Cancel"), ResponseType::Cancel);
+    dialog.add_button(&tr("Continue"), ResponseType::Accept);
+    dialog.set_default_response(ResponseType::Cancel);
+
+    let app_state = app_state.clone();
+    let window = window.clone();
+    let link = link.to_string();
+    dialog.run_async(move |dialog, response| {
+        dialog.close();
+        if response != ResponseType::Accept {
+            log_info!("uri", "Declined {}", link);
+            return;
+        }
+        let result = match request {
+            uri::Request::Control(command) => handle_control_command(&app_state, &window, command, Trigger::Link)
+                .map(|_| ())
+                .map_err(|e| e.message),
+            _ => {
+                if !app_state.hosts_busy.get() {
+                    apply_last_selection(&app_state, &window, Trigger::Link);
+                }
+                Ok(())
+            }
+        };
+        if let Err(message) = result {
+            report_link_error(&app_state, &window, &link, &message);
+        }
+    });
+}
+
+fn report_link_error(app_state: &AppState, window: &ApplicationWindow, link: &str, message: &str) {
+    log_warn!("uri", "Failed to open {}: {}", link, message);
+    send_desktop_notification(app_state, window, NotificationKind::Error, "link", &tr("The link could not be used"), message);
+}
+
+// A dialog could open behind the game when the change wasn't started from the window,
+// so unfocused failures are reported as a desktop notification instead
+fn report_hosts_error(app_state: &AppState, window: &ApplicationWindow, error: &anyhow::Error) {
+    if window.is_active() {
+        show_error_dialog(window, &tr("Error"), &error_details(error));
+    } else {
+        send_desktop_notification(
+            app_state,
+            window,
+            NotificationKind::Error,
+            "hosts-change",
+            &tr("Failed to update the hosts file"),
+            &error_details(error),
+        );
+    }
+}
+
+// What went wrong and what to do about it in the user's language when errors.rs knows
+// the kind, then the message itself as the details
+fn error_details(error: &anyhow::Error) -> String {
+    let kind = errors::classify(error);
+    if kind == ErrorKind::Failed {
+        return format!("{:#}", error);
+    }
+    let mut details = tr(kind.summary());
+    if let Some(hint) = kind.remediation() {
+        details.push_str("\n\n");
+        details.push_str(&tr(hint));
+    }
+    details.push_str("\n\n");
+    details.push_str(&trf("Details: {}", &[&format!("{:#}", error)]));
+    details
+}
+
+// A newer notification with the same id replaces the previous one, e.g. one id for all
+// hosts changes
+fn send_desktop_notification(
+    app_state: &AppState,
+    window: &ApplicationWindow,
+    kind: NotificationKind,
+    id: &str,
+    title: &str,
+    body: &str,
+) {
+    let Some(app) = window.application() else {
+        return;
+    };
+    let preferences = app_state.settings.lock().unwrap().notifications.clone();
+    post_notification(&app, &preferences, kind, id, title, body);
+}
+
+// Unless the notification preferences turn this kind off or it is quiet hours
+fn post_notification(
+    app: &impl IsA<gio::Application>,
+    preferences: &NotificationSettings,
+    kind: NotificationKind,
+    id: &str,
+    title: &str,
+    body: &str,
+) {
+    if !preferences.allows_now(kind) {
+        return;
+    }
+    let notification = gio::Notification::new(title);
+    notification.set_body(Some(body));
+    notification.set_icon(&gio::ThemedIcon::new("make-your-choice"));
+    app.send_notification(Some(id), &notification);
+    if preferences.sound {
+        play_notification_sound();
+    }
+}
+
+// GNotification can't ask for a sound, so the desktop's message sound is played through
+// canberra-gtk-play where it's installed and the display bell rings otherwise
+fn play_notification_sound() {
+    let child = std::process::Command::new("canberra-gtk-play")
+        .args(["--id", "message-new-instant", "--description", "Make Your Choice"])
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .spawn();
+    match child {
+        Ok(mut child) => {
+            std::thread::spawn(move || child.wait());
+        }
+        Err(_) => {
+            if let Some(display) = gtk4::gdk::Display::default() {
+                display.beep();
+            }
+        }
+    }
+}
+
+fn show_help_window(app_state: &Rc<AppState>, parent: &ApplicationWindow) {
+    let help_window = adw::Window::builder()
+        .title(tr("Help"))
+        .transient_for(parent)
+        .default_width(480)
+        .default_height(560)
+        .build();
+
+    let search_entry = gtk4::SearchEntry::builder()
+        .placeholder_text(tr("Search help"))
+        .build();
+    let header_bar = adw::HeaderBar::new();
+    header_bar.set_title_widget(Some(&search_entry));
+
+    let page = adw::PreferencesPage::new();
+
+    // Each row remembers its lowercase text for searching
+    let mut groups: Vec<(adw::PreferencesGroup, Vec<(adw::ExpanderRow, String)>)> = Vec::new();
+    for topic in help::topics() {
+        let group = adw::PreferencesGroup::builder().title(&topic.title).build();
+        let mut rows = Vec::new();
+        for entry in topic.entries {
+            let row = adw::ExpanderRow::builder()
+                .title(&entry.question)
+                .build();
+            let answer = Label::builder()
+                .label(help::to_pango(&entry.answer))
+                .use_markup(true)
+                .wrap(true)
+                .xalign(0.0)
+                .selectable(true)
+                .margin_start(12)
+                .margin_end(12)
+                .margin_top(12)
+                .margin_bottom(12)
+                .build();
+            row.add_row(&answer);
+            group.add(&row);
+            rows.push((row, format!("{}\n{}", entry.question, entry.answer).to_lowercase()));
+        }
+        page.add(&group);
+        groups.push((group, rows));
+    }
+
+    // Still stuck: point to Discord for people who need more than the FAQ
+    let support_group = adw::PreferencesGroup::new();
+    let discord_row = adw::ActionRow::builder()
+        .title(tr("Still need help?"))
+        .subtitle(tr("Ask on the Discord server."))
+        .activatable(true)
+        .build();
+    discord_row.add_suffix(&Image::from_icon_name("adw-external-link-symbolic"));
+    let discord_url = app_state.config.discord_url.clone();
+    discord_row.connect_activated(move |_| open_url(&discord_url));
+    support_group.add(&discord_row);
+    page.add(&support_group);
+
+    let no_results = adw::StatusPage::builder()
+        .icon_name("system-search-symbolic")
+        .title(tr("No results"))
+        .visible(false)
+        .vexpand(true)
+        .build();
+
+    let content = GtkBox::new(Orientation::Vertical, 0);
+    page.set_vexpand(true);
+    content.append(&page);
+    content.append(&no_results);
+
+    search_entry.connect_search_changed(move |entry| {
+        let query = entry.text().trim().to_lowercase();
+        let mut any_visible = false;
+        for (group, rows) in &groups {
+            let mut group_visible = false;
+            for (row, text) in rows {
+                let matches = query.is_empty() || text.contains(&query);
+                row.set_visible(matches);
+                row.set_expanded(matches && !query.is_empty());
+                group_visible |= matches;
+            }
+            group.set_visible(group_visible);
+            any_visible |= group_visible;
+        }
+        page.set_visible(any_visible);
+        no_results.set_visible(!any_visible);
+    });
+
+    let toolbar_view = adw::ToolbarView::new();
+    toolbar_view.add_top_bar(&header_bar);
+    toolbar_view.set_content(Some(&content));
+    help_window.set_content(Some(&toolbar_view));
+    help_window.present();
+}
+
+// Read-only view of /etc/hosts with the managed section highlighted and conflicts flagged,
+// so users can copy it for support instead of digging the file up themselves
+fn show_hosts_viewer(app_state: &Rc<AppState>, parent: &ApplicationWindow) {
+    let viewer = adw::Window::builder()
+        .title(tr("Hosts File"))
+        .transient_for(parent)
+        .default_width(640)
+        .default_height(520)
+        .build();
+
+    let managed_tag = gtk4::TextTag::builder()
+        .paragraph_background("rgba(53, 132, 228, 0.18)")
+        .build();
+    let conflict_tag = gtk4::TextTag::builder()
+        .paragraph_background("rgba(224, 27, 36, 0.22)")
+        .weight(700)
+        .build();
+    let tag_table = gtk4::TextTagTable::new();
+    tag_table.add(&managed_tag);
+    tag_table.add(&conflict_tag);
+    let buffer = gtk4::TextBuffer::new(Some(&tag_table));
+
+    let text_view = gtk4::TextView::builder()
+        .buffer(&buffer)
+        .editable(false)
+        .cursor_visible(false)
+        .monospace(true)
+        .top_margin(8)
+        .bottom_margin(8)
+        .left_margin(8)
+        .right_margin(8)
+        .build();
+    let scrolled = ScrolledWindow::builder()
+        .child(&text_view)
+        .vexpand(true)
+        .build();
+
+    let legend = Label::builder()
+        .wrap(true)
+        .xalign(0.0)
+        .margin_start(12)
+        .margin_end(12)
+        .margin_top(6)
+        .margin_bottom(6)
+        .css_classes(["dim-label"])
+        .build();
+
+    let load = {
+        let app_state = app_state.clone();
+        let buffer = buffer.clone();
+        let legend = legend.clone();
+        move || {
+            buffer.set_text("");
+            let lines = match app_state.hosts_manager.annotated_lines(&app_state.regions) {
+                Ok(lines) => lines,
+                Err(e) => {
+                    legend.set_label(&trf("Failed to read the hosts file:\n{}", &[&e]));
+                    return;
+                }
+            };
+
+            let mut conflicts = 0;
+            for (line, kind) in &lines {
+                let mut end = buffer.end_iter();
+                match kind {
+                    HostsLineKind::Managed => {
+                        buffer.insert_with_tags(&mut end, &format!("{}\n", line), &[&managed_tag]);
+                    }
+                    HostsLineKind::Conflict => {
+                        conflicts += 1;
+                        buffer.insert_with_tags(&mut end, &format!("{}  ⚠\n", line), &[&conflict_tag]);
+                    }
+                    HostsLineKind::Other => buffer.insert(&mut end, &format!("{}\n", line)),
+                }
+            }
+
+            let mut legend_text = tr("Highlighted lines belong to the Make Your Choice section.");
+            if conflicts > 0 {
+                legend_text.push(' ');
+                legend_text.push_str(&trf(
+                    "{} lines marked with ⚠ override servers it manages.",
+                    &[&conflicts],
+                ));
+            }
+            legend.set_label(&legend_text);
+        }
+    };
+    load();
+
+    let refresh_button = Button::from_icon_name("view-refresh-symbolic");
+    refresh_button.set_tooltip_text(Some(&tr("Reload")));
+    refresh_button.connect_clicked(move |_| load());
+
+    let copy_button = Button::with_label(&tr("Copy"));
+    copy_button.set_tooltip_text(Some(&tr("Copy the whole file to the clipboard")));
+    let viewer_clone = viewer.clone();
+    copy_button.connect_clicked(move |_| {
+        let (start, end) = buffer.bounds();
+        let text = buffer.text(&start, &end, false).replace("  ⚠", "");
+        viewer_clone.clipboard().set_text(&text);
+    });
+
+    let header_bar = adw::HeaderBar::new();
+    header_bar.pack_start(&refresh_button);
+    header_bar.pack_end(&copy_button);
+
+    let content = GtkBox::new(Orientation::Vertical, 0);
+    content.append(&legend);
+    content.append(&scrolled);
+
+    let toolbar_view = adw::ToolbarView::new();
+    toolbar_view.add_top_bar(&header_bar);
+    toolbar_view.set_content(Some(&content));
+    viewer.set_content(Some(&toolbar_view));
+    viewer.present();
+}
+
+// Tails the in-memory log; the full history stays in the log file
+fn show_log_viewer(parent: &ApplicationWindow) {
+    let viewer = adw::Window::builder()
+        .title(tr("Log"))
+        .transient_for(parent)
+        .default_width(720)
+        .default_height(480)
+        .build();
+
+    let buffer = gtk4::TextBuffer::new(None);
+    let text_view = gtk4::TextView::builder()
+        .buffer(&buffer)
+        .editable(false)
+        .cursor_visible(false)
+        .monospace(true)
+        .top_margin(8)
+        .bottom_margin(8)
+        .left_margin(8)
+        .right_margin(8)
+        .build();
+    let scrolled = ScrolledWindow::builder()
+        .child(&text_view)
+        .vexpand(true)
+        .build();
+
+    let level_dropdown = gtk4::DropDown::from_strings(&[
+        &tr("All messages"),
+        &tr("Warnings and errors"),
+        &tr("Errors only"),
+    ]);
+    level_dropdown.set_tooltip_text(Some(&tr("Minimum level to show")));
+
+    let render = {
+        let buffer = buffer.clone();
+        let text_view = text_view.clone();
+        let level_dropdown = level_dropdown.clone();
+        move || {
+            let minimum = match level_dropdown.selected() {
+                1 => logging::Level::Warn,
+                2 => logging::Level::Error,
+                _ => logging::Level::Info,
+            };
+            let (_, entries) = logging::entries();
+            let text: String = entries
+                .iter()
+                .filter(|entry| entry.level >= minimum)
+                .map(|entry| format!("{}\n", entry))
+                .collect();
+            buffer.set_text(&text);
+            text_view.scroll_to_iter(&mut buffer.end_iter(), 0.0, false, 0.0, 1.0);
+        }
+    };
+    let render = Rc::new(render);
+    render();
+
+    let render_clone = render.clone();
+    level_dropdown.connect_selected_notify(move |_| render_clone());
+
+    // Poll for new entries while the window is open
+    let seen = Cell::new(logging::recorded());
+    let viewer_weak = viewer.downgrade();
+    let render_clone = render.clone();
+    glib::timeout_add_local(Duration::from_secs(1), move || {
+        if viewer_weak.upgrade().is_none() {
+            return glib::ControlFlow::Break;
+        }
+        let recorded = logging::recorded();
+        if recorded != seen.replace(recorded) {
+            render_clone();
+        }
+        glib::ControlFlow::Continue
+    });
+
+    let copy_button = Button::with_label(&tr("Copy"));
+    copy_button.set_tooltip_text(Some(&tr("Copy the shown lines to the clipboard")));
+    let viewer_clone = viewer.clone();
+    copy_button.connect_clicked(move |_| {
+        let (start, end) = buffer.bounds();
+        viewer_clone
+            .clipboard()
+            .set_text(&buffer.text(&start, &end, false));
+    });
+
+    let folder_button = Button::from_icon_name("folder-open-symbolic");
+    folder_button.set_tooltip_text(Some(&tr("Open log folder")));
+    folder_button.connect_clicked(|_| {
+        if let Some(dir) = logging::log_file().parent() {
+            let _ = std::process::Command::new("xdg-open").arg(dir).spawn();
+        }
+    });
+
+    let header_bar = adw::HeaderBar::new();
+    header_bar.pack_start(&level_dropdown);
+    header_bar.pack_end(&copy_button);
+    header_bar.pack_end(&folder_button);
+
+    let toolbar_view = adw::ToolbarView::new();
+    toolbar_view.add_top_bar(&header_bar);
+    toolbar_view.set_content(Some(&scrolled));
+    viewer.set_content(Some(&toolbar_view));
+    viewer.present();
+}
+
+// Hosts section state for bug reports, left untranslated like the rest of the report
+fn hosts_status_summary(app_state: &AppState) -> String {
+    let mut summary = match app_state.backend.status(&app_state.regions) {
+        ActiveConfiguration::Inactive => "not present".to_string(),
+        ActiveConfiguration::Gatekeep { allowed } => {
+            format!("gatekeep, allowing {}", allowed.join(", "))
+        }
+        ActiveConfiguration::UniversalRedirect { target_ip } => {
+            format!("universal redirect to {}", target_ip)
+        }
+    };
+    match app_state
+        .backend
+        .detect_conflicts(&get_all_regions_map(&app_state.regions, &app_state.blocked_regions))
+    {
+        Ok(conflicts) if !conflicts.is_empty() => {
+            summary.push_str(&format!(", {} conflicting lines", conflicts.len()));
+        }
+        Ok(_) => {}
+        Err(e) => summary.push_str(&format!(", unreadable: {:#}", e)),
+    }
+    summary
+}
+
+// Shows the diagnostics that go into a bug report, then copies them or opens a
+// pre-filled GitHub issue
+fn show_report_issue_dialog(app_state: &Rc<AppState>, window: &ApplicationWindow) {
+    let report = {
+        let settings = app_state.settings.lock().unwrap();
+        diagnostics::report(
+            &app_state.config.current_version,
+            &settings,
+            &hosts_status_summary(app_state),
+        )
+    };
+    show_report_dialog(
+        window,
+        tr("Report an Issue"),
+        tr("The details below help with finding the cause. Nothing personal is included, your game path is left out."),
+        report.clone(),
+        report,
+        app_state.config.repo_url(),
+    );
+}
+
+// Shows a report to copy, or to open as a new GitHub issue with issue_report filled in
+fn show_report_dialog(
+    window: &ApplicationWindow,
+    title: String,
+    description: String,
+    report: String,
+    issue_report: String,
+    repo_url: Option<String>,
+) {
+    let dialog = MessageDialog::new(
+        Some(window),
+        gtk4::DialogFlags::MODAL,
+        MessageType::Info,
+        ButtonsType::None,
+        title,
+    );
+    dialog.set_secondary_text(Some(&description));
+
+    let text_view = gtk4::TextView::builder()
+        .editable(false)
+        .monospace(true)
+        .wrap_mode(gtk4::WrapMode::WordChar)
+        .top_margin(6)
+        .bottom_margin(6)
+        .left_margin(6)
+        .right_margin(6)
+        .build();
+    text_view.buffer().set_text(&report);
+    let scrolled = ScrolledWindow::builder()
+        .child(&text_view)
+        .min_content_height(220)
+        .min_content_width(420)
+        .margin_start(10)
+        .margin_end(10)
+        .build();
+    dialog.content_area().append(&scrolled);
+
+    dialog.add_button(&tr("Cancel"), ResponseType::Cancel);
+    dialog.add_button(&tr("Copy"), ResponseType::Apply);
+    if repo_url.is_some() {
+        dialog.add_button(&tr("Open GitHub Issue"), ResponseType::Ok);
+    }
+
+    dialog.run_async(move |dialog, response| {
+        match response {
+            ResponseType::Apply => dialog.clipboard().set_text(&report),
+            ResponseType::Ok => {
+                if let Some(url) = &repo_url {
+                    open_url(&diagnostics::issue_url(url, &issue_report));
+                }
+            }
+            _ => {}
+        }
+        dialog.close();
+    });
+}
+
+fn show_settings_dialog(app_state: &Rc<AppState>, parent: &ApplicationWindow) {
+    let prefs = adw::PreferencesWindow::builder()
+        .title(tr("Program Settings"))
+        .transient_for(parent)
+        .modal(true)
+        .search_enabled(false)
+        .default_width(420)
+        .build();
+
+    let page = adw::PreferencesPage::new();
+    let settings = app_state.settings.lock().unwrap();
+
+    // Appearance
+    let appearance_group = adw::PreferencesGroup::builder()
+        .title(tr("Appearance"))
+        .build();
+    let scheme_row = adw::ComboRow::builder()
+        .title(tr("Style"))
+        .model(&gtk4::StringList::new(&[&tr("Follow system"), &tr("Light"), &tr("Dark")]))
+        .selected(match settings.color_scheme {
+            ColorScheme::System => 0,
+            ColorScheme::Light => 1,
+            ColorScheme::Dark => 2,
+        })
+        .build();
+    appearance_group.add(&scheme_row);
+    let density_row = adw::ComboRow::builder()
+        .title(tr("Density"))
+        .model(&gtk4::StringList::new(&[&tr("Comfortable"), &tr("Compact")]))
+        .selected(match settings.density {
+            Density::Comfortable => 0,
+            Density::Compact => 1,
+        })
+        .build();
+    appearance_group.add(&density_row);
+    let font_scale_row = adw::SpinRow::builder()
+        .title(tr("Text size"))
+        .subtitle(tr("Percent of the system font size"))
+        .adjustment(&gtk4::Adjustment::new(
+            (settings.font_scale * 100.0).round(),
+            70.0,
+            200.0,
+            10.0,
+            10.0,
+            0.0,
+        ))
+        .build();
+    appearance_group.add(&font_scale_row);
+    let high_contrast_row = adw::SwitchRow::builder()
+        .title(tr("High contrast"))
+        .subtitle(tr("Bold text and stronger colors, with the latency quality and unstable servers spelled out"))
+        .active(settings.high_contrast)
+        .build();
+    appearance_group.add(&high_contrast_row);
+    let palette_row = adw::ComboRow::builder()
+        .title(tr("Latency colors"))
+        .subtitle(tr("The other palettes keep the states apart with color blindness and add the quality as a word"))
+        .model(&gtk4::StringList::new(&[
+            &tr("Standard"),
+            &tr("Deuteranopia (red-green)"),
+            &tr("Protanopia (red-green, reds look dark)"),
+            &tr("Tritanopia (blue-yellow)"),
+        ]))
+        .selected(match settings.latency_palette {
+            LatencyPalette::Standard => 0,
+            LatencyPalette::Deuteranopia => 1,
+            LatencyPalette::Protanopia => 2,
+            LatencyPalette::Tritanopia => 3,
+        })
+        .build();
+    appearance_group.add(&palette_row);
+    let deck_row = adw::ComboRow::builder()
+        .title(tr("Steam Deck mode"))
+        .subtitle(tr("Large touch targets and controller navigation"))
+        .model(&gtk4::StringList::new(&[&tr("Automatic"), &tr("On"), &tr("Off")]))
+        .selected(match settings.deck_mode {
+            DeckMode::Auto => 0,
+            DeckMode::On => 1,
+            DeckMode::Off => 2,
+        })
+        .build();
+    appearance_group.add(&deck_row);
+    let languages = i18n::available_languages();
+    let mut language_names = vec![tr("System default"), "English".to_string()];
+    language_names.extend(languages.iter().map(|(_, name)| name.to_string()));
+    let language_names: Vec<&str> = language_names.iter().map(String::as_str).collect();
+    let language_row = adw::ComboRow::builder()
+        .title(tr("Language"))
+        .model(&gtk4::StringList::new(&language_names))
+        .selected(match settings.language.as_str() {
+            "" => 0,
+            "en" => 1,
+            code => languages
+                .iter()
+                .position(|(c, _)| *c == code)
+                .map_or(0, |i| i as u32 + 2),
+        })
+        .build();
+    appearance_group.add(&language_row);
+
+    // Background
+    let background_group = adw::PreferencesGroup::builder()
+        .title(tr("Background"))
+        .build();
+    let close_to_tray_row = adw::SwitchRow::builder()
+        .title(tr("Keep running in the tray when closed"))
+        .subtitle(tr("Pings and match monitoring continue. Use Quit from the tray icon to exit."))
+        .active(settings.close_to_tray)
+        .build();
+    background_group.add(&close_to_tray_row);
+    let autostart_row = adw::SwitchRow::builder()
+        .title(tr("Start on login"))
+        .subtitle(tr("Launch Make Your Choice automatically when you sign in."))
+        .active(autostart::is_enabled())
+        .build();
+    background_group.add(&autostart_row);
+    let start_minimized_row = adw::SwitchRow::builder()
+        .title(tr("Start minimized to tray"))
+        .subtitle(tr("When started on login, only show the tray icon."))
+        .active(settings.start_minimized)
+        .sensitive(autostart_row.is_active())
+        .build();
+    background_group.add(&start_minimized_row);
+    let game_launch_row = adw::SwitchRow::builder()
+        .title(tr("Apply the active profile when the game starts"))
+        .subtitle(tr("Watches for Dead by Daylight starting and applies the profile loaded last, with a notification."))
+        .active(settings.apply_on_game_launch)
+        .build();
+    background_group.add(&game_launch_row);
+    let service_row = adw::SwitchRow::builder()
+        .title(tr("Run as a background service"))
+        .subtitle(tr("A systemd user service keeps the selection current and answers D-Bus calls without the window."))
+        .active(service::is_enabled())
+        .build();
+    background_group.add(&service_row);
+    let metrics_row = adw::SwitchRow::builder()
+        .title(tr("Serve Prometheus metrics"))
+        .subtitle(trf(
+            "The background service publishes latencies, matches and the hosts status on http://{}/metrics.",
+            &[&format!("{}:{}", settings.metrics.address, settings.metrics.port)],
+        ))
+        .active(settings.metrics.enabled)
+        .build();
+    background_group.add(&metrics_row);
+    let metrics_port_row = adw::SpinRow::with_range(1024.0, 65535.0, 1.0);
+    metrics_port_row.set_title(&tr("Metrics port"));
+    metrics_port_row.set_value(settings.metrics.port as f64);
+    background_group.add(&metrics_port_row);
+
+    // Notifications
+    let notifications_group = adw::PreferencesGroup::builder()
+        .title(tr("Notifications"))
+        .description(tr("Desktop notifications for what happens while the window is hidden or behind the game."))
+        .build();
+    let notification_switch = |title: String, subtitle: String, active: bool| {
+        let row = adw::SwitchRow::builder().title(title).subtitle(subtitle).active(active).build();
+        notifications_group.add(&row);
+        row
+    };
+    let match_notify_row = notification_switch(
+        tr("Match region"),
+        tr("The region of each new match while the window is in the tray."),
+        settings.notifications.match_region,
+    );
+    let latency_notify_row = notification_switch(
+        tr("Latency alerts"),
+        tr("Once per match, when its server gets slower than the threshold below."),
+        settings.notifications.latency_alerts,
+    );
+    let latency_threshold_row = adw::SpinRow::with_range(50.0, 500.0, 10.0);
+    latency_threshold_row.set_title(&tr("Alert threshold (ms)"));
+    latency_threshold_row.set_value(settings.notifications.latency_threshold_ms as f64);
+    latency_threshold_row.set_sensitive(settings.notifications.latency_alerts);
+    notifications_group.add(&latency_threshold_row);
+    let apply_notify_row = notification_switch(
+        tr("Apply results"),
+        tr("Changes made from the tray, links or the game launch. Failures are always shown."),
+        settings.notifications.apply_results,
+    );
+    let update_notify_row = notification_switch(
+        tr("Updates"),
+        tr("A new version is available."),
+        settings.notifications.updates,
+    );
+    let sound_row = notification_switch(
+        tr("Play a sound"),
+        tr("The desktop's message sound, through canberra-gtk-play when installed."),
+        settings.notifications.sound,
+    );
+    let quiet_row = notification_switch(
+        tr("Quiet hours"),
+        tr("Only failures are shown between these hours."),
+        settings.notifications.quiet_hours,
+    );
+    let quiet_start_row = adw::SpinRow::with_range(0.0, 23.0, 1.0);
+    quiet_start_row.set_title(&tr("From (hour)"));
+    quiet_start_row.set_value(settings.notifications.quiet_start as f64);
+    notifications_group.add(&quiet_start_row);
+    let quiet_end_row = adw::SpinRow::with_range(0.0, 23.0, 1.0);
+    quiet_end_row.set_title(&tr("Until (hour)"));
+    quiet_end_row.set_value(settings.notifications.quiet_end as f64);
+    notifications_group.add(&quiet_end_row);
+    for row in [&quiet_start_row, &quiet_end_row] {
+        row.set_sensitive(settings.notifications.quiet_hours);
+    }
+
+    // Startup
+    let startup_group = adw::PreferencesGroup::builder()
+        .title(tr("Startup"))
+        .build();
+    let check_updates_row = adw::SwitchRow::builder()
+        .title(tr("Check for updates on start"))
+        .subtitle(tr("Updates can still be checked from the menu."))
+        .active(settings.check_updates_on_start)
+        .build();
+    startup_group.add(&check_updates_row);
+    let patch_notes_row = adw::SwitchRow::builder()
+        .title(tr("Show patch notes after updating"))
+        .subtitle(tr("When off, a banner links to them instead."))
+        .active(settings.patch_notes_popup)
+        .build();
+    startup_group.add(&patch_notes_row);
+    let channel_row = adw::ComboRow::builder()
+        .title(tr("Update channel"))
+        .subtitle(tr("Pre-releases get fixes sooner but may be less tested."))
+        .model(&gtk4::StringList::new(&[&tr("Stable"), &tr("Pre-release")]))
+        .selected(match settings.update_channel {
+            UpdateChannel::Stable => 0,
+            UpdateChannel::PreRelease => 1,
+        })
+        .build();
+    startup_group.add(&channel_row);
+    const CHECK_INTERVALS: [u32; 4] = [0, 6, 12, 24];
+    let interval_row = adw::ComboRow::builder()
+        .title(tr("Check while running"))
+        .subtitle(tr("Useful when the app stays in the tray. New versions are announced with a notification."))
+        .model(&gtk4::StringList::new(&[
+            &tr("Off"),
+            &tr("Every 6 hours"),
+            &tr("Every 12 hours"),
+            &tr("Every 24 hours"),
+        ]))
+        .selected(
+            CHECK_INTERVALS
+                .iter()
+                .position(|hours| *hours == settings.update_check_interval_hours)
+                .unwrap_or(0) as u32,
+        )
+        .build();
+    startup_group.add(&interval_row);
+    let mirror_row = adw::EntryRow::builder()
+        .title(tr("Fallback release source (Codeberg, GitLab or JSON URL)"))
+        .text(settings.update_mirror.as_str())
+        .show_apply_button(true)
+        .build();
+    startup_group.add(&mirror_row);
+
+    // Network
+    let network_group = adw::PreferencesGroup::builder()
+        .title(tr("Network"))
+        .description(tr("Leave the proxy empty to use the http_proxy, https_proxy and all_proxy environment variables."))
+        .build();
+    let proxy_row = adw::EntryRow::builder()
+        .title(tr("Proxy (e.g. http://proxy.example:3128)"))
+        .text(settings.proxy.url.as_str())
+        .show_apply_button(true)
+        .build();
+    network_group.add(&proxy_row);
+    let proxy_user_row = adw::EntryRow::builder()
+        .title(tr("Proxy username"))
+        .text(settings.proxy.username.as_str())
+        .show_apply_button(true)
+        .build();
+    network_group.add(&proxy_user_row);
+    let proxy_password_row = adw::PasswordEntryRow::builder()
+        .title(tr("Proxy password"))
+        .text(settings.proxy.password.as_str())
+        .show_apply_button(true)
+        .build();
+    network_group.add(&proxy_password_row);
+    let dns_row = adw::ComboRow::builder()
+        .title(tr("Look up servers with"))
+        .subtitle(tr("Used for pings, Universal Redirect and the match monitor. The hosts file is never asked."))
+        .model(&gtk4::StringList::new(&[
+            &tr("System DNS servers"),
+            &tr("Custom DNS server"),
+            &tr("DNS over HTTPS"),
+        ]))
+        .selected(match settings.dns.upstream {
+            DnsUpstream::System => 0,
+            DnsUpstream::Udp => 1,
+            DnsUpstream::Https => 2,
+        })
+        .build();
+    network_group.add(&dns_row);
+    let dns_server_row = adw::EntryRow::builder()
+        .title(tr("DNS server (IP or IP:port)"))
+        .text(settings.dns.server.as_str())
+        .show_apply_button(true)
+        .visible(settings.dns.upstream == DnsUpstream::Udp)
+        .build();
+    network_group.add(&dns_server_row);
+    let dns_https_row = adw::EntryRow::builder()
+        .title(tr("DNS over HTTPS address (e.g. https://dns.example/dns-query)"))
+        .text(settings.dns.https_url.as_str())
+        .show_apply_button(true)
+        .visible(settings.dns.upstream == DnsUpstream::Https)
+        .build();
+    network_group.add(&dns_https_row);
+    let community_row = adw::SwitchRow::builder()
+        .title(tr("Share anonymous server stability"))
+        .subtitle(tr("Sends how many pings were lost and matches ended early per server, without addresses or ids. Servers are then marked unstable by everyone's reports, from the next start on."))
+        .active(settings.community.enabled)
+        .build();
+    network_group.add(&community_row);
+    let community_endpoint_row = adw::EntryRow::builder()
+        .title(tr("Community server"))
+        .text(settings.community.endpoint.as_str())
+        .show_apply_button(true)
+        .build();
+    network_group.add(&community_endpoint_row);
+
+    // Local HTTP API
+    let api_group = adw::PreferencesGroup::builder()
+        .title(tr("Local HTTP API"))
+        .description(tr("For Stream Deck plugins, overlays and dashboards. Only reachable from this computer, every request needs the token."))
+        .build();
+    let api_row = adw::SwitchRow::builder()
+        .title(tr("Enable local HTTP API"))
+        .active(settings.http_api.enabled)
+        .build();
+    api_group.add(&api_row);
+    let api_port_row = adw::SpinRow::with_range(1024.0, 65535.0, 1.0);
+    api_port_row.set_title(&tr("Port"));
+    api_port_row.set_value(settings.http_api.port as f64);
+    api_group.add(&api_port_row);
+    let api_token_row = adw::ActionRow::builder()
+        .title(tr("Token"))
+        .subtitle(settings.http_api.token.as_str())
+        .subtitle_selectable(true)
+        .build();
+    let copy_token_button = Button::builder()
+        .icon_name("edit-copy-symbolic")
+        .tooltip_text(tr("Copy"))
+        .valign(gtk4::Align::Center)
+        .build();
+    copy_token_button.add_css_class("flat");
+    let new_token_button = Button::builder()
+        .icon_name("view-refresh-symbolic")
+        .tooltip_text(tr("Create a new token, the old one stops working"))
+        .valign(gtk4::Align::Center)
+        .build();
+    new_token_button.add_css_class("flat");
+    api_token_row.add_suffix(&copy_token_button);
+    api_token_row.add_suffix(&new_token_button);
+    api_group.add(&api_token_row);
+
+    // Apply mode
+    let method_group = adw::PreferencesGroup::builder()
+        .title(tr("Method"))
+        .description(tr("After changing this setting, reapply your selection to apply changes."))
+        .build();
+    let mode_row = adw::ComboRow::builder()
+        .title(tr("Method"))
+        .model(&gtk4::StringList::new(&[
+            &tr("Gatekeep (default)"),
+            &tr("Universal Redirect (deprecated)"),
+        ]))
+        .selected(match settings.apply_mode {
+            ApplyMode::Gatekeep => 0,
+            ApplyMode::UniversalRedirect => 1,
+        })
+        .build();
+    if let Some(mode) = overrides::get().apply_mode {
+        let forced = match mode {
+            ApplyMode::Gatekeep => tr("Gatekeep"),
+            ApplyMode::UniversalRedirect => tr("Universal Redirect"),
+        };
+        mode_row.set_subtitle(&trf("{} is forced for this session by --apply-mode", &[&forced]));
+    }
+    method_group.add(&mode_row);
+    let confirm_apply_row = adw::SwitchRow::builder()
+        .title(tr("Confirm before applying"))
+        .subtitle(tr("Show which servers will be allowed and blocked before the hosts file is written."))
+        .active(settings.confirm_apply)
+        .build();
+    method_group.add(&confirm_apply_row);
+
+    // Block mode - using CheckButtons in radio mode
+    let block_group = adw::PreferencesGroup::builder()
+        .title(tr("Gatekeep Options"))
+        .build();
+    let rb_both = CheckButton::new();
+    let rb_ping = CheckButton::new();
+    let rb_service = CheckButton::new();
+
+    // Group the checkbuttons to act like radio buttons
+    rb_ping.set_group(Some(&rb_both));
+    rb_service.set_group(Some(&rb_both));
+
+    match settings.block_mode {
+        BlockMode::Both => rb_both.set_active(true),
+        BlockMode::OnlyPing => rb_ping.set_active(true),
+        BlockMode::OnlyService => rb_service.set_active(true),
+    }
+
+    for (radio, title) in [
+        (&rb_both, &tr("Block both (default)")),
+        (&rb_ping, &tr("Block UDP ping beacon endpoints")),
+        (&rb_service, &tr("Block service endpoints")),
+    ] {
+        let row = adw::ActionRow::builder()
+            .title(title)
+            .activatable_widget(radio)
+            .build();
+        row.add_prefix(radio);
+        block_group.add(&row);
+    }
+
+    // Merge unstable
+    let merge_row = adw::SwitchRow::builder()
+        .title(tr("Merge unstable servers"))
+        .subtitle(tr("Recommended"))
+        .active(settings.merge_unstable)
+        .build();
+    block_group.add(&merge_row);
+
+    // Game folders
+    let game_group = adw::PreferencesGroup::builder()
+        .title(tr("Game folders"))
+        .description(tr("Tip: In Steam, right-click Dead by Daylight → Manage → Browse local files. The folder that opens is the one you should select.\n\nThis setting is only required for some features like custom splash art and startup movies. Add every installation you play on, e.g. Steam and an SD card."))
+        .build();
+    let add_install_button = Button::from_icon_name("list-add-symbolic");
+    add_install_button.set_tooltip_text(Some(&tr("Add game folder…")));
+    add_install_button.set_valign(gtk4::Align::Center);
+    add_install_button.add_css_class("flat");
+    let detect_installs_button = Button::from_icon_name("system-search-symbolic");
+    detect_installs_button.set_tooltip_text(Some(&tr("Find Steam, Heroic and Lutris installs")));
+    detect_installs_button.set_valign(gtk4::Align::Center);
+    detect_installs_button.add_css_class("flat");
+    let game_group_buttons = GtkBox::new(Orientation::Horizontal, 0);
+    game_group_buttons.append(&detect_installs_button);
+    game_group_buttons.append(&add_install_button);
+    game_group.set_header_suffix(Some(&game_group_buttons));
+    drop(settings);
+    let install_rows = Rc::new(RefCell::new(Vec::new()));
+    fill_game_installs_group(app_state, &game_group, install_rows.clone());
+
+    // Defaults
+    let defaults_group = adw::PreferencesGroup::builder()
+        .description(tr("The default options are recommended. You may not want to change these if you aren't sure of what you are doing. Your experience may vary by using settings other than the default."))
+        .build();
+    let reset_button = Button::with_label(&tr("Revert to Default"));
+    let restore_button = Button::with_label(&tr("Restore Previous Settings…"));
+    restore_button.set_tooltip_text(Some(&tr(
+        "Settings are saved automatically before a reset, an upgrade or an import",
+    )));
+    let defaults_buttons = GtkBox::new(Orientation::Horizontal, 6);
+    defaults_buttons.append(&reset_button);
+    defaults_buttons.append(&restore_button);
+    defaults_group.add(&defaults_buttons);
+
+    page.add(&appearance_group);
+    page.add(&background_group);
+    page.add(&notifications_group);
+    page.add(&startup_group);
+    page.add(&network_group);
+    page.add(&api_group);
+    page.add(&method_group);
+    page.add(&block_group);
+    page.add(&game_group);
+    page.add(&defaults_group);
+    prefs.add(&page);
+
+    // Changes apply immediately, like any other preferences window
+    let app_state_clone = app_state.clone();
+    scheme_row.connect_selected_notify(move |row| {
+        let scheme = match row.selected() {
+            1 => ColorScheme::Light,
+            2 => ColorScheme::Dark,
+            _ => ColorScheme::System,
+        };
+        theme::apply_color_scheme(scheme);
+        update_settings(&app_state_clone, |settings| settings.color_scheme = scheme);
+    });
+
+    let app_state_clone = app_state.clone();
+    let parent_clone = parent.clone();
+    density_row.connect_selected_notify(move |row| {
+        let density = match row.selected() {
+            1 => Density::Compact,
+            _ => Density::Comfortable,
+        };
+        update_settings(&app_state_clone, |settings| settings.density = density);
+        apply_layout_settings(&app_state_clone, &parent_clone);
+    });
+
+    let app_state_clone = app_state.clone();
+    let parent_clone = parent.clone();
+    font_scale_row.connect_value_notify(move |row| {
+        let font_scale = row.value() / 100.0;
+        update_settings(&app_state_clone, |settings| settings.font_scale = font_scale);
+        apply_layout_settings(&app_state_clone, &parent_clone);
+    });
+
+    let app_state_clone = app_state.clone();
+    palette_row.connect_selected_notify(move |row| {
+        let palette = match row.selected() {
+            1 => LatencyPalette::Deuteranopia,
+            2 => LatencyPalette::Protanopia,
+            3 => LatencyPalette::Tritanopia,
+            _ => LatencyPalette::Standard,
+        };
+        update_settings(&app_state_clone, |settings| settings.latency_palette = palette);
+        theme::set_latency_palette(palette);
+        refresh_spelled_out(&app_state_clone.list_store);
+    });
+
+    let app_state_clone = app_state.clone();
+    high_contrast_row.connect_active_notify(move |row| {
+        let high_contrast = row.is_active();
+        update_settings(&app_state_clone, |settings| settings.high_contrast = high_contrast);
+        theme::set_high_contrast(high_contrast);
+        refresh_spelled_out(&app_state_clone.list_store);
+    });
+
+    let app_state_clone = app_state.clone();
+    let parent_clone = parent.clone();
+    deck_row.connect_selected_notify(move |row| {
+        let deck_mode = match row.selected() {
+            1 => DeckMode::On,
+            2 => DeckMode::Off,
+            _ => DeckMode::Auto,
+        };
+        update_settings(&app_state_clone, |settings| settings.deck_mode = deck_mode);
+        apply_layout_settings(&app_state_clone, &parent_clone);
+    });
+
+    let app_state_clone = app_state.clone();
+    let prefs_clone = prefs.clone();
+    language_row.connect_selected_notify(move |row| {
+        let language = match row.selected() {
+            0 => String::new(),
+            1 => "en".to_string(),
+            i => languages
+                .get(i as usize - 2)
+                .map(|(code, _)| code.to_string())
+                .unwrap_or_default(),
+        };
+        update_settings(&app_state_clone, |settings| settings.language = language);
+        prefs_clone.add_toast(adw::Toast::new(&tr(
+            "Restart Make Your Choice to change the language.",
+        )));
+    });
+
+    let app_state_clone = app_state.clone();
+    mode_row.connect_selected_notify(move |row| {
+        update_settings(&app_state_clone, |settings| {
+            settings.apply_mode = match row.selected() {
+                1 => ApplyMode::UniversalRedirect,
+                _ => ApplyMode::Gatekeep,
+            };
+        });
+    });
+
+    for (radio, block_mode) in [
+        (&rb_both, BlockMode::Both),
+        (&rb_ping, BlockMode::OnlyPing),
+        (&rb_service, BlockMode::OnlyService),
+    ] {
+        let app_state_clone = app_state.clone();
+        radio.connect_toggled(move |radio| {
+            if radio.is_active() {
+                update_settings(&app_state_clone, |settings| settings.block_mode = block_mode);
+            }
+        });
+    }
+
+    let app_state_clone = app_state.clone();
+    close_to_tray_row.connect_active_notify(move |row| {
+        update_settings(&app_state_clone, |settings| settings.close_to_tray = row.is_active());
+    });
+
+    type Setter = fn(&mut NotificationSettings, bool);
+    let switches: [(&adw::SwitchRow, Setter); 6] = [
+        (&match_notify_row, |n, active| n.match_region = active),
+        (&latency_notify_row, |n, active| n.latency_alerts = active),
+        (&apply_notify_row, |n, active| n.apply_results = active),
+        (&update_notify_row, |n, active| n.updates = active),
+        (&sound_row, |n, active| n.sound = active),
+        (&quiet_row, |n, active| n.quiet_hours = active),
+    ];
+    for (row, set) in switches {
+        let app_state_clone = app_state.clone();
+        row.connect_active_notify(move |row| {
+            update_settings(&app_state_clone, |settings| set(&mut settings.notifications, row.is_active()));
+        });
+    }
+    let latency_threshold_row_clone = latency_threshold_row.clone();
+    latency_notify_row.connect_active_notify(move |row| latency_threshold_row_clone.set_sensitive(row.is_active()));
+    let quiet_start_row_clone = quiet_start_row.clone();
+    let quiet_end_row_clone = quiet_end_row.clone();
+    quiet_row.connect_active_notify(move |row| {
+        quiet_start_row_clone.set_sensitive(row.is_active());
+        quiet_end_row_clone.set_sensitive(row.is_active());
+    });
+
+    let app_state_clone = app_state.clone();
+    latency_threshold_row.connect_value_notify(move |row| {
+        let threshold = row.value() as u32;
+        update_settings(&app_state_clone, |settings| settings.notifications.latency_threshold_ms = threshold);
+    });
+
+    let app_state_clone = app_state.clone();
+    quiet_start_row.connect_value_notify(move |row| {
+        let hour = row.value() as u32;
+        update_settings(&app_state_clone, |settings| settings.notifications.quiet_start = hour);
+    });
+
+    let app_state_clone = app_state.clone();
+    quiet_end_row.connect_value_notify(move |row| {
+        let hour = row.value() as u32;
+        update_settings(&app_state_clone, |settings| settings.notifications.quiet_end = hour);
+    });
+
+    let app_state_clone = app_state.clone();
+    game_launch_row.connect_active_notify(move |row| {
+        update_settings(&app_state_clone, |settings| settings.apply_on_game_launch = row.is_active());
+    });
+
+    let app_state_clone = app_state.clone();
+    let prefs_clone = prefs.clone();
+    let start_minimized_row_clone = start_minimized_row.clone();
+    autostart_row.connect_active_notify(move |row| {
+        let start_minimized = app_state_clone.settings.lock().unwrap().start_minimized;
+        let result = if row.is_active() {
+            autostart::enable(start_minimized)
+        } else {
+            autostart::disable()
+        };
+        if let Err(e) = result {
+            show_error_dialog(
+                &prefs_clone,
+                &tr("Autostart"),
+                &trf("Failed to change the login entry:\n{}", &[&format!("{:#}", e)]),
+            );
+        }
+        start_minimized_row_clone.set_sensitive(autostart::is_enabled());
+    });
+
+    let prefs_clone = prefs.clone();
+    service_row.connect_active_notify(move |row| {
+        let result = if row.is_active() { service::enable() } else { service::disable() };
+        if let Err(e) = result {
+            show_error_dialog(
+                &prefs_clone,
+                &tr("Background service"),
+                &trf("Failed to change the background service:\n{}", &[&format!("{:#}", e)]),
+            );
+        }
+    });
+
+    let app_state_clone = app_state.clone();
+    metrics_row.connect_active_notify(move |row| {
+        let enabled = row.is_active();
+        update_settings(&app_state_clone, |settings| settings.metrics.enabled = enabled);
+        restart_service();
+    });
+
+    let app_state_clone = app_state.clone();
+    let metrics_row_clone = metrics_row.clone();
+    metrics_port_row.connect_value_notify(move |row| {
+        let port = row.value() as u16;
+        update_settings(&app_state_clone, |settings| settings.metrics.port = port);
+        let address = app_state_clone.settings.lock().unwrap().metrics.address.clone();
+        metrics_row_clone.set_subtitle(&trf(
+            "The background service publishes latencies, matches and the hosts status on http://{}/metrics.",
+            &[&format!("{}:{}", address, port)],
+        ));
+        restart_service();
+    });
+
+    let app_state_clone = app_state.clone();
+    let prefs_clone = prefs.clone();
+    proxy_row.connect_apply(move |row| {
+        let url = row.text().trim().to_string();
+        if !url.is_empty() && net::check_proxy_url(&url).is_err() {
+            prefs_clone.add_toast(adw::Toast::new(&trf("Invalid proxy address {}", &[&url])));
+            return;
+        }
+        update_settings(&app_state_clone, |settings| {
+            settings.proxy.url = url;
+            settings.proxy.activate();
+        });
+    });
+
+    let app_state_clone = app_state.clone();
+    proxy_user_row.connect_apply(move |row| {
+        let username = row.text().to_string();
+        update_settings(&app_state_clone, |settings| {
+            settings.proxy.username = username;
+            settings.proxy.activate();
+        });
+    });
+
+    let app_state_clone = app_state.clone();
+    proxy_password_row.connect_apply(move |row| {
+        let password = row.text().to_string();
+        update_settings(&app_state_clone, |settings| {
+            settings.proxy.password = password;
+            settings.proxy.activate();
+        });
+    });
+
+    let app_state_clone = app_state.clone();
+    let dns_server_row_clone = dns_server_row.clone();
+    let dns_https_row_clone = dns_https_row.clone();
+    dns_row.connect_selected_notify(move |row| {
+        let upstream = match row.selected() {
+            1 => DnsUpstream::Udp,
+            2 => DnsUpstream::Https,
+            _ => DnsUpstream::System,
+        };
+        dns_server_row_clone.set_visible(upstream == DnsUpstream::Udp);
+        dns_https_row_clone.set_visible(upstream == DnsUpstream::Https);
+        update_settings(&app_state_clone, |settings| {
+            settings.dns.upstream = upstream;
+            settings.dns.activate();
+        });
+    });
+
+    let app_state_clone = app_state.clone();
+    let prefs_clone = prefs.clone();
+    dns_server_row.connect_apply(move |row| {
+        let server = row.text().trim().to_string();
+        if dns::parse_server(&server).is_err() {
+            prefs_clone.add_toast(adw::Toast::new(&trf("Invalid DNS server {}, use an IP or IP:PORT", &[&server])));
+            return;
+        }
+        update_settings(&app_state_clone, |settings| {
+            settings.dns.server = server;
+            settings.dns.activate();
+        });
+    });
+
+    let app_state_clone = app_state.clone();
+    let prefs_clone = prefs.clone();
+    dns_https_row.connect_apply(move |row| {
+        let url = row.text().trim().to_string();
+        if dns::check_https_url(&url).is_err() {
+            prefs_clone.add_toast(adw::Toast::new(&trf("Invalid address {}, DNS over HTTPS needs an https:// address", &[&url])));
+            return;
+        }
+        update_settings(&app_state_clone, |settings| {
+            settings.dns.https_url = url;
+            settings.dns.activate();
+        });
+    });
+
+    let app_state_clone = app_state.clone();
+    community_row.connect_active_notify(move |row| {
+        let enabled = row.is_active();
+        update_settings(&app_state_clone, |settings| settings.community.enabled = enabled);
+        if !enabled {
+            community::forget();
+        }
+    });
+
+    let app_state_clone = app_state.clone();
+    community_endpoint_row.connect_apply(move |row| {
+        let endpoint = row.text().trim().to_string();
+        update_settings(&app_state_clone, |settings| settings.community.endpoint = endpoint);
+    });
+
+    let app_state_clone = app_state.clone();
+    let api_token_row_clone = api_token_row.clone();
+    api_row.connect_active_notify(move |row| {
+        let enabled = row.is_active();
+        update_settings(&app_state_clone, |settings| settings.http_api.enabled = enabled);
+        refresh_http_api(&app_state_clone);
+        let token = app_state_clone.settings.lock().unwrap().http_api.token.clone();
+        api_token_row_clone.set_subtitle(&token);
+    });
+
+    let app_state_clone = app_state.clone();
+    api_port_row.connect_value_notify(move |row| {
+        let port = row.value() as u16;
+        update_settings(&app_state_clone, |settings| settings.http_api.port = port);
+        refresh_http_api(&app_state_clone);
+    });
+
+    let api_token_row_clone = api_token_row.clone();
+    copy_token_button.connect_clicked(move |button| {
+        button.clipboard().set_text(&api_token_row_clone.subtitle().unwrap_or_default());
+    });
+
+    let app_state_clone = app_state.clone();
+    let api_token_row_clone = api_token_row.clone();
+    new_token_button.connect_clicked(move |_| {
+        let token = api::new_token();
+        api_token_row_clone.set_subtitle(&token);
+        update_settings(&app_state_clone, |settings| settings.http_api.token = token);
+        refresh_http_api(&app_state_clone);
+    });
+
+    let app_state_clone = app_state.clone();
+    check_updates_row.connect_active_notify(move |row| {
+        let active = row.is_active();
+        update_settings(&app_state_clone, |settings| settings.check_updates_on_start = active);
+    });
+
+    let app_state_clone = app_state.clone();
+    mirror_row.connect_apply(move |row| {
+        let mirror = row.text().trim().to_string();
+        update_settings(&app_state_clone, |settings| settings.update_mirror = mirror);
+    });
+
+    let app_state_clone = app_state.clone();
+    interval_row.connect_selected_notify(move |row| {
+        let hours = CHECK_INTERVALS.get(row.selected() as usize).copied().unwrap_or(0);
+        update_settings(&app_state_clone, |settings| settings.update_check_interval_hours = hours);
+    });
+
+    let app_state_clone = app_state.clone();
+    channel_row.connect_selected_notify(move |row| {
+        let channel = match row.selected() {
+            1 => UpdateChannel::PreRelease,
+            _ => UpdateChannel::Stable,
+        };
+        update_settings(&app_state_clone, |settings| settings.update_channel = channel);
+    });
+
+    let app_state_clone = app_state.clone();
+    patch_notes_row.connect_active_notify(move |row| {
+        let active = row.is_active();
+        update_settings(&app_state_clone, |settings| settings.patch_notes_popup = active);
+    });
+
+    let app_state_clone = app_state.clone();
+    let prefs_clone = prefs.clone();
+    start_minimized_row.connect_active_notify(move |row| {
+        let start_minimized = row.is_active();
+        update_settings(&app_state_clone, |settings| settings.start_minimized = start_minimized);
+        // Rewrite the login entry so its command line matches
+        if autostart::is_enabled() {
+            if let Err(e) = autostart::enable(start_minimized) {
+                show_error_dialog(
+                    &prefs_clone,
+                    &tr("Autostart"),
+                    &trf("Failed to change the login entry:\n{}", &[&format!("{:#}", e)]),
+                );
+            }
+        }
+    });
+
+    let app_state_clone = app_state.clone();
+    confirm_apply_row.connect_active_notify(move |row| {
+        update_settings(&app_state_clone, |settings| settings.confirm_apply = row.is_active());
+    });
+
+    let app_state_clone = app_state.clone();
+    merge_row.connect_active_notify(move |row| {
+        update_settings(&app_state_clone, |settings| settings.merge_unstable = row.is_active());
+    });
+
+    let app_state_clone = app_state.clone();
+    let prefs_clone = prefs.clone();
+    let game_group_clone = game_group.clone();
+    let install_rows_clone = install_rows.clone();
+    detect_installs_button.connect_clicked(move |_| {
+        let added = add_detected_installs(&app_state_clone);
+        fill_game_installs_group(&app_state_clone, &game_group_clone, install_rows_clone.clone());
+        let message = if added == 0 {
+            tr("No new game folders found")
+        } else {
+            trf("Added {} game folder(s)", &[&added])
+        };
+        prefs_clone.add_toast(adw::Toast::new(&message));
+    });
+
+    let app_state_clone = app_state.clone();
+    let prefs_clone = prefs.clone();
+    let game_group_clone = game_group.clone();
+    let install_rows_clone = install_rows.clone();
+    add_install_button.connect_clicked(move |_| {
+        let app_state = app_state_clone.clone();
+        let prefs = prefs_clone.clone();
+        let group = game_group_clone.clone();
+        let rows = install_rows_clone.clone();
+        select_game_path(&prefs_clone, move |path| {
+            if !is_valid_game_folder(&path) {
+                show_error_dialog(
+                    &prefs,
+                    &tr("Invalid game folder"),
+                    &tr("Please select the folder named \"Dead by Daylight\" (Steam) or \"DeadByDaylight\" (Epic)."),
+                );
+                return;
+            }
+            let path_text = path.to_string_lossy().to_string();
+            let message = path_text.clone();
+            ask_name(
+                &prefs,
+                &tr("Name This Installation"),
+                &message,
+                &games::suggest_install_name(&path),
+                &tr("e.g. Steam"),
+                move |name| {
+                    update_settings(&app_state, |settings| {
+                        let mut install = GameInstall {
+                            name: name.clone(),
+                            path: path_text.clone(),
+                            prefix: None,
+                            changes: None,
+                        };
+                        match settings.game_installs.iter_mut().find(|i| i.name == name) {
+                            Some(existing) => {
+                                if existing.path == install.path {
+                                    install.changes = existing.changes.take();
+                                }
+                                *existing = install;
+                            }
+                            None => settings.game_installs.push(install),
+                        }
+                    });
+                    fill_game_installs_group(&app_state, &group, rows.clone());
+                },
+            );
+        });
+    });
+
+    let app_state_clone = app_state.clone();
+    let prefs_clone = prefs.clone();
+    let parent_clone = parent.clone();
+    restore_button.connect_clicked(move |_| {
+        show_restore_settings_dialog(&app_state_clone, &prefs_clone, &parent_clone);
+    });
+
+    let app_state_clone = app_state.clone();
+    reset_button.connect_clicked(move |_| {
+        if let Err(e) = settings::take_snapshot(SnapshotReason::Reset) {
+            log_warn!("settings", "Failed to snapshot settings before reset: {:#}", e);
+        }
+
+        // Reset to default values
+        update_settings(&app_state_clone, |settings| {
+            settings.apply_mode = ApplyMode::Gatekeep;
+            settings.block_mode = BlockMode::Both;
+            settings.merge_unstable = true;
+            settings.game_installs.clear();
+            settings.last_game_install = None;
+        });
+
+        // Update UI controls to reflect defaults
+        fill_game_installs_group(&app_state_clone, &game_group, install_rows.clone());
+        mode_row.set_selected(0);
+        rb_both.set_active(true);
+        merge_row.set_active(true);
+    });
+
+    prefs.present();
+}
+
+// Lets the user pick one of the settings snapshots and put it back. The preferences
+// window is closed afterwards since its controls show the replaced values.
+fn show_restore_settings_dialog(
+    app_state: &Rc<AppState>,
+    prefs: &adw::PreferencesWindow,
+    window: &ApplicationWindow,
+) {
+    let snapshots = settings::snapshots();
+    if snapshots.is_empty() {
+        show_info_dialog(
+            prefs,
+            &tr("No Previous Settings"),
+            &tr("A copy of your settings is kept before they are reset, upgraded or imported. There is none yet."),
+        );
+        return;
+    }
+
+    let dialog = MessageDialog::new(
+        Some(prefs),
+        gtk4::DialogFlags::MODAL,
+        MessageType::Question,
+        ButtonsType::None,
+        tr("Restore Previous Settings"),
+    );
+    dialog.set_secondary_text(Some(&tr(
+        "Your current settings are kept as well, so you can switch back later.",
+    )));
+    let labels: Vec<String> = snapshots
+        .iter()
+        .map(|snapshot| {
+            let reason = match snapshot.reason {
+                SnapshotReason::Reset => tr("before reset"),
+                SnapshotReason::Migration => tr("before upgrade"),
+                SnapshotReason::Import => tr("before import"),
+                SnapshotReason::Restore => tr("before restore"),
+            };
+            format!("{} ({})", snapshot.taken.format("%Y-%m-%d %H:%M"), reason)
+        })
+        .collect();
+    let labels: Vec<&str> = labels.iter().map(String::as_str).collect();
+    let dropdown = gtk4::DropDown::from_strings(&labels);
+    if let Some(message_area) = dialog.message_area().downcast_ref::<GtkBox>() {
+        message_area.append(&dropdown);
+    }
+    dialog.add_button(&tr("Cancel"), ResponseType::Cancel);
+    dialog.add_button(&tr("Restore"), ResponseType::Accept);
+    dialog.set_default_response(ResponseType::Accept);
+
+    let app_state = app_state.clone();
+    let prefs = prefs.clone();
+    let window = window.clone();
+    dialog.run_async(move |dialog, response| {
+        dialog.close();
+        if response != ResponseType::Accept {
+            return;
+        }
+        let Some(snapshot) = snapshots.get(dropdown.selected() as usize) else {
+            return;
+        };
+        match settings::restore_snapshot(snapshot) {
+            Ok(restored) => {
+                log_info!("settings", "Restored settings from {:?}", snapshot.path);
+                *app_state.settings.lock().unwrap() = restored;
+                apply_reloaded_settings(&app_state, &window);
+                prefs.close();
+                app_state
+                    .toast_overlay
+                    .add_toast(adw::Toast::new(&tr("Previous settings restored")));
+            }
+            Err(e) => show_error_dialog(
+                &prefs,
+                &tr("Restore failed"),
+                &trf("Could not restore the settings: {}", &[&format!("{:#}", e)]),
+            ),
+        }
+    });
+}
+
+// One row per game folder with a button to forget it
+fn fill_game_installs_group(
+    app_state: &Rc<AppState>,
+    group: &adw::PreferencesGroup,
+    rows: Rc<RefCell<Vec<adw::ActionRow>>>,
+) {
+    for row in rows.borrow_mut().drain(..) {
+        group.remove(&row);
+    }
+
+    let installs = app_state.settings.lock().unwrap().game_installs.clone();
+    if installs.is_empty() {
+        let row = adw::ActionRow::builder()
+            .title(tr("No game folder set"))
+            .build();
+        group.add(&row);
+        rows.borrow_mut().push(row);
+        return;
+    }
+
+    for install in installs {
+        let subtitle = match &install.prefix {
+            Some(prefix) => trf("{}\nPrefix: {}", &[&install.path, prefix]),
+            None => install.path.clone(),
+        };
+        let row = adw::ActionRow::builder()
+            .title(install.name.as_str())
+            .subtitle(subtitle)
+            .build();
+        let remove_button = Button::from_icon_name("user-trash-symbolic");
+        remove_button.set_tooltip_text(Some(&tr("Remove")));
+        remove_button.set_valign(gtk4::Align::Center);
+        remove_button.add_css_class("flat");
+
+        let app_state_clone = app_state.clone();
+        let group_clone = group.clone();
+        let rows_clone = rows.clone();
+        remove_button.connect_clicked(move |_| {
+            update_settings(&app_state_clone, |settings| {
+                settings.game_installs.retain(|i| i.name != install.name);
+                if settings.last_game_install.as_deref() == Some(install.name.as_str()) {
+                    settings.last_game_install = None;
+                }
+            });
+            fill_game_installs_group(&app_state_clone, &group_clone, rows_clone.clone());
+        });
+
+        row.add_suffix(&remove_button);
+        group.add(&row);
+        rows.borrow_mut().push(row);
+    }
+}
+
+// Reloads the settings when config.yaml is edited by hand or synced from another
+// machine, so the next save doesn't overwrite those edits. Our own saves are told apart
+// by comparing the file with the settings in memory.
+fn watch_settings_file(app_state: &Rc<AppState>, window: &ApplicationWindow) {
+    let file = gio::File::for_path(UserSettings::config_file());
+    let monitor = match file.monitor_file(gio::FileMonitorFlags::NONE, gio::Cancellable::NONE) {
+        Ok(monitor) => monitor,
+        Err(e) => {
+            log_warn!("settings", "Cannot watch the settings file: {}", e);
+            return;
+        }
+    };
+
+    let app_state_weak = Rc::downgrade(app_state);
+    let window = window.clone();
+    monitor.connect_changed(move |_, _, _, event| {
+        if !matches!(
+            event,
+            gio::FileMonitorEvent::ChangesDoneHint | gio::FileMonitorEvent::Created
+        ) {
+            return;
+        }
+        let Some(app_state) = app_state_weak.upgrade() else {
+            return;
+        };
+        if app_state.settings.lock().unwrap().matches_file() {
+            return;
+        }
+
+        match UserSettings::load() {
+            Ok(reloaded) => {
+                log_info!("settings", "Settings file changed on disk, reloaded");
+                *app_state.settings.lock().unwrap() = reloaded;
+                apply_reloaded_settings(&app_state, &window);
+                app_state
+                    .toast_overlay
+                    .add_toast(adw::Toast::new(&tr("Settings reloaded from disk")));
+            }
+            Err(e) => {
+                log_warn!("settings", "Ignoring unreadable settings file: {:#}", e);
+                app_state.toast_overlay.add_toast(adw::Toast::new(&tr(
+                    "The settings file has errors, keeping the current settings",
+                )));
+            }
+        }
+    });
+    app_state.settings_monitor.replace(Some(monitor));
+}
+
+// Brings the window in line with settings that were replaced wholesale
+fn apply_reloaded_settings(app_state: &AppState, window: &ApplicationWindow) {
+    let (color_scheme, merge_unstable, high_contrast, latency_palette) = {
+        let settings = app_state.settings.lock().unwrap();
+        (settings.color_scheme, settings.merge_unstable, settings.high_contrast, settings.latency_palette)
+    };
+    theme::apply_color_scheme(color_scheme);
+    theme::set_high_contrast(high_contrast);
+    theme::set_latency_palette(latency_palette);
+    refresh_spelled_out(&app_state.list_store);
+    apply_layout_settings(app_state, window);
+    refresh_warning_symbols(&app_state.list_store, merge_unstable);
+    refresh_profiles(app_state);
+    app_state.settings.lock().unwrap().proxy.activate();
+    app_state.settings.lock().unwrap().dns.activate();
+    refresh_http_api(app_state);
+}
+
+// Remembers the window size, filter and scroll offset for the next launch
+fn save_window_state(app_state: &Rc<AppState>, window: &ApplicationWindow) {
+    let (width, height) = window.default_size();
+    let window_state = WindowState {
+        width,
+        height,
+        maximized: window.is_maximized(),
+        filter_text: app_state.search_entry.text().to_string(),
+        scroll_position: app_state.region_scroll.vadjustment().value(),
+    };
+    update_settings(app_state, |settings| settings.window_state = window_state);
+}
+
+// Applies a change to the user settings, persists it and refreshes dependent UI
+fn update_settings(app_state: &Rc<AppState>, update: impl FnOnce(&mut UserSettings)) {
+    let mut settings = app_state.settings.lock().unwrap();
+    update(&mut settings);
+    let _ = settings.save();
+
+    // Refresh the warning symbols in the list view
+    refresh_warning_symbols(&app_state.list_store, settings.merge_unstable);
+}
+
+// Saves game folders found by `games::detect` that are not listed yet, named after their
+// launcher. Returns how many were added.
+fn add_detected_installs(app_state: &Rc<AppState>) -> usize {
+    let detected = games::detect();
+    let mut added = 0;
+    update_settings(app_state, |settings| {
+        for install in detected {
+            let path = install.path.to_string_lossy().to_string();
+            if settings.game_installs.iter().any(|i| i.path == path) {
+                continue;
+            }
+            let mut name = install.launcher.to_string();
+            let mut n = 2;
+            while settings.game_installs.iter().any(|i| i.name == name) {
+                name = format!("{} {}", install.launcher, n);
+                n += 1;
+            }
+            log_info!("games", "Found {} install at {}", install.launcher, path);
+            settings.game_installs.push(GameInstall {
+                name,
+                path,
+                prefix: install.prefix.map(|p| p.to_string_lossy().to_string()),
+                changes: None,
+            });
+            added += 1;
+        }
+    });
+    added
+}
+
+// Remembers what the splash art and movie options changed in a game folder, after each
+// change, so `game_update_banner` can tell when an update undid it
+fn remember_game_changes(app_state: &Rc<AppState>, game_path: &std::path::Path) {
+    let changes = match integrity::snapshot(game_path) {
+        Ok(changes) => Some(changes).filter(|changes| !changes.is_empty()),
+        Err(e) => {
+            log_warn!("games", "Failed to remember the game folder changes: {:#}", e);
+            return;
+        }
+    };
+    update_settings(app_state, |settings| {
+        let install = settings
+            .game_installs
+            .iter_mut()
+            .find(|i| std::path::Path::new(i.path.trim()) == game_path);
+        if let Some(install) = install {
+            install.changes = changes;
+        }
+    });
+}
+
+// Runs `on_selected` with the game folder to modify. With several installs the user
+// picks one, preselecting the one used last.
+fn with_game_install(
+    app_state: &Rc<AppState>,
+    window: &ApplicationWindow,
+    on_selected: impl FnOnce(std::path::PathBuf) + 'static,
+) {
+    if app_state.settings.lock().unwrap().game_installs.is_empty() {
+        add_detected_installs(app_state);
+    }
+    let (installs, last) = {
+        let settings = app_state.settings.lock().unwrap();
+        (settings.game_installs.clone(), settings.last_game_install.clone())
+    };
+
+    let use_install = {
+        let app_state = app_state.clone();
+        let window = window.clone();
+        move |install: GameInstall| {
+            let path = std::path::PathBuf::from(install.path.trim());
+            if !is_valid_game_folder(&path) {
+                show_error_dialog(
+                    &window,
+                    &tr("Invalid game folder"),
+                    &trf("The folder of \"{}\" is not named 'Dead by Daylight' or 'DeadByDaylight'. Please fix it in Options → Program settings.", &[&install.name]),
+                );
+                return;
+            }
+            update_settings(&app_state, |settings| settings.last_game_install = Some(install.name));
+            on_selected(path);
+        }
+    };
+
+    match installs.len() {
+        0 => show_info_dialog(
+            window,
+            &tr("Game folder required"),
+            &tr("No Steam, Heroic or Lutris install of the game was found. Please set the game folder in Options → Program settings.\n\nTip: In Steam, right-click Dead by Daylight → Manage → Browse local files. The folder that opens is the one you should select."),
+        ),
+        1 => use_install(installs[0].clone()),
+        _ => {
+            let dialog = MessageDialog::new(
+                Some(window),
+                gtk4::DialogFlags::MODAL,
+                MessageType::Question,
+                ButtonsType::None,
+                tr("Choose Game Installation"),
+            );
+            dialog.set_secondary_text(Some(&tr("Which installation should be changed?")));
+            let names: Vec<&str> = installs.iter().map(|i| i.name.as_str()).collect();
+            let dropdown = gtk4::DropDown::from_strings(&names);
+            if let Some(index) = installs.iter().position(|i| Some(&i.name) == last.as_ref()) {
+                dropdown.set_selected(index as u32);
+            }
+            if let Some(message_area) = dialog.message_area().downcast_ref::<GtkBox>() {
+                message_area.append(&dropdown);
+            }
+            dialog.add_button(&tr("Cancel"), ResponseType::Cancel);
+            dialog.add_button(&tr("Continue"), ResponseType::Accept);
+            dialog.set_default_response(ResponseType::Accept);
+
+            let use_install = RefCell::new(Some(use_install));
+            dialog.run_async(move |dialog, response| {
+                dialog.close();
+                if response != ResponseType::Accept {
+                    return;
+                }
+                let install = installs.get(dropdown.selected() as usize).cloned();
+                if let (Some(install), Some(use_install)) = (install, use_install.take()) {
+                    use_install(install);
+                }
+            });
+        }
+    }
+}
+
+fn is_valid_game_folder(path: &std::path::Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .map(games::is_game_folder_name)
+        .unwrap_or(false)
+}
+
+fn show_info_dialog(parent: &impl IsA<gtk4::Window>, title: &str, message: &str) {
+    let dialog = MessageDialog::new(
+        Some(parent),
+        gtk4::DialogFlags::MODAL,
+        MessageType::Info,
+        ButtonsType::Ok,
+        title,
+    );
+    dialog.set_secondary_text(Some(message));
+    dialog.run_async(|dialog, _| dialog.close());
+}
+
+fn show_error_dialog(parent: &impl IsA<gtk4::Window>, title: &str, message: &str) {
+    let dialog = MessageDialog::new(
+        Some(parent),
+        gtk4::DialogFlags::MODAL,
+        MessageType::Error,
+        ButtonsType::Ok,
+        title,
+    );
+    dialog.set_secondary_text(Some(message));
+    dialog.run_async(|dialog, _| dialog.close());
+}
+
+fn start_ping_timer(app_state: Rc<AppState>) {
+    let delay = app_state.ping_scheduler.borrow().interval();
+    schedule_ping_round(app_state, delay);
+}
+
+fn schedule_ping_round(app_state: Rc<AppState>, delay: Duration) {
+    let app_state_clone = app_state.clone();
+    let source = glib::timeout_add_local_once(delay, move || {
+        app_state_clone.ping_source.borrow_mut().take();
+        run_ping_round(app_state_clone);
+    });
+    if let Some(previous) = app_state.ping_source.replace(Some(source)) {
+        previous.remove();
+    }
+}
+
+// Tightens the probe interval again and, if the loop had backed off, probes right away
+// instead of waiting out the long delay.
+fn request_fresh_pings(app_state: &Rc<AppState>) {
+    let was_relaxed = app_state.ping_scheduler.borrow_mut().notify_interaction();
+    if was_relaxed && !app_state.ping_in_flight.get() {
+        schedule_ping_round(app_state.clone(), Duration::ZERO);
+    }
+}
+
+fn run_ping_round(app_state: Rc<AppState>) {
+    app_state.ping_in_flight.set(true);
+    refresh_ping_status(&app_state);
+    let targets = app_state.ping_targets.clone();
+    let runtime = app_state.tokio_runtime.clone();
+
+    // Spawn work on tokio runtime in background thread
+    glib::spawn_future_local(async move {
+        let latency_results = runtime
+            .spawn(async move { ping::ping_all(&targets).await })
+            .await
+            .unwrap();
+
+        // Update the UI on the main thread
+        let blocked_hosts = app_state.hosts_manager.get_blocked_hostnames();
+        for row in region_rows(&app_state.list_store) {
+            let name = row.name();
+            if is_region_blocked_by_hosts(&name, &app_state.regions, &app_state.blocked_regions, &blocked_hosts) {
+                row.set_latency(tr("disconnected"));
+                row.set_severity("latency-none");
+            } else if let Some(&latency) = latency_results.get(&name) {
+                let latency_text = if latency >= 0 {
+                    trf("{} ms", &[&latency])
+                } else {
+                    tr("disconnected")
+                };
+                row.set_latency(latency_text);
+                row.set_severity(get_severity_for_latency(latency));
+            }
+        }
+
+        app_state.ping_in_flight.set(false);
+        let next = app_state.ping_scheduler.borrow_mut().record_results(&latency_results);
+        plugins::broadcast_latencies(&latency_results);
+        community::record_pings(&latency_results);
+        app_state.ping_history.borrow_mut().record(&latency_results);
+        check_latency_alert(&app_state, &latency_results);
+        *app_state.latencies.borrow_mut() = latency_results;
+        refresh_ping_status(&app_state);
+        schedule_ping_round(app_state, next);
+    });
+}
+
+// Warns once per match when its server gets slower than the alert threshold, the
+// window is usually behind the game by then
+fn check_latency_alert(app_state: &AppState, latencies: &HashMap<String, i64>) {
+    let region = app_state.last_seen.lock().unwrap().as_ref().and_then(|(_, region)| region.clone());
+    let Some(region) = region else {
+        app_state.latency_alerted.replace(None);
+        return;
+    };
+    let preferences = app_state.settings.lock().unwrap().notifications.clone();
+    let Some(&ms) = latencies.get(&region) else {
+        return;
+    };
+    if ms <= i64::from(preferences.latency_threshold_ms)
+        || app_state.latency_alerted.borrow().as_deref() == Some(region.as_str())
+    {
+        return;
+    }
+    app_state.latency_alerted.replace(Some(region.clone()));
+    log_info!("ping", "{} is at {} ms during the match", region, ms);
+    if let Some(app) = gio::Application::default() {
+        post_notification(
+            &app,
+            &preferences,
+            NotificationKind::LatencyAlert,
+            "latency-alert",
+            &tr("High latency"),
+            &trf("{} is at {} ms, above your alert threshold of {} ms.", &[&region, &ms, &preferences.latency_threshold_ms]),
+        );
+    }
+}
+
+// System answer (hosts file included) and DNS answer of a hostname
+type HostAddresses = (Option<std::net::Ipv4Addr>, Option<std::net::Ipv4Addr>);
+
+// Fills the addresses column with what programs on this machine get for each hostname,
+// 0.0.0.0 while blocked, and what the DNS servers answer, which is where a redirect would
+// point. Nothing is looked up while the column is hidden.
+fn refresh_region_addresses(app_state: &Rc<AppState>) {
+    if !app_state.settings.lock().unwrap().show_addresses {
+        return;
+    }
+    let hostnames: Vec<String> = region_rows(&app_state.list_store)
+        .filter_map(|row| region_hosts(app_state, &row.name()))
+        .flatten()
+        .collect();
+    let runtime = app_state.tokio_runtime.clone();
+    let app_state = app_state.clone();
+    glib::spawn_future_local(async move {
+        let results: HashMap<String, HostAddresses> = runtime
+            .spawn(async move {
+                let tasks: Vec<_> = hostnames
+                    .into_iter()
+                    .map(|hostname| {
+                        tokio::spawn(async move {
+                            let (system, real) = tokio::join!(dns::system_ipv4(&hostname), dns::resolve_ipv4(&hostname));
+                            (hostname, (system.ok(), real.ok()))
+                        })
+                    })
+                    .collect();
+                let mut results = HashMap::new();
+                for task in tasks {
+                    if let Ok((hostname, addresses)) = task.await {
+                        results.insert(hostname, addresses);
+                    }
+                }
+                results
+            })
+            .await
+            .unwrap_or_default();
+
+        for row in region_rows(&app_state.list_store) {
+            let Some(hosts) = region_hosts(&app_state, &row.name()) else { continue; };
+            let mut shown: Vec<String> = Vec::new();
+            let mut lines = Vec::new();
+            let mut blocked = false;
+            for host in &hosts {
+                let (system, real) = results.get(host).copied().unwrap_or_default();
+                blocked |= system.is_some_and(|ip| ip.is_unspecified()) || real.is_some_and(|ip| ip.is_unspecified());
+                let shown_ip = system.map(|ip| ip.to_string()).unwrap_or_else(|| "–".to_string());
+                if !shown.contains(&shown_ip) {
+                    shown.push(shown_ip);
+                }
+                lines.push(match (system, real) {
+                    (_, Some(real)) if real.is_unspecified() => trf("{}: blocked by the DNS server", &[host]),
+                    (Some(system), Some(real)) if system.is_unspecified() => {
+                        trf("{}: 0.0.0.0, blocked (DNS answers {})", &[host, &real])
+                    }
+                    (Some(system), Some(real)) if system != real => trf("{}: {} (DNS answers {})", &[host, &system, &real]),
+                    (Some(system), _) => format!("{}: {}", host, system),
+                    (None, Some(real)) => trf("{}: not resolved here (DNS answers {})", &[host, &real]),
+                    (None, None) => trf("{}: could not be resolved", &[host]),
+                });
+            }
+            row.set_addresses(shown.join(", "));
+            row.set_addresses_tooltip(lines.join("\n"));
+            row.set_addresses_blocked(blocked);
+        }
+    });
+}
+
+// Hostnames of a row, blocked regions included; None for dividers
+fn region_hosts(app_state: &AppState, name: &str) -> Option<Vec<String>> {
+    app_state
+        .regions
+        .get(name)
+        .or_else(|| app_state.blocked_regions.get(name))
+        .map(|info| info.hosts.clone())
+}
+
+fn is_region_blocked_by_hosts(
+    region_key: &str,
+    regions: &HashMap<String, RegionInfo>,
+    blocked_regions: &HashMap<String, RegionInfo>,
+    blocked_hosts: &HashSet<String>,
+) -> bool {
+    if blocked_hosts.is_empty() {
+        return false;
+    }
+
+    if let Some(info) = regions.get(region_key) {
+        return info.hosts.iter().any(|h| blocked_hosts.contains(&h.to_lowercase()));
+    }
+
+    if let Some(info) = blocked_regions.get(region_key) {
+        return info.hosts.iter().any(|h| blocked_hosts.contains(&h.to_lowercase()));
+    }
+
+    false
+}
+
+fn format_update_tooltip(last_update: DateTime<Local>) -> String {
+    let seconds = (Local::now() - last_update).num_seconds().max(0);
+    let time = last_update.format("%-I:%M%p").to_string();
+    let time = time.replace("AM", "ᴀᴍ").replace("PM", "ᴘᴍ");
+    trf(
+        "Most recent connection: {}s ago, at {}\n\nThis is the region that Dead by Daylight chose\nwhen connecting you to their game.",
+        &[&seconds, &time],
+    )
+}
@@ -0,0 +1,107 @@
+// Bundled help pages, written as a small subset of Markdown:
+// "# " starts a topic, "## " a question within it, paragraphs and "- " bullets below.
+const HELP_MD: &str = include_str!("../help.md");
+
+pub struct HelpEntry {
+    pub question: String,
+    pub answer: String,
+}
+
+pub struct HelpTopic {
+    pub title: String,
+    pub entries: Vec<HelpEntry>,
+}
+
+pub fn topics() -> Vec<HelpTopic> {
+    let mut topics: Vec<HelpTopic> = Vec::new();
+
+    for line in HELP_MD.lines() {
+        if let Some(title) = line.strip_prefix("# ") {
+            topics.push(HelpTopic {
+                title: title.trim().to_string(),
+                entries: Vec::new(),
+            });
+        } else if let Some(question) = line.strip_prefix("## ") {
+            if let Some(topic) = topics.last_mut() {
+                topic.entries.push(HelpEntry {
+                    question: question.trim().to_string(),
+                    answer: String::new(),
+                });
+            }
+        } else if let Some(entry) = topics.last_mut().and_then(|t| t.entries.last_mut()) {
+            entry.answer.push_str(line);
+            entry.answer.push('\n');
+        }
+    }
+
+    for entry in topics.iter_mut().flat_map(|t| t.entries.iter_mut()) {
+        entry.answer = entry.answer.trim().to_string();
+    }
+
+    topics
+}
+
+// Converts an answer to Pango markup: **bold**, `code`, [text](url), "- " and "* "
+// bullets and "#" headings, which GitHub release notes use
+pub fn to_pango(markdown: &str) -> String {
+    markdown
+        .lines()
+        .map(|line| {
+            let line = line.trim_end();
+            if let Some(item) = line.strip_prefix("- ").or_else(|| line.strip_prefix("* ")) {
+                format!("• {}", inline_to_pango(item))
+            } else if line.starts_with('#') {
+                format!("<b>{}</b>", inline_to_pango(line.trim_start_matches('#').trim()))
+            } else {
+                inline_to_pango(line)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn inline_to_pango(text: &str) -> String {
+    let mut out = String::new();
+    let mut rest = text;
+    let mut bold = false;
+
+    while let Some(c) = rest.chars().next() {
+        if let Some(after) = rest.strip_prefix("**") {
+            out.push_str(if bold { "</b>" } else { "<b>" });
+            bold = !bold;
+            rest = after;
+        } else if let Some((code, after)) = rest.strip_prefix('`').and_then(|r| r.split_once('`')) {
+            out.push_str(&format!("<tt>{}</tt>", escape(code)));
+            rest = after;
+        } else if let Some((link, after)) = parse_link(rest) {
+            out.push_str(&format!(
+                "<a href=\"{}\">{}</a>",
+                escape(link.1),
+                escape(link.0)
+            ));
+            rest = after;
+        } else {
+            out.push_str(&escape(&c.to_string()));
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+
+    if bold {
+        out.push_str("</b>");
+    }
+    out
+}
+
+fn parse_link(text: &str) -> Option<((&str, &str), &str)> {
+    let inner = text.strip_prefix('[')?;
+    let (label, after_label) = inner.split_once("](")?;
+    let (url, after) = after_label.split_once(')')?;
+    Some(((label, url), after))
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,158 @@
+use serde_json::{Map, Value};
+use std::io::Write;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+// User scripts for integrations the app doesn't ship, like smart lights or OBS scene
+// switches. Every executable in ~/.config/make-your-choice/hooks/<event>/ runs on that
+// event, in name order. The event data arrives both as JSON on stdin and as MYC_*
+// environment variables, lists joined with commas:
+//
+//   pre-apply, post-apply   {"event": "post-apply", "mode": "gatekeep", "servers": [...]}
+//   revert                  {"event": "revert"}
+//   match-detected          {"event": "match-detected", "ip": "...", "region": "..."}
+//   match-ended             the same as match-detected, for the match that ended
+#[derive(Debug, Clone, Copy)]
+pub enum Event {
+    PreApply,
+    PostApply,
+    Revert,
+    MatchDetected,
+    MatchEnded,
+}
+
+impl Event {
+    pub fn id(&self) -> &'static str {
+        match self {
+            Event::PreApply => "pre-apply",
+            Event::PostApply => "post-apply",
+            Event::Revert => "revert",
+            Event::MatchDetected => "match-detected",
+            Event::MatchEnded => "match-ended",
+        }
+    }
+}
+
+// A hook that hangs must not hold up applying
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+// Data for apply events
+pub fn apply_data(mode: &str, servers: &[String]) -> Value {
+    serde_json::json!({ "mode": mode, "servers": servers })
+}
+
+// Data for match events; region is null when it isn't a known region
+pub fn match_data(ip: &str, region: Option<&str>) -> Value {
+    serde_json::json!({ "ip": ip, "region": region })
+}
+
+// Runs the hooks one after another and waits for them. Pre-apply hooks run this way
+// so they finish before the hosts file changes. Plugins hear about the event as well.
+pub fn run(event: Event, data: Value) {
+    crate::plugins::broadcast(event.id(), data.clone());
+    run_hooks(event, data);
+}
+
+// Runs the hooks on a background thread without waiting
+pub fn spawn(event: Event, data: Value) {
+    crate::plugins::broadcast(event.id(), data.clone());
+    if find(event).is_empty() {
+        return;
+    }
+    std::thread::spawn(move || run_hooks(event, data));
+}
+
+fn run_hooks(event: Event, data: Value) {
+    let hooks = find(event);
+    if hooks.is_empty() {
+        return;
+    }
+
+    let mut payload = match data {
+        Value::Object(map) => map,
+        _ => Map::new(),
+    };
+    payload.insert("event".to_string(), Value::from(event.id()));
+    let env = environment(&payload);
+    let stdin = Value::Object(payload).to_string();
+
+    for hook in hooks {
+        if let Err(e) = run_hook(&hook, &env, &stdin) {
+            log_warn!("hooks", "{} hook {}: {}", event.id(), hook.display(), e);
+        }
+    }
+}
+
+fn find(event: Event) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(crate::paths::hooks_dir().join(event.id())) else {
+        return Vec::new();
+    };
+    let mut hooks: Vec<PathBuf> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| is_executable(path))
+        .collect();
+    hooks.sort();
+    // As root (the launchd daemon, sudo on macOS) these would give root to anything that
+    // can write the user's config directory
+    if !hooks.is_empty() && crate::is_running_as_root() {
+        log_warn!("hooks", "Not running {} hooks as root", event.id());
+        return Vec::new();
+    }
+    hooks
+}
+
+fn is_executable(path: &Path) -> bool {
+    std::fs::metadata(path).is_ok_and(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+}
+
+// MYC_EVENT, MYC_MODE, MYC_SERVERS, MYC_IP, MYC_REGION, ... with empty strings for null
+fn environment(payload: &Map<String, Value>) -> Vec<(String, String)> {
+    payload
+        .iter()
+        .map(|(key, value)| {
+            let value = match value {
+                Value::Null => String::new(),
+                Value::String(s) => s.clone(),
+                Value::Array(items) => items
+                    .iter()
+                    .map(|item| item.as_str().map_or_else(|| item.to_string(), str::to_string))
+                    .collect::<Vec<_>>()
+                    .join(","),
+                other => other.to_string(),
+            };
+            (format!("MYC_{}", key.to_uppercase()), value)
+        })
+        .collect()
+}
+
+fn run_hook(hook: &Path, env: &[(String, String)], stdin: &str) -> Result<(), String> {
+    let mut child = Command::new(hook)
+        .envs(env.iter().map(|(key, value)| (key, value)))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("failed to start: {}", e))?;
+
+    // A hook that ignores stdin closes the pipe early, that's fine
+    if let Some(mut pipe) = child.stdin.take() {
+        let _ = pipe.write_all(stdin.as_bytes());
+    }
+
+    let started = Instant::now();
+    loop {
+        match child.try_wait() {
+            Ok(Some(status)) if status.success() => return Ok(()),
+            Ok(Some(status)) => return Err(format!("exited with {}", status)),
+            Ok(None) if started.elapsed() >= TIMEOUT => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(format!("killed after {} seconds", TIMEOUT.as_secs()));
+            }
+            Ok(None) => std::thread::sleep(Duration::from_millis(50)),
+            Err(e) => return Err(e.to_string()),
+        }
+    }
+}
@@ -0,0 +1,190 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::sync::OnceLock;
+
+// Translations are regular gettext catalogs under linux/po. They are embedded at build
+// time so the precompiled binary is translated without any installed locale files.
+// Regenerate the template and merge new strings into the catalogs with `make pot`.
+const CATALOGS: &[(&str, &str, &str)] = &[
+    ("de", "Deutsch", include_str!("../po/de.po")),
+    ("es", "Español", include_str!("../po/es.po")),
+    ("pt_BR", "Português (Brasil)", include_str!("../po/pt_BR.po")),
+    ("ar", "العربية", include_str!("../po/ar.po")),
+];
+
+// Languages written right to left; the window layout is mirrored for these
+const RTL_LANGUAGES: &[&str] = &["ar", "fa", "he", "ur"];
+
+static CATALOG: OnceLock<(String, HashMap<String, String>)> = OnceLock::new();
+
+// Loads the catalog for `language` ("" follows the system locale). Call once, before any
+// string is translated; later calls are ignored.
+pub fn init(language: &str) {
+    let wanted = if language.is_empty() {
+        system_languages()
+    } else {
+        vec![language.to_string()]
+    };
+
+    let found = wanted.iter().find_map(|lang| {
+        let base = lang.split('_').next().unwrap_or(lang);
+        CATALOGS
+            .iter()
+            .find(|(code, _, _)| code == lang)
+            .or_else(|| CATALOGS.iter().find(|(code, _, _)| *code == base))
+    });
+
+    let loaded = match found {
+        Some((code, _, po)) => (code.to_string(), parse_po(po)),
+        None => ("en".to_string(), HashMap::new()),
+    };
+    let _ = CATALOG.set(loaded);
+}
+
+// Whether the language in use is written right to left
+pub fn is_rtl() -> bool {
+    let code = CATALOG.get().map(|(code, _)| code.as_str()).unwrap_or("en");
+    RTL_LANGUAGES.contains(&code.split('_').next().unwrap_or(code))
+}
+
+// (code, native name) of every bundled translation
+#[cfg(feature = "gui")]
+pub fn available_languages() -> Vec<(&'static str, &'static str)> {
+    CATALOGS.iter().map(|(code, name, _)| (*code, *name)).collect()
+}
+
+pub fn tr(msgid: &str) -> String {
+    CATALOG
+        .get()
+        .and_then(|(_, messages)| messages.get(msgid))
+        .cloned()
+        .unwrap_or_else(|| msgid.to_string())
+}
+
+// Translates and fills each "{}" placeholder in order. In right-to-left languages the
+// values are wrapped in Unicode isolates, so server names, IPs and numbers keep their
+// own direction instead of reordering the surrounding sentence.
+pub fn trf(msgid: &str, args: &[&dyn Display]) -> String {
+    let template = tr(msgid);
+    let isolate = is_rtl();
+    let mut out = String::with_capacity(template.len());
+    let mut args = args.iter();
+    let mut rest = template.as_str();
+
+    while let Some(pos) = rest.find("{}") {
+        out.push_str(&rest[..pos]);
+        match args.next() {
+            Some(arg) if isolate => out.push_str(&format!("\u{2068}{}\u{2069}", arg)),
+            Some(arg) => out.push_str(&arg.to_string()),
+            None => out.push_str("{}"),
+        }
+        rest = &rest[pos + 2..];
+    }
+    out.push_str(rest);
+    out
+}
+
+// Preference order from the usual gettext environment variables, e.g. ["pt_BR", "pt"]
+fn system_languages() -> Vec<String> {
+    if let Ok(list) = std::env::var("LANGUAGE") {
+        let languages: Vec<String> = list
+            .split(':')
+            .filter(|lang| !lang.is_empty())
+            .map(strip_locale)
+            .collect();
+        if !languages.is_empty() {
+            return languages;
+        }
+    }
+
+    ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|value| !value.is_empty())
+        .map(|value| vec![strip_locale(&value)])
+        .unwrap_or_default()
+}
+
+// "pt_BR.UTF-8@euro" -> "pt_BR"
+fn strip_locale(locale: &str) -> String {
+    locale
+        .split(['.', '@'])
+        .next()
+        .unwrap_or(locale)
+        .to_string()
+}
+
+// Minimal .po reader: msgid/msgstr pairs with continuation lines. Fuzzy and untranslated
+// entries are skipped so they fall back to English.
+fn parse_po(po: &str) -> HashMap<String, String> {
+    enum Field {
+        None,
+        Id,
+        Str,
+    }
+
+    let mut messages = HashMap::new();
+    let mut msgid = String::new();
+    let mut msgstr = String::new();
+    let mut fuzzy = false;
+    let mut field = Field::None;
+
+    let mut flush = |msgid: &mut String, msgstr: &mut String, fuzzy: &mut bool| {
+        if !msgid.is_empty() && !msgstr.is_empty() && !*fuzzy {
+            messages.insert(std::mem::take(msgid), std::mem::take(msgstr));
+        }
+        msgid.clear();
+        msgstr.clear();
+        *fuzzy = false;
+    };
+
+    for line in po.lines().map(str::trim) {
+        if line.starts_with("#,") && line.contains("fuzzy") {
+            flush(&mut msgid, &mut msgstr, &mut fuzzy);
+            fuzzy = true;
+            field = Field::None;
+        } else if let Some(value) = line.strip_prefix("msgid ") {
+            if !fuzzy {
+                flush(&mut msgid, &mut msgstr, &mut fuzzy);
+            }
+            msgid = unquote(value);
+            field = Field::Id;
+        } else if let Some(value) = line.strip_prefix("msgstr ") {
+            msgstr = unquote(value);
+            field = Field::Str;
+        } else if line.starts_with('"') {
+            match field {
+                Field::Id => msgid.push_str(&unquote(line)),
+                Field::Str => msgstr.push_str(&unquote(line)),
+                Field::None => {}
+            }
+        } else if line.is_empty() {
+            flush(&mut msgid, &mut msgstr, &mut fuzzy);
+            field = Field::None;
+        }
+    }
+    flush(&mut msgid, &mut msgstr, &mut fuzzy);
+
+    messages
+}
+
+fn unquote(value: &str) -> String {
+    let value = value.trim();
+    let inner = value.strip_prefix('"').unwrap_or(value);
+    let inner = inner.strip_suffix('"').unwrap_or(inner);
+    let mut out = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some('t') => out.push('\t'),
+            Some(other) => out.push(other),
+            None => {}
+        }
+    }
+    out
+}
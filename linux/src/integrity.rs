@@ -0,0 +1,394 @@
+use crate::errors::{self, ErrorKind};
+use crate::i18n::{tr, trf};
+use crate::settings::GameChanges;
+use crate::{games, movies, splash};
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+// What Make Your Choice changed in a game folder, for Options → Verify game changes.
+// Only two kinds of changes are ever made: the splash screen is replaced with the
+// original kept as SplashScreen.png.bak, and skipped movies are renamed to "<name>.bak".
+// The game's executables and archives are never touched.
+//
+// Every change goes through `replace_file`, `move_away` and `restore_file`, which keep a
+// manifest with the SHA-256 of each original. A change made twice keeps the first
+// backup, a file the game put back replaces the old backup, and a backup that changed
+// since is never restored over the game's file. Backups made before the manifest
+// existed are still found by their names.
+//
+// A game update puts the originals back without removing the backups. What was changed
+// is remembered per install (see `snapshot`), so the next start can notice and offer to
+// make the changes again.
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+// Bink 2, the format of the game's movies
+const BINK2_SIGNATURE: &[u8] = b"KB2";
+
+pub enum Change {
+    Splash,
+    // File name of a skipped movie
+    Movie(String),
+}
+
+pub struct Entry {
+    pub change: Change,
+    pub label: String,
+    // The file the game reads, relative to the game folder
+    pub file: PathBuf,
+    pub backup: PathBuf,
+    // The backup is there and matches the original, or starts like a file of its kind
+    // when it was made before the manifest
+    pub backup_ok: bool,
+}
+
+// Backups by game folder, in data_dir/game-backups.json
+#[derive(Default, Serialize, Deserialize)]
+struct Manifest {
+    #[serde(default)]
+    folders: HashMap<String, Vec<Backup>>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct Backup {
+    // Both relative to the game folder
+    file: PathBuf,
+    backup: PathBuf,
+    // SHA-256 of the original
+    original: String,
+    // SHA-256 of what was written in its place, none when the file was only moved away
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    replacement: Option<String>,
+}
+
+fn manifest_file() -> PathBuf {
+    crate::paths::data_dir().join("game-backups.json")
+}
+
+fn load_manifest() -> Manifest {
+    fs::read_to_string(manifest_file())
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_manifest(manifest: &Manifest) -> Result<()> {
+    let path = manifest_file();
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).with_context(|| format!("Failed to create {:?}", dir))?;
+    }
+    fs::write(&path, serde_json::to_string_pretty(manifest)?)
+        .with_context(|| format!("Failed to write the backup manifest {:?}", path))
+}
+
+fn folder_key(game_path: &Path) -> String {
+    games::game_root(game_path).to_string_lossy().to_string()
+}
+
+fn relative(game_path: &Path, path: &Path) -> PathBuf {
+    path.strip_prefix(games::game_root(game_path)).unwrap_or(path).to_path_buf()
+}
+
+// The manifest entry of a file, if it was changed since the manifest exists
+fn recorded(game_path: &Path, file: &Path) -> Option<Backup> {
+    let file = relative(game_path, file);
+    load_manifest()
+        .folders
+        .remove(&folder_key(game_path))?
+        .into_iter()
+        .find(|backup| backup.file == file)
+}
+
+fn record(game_path: &Path, backup: Option<Backup>, file: &Path) -> Result<()> {
+    let file = relative(game_path, file);
+    let mut manifest = load_manifest();
+    let key = folder_key(game_path);
+    let backups = manifest.folders.entry(key.clone()).or_default();
+    backups.retain(|backup| backup.file != file);
+    backups.extend(backup);
+    if backups.is_empty() {
+        manifest.folders.remove(&key);
+    }
+    save_manifest(&manifest)
+}
+
+// Read in pieces, movies are hundreds of megabytes
+fn sha256(path: &Path) -> Result<String> {
+    let mut file = File::open(path).with_context(|| format!("Failed to read {:?}", path))?;
+    let mut checksum = glib::Checksum::new(glib::ChecksumType::Sha256).context("SHA-256 is unavailable")?;
+    let mut buffer = vec![0; 1 << 16];
+    loop {
+        let read = file.read(&mut buffer).with_context(|| format!("Failed to read {:?}", path))?;
+        if read == 0 {
+            break;
+        }
+        checksum.update(&buffer[..read]);
+    }
+    checksum.string().map(|hash| hash.to_string()).context("SHA-256 is unavailable")
+}
+
+// Replaces a game file with what `write` puts at the path, keeping the original as
+// `backup`. Doing it again only writes the file again.
+pub fn replace_file(
+    game_path: &Path,
+    file: &Path,
+    backup: &Path,
+    write: impl FnOnce(&Path) -> Result<()>,
+) -> Result<()> {
+    let entry = recorded(game_path, file);
+    let kept = if file.exists() {
+        let current = sha256(file)?;
+        let ours = match &entry {
+            Some(entry) => entry.replacement.as_deref() == Some(current.as_str()),
+            // A backup from before the manifest, the file is the earlier replacement
+            None => backup.exists(),
+        };
+        if !ours {
+            fs::rename(file, backup).with_context(|| format!("Failed to back up {:?}", file))?;
+            Some(Backup {
+                file: relative(game_path, file),
+                backup: relative(game_path, backup),
+                original: current,
+                replacement: None,
+            })
+        } else if !backup.exists() {
+            // The original went missing, there is nothing left to keep
+            None
+        } else {
+            match entry {
+                Some(entry) => Some(entry),
+                None => Some(Backup {
+                    file: relative(game_path, file),
+                    backup: relative(game_path, backup),
+                    original: sha256(backup)?,
+                    replacement: None,
+                }),
+            }
+        }
+    } else {
+        entry.filter(|_| backup.exists())
+    };
+
+    write(file)?;
+    let entry = match kept {
+        Some(entry) => Some(Backup {
+            replacement: Some(sha256(file)?),
+            ..entry
+        }),
+        None => None,
+    };
+    record(game_path, entry, file)
+}
+
+// Moves a game file aside as `backup`, so the game goes without it. The file is always
+// the game's own, an older backup is replaced.
+pub fn move_away(game_path: &Path, file: &Path, backup: &Path) -> Result<()> {
+    let original = sha256(file)?;
+    if backup.exists() {
+        let _ = fs::remove_file(backup);
+    }
+    fs::rename(file, backup).with_context(|| format!("Failed to move {:?} aside", file))?;
+    record(
+        game_path,
+        Some(Backup {
+            file: relative(game_path, file),
+            backup: relative(game_path, backup),
+            original,
+            replacement: None,
+        }),
+        file,
+    )
+}
+
+// Puts the original back. When the game restored its own file in the meantime, that
+// one is newer and stays, only the backup is removed.
+pub fn restore_file(game_path: &Path, file: &Path, backup: &Path) -> Result<()> {
+    let name = file.file_name().unwrap_or_default().to_string_lossy().to_string();
+    if !backup.exists() {
+        return Err(errors::error(
+            ErrorKind::BackupMissing,
+            trf("There is no backup of {} to restore.", &[&name]),
+        ));
+    }
+
+    if let Some(entry) = recorded(game_path, file) {
+        if sha256(backup)? != entry.original {
+            bail!("The backup of {} changed since it was made and is left as it is", name);
+        }
+        let ours = match (&entry.replacement, file.exists()) {
+            (Some(replacement), true) => sha256(file)? == *replacement,
+            (None, true) => false,
+            (_, false) => true,
+        };
+        if !ours {
+            fs::remove_file(backup).with_context(|| format!("Failed to remove {:?}", backup))?;
+            return record(game_path, None, file);
+        }
+    }
+
+    if file.exists() {
+        let _ = fs::remove_file(file);
+    }
+    fs::rename(backup, file).with_context(|| format!("Failed to restore {:?}", file))?;
+    record(game_path, None, file)
+}
+
+fn starts_with(path: &Path, signature: &[u8]) -> bool {
+    let mut head = vec![0; signature.len()];
+    File::open(path).and_then(|mut file| file.read_exact(&mut head)).is_ok() && head == signature
+}
+
+// Whether the backup is the original that was moved there
+fn backup_ok(game_path: &Path, file: &Path, backup: &Path, signature: &[u8]) -> bool {
+    match recorded(game_path, file) {
+        Some(entry) => sha256(backup).is_ok_and(|hash| hash == entry.original),
+        None => starts_with(backup, signature),
+    }
+}
+
+// Every change currently in place, including recorded ones whose backup went missing
+pub fn scan(game_path: &Path) -> Result<Vec<Entry>> {
+    let mut entries = Vec::new();
+
+    let target = splash::target_path(game_path);
+    let backup = splash::backup_path(game_path);
+    if backup.exists() || recorded(game_path, &target).is_some() {
+        entries.push(Entry {
+            change: Change::Splash,
+            label: tr("Custom splash art"),
+            file: relative(game_path, &target),
+            backup_ok: backup_ok(game_path, &target, &backup, PNG_SIGNATURE),
+            backup,
+        });
+    }
+
+    let mut skipped: Vec<String> = movies::list(game_path)?
+        .into_iter()
+        .filter(|movie| movie.skipped)
+        .map(|movie| movie.file_name)
+        .collect();
+    let recorded_movies = load_manifest()
+        .folders
+        .remove(&folder_key(game_path))
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|backup| backup.replacement.is_none())
+        .filter_map(|backup| Some(backup.file.file_name()?.to_string_lossy().to_string()));
+    for file_name in recorded_movies {
+        // Skipped ones have no file, a movie that plays again was put back by the game
+        if !skipped.contains(&file_name) && !movies::movie_path(game_path, &file_name).exists() {
+            skipped.push(file_name);
+        }
+    }
+    for file_name in skipped {
+        let target = movies::movie_path(game_path, &file_name);
+        let backup = movies::backup_path(game_path, &file_name);
+        let movie = movies::Movie { file_name, skipped: true };
+        entries.push(Entry {
+            label: trf("{} (skipped)", &[&movie.label()]),
+            file: relative(game_path, &target),
+            backup_ok: backup_ok(game_path, &target, &backup, BINK2_SIGNATURE),
+            backup,
+            change: Change::Movie(movie.file_name),
+        });
+    }
+    Ok(entries)
+}
+
+// Puts every original back. Keeps going past failures and reports them together,
+// returns how many files were restored.
+pub fn restore_all(game_path: &Path) -> Result<usize> {
+    let mut restored = 0;
+    let mut failures = Vec::new();
+    for entry in scan(game_path)? {
+        let result = match &entry.change {
+            Change::Splash => splash::revert(game_path),
+            Change::Movie(file_name) => movies::restore(game_path, file_name),
+        };
+        match result {
+            Ok(()) => restored += 1,
+            Err(e) => failures.push(format!("{}: {:#}", entry.file.display(), e)),
+        }
+    }
+    if !failures.is_empty() {
+        bail!("{}", failures.join("\n"));
+    }
+    Ok(restored)
+}
+
+// Where the custom splash screen of a game folder is kept, for putting it back after an
+// update. Named after the folder, so installs don't share it.
+fn saved_splash_path(game_path: &Path) -> PathBuf {
+    let name = game_path.to_string_lossy().trim_matches('/').replace('/', "_");
+    crate::paths::data_dir().join("splash").join(format!("{}.png", name))
+}
+
+// What is changed in the game folder right now, keeping a copy of the custom splash
+pub fn snapshot(game_path: &Path) -> Result<GameChanges> {
+    let saved = saved_splash_path(game_path);
+    let custom_splash = splash::backup_path(game_path).exists();
+    if custom_splash {
+        if let Some(dir) = saved.parent() {
+            fs::create_dir_all(dir).with_context(|| format!("Failed to create {:?}", dir))?;
+        }
+        fs::copy(splash::target_path(game_path), &saved)
+            .with_context(|| format!("Failed to keep a copy of the splash screen in {:?}", saved))?;
+    } else {
+        let _ = fs::remove_file(&saved);
+    }
+
+    Ok(GameChanges {
+        build_id: games::build_id(game_path),
+        custom_splash,
+        skipped_movies: movies::list(game_path)?
+            .into_iter()
+            .filter(|movie| movie.skipped)
+            .map(|movie| movie.file_name)
+            .collect(),
+    })
+}
+
+// The remembered changes the game no longer has, after an update restored its files.
+// The splash screen counts when it differs from the kept copy.
+pub fn undone(game_path: &Path, changes: &GameChanges) -> Vec<Change> {
+    let mut undone = Vec::new();
+    if changes.custom_splash {
+        let saved = fs::read(saved_splash_path(game_path));
+        let current = fs::read(splash::target_path(game_path));
+        if let (Ok(saved), Ok(current)) = (saved, current) {
+            if saved != current {
+                undone.push(Change::Splash);
+            }
+        }
+    }
+    if let Ok(list) = movies::list(game_path) {
+        for movie in list {
+            if !movie.skipped && changes.skipped_movies.contains(&movie.file_name) {
+                undone.push(Change::Movie(movie.file_name));
+            }
+        }
+    }
+    undone
+}
+
+// Makes the undone changes again. The files the update wrote are the new originals and
+// replace the old backups, see `replace_file`.
+pub fn reapply(game_path: &Path, undone: &[Change]) -> Result<()> {
+    for change in undone {
+        match change {
+            Change::Splash => {
+                let target = splash::target_path(game_path);
+                replace_file(game_path, &target, &splash::backup_path(game_path), |path| {
+                    fs::copy(saved_splash_path(game_path), path)
+                        .with_context(|| format!("Failed to write {:?}", path))?;
+                    Ok(())
+                })?;
+            }
+            Change::Movie(file_name) => movies::skip(game_path, file_name)?,
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,10 @@
+use anyhow::{Context, Result};
+
+// Dead by Daylight's Steam app id
+pub const STEAM_APP_ID: &str = "381210";
+
+// Starts the game through Steam, which also handles Proton and the EAC runtime
+pub fn launch() -> Result<()> {
+    let url = format!("steam://rungameid/{}", STEAM_APP_ID);
+    open::that(&url).with_context(|| format!("Failed to open {}", url))
+}
@@ -0,0 +1,236 @@
+use crate::backend::{ActiveConfiguration, EnforcementBackend};
+use crate::region::{self, RegionInfo};
+use anyhow::{Context, Result};
+use std::collections::{HashMap, VecDeque};
+use std::fmt::Write;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::runtime::Runtime;
+
+// Prometheus endpoint of the background service (daemon.rs), off unless turned on in the
+// settings. GET /metrics returns
+//   myc_latency_milliseconds{region,code}   latest ping, missing while unreachable
+//   myc_server_up{region,code}              whether the latest ping got an answer
+//   myc_packet_loss_ratio{region,code}      share of the last 10 pings without an answer
+//   myc_pings_total / myc_ping_failures_total{region,code}
+//   myc_matches_total{region}               matches played per region, "unknown" otherwise
+//   myc_hosts_enforced{mode}                1 while a selection is applied
+//   myc_region_allowed{region,code}         0 for servers the selection blocks
+
+const LOSS_WINDOW: usize = 10;
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Default)]
+struct Counters {
+    latencies: HashMap<String, i64>,
+    pings: HashMap<String, u64>,
+    failures: HashMap<String, u64>,
+    recent: HashMap<String, VecDeque<bool>>,
+    matches: HashMap<String, u64>,
+}
+
+pub struct Metrics {
+    regions: HashMap<String, RegionInfo>,
+    backend: Arc<dyn EnforcementBackend>,
+    counters: Mutex<Counters>,
+}
+
+impl Metrics {
+    pub fn new(regions: HashMap<String, RegionInfo>, backend: Arc<dyn EnforcementBackend>) -> Self {
+        Self {
+            regions,
+            backend,
+            counters: Mutex::new(Counters::default()),
+        }
+    }
+
+    // One round of pings, -1 for servers that did not answer
+    pub fn record_pings(&self, results: &HashMap<String, i64>) {
+        let mut counters = self.counters.lock().unwrap();
+        for (name, &ms) in results {
+            let reached = ms >= 0;
+            *counters.pings.entry(name.clone()).or_default() += 1;
+            if !reached {
+                *counters.failures.entry(name.clone()).or_default() += 1;
+            }
+            let recent = counters.recent.entry(name.clone()).or_default();
+            recent.push_back(reached);
+            if recent.len() > LOSS_WINDOW {
+                recent.pop_front();
+            }
+        }
+        counters.latencies = results.clone();
+    }
+
+    pub fn record_match(&self, region: Option<&str>) {
+        let region = region.unwrap_or("unknown").to_string();
+        *self.counters.lock().unwrap().matches.entry(region).or_default() += 1;
+    }
+
+    fn render(&self) -> String {
+        let mut out = String::new();
+        let counters = self.counters.lock().unwrap();
+        let mut names: Vec<&String> = self.regions.keys().collect();
+        names.sort();
+        let labels = |name: &str| match self.regions.get(name).and_then(region::aws_region_code) {
+            Some(code) => format!("region=\"{}\",code=\"{}\"", escape(name), escape(code)),
+            None => format!("region=\"{}\"", escape(name)),
+        };
+
+        header(&mut out, "myc_latency_milliseconds", "gauge", "Latest ping to the server");
+        for name in &names {
+            if let Some(ms) = counters.latencies.get(*name).filter(|ms| **ms >= 0) {
+                let _ = writeln!(out, "myc_latency_milliseconds{{{}}} {}", labels(name), ms);
+            }
+        }
+        header(&mut out, "myc_server_up", "gauge", "Whether the latest ping got an answer");
+        for name in &names {
+            if let Some(ms) = counters.latencies.get(*name) {
+                let _ = writeln!(out, "myc_server_up{{{}}} {}", labels(name), u8::from(*ms >= 0));
+            }
+        }
+        header(&mut out, "myc_packet_loss_ratio", "gauge", "Share of the last pings without an answer");
+        for name in &names {
+            if let Some(recent) = counters.recent.get(*name).filter(|recent| !recent.is_empty()) {
+                let lost = recent.iter().filter(|reached| !**reached).count();
+                let _ = writeln!(
+                    out,
+                    "myc_packet_loss_ratio{{{}}} {}",
+                    labels(name),
+                    lost as f64 / recent.len() as f64
+                );
+            }
+        }
+        header(&mut out, "myc_pings_total", "counter", "Pings sent to the server");
+        for name in &names {
+            if let Some(count) = counters.pings.get(*name) {
+                let _ = writeln!(out, "myc_pings_total{{{}}} {}", labels(name), count);
+            }
+        }
+        header(&mut out, "myc_ping_failures_total", "counter", "Pings the server did not answer");
+        for name in &names {
+            let count = counters.failures.get(*name).copied().unwrap_or_default();
+            if counters.pings.contains_key(*name) {
+                let _ = writeln!(out, "myc_ping_failures_total{{{}}} {}", labels(name), count);
+            }
+        }
+        header(&mut out, "myc_matches_total", "counter", "Matches played per region");
+        let mut matches: Vec<(&String, &u64)> = counters.matches.iter().collect();
+        matches.sort();
+        for (region, count) in matches {
+            let _ = writeln!(out, "myc_matches_total{{region=\"{}\"}} {}", escape(region), count);
+        }
+        drop(counters);
+
+        let active = self.backend.status(&self.regions);
+        header(&mut out, "myc_hosts_enforced", "gauge", "Whether a selection is applied to the hosts file");
+        let _ = writeln!(
+            out,
+            "myc_hosts_enforced{{mode=\"{}\"}} {}",
+            active.mode_id(),
+            u8::from(!matches!(active, ActiveConfiguration::Inactive))
+        );
+        // Universal Redirect sends every server to the target, nothing is blocked
+        let allowed = |name: &str| match &active {
+            ActiveConfiguration::Gatekeep { allowed } => allowed.iter().any(|allowed| allowed == name),
+            _ => true,
+        };
+        header(&mut out, "myc_region_allowed", "gauge", "Whether the selection lets the server through");
+        for name in &names {
+            let _ = writeln!(out, "myc_region_allowed{{{}}} {}", labels(name), u8::from(allowed(name)));
+        }
+        out
+    }
+}
+
+fn header(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+}
+
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+// Serves until the returned task is aborted or the runtime shuts down
+pub fn start(
+    runtime: &Runtime,
+    address: &str,
+    port: u16,
+    metrics: Arc<Metrics>,
+) -> Result<tokio::task::JoinHandle<()>> {
+    let listener = std::net::TcpListener::bind((address, port))
+        .with_context(|| format!("Failed to listen on {}:{}", address, port))?;
+    listener.set_nonblocking(true)?;
+    let listener = {
+        let _guard = runtime.enter();
+        TcpListener::from_std(listener)?
+    };
+
+    log_info!("metrics", "Serving metrics on http://{}:{}/metrics", address, port);
+    Ok(runtime.spawn(async move {
+        loop {
+            match listener.accept().await {
+                Ok((stream, _)) => {
+                    let metrics = metrics.clone();
+                    tokio::spawn(async move {
+                        if let Err(e) = serve(stream, &metrics).await {
+                            log_warn!("metrics", "{:#}", e);
+                        }
+                    });
+                }
+                Err(e) => log_warn!("metrics", "Failed to accept a connection: {}", e),
+            }
+        }
+    }))
+}
+
+async fn serve(mut stream: TcpStream, metrics: &Arc<Metrics>) -> Result<()> {
+    let request_line = tokio::time::timeout(READ_TIMEOUT, read_request_line(&mut stream))
+        .await
+        .context("Timed out reading a request")??;
+    let mut parts = request_line.split_whitespace();
+    let target = (parts.next().unwrap_or_default(), parts.next().unwrap_or_default());
+
+    let (status, body) = match target {
+        ("GET", "/metrics") => {
+            // Reads the hosts file
+            let metrics = metrics.clone();
+            let body = tokio::task::spawn_blocking(move || metrics.render()).await?;
+            ("200 OK", body)
+        }
+        _ => ("404 Not Found", "Not found\n".to_string()),
+    };
+    let response = format!(
+        "HTTP/1.1 {}\r\n\
+         Content-Type: text/plain; version=0.0.4; charset=utf-8\r\n\
+         Content-Length: {}\r\n\
+         Connection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await?;
+    Ok(())
+}
+
+// Only the request line matters, the rest of the head is read and dropped
+async fn read_request_line(stream: &mut TcpStream) -> Result<String> {
+    let mut buffer = Vec::new();
+    let mut chunk = [0u8; 1024];
+    while !buffer.windows(4).any(|w| w == b"\r\n\r\n") {
+        if buffer.len() > 16 * 1024 {
+            anyhow::bail!("Request head too large");
+        }
+        let read = stream.read(&mut chunk).await?;
+        if read == 0 {
+            break;
+        }
+        buffer.extend_from_slice(&chunk[..read]);
+    }
+    let head = String::from_utf8_lossy(&buffer);
+    Ok(head.lines().next().unwrap_or_default().to_string())
+}
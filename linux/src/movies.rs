@@ -0,0 +1,86 @@
+use crate::errors::{self, ErrorKind};
+use crate::{games, integrity};
+use crate::i18n::{tr, trf};
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// Skipped movies are renamed to "<name>.bak"; the game then starts without them
+const BACKUP_SUFFIX: &str = ".bak";
+
+pub struct Movie {
+    // File name of the playable movie, e.g. "LoadingScreen.bk2"
+    pub file_name: String,
+    pub skipped: bool,
+}
+
+impl Movie {
+    // Friendlier name for the movies we know, the file name otherwise
+    pub fn label(&self) -> String {
+        match self.file_name.as_str() {
+            "LoadingScreen.bk2" => tr("Chapter trailer"),
+            name => name.trim_end_matches(".bk2").to_string(),
+        }
+    }
+}
+
+fn movies_dir(game_path: &Path) -> PathBuf {
+    games::content_dir(game_path).join("Movies")
+}
+
+pub fn movie_path(game_path: &Path, file_name: &str) -> PathBuf {
+    movies_dir(game_path).join(file_name)
+}
+
+// Where a skipped movie is kept
+pub fn backup_path(game_path: &Path, file_name: &str) -> PathBuf {
+    movies_dir(game_path).join(format!("{}{}", file_name, BACKUP_SUFFIX))
+}
+
+// Every movie under Content/Movies, whether it currently plays or is skipped
+pub fn list(game_path: &Path) -> Result<Vec<Movie>> {
+    let dir = movies_dir(game_path);
+    let entries = fs::read_dir(&dir).with_context(|| format!("Failed to read {:?}", dir))?;
+
+    let mut movies: Vec<Movie> = Vec::new();
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let (file_name, skipped) = match name.strip_suffix(BACKUP_SUFFIX) {
+            Some(original) => (original.to_string(), true),
+            None => (name, false),
+        };
+        if !file_name.ends_with(".bk2") {
+            continue;
+        }
+        // Both copies present means the game restored the file, so it plays again
+        match movies.iter_mut().find(|m| m.file_name == file_name) {
+            Some(existing) => existing.skipped &= skipped,
+            None => movies.push(Movie { file_name, skipped }),
+        }
+    }
+    movies.sort_by(|a, b| a.file_name.cmp(&b.file_name));
+    Ok(movies)
+}
+
+pub fn skip(game_path: &Path, file_name: &str) -> Result<()> {
+    let target_path = movie_path(game_path, file_name);
+    let backup_path = backup_path(game_path, file_name);
+
+    if !target_path.exists() {
+        anyhow::bail!("{} not found.", file_name);
+    }
+    integrity::move_away(game_path, &target_path, &backup_path)
+}
+
+pub fn restore(game_path: &Path, file_name: &str) -> Result<()> {
+    let target_path = movie_path(game_path, file_name);
+    let backup_path = backup_path(game_path, file_name);
+
+    if !backup_path.exists() {
+        return Err(errors::error(
+            ErrorKind::BackupMissing,
+            trf("There is no backup of {} to restore.", &[&file_name]),
+        ));
+    }
+    integrity::restore_file(game_path, &target_path, &backup_path)
+}
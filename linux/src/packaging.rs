@@ -0,0 +1,67 @@
+use std::path::Path;
+use std::process::Command;
+use std::sync::OnceLock;
+
+// How this copy of the app was installed, which decides how it should be updated
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InstallKind {
+    AppImage,
+    Flatpak { app_id: String },
+    // Owned by pacman, usually built from the AUR
+    Pacman { package: String },
+    Cargo,
+    Manual,
+}
+
+static KIND: OnceLock<InstallKind> = OnceLock::new();
+
+pub fn install_kind() -> &'static InstallKind {
+    KIND.get_or_init(|| {
+        let kind = detect();
+        log_info!("packaging", "Installed as {:?}", kind);
+        kind
+    })
+}
+
+fn detect() -> InstallKind {
+    if std::env::var_os("APPIMAGE").is_some() {
+        return InstallKind::AppImage;
+    }
+    if let Ok(app_id) = std::env::var("FLATPAK_ID") {
+        return InstallKind::Flatpak { app_id };
+    }
+    if Path::new("/.flatpak-info").exists() {
+        return InstallKind::Flatpak {
+            app_id: crate::APP_ID.to_string(),
+        };
+    }
+
+    let Ok(exe) = std::env::current_exe() else {
+        return InstallKind::Manual;
+    };
+    if exe.starts_with(dirs::home_dir().unwrap_or_default().join(".cargo")) {
+        return InstallKind::Cargo;
+    }
+    if Path::new("/var/lib/pacman").is_dir() {
+        let owner = Command::new("pacman").arg("-Qqo").arg(&exe).output();
+        if let Ok(output) = owner {
+            let package = String::from_utf8_lossy(&output.stdout).trim().to_string();
+            if output.status.success() && !package.is_empty() {
+                return InstallKind::Pacman { package };
+            }
+        }
+    }
+    InstallKind::Manual
+}
+
+// Command that updates a package manager install, None where the user downloads
+// releases themselves
+#[cfg(feature = "gui")]
+pub fn update_command(repo_url: Option<&str>) -> Option<String> {
+    match install_kind() {
+        InstallKind::Flatpak { app_id } => Some(format!("flatpak update {}", app_id)),
+        InstallKind::Pacman { package } => Some(format!("yay -Syu {}", package)),
+        InstallKind::Cargo => repo_url.map(|url| format!("cargo install --force --git {}", url)),
+        InstallKind::AppImage | InstallKind::Manual => None,
+    }
+}
@@ -0,0 +1,234 @@
+use crate::control::{self, Command, Requests};
+use crate::errors::ErrorKind;
+use rhai::{Array, CallFnOptions, Dynamic, Engine, EvalAltResult, Position, Scope, AST};
+use serde_json::{json, Value};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Mutex};
+use tokio::sync::oneshot;
+
+// Rhai scripts in ~/.config/make-your-choice/plugins/*.rhai that run alongside the window
+// or the background service, for workflows that don't need a feature of their own (say,
+// switching profiles once the average EU ping passes 120 ms). Each runs on a thread of
+// its own: the top level once at start, then the functions named after events, when the
+// script has them. `this` is a map kept between calls, for the script's own state.
+//
+// Events
+//   fn on_start(regions)          [#{name, code, group, stable}, ...]
+//   fn on_latencies(latencies)    #{"Europe (Ireland)": 31, ...}, () when unreachable
+//   fn on_pre_apply(data), on_post_apply(data), on_revert(data),
+//      on_match_detected(data), on_match_ended(data)
+//                                 the same data as the hook scripts (hooks.rs)
+//
+// Functions
+//   regions()                     the list on_start gets
+//   latencies()                   the latest ping round
+//   status()                      #{mode, allowed, target_ip}
+//   current_server()              #{ip, region}, () without a match
+//   apply(["eu-west-1", ...]), apply(servers, "gatekeep"), apply_profile("EU"), revert()
+//   notify(title, body)
+//   print(...), debug(...)        written to the log
+//
+// Failed calls throw a map with the error message and its kind from errors.rs, which
+// try/catch can inspect.
+struct Plugin {
+    events: mpsc::Sender<(String, Value)>,
+}
+
+static PLUGINS: Mutex<Vec<Plugin>> = Mutex::new(Vec::new());
+// Ends scripts stuck in a loop once the app shuts down
+static STOPPING: AtomicBool = AtomicBool::new(false);
+
+// Starts every plugin; their calls are answered like D-Bus and HTTP API requests
+pub fn start(requests: &Requests) {
+    // Root would run whatever the user's account put in the plugins directory, see hooks.rs
+    if crate::is_running_as_root() {
+        log_warn!("plugins", "Not starting plugins as root");
+        return;
+    }
+    let dir = crate::paths::plugins_dir();
+    let Ok(entries) = std::fs::read_dir(&dir) else {
+        return;
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "rhai"))
+        .collect();
+    paths.sort();
+
+    STOPPING.store(false, Ordering::Relaxed);
+    let mut plugins = PLUGINS.lock().unwrap();
+    for path in paths {
+        match spawn(&path, requests) {
+            Ok(plugin) => plugins.push(plugin),
+            Err(e) => log_warn!("plugins", "Failed to start {}: {}", path.display(), e),
+        }
+    }
+    drop(plugins);
+    broadcast("start", Value::from(regions()));
+}
+
+fn regions() -> Vec<Value> {
+    let regions = crate::region::get_selectable_regions();
+    let mut names: Vec<&String> = regions.keys().collect();
+    names.sort();
+    names
+        .into_iter()
+        .map(|name| {
+            let info = &regions[name];
+            json!({
+                "name": name,
+                "code": crate::region::aws_region_code(info),
+                "group": crate::region::get_group_name(name),
+                "stable": info.stable,
+            })
+        })
+        .collect()
+}
+
+fn plugin_name(path: &Path) -> String {
+    path.file_stem().unwrap_or_default().to_string_lossy().into_owned()
+}
+
+fn spawn(path: &Path, requests: &Requests) -> std::io::Result<Plugin> {
+    let name = plugin_name(path);
+    let path = path.to_path_buf();
+    let requests = requests.clone();
+    let (events, events_rx) = mpsc::channel();
+    std::thread::Builder::new()
+        .name(format!("plugin {}", name))
+        .spawn(move || run(&name, path, requests, events_rx))?;
+    Ok(Plugin { events })
+}
+
+fn run(name: &str, path: PathBuf, requests: Requests, events: mpsc::Receiver<(String, Value)>) {
+    let engine = engine(name, &requests);
+    let ast: AST = match engine.compile_file(path) {
+        Ok(ast) => ast,
+        Err(e) => {
+            log_warn!("plugins", "{} does not compile: {}", name, e);
+            return;
+        }
+    };
+    let mut scope = Scope::new();
+    if let Err(e) = engine.run_ast_with_scope(&mut scope, &ast) {
+        log_warn!("plugins", "{} failed to start: {}", name, e);
+        return;
+    }
+    log_info!("plugins", "Started {}", name);
+
+    let handlers: HashSet<String> = ast
+        .iter_functions()
+        .filter(|function| function.params.len() == 1)
+        .map(|function| function.name.to_string())
+        .collect();
+    let mut state = Dynamic::from_map(rhai::Map::new());
+    for (event, data) in events {
+        let handler = format!("on_{}", event.replace('-', "_"));
+        if !handlers.contains(&handler) {
+            continue;
+        }
+        let data = rhai::serde::to_dynamic(&data).unwrap_or_default();
+        let options = CallFnOptions::new().eval_ast(false).bind_this_ptr(&mut state);
+        if let Err(e) = engine.call_fn_with_options::<Dynamic>(options, &mut scope, &ast, &handler, (data,)) {
+            log_warn!("plugins", "{}: {} failed: {}", name, handler, e);
+        }
+    }
+    log_info!("plugins", "{} stopped", name);
+}
+
+type CallResult<T> = Result<T, Box<EvalAltResult>>;
+
+fn engine(name: &str, requests: &Requests) -> Engine {
+    let mut engine = Engine::new();
+    let plugin = name.to_string();
+    engine.on_print(move |text| log_info!("plugins", "{}: {}", plugin, text));
+    let plugin = name.to_string();
+    engine.on_debug(move |text, _, position| log_info!("plugins", "{}: {} ({})", plugin, text, position));
+    engine.on_progress(|_| STOPPING.load(Ordering::Relaxed).then(Dynamic::default));
+    // Debug builds default to lower limits, scripts should behave the same in both
+    engine.set_max_expr_depths(64, 64);
+
+    engine.register_fn("regions", || rhai::serde::to_dynamic(Value::from(regions())));
+    let r = requests.clone();
+    engine.register_fn("latencies", move || -> CallResult<Dynamic> {
+        let reply = call(&r, Command::GetLatencies)?;
+        rhai::serde::to_dynamic(&reply["latencies"])
+    });
+    let r = requests.clone();
+    engine.register_fn("status", move || -> CallResult<Dynamic> {
+        rhai::serde::to_dynamic(call(&r, Command::GetStatus)?)
+    });
+    let r = requests.clone();
+    engine.register_fn("current_server", move || -> CallResult<Dynamic> {
+        let reply = call(&r, Command::GetCurrentServer)?;
+        if reply["ip"].is_null() {
+            return Ok(Dynamic::UNIT);
+        }
+        rhai::serde::to_dynamic(reply)
+    });
+    let r = requests.clone();
+    engine.register_fn("apply", move |servers: Array| -> CallResult<()> {
+        call(&r, Command::ApplySelection(names(servers), String::new())).map(|_| ())
+    });
+    let r = requests.clone();
+    engine.register_fn("apply", move |servers: Array, mode: &str| -> CallResult<()> {
+        call(&r, Command::ApplySelection(names(servers), mode.to_string())).map(|_| ())
+    });
+    let r = requests.clone();
+    engine.register_fn("apply_profile", move |profile: &str| -> CallResult<()> {
+        call(&r, Command::ApplyProfile(profile.to_string())).map(|_| ())
+    });
+    let r = requests.clone();
+    engine.register_fn("revert", move || -> CallResult<()> { call(&r, Command::Revert).map(|_| ()) });
+    let r = requests.clone();
+    engine.register_fn("notify", move |title: &str, body: &str| -> CallResult<()> {
+        call(&r, Command::Notify(title.to_string(), body.to_string())).map(|_| ())
+    });
+    engine
+}
+
+// Server names or AWS codes, anything else in the array is skipped
+fn names(servers: Array) -> Vec<String> {
+    servers.into_iter().filter_map(|server| server.into_string().ok()).collect()
+}
+
+// Answered by the window or the service on their own thread, this one waits
+fn call(requests: &Requests, command: Command) -> CallResult<Value> {
+    let shutting_down = || control::Error::new(ErrorKind::Failed, "The application is shutting down");
+    let (reply_tx, reply_rx) = oneshot::channel();
+    let result = requests
+        .send((command, reply_tx))
+        .map_err(|_| shutting_down())
+        .and_then(|_| reply_rx.blocking_recv().map_err(|_| shutting_down())?);
+    match result {
+        Ok(reply) => Ok(control::reply_json(reply)),
+        Err(error) => {
+            let error = rhai::serde::to_dynamic(control::error_json(&error))?;
+            Err(EvalAltResult::ErrorRuntime(error, Position::NONE).into())
+        }
+    }
+}
+
+// Sends an event to every running plugin
+pub fn broadcast(event: &str, data: Value) {
+    let plugins = PLUGINS.lock().unwrap();
+    for plugin in plugins.iter() {
+        let _ = plugin.events.send((event.to_string(), data.clone()));
+    }
+}
+
+// Latest ping round, () for servers that could not be reached
+pub fn broadcast_latencies(latencies: &std::collections::HashMap<String, i64>) {
+    let reply = control::reply_json(control::Reply::Latencies(latencies.clone()));
+    broadcast("latencies", reply["latencies"].clone());
+}
+
+// Plugins don't outlive the app. Their threads end with the event channel, or at the
+// next step of a script that is still busy; a call waiting for an answer gets an error.
+pub fn stop() {
+    STOPPING.store(true, Ordering::Relaxed);
+    PLUGINS.lock().unwrap().clear();
+}
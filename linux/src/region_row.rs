@@ -0,0 +1,86 @@
+use crate::i18n::tr;
+use gtk4::glib;
+use gtk4::prelude::*;
+use gtk4::subclass::prelude::*;
+
+mod imp {
+    use super::*;
+    use std::cell::{Cell, RefCell};
+
+    #[derive(Default, glib::Properties)]
+    #[properties(wrapper_type = super::RegionRow)]
+    pub struct RegionRow {
+        // Region key for regular rows, group label for dividers
+        #[property(get, set)]
+        name: RefCell<String>,
+        #[property(get, set)]
+        latency: RefCell<String>,
+        // CSS class describing how good the latency is, see theme.rs
+        #[property(get, set)]
+        severity: RefCell<String>,
+        // Country code for the flag icon, empty for dividers
+        #[property(get, set)]
+        country: RefCell<String>,
+        #[property(get, set)]
+        stable: Cell<bool>,
+        #[property(get, set)]
+        show_warning: Cell<bool>,
+        #[property(get, set)]
+        selected: Cell<bool>,
+        #[property(get, set)]
+        is_divider: Cell<bool>,
+        #[property(get, set)]
+        tooltip: RefCell<String>,
+        // What the hostnames resolve to, for the optional addresses column
+        #[property(get, set)]
+        addresses: RefCell<String>,
+        #[property(get, set)]
+        addresses_tooltip: RefCell<String>,
+        // Some hostname resolves to 0.0.0.0
+        #[property(get, set)]
+        addresses_blocked: Cell<bool>,
+        // Mirrors theme::spells_out, so the cells re-render when it changes
+        #[property(get, set)]
+        spell_out: Cell<bool>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for RegionRow {
+        const NAME: &'static str = "MycRegionRow";
+        type Type = super::RegionRow;
+    }
+
+    #[glib::derived_properties]
+    impl ObjectImpl for RegionRow {}
+}
+
+glib::wrapper! {
+    pub struct RegionRow(ObjectSubclass<imp::RegionRow>);
+}
+
+impl RegionRow {
+    pub fn new(name: &str, country: &str, stable: bool) -> Self {
+        glib::Object::builder()
+            .property("name", name)
+            .property("country", country)
+            .property("latency", "…")
+            .property("severity", "latency-none")
+            .property("stable", stable)
+            .build()
+    }
+
+    pub fn divider(label: &str) -> Self {
+        glib::Object::builder()
+            .property("name", label)
+            .property("stable", true)
+            .property("is-divider", true)
+            .build()
+    }
+
+    // Only flag unstable servers when they won't be merged with a stable alternative
+    pub fn refresh_warning(&self, merge_unstable: bool) {
+        let warn = !self.stable() && !merge_unstable;
+        self.set_show_warning(warn);
+        self.set_tooltip(if warn { tr("Unstable: issues may occur.") } else { String::new() });
+    }
+}
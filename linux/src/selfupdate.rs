@@ -0,0 +1,156 @@
+use crate::update::{Asset, Release};
+use anyhow::{bail, Context, Result};
+use std::fs;
+use std::io::Read;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+
+// Names the checksum list is published under, in "<sha256>  <file name>" lines
+const CHECKSUM_FILES: &[&str] = &["SHA256SUMS", "SHA256SUMS.txt"];
+
+// Everything needed to replace the running AppImage with a release
+#[derive(Debug, Clone)]
+pub struct SelfUpdate {
+    pub appimage: PathBuf,
+    pub asset: Asset,
+    checksums: Asset,
+}
+
+// Self-update is only offered when running from an AppImage and the release has both
+// an AppImage for this architecture, with its size, and a checksum list to verify it
+// against. A mirror serves the checksums along with the file, so its releases only link
+// to the download page.
+pub fn available(release: &Release) -> Option<SelfUpdate> {
+    if release.from_mirror {
+        return None;
+    }
+    let asset = appimage_asset(release).filter(|asset| asset.size != 0)?;
+    let checksums = release
+        .assets
+        .iter()
+        .find(|asset| CHECKSUM_FILES.contains(&asset.name.as_str()))?;
+    Some(SelfUpdate {
+        appimage: appimage_path()?,
+        asset: asset.clone(),
+        checksums: checksums.clone(),
+    })
+}
+
+// The AppImage runtime exports its own path, the running binary is a mount inside it
+fn appimage_path() -> Option<PathBuf> {
+    let path = PathBuf::from(std::env::var_os("APPIMAGE")?);
+    path.is_file().then_some(path)
+}
+
+// The release's AppImage for this architecture
+fn appimage_asset(release: &Release) -> Option<&Asset> {
+    let appimages: Vec<&Asset> = release
+        .assets
+        .iter()
+        .filter(|asset| asset.name.ends_with(".AppImage"))
+        .collect();
+    appimages
+        .iter()
+        .find(|asset| asset.name.contains(std::env::consts::ARCH))
+        .or_else(|| appimages.first())
+        .copied()
+}
+
+// Downloads the AppImage next to the running one, so the final rename stays on one
+// filesystem, after checking it against the release's SHA256SUMS. Nothing is written
+// when the checksum is missing or doesn't match.
+pub async fn download(update: &SelfUpdate) -> Result<PathBuf> {
+    let asset = &update.asset;
+    let checksums = fetch(&update.checksums).await?;
+    let checksums = String::from_utf8_lossy(&checksums);
+    let expected = expected_checksum(&checksums, &asset.name)
+        .with_context(|| format!("{} lists no checksum for {}", update.checksums.name, asset.name))?;
+
+    let bytes = fetch(asset).await?;
+    if bytes.len() as u64 != asset.size {
+        bail!(
+            "Download of {} is incomplete ({} of {} bytes)",
+            asset.name,
+            bytes.len(),
+            asset.size
+        );
+    }
+    let actual = glib::compute_checksum_for_data(glib::ChecksumType::Sha256, &bytes)
+        .context("Failed to compute the checksum")?;
+    if !actual.eq_ignore_ascii_case(&expected) {
+        bail!(
+            "Checksum mismatch for {}: expected {}, got {}",
+            asset.name,
+            expected,
+            actual
+        );
+    }
+
+    let partial = update
+        .appimage
+        .with_file_name(format!(".{}.download", asset.name));
+    fs::write(&partial, &bytes).with_context(|| format!("Failed to write {:?}", partial))?;
+    Ok(partial)
+}
+
+async fn fetch(asset: &Asset) -> Result<Vec<u8>> {
+    let bytes = crate::net::client(None)?
+        .get(&asset.browser_download_url)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .with_context(|| format!("Failed to download {}", asset.name))?
+        .bytes()
+        .await
+        .with_context(|| format!("Failed to download {}", asset.name))?;
+    Ok(bytes.to_vec())
+}
+
+// "<hex>  name" or "<hex> *name" (binary mode), as written by sha256sum
+fn expected_checksum(checksums: &str, file_name: &str) -> Option<String> {
+    checksums.lines().find_map(|line| {
+        let (hash, name) = line.trim().split_once(char::is_whitespace)?;
+        let name = name.trim_start().trim_start_matches('*');
+        (name == file_name && hash.len() == 64).then(|| hash.to_string())
+    })
+}
+
+// Checks the download is an AppImage, then swaps it in with a rename so the old file
+// is either fully replaced or left untouched
+pub fn install(downloaded: &Path, target: &Path) -> Result<()> {
+    let result = verify_appimage(downloaded).and_then(|_| {
+        fs::set_permissions(downloaded, fs::Permissions::from_mode(0o755))
+            .with_context(|| format!("Failed to make {:?} executable", downloaded))?;
+        fs::rename(downloaded, target)
+            .with_context(|| format!("Failed to replace {:?}", target))
+    });
+    if result.is_err() {
+        let _ = fs::remove_file(downloaded);
+    }
+    result
+}
+
+// ELF header with the AppImage type 2 magic ("AI\x02") at offset 8
+fn verify_appimage(path: &Path) -> Result<()> {
+    let mut header = [0u8; 11];
+    fs::File::open(path)
+        .and_then(|mut file| file.read_exact(&mut header))
+        .with_context(|| format!("Failed to read {:?}", path))?;
+    if &header[..4] != b"\x7fELF" || &header[8..11] != b"AI\x02" {
+        bail!("The downloaded file is not an AppImage");
+    }
+    Ok(())
+}
+
+// Starts the new AppImage once this process has exited, otherwise it would find the
+// running instance and hand over to it
+pub fn relaunch(appimage: &Path) -> Result<()> {
+    std::process::Command::new("sh")
+        .arg("-c")
+        .arg("while kill -0 \"$1\" 2>/dev/null; do sleep 0.2; done; exec \"$0\"")
+        .arg(appimage)
+        .arg(std::process::id().to_string())
+        .spawn()
+        .context("Failed to start the updated AppImage")?;
+    Ok(())
+}
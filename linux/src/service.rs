@@ -0,0 +1,199 @@
+use anyhow::{bail, Context, Result};
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+
+// Command line option the service is started with, see daemon.rs
+pub const DAEMON_OPTION: &str = "daemon";
+
+// What `make-your-choice service` takes, the window has its own switch
+pub const ACTIONS: &[&str] = &["enable", "disable", "status"];
+
+// A systemd user unit on Linux, a launchd daemon on macOS
+#[cfg(not(target_os = "macos"))]
+pub use systemd::*;
+#[cfg(target_os = "macos")]
+pub use launchd::*;
+
+fn executable() -> Result<PathBuf> {
+    // An AppImage runs from a temporary mount, the unit has to start the image itself
+    match std::env::var_os("APPIMAGE") {
+        Some(path) => Ok(PathBuf::from(path)),
+        None => std::env::current_exe().context("Failed to get executable path"),
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+mod systemd {
+    use super::*;
+
+    const UNIT: &str = "make-your-choice.service";
+
+    pub fn unit_file() -> PathBuf {
+        crate::paths::systemd_unit_file()
+    }
+
+    pub fn is_enabled() -> bool {
+        unit_file().exists()
+    }
+
+    // Writes a user unit for this executable, then starts it now and on every login
+    pub fn enable() -> Result<()> {
+        let unit = format!(
+            "[Unit]\n\
+             Description=Make Your Choice background service (DbD server selector)\n\
+             Documentation=https://github.com/laewliet/make-your-choice\n\
+             \n\
+             [Service]\n\
+             ExecStart=\"{}\" --{}\n\
+             Restart=on-failure\n\
+             RestartSec=10\n\
+             \n\
+             [Install]\n\
+             WantedBy=default.target\n",
+            executable()?.to_string_lossy().replace('"', "\\\""),
+            DAEMON_OPTION
+        );
+
+        let path = unit_file();
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .with_context(|| format!("Failed to create systemd user directory {:?}", dir))?;
+        }
+        fs::write(&path, unit).with_context(|| format!("Failed to write service unit {:?}", path))?;
+
+        systemctl(&["daemon-reload"])?;
+        systemctl(&["enable", "--now", UNIT])
+    }
+
+    pub fn disable() -> Result<()> {
+        let path = unit_file();
+        if !path.exists() {
+            return Ok(());
+        }
+        // Stopped first, the unit file has to exist for that
+        if let Err(e) = systemctl(&["disable", "--now", UNIT]) {
+            log_warn!("service", "{:#}", e);
+        }
+        fs::remove_file(&path).with_context(|| format!("Failed to remove service unit {:?}", path))?;
+        systemctl(&["daemon-reload"])
+    }
+
+    // Picks up changed settings that are only read when the service starts
+    #[cfg(feature = "gui")]
+    pub fn restart() -> Result<()> {
+        if !is_enabled() {
+            return Ok(());
+        }
+        systemctl(&["try-restart", UNIT])
+    }
+
+    fn systemctl(args: &[&str]) -> Result<()> {
+        let status = Command::new("systemctl")
+            .arg("--user")
+            .args(args)
+            .status()
+            .context("Failed to run systemctl")?;
+        if !status.success() {
+            bail!("systemctl --user {} failed ({})", args.join(" "), status);
+        }
+        Ok(())
+    }
+}
+
+// Only root may write /etc/hosts on macOS and there are no file capabilities, so the
+// service is a system daemon rather than a login agent. HOME points it at the settings
+// of the user who set it up. Setting it up needs sudo.
+#[cfg(target_os = "macos")]
+mod launchd {
+    use super::*;
+
+    const LABEL: &str = "dev.lawliet.makeyourchoice.daemon";
+
+    pub fn unit_file() -> PathBuf {
+        crate::paths::launchd_plist_file()
+    }
+
+    pub fn is_enabled() -> bool {
+        unit_file().exists()
+    }
+
+    fn escape(value: &str) -> String {
+        value
+            .replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+            .replace('"', "&quot;")
+    }
+
+    fn target() -> String {
+        format!("system/{}", LABEL)
+    }
+
+    // Writes a daemon for this executable, then starts it now and on every boot
+    pub fn enable() -> Result<()> {
+        let home = dirs::home_dir().context("Failed to get the home directory")?;
+        let plist = format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+             <!DOCTYPE plist PUBLIC \"-//Apple//DTD PLIST 1.0//EN\" \"http://www.apple.com/DTDs/PropertyList-1.0.dtd\">\n\
+             <plist version=\"1.0\">\n\
+             <dict>\n\
+             \x20   <key>Label</key>\n\
+             \x20   <string>{}</string>\n\
+             \x20   <key>ProgramArguments</key>\n\
+             \x20   <array>\n\
+             \x20       <string>{}</string>\n\
+             \x20       <string>--{}</string>\n\
+             \x20   </array>\n\
+             \x20   <key>EnvironmentVariables</key>\n\
+             \x20   <dict>\n\
+             \x20       <key>HOME</key>\n\
+             \x20       <string>{}</string>\n\
+             \x20   </dict>\n\
+             \x20   <key>RunAtLoad</key>\n\
+             \x20   <true/>\n\
+             \x20   <key>KeepAlive</key>\n\
+             \x20   <dict>\n\
+             \x20       <key>SuccessfulExit</key>\n\
+             \x20       <false/>\n\
+             \x20   </dict>\n\
+             \x20   <key>ThrottleInterval</key>\n\
+             \x20   <integer>10</integer>\n\
+             </dict>\n\
+             </plist>\n",
+            LABEL,
+            escape(&executable()?.to_string_lossy()),
+            DAEMON_OPTION,
+            escape(&home.to_string_lossy()),
+        );
+
+        let path = unit_file();
+        fs::write(&path, plist).with_context(|| format!("Failed to write launchd daemon {:?}", path))?;
+
+        // Loaded again, so a changed executable path takes effect
+        let _ = launchctl(&["bootout", &target()]);
+        launchctl(&["bootstrap", "system", &path.to_string_lossy()])
+    }
+
+    pub fn disable() -> Result<()> {
+        let path = unit_file();
+        if !path.exists() {
+            return Ok(());
+        }
+        if let Err(e) = launchctl(&["bootout", &target()]) {
+            log_warn!("service", "{:#}", e);
+        }
+        fs::remove_file(&path).with_context(|| format!("Failed to remove launchd daemon {:?}", path))
+    }
+
+    fn launchctl(args: &[&str]) -> Result<()> {
+        let status = Command::new("launchctl")
+            .args(args)
+            .status()
+            .context("Failed to run launchctl")?;
+        if !status.success() {
+            bail!("launchctl {} failed ({})", args.join(" "), status);
+        }
+        Ok(())
+    }
+}
@@ -6,6 +6,11 @@ use pnet::packet::Packet;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::thread;
+use std::time::{Duration, Instant};
+
+// Made-up matches in --demo: a lobby, a match of this length, then the next one
+const DEMO_LOBBY: Duration = Duration::from_secs(20);
+const DEMO_MATCH: Duration = Duration::from_secs(45);
 
 pub struct TrafficSniffer {
     running: Arc<AtomicBool>,
@@ -20,7 +25,11 @@ impl TrafficSniffer {
         // Spawn sniffing thread
         let running_clone = running.clone();
         thread::spawn(move || {
-            Self::sniff(running_clone, callback);
+            if crate::demo::enabled() {
+                Self::simulate(running_clone, callback);
+            } else {
+                Self::sniff(running_clone, callback);
+            }
         });
 
         Self {
@@ -39,7 +48,7 @@ impl TrafficSniffer {
         let interface = match interface {
             Some(i) => i,
             None => {
-                eprintln!("Sniffer: No suitable network interface found.");
+                log_warn!("sniffer", "No suitable network interface found.");
                 return;
             }
         };
@@ -47,11 +56,11 @@ impl TrafficSniffer {
         let (_, mut rx) = match datalink::channel(&interface, Default::default()) {
             Ok(Ethernet(tx, rx)) => (tx, rx),
             Ok(_) => {
-                eprintln!("Sniffer: Unhandled channel type or error.");
+                log_error!("sniffer", "Unhandled channel type or error.");
                 return;
             }
             Err(e) => {
-                eprintln!("Sniffer: Failed to create channel: {}", e);
+                log_error!("sniffer", "Failed to create channel: {}", e);
                 return;
             }
         };
@@ -89,6 +98,22 @@ impl TrafficSniffer {
         }
     }
 
+    // Game traffic twice a second while a fake match runs, silence in between
+    fn simulate<F>(running: Arc<AtomicBool>, callback: F)
+    where F: Fn(String, u16)
+    {
+        log_info!("sniffer", "Demo mode, simulating matches");
+        while running.load(Ordering::Relaxed) {
+            thread::sleep(DEMO_LOBBY);
+            let server = crate::demo::match_server();
+            let started = Instant::now();
+            while running.load(Ordering::Relaxed) && started.elapsed() < DEMO_MATCH {
+                callback(server.clone(), 7777);
+                thread::sleep(Duration::from_millis(500));
+            }
+        }
+    }
+
     #[allow(dead_code)]
     pub fn stop(&self) {
         self.running.store(false, Ordering::Relaxed);
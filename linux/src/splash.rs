@@ -0,0 +1,112 @@
+use crate::errors::{self, ErrorKind};
+use crate::{games, integrity};
+use crate::i18n::tr;
+use anyhow::{Context, Result};
+use gtk4::gdk_pixbuf::{Colorspace, InterpType, Pixbuf, PixbufLoader};
+use gtk4::prelude::*;
+use std::path::{Path, PathBuf};
+
+// EasyAntiCheat only shows the splash screen at exactly this size, as a PNG
+pub const WIDTH: i32 = 800;
+pub const HEIGHT: i32 = 450;
+
+// How an image with a different size or aspect ratio is fitted into 800x450
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FitMode {
+    // Scale to cover the whole area and crop the overflow around the center
+    Fill,
+    // Scale to fit inside the area and pad the rest with black bars
+    Letterbox,
+}
+
+// Ready-made artwork offered in the custom splash dialog, drawn at the splash size
+pub const PRESETS: &[(&str, &[u8])] = &[
+    ("Fog", include_bytes!("../splash/fog.svg")),
+    ("Ember", include_bytes!("../splash/ember.svg")),
+    ("Moonlight", include_bytes!("../splash/moonlight.svg")),
+    ("Campfire", include_bytes!("../splash/campfire.svg")),
+];
+
+pub fn render_preset(svg: &[u8]) -> Result<Pixbuf> {
+    let loader = PixbufLoader::new();
+    loader.set_size(WIDTH, HEIGHT);
+    loader.write(svg).context("Failed to render preset")?;
+    loader.close().context("Failed to render preset")?;
+    loader.pixbuf().context("Failed to render preset")
+}
+
+pub fn target_path(game_path: &Path) -> PathBuf {
+    games::anti_cheat_dir(game_path).join("SplashScreen.png")
+}
+
+// The original splash screen while a custom one is in place
+pub fn backup_path(game_path: &Path) -> PathBuf {
+    games::anti_cheat_dir(game_path).join("SplashScreen.png.bak")
+}
+
+// Loads any image gdk-pixbuf can read
+pub fn load(image_path: &Path) -> Result<Pixbuf> {
+    Pixbuf::from_file(image_path).with_context(|| format!("Failed to load image {:?}", image_path))
+}
+
+// Brings an image to the splash size
+pub fn fit(source: &Pixbuf, mode: FitMode) -> Result<Pixbuf> {
+    let (width, height) = (source.width(), source.height());
+    if width == WIDTH && height == HEIGHT {
+        return Ok(source.clone());
+    }
+
+    let scale_x = WIDTH as f64 / width as f64;
+    let scale_y = HEIGHT as f64 / height as f64;
+    let scale = match mode {
+        FitMode::Fill => scale_x.max(scale_y),
+        FitMode::Letterbox => scale_x.min(scale_y),
+    };
+    let scaled_width = ((width as f64 * scale).round() as i32).max(1);
+    let scaled_height = ((height as f64 * scale).round() as i32).max(1);
+    let scaled = source
+        .scale_simple(scaled_width, scaled_height, InterpType::Hyper)
+        .context("Failed to scale image")?;
+
+    let canvas = Pixbuf::new(Colorspace::Rgb, true, 8, WIDTH, HEIGHT)
+        .context("Failed to allocate splash image")?;
+    canvas.fill(0x000000ff);
+
+    // Copy the overlapping part centered, which crops for Fill and pads for Letterbox
+    let copy_width = scaled_width.min(WIDTH);
+    let copy_height = scaled_height.min(HEIGHT);
+    let src_x = (scaled_width - copy_width) / 2;
+    let src_y = (scaled_height - copy_height) / 2;
+    let dest_x = (WIDTH - copy_width) / 2;
+    let dest_y = (HEIGHT - copy_height) / 2;
+    scaled
+        .add_alpha(false, 0, 0, 0)
+        .context("Failed to convert image")?
+        .copy_area(src_x, src_y, copy_width, copy_height, &canvas, dest_x, dest_y);
+
+    Ok(canvas)
+}
+
+// Writes the image as the game's splash screen, keeping the original as a backup
+pub fn apply(game_path: &Path, image: &Pixbuf) -> Result<()> {
+    let target_path = target_path(game_path);
+
+    if let Some(dir) = target_path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    integrity::replace_file(game_path, &target_path, &backup_path(game_path), |path| {
+        image
+            .savev(path, "png", &[])
+            .with_context(|| format!("Failed to write {:?}", path))
+    })
+}
+
+pub fn revert(game_path: &Path) -> Result<()> {
+    let target_path = target_path(game_path);
+    let backup_path = backup_path(game_path);
+
+    if !backup_path.exists() {
+        return Err(errors::error(ErrorKind::BackupMissing, tr("No backup found to restore.")));
+    }
+    integrity::restore_file(game_path, &target_path, &backup_path)
+}
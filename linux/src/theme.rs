@@ -0,0 +1,266 @@
+use crate::i18n::tr;
+use crate::settings::{ColorScheme, Density, LatencyPalette};
+use gtk4::prelude::*;
+use libadwaita as adw;
+use std::cell::{Cell, RefCell};
+
+// Latency and match-monitor colors. The light palette matches the Windows version,
+// the dark one is lifted so the same states stay readable on dark backgrounds.
+const LIGHT_PALETTE: &str = "
+    label.latency-good { color: #008000; }
+    label.latency-fair { color: #ffa500; }
+    label.latency-poor { color: #dc143c; }
+    label.latency-severe { color: #c71585; }
+    label.latency-none { color: #778899; }
+    label.waiting { color: #778899; }
+    label.allowed { color: #008000; }
+    label.blocked { color: #ff0000; }
+    label.unknown { color: #ffa500; }
+";
+
+const DARK_PALETTE: &str = "
+    label.latency-good { color: #57e389; }
+    label.latency-fair { color: #ffbe6f; }
+    label.latency-poor { color: #ff7b63; }
+    label.latency-severe { color: #dc8add; }
+    label.latency-none { color: #9a9996; }
+    label.waiting { color: #9a9996; }
+    label.allowed { color: #57e389; }
+    label.blocked { color: #ff6b6b; }
+    label.unknown { color: #ffbe6f; }
+";
+
+// High contrast: darker or brighter shades with a wider gap between the states, bold
+// and upright, for low vision. Latencies and the unstable marker are spelled out as
+// well, see spells_out.
+const HIGH_CONTRAST_STYLE: &str = "
+    label.latency-good, label.latency-fair, label.latency-poor, label.latency-severe,
+    label.latency-none, label.waiting, label.allowed, label.blocked, label.unknown,
+    label.unstable-warning { font-weight: 800; }
+    window label.italic-label { font-style: normal; }
+    label.unstable-warning { border-radius: 4px; padding: 0 6px; }
+";
+
+const LIGHT_HIGH_CONTRAST_PALETTE: &str = "
+    label.latency-good { color: #004d00; }
+    label.latency-fair { color: #7a3d00; }
+    label.latency-poor { color: #a30000; }
+    label.latency-severe { color: #5c005c; }
+    label.latency-none { color: #000000; }
+    label.waiting { color: #000000; }
+    label.allowed { color: #004d00; }
+    label.blocked { color: #a30000; }
+    label.unknown { color: #7a3d00; }
+    label.unstable-warning { color: #ffffff; background-color: #a30000; }
+";
+
+const DARK_HIGH_CONTRAST_PALETTE: &str = "
+    label.latency-good { color: #8ff0a4; }
+    label.latency-fair { color: #ffd38a; }
+    label.latency-poor { color: #ff9e91; }
+    label.latency-severe { color: #f5b8f5; }
+    label.latency-none { color: #ffffff; }
+    label.waiting { color: #ffffff; }
+    label.allowed { color: #8ff0a4; }
+    label.blocked { color: #ff9e91; }
+    label.unknown { color: #ffd38a; }
+    label.unstable-warning { color: #000000; background-color: #ffd38a; }
+";
+
+// Good, fair, poor, very poor and unknown for the palettes that avoid the colors a
+// deficiency confuses; allowed, blocked and waiting reuse them. Built on the Okabe-Ito
+// colors, ordered by lightness as well so the states stay apart in grayscale.
+fn vision_colors(palette: LatencyPalette, dark: bool) -> Option<[&'static str; 5]> {
+    Some(match (palette, dark) {
+        (LatencyPalette::Standard, _) => return None,
+        // Red and green look alike, blue to orange to purple doesn't rely on them
+        (LatencyPalette::Deuteranopia, false) => ["#0072b2", "#b87f00", "#d55e00", "#882255", "#6b6b6b"],
+        (LatencyPalette::Deuteranopia, true) => ["#56b4e9", "#f0c94a", "#ff8c42", "#e8a6cc", "#9a9996"],
+        // Reds also look dark, so the worse states get darker rather than redder
+        (LatencyPalette::Protanopia, false) => ["#0072b2", "#a68a00", "#5e3c99", "#1a1a1a", "#8a8a8a"],
+        (LatencyPalette::Protanopia, true) => ["#56b4e9", "#f0e442", "#b2abd2", "#ffffff", "#8a8a8a"],
+        // Blue and yellow look alike, teal to pink to dark red doesn't rely on them
+        (LatencyPalette::Tritanopia, false) => ["#007a7a", "#c2185b", "#a30000", "#4a0000", "#6b6b6b"],
+        (LatencyPalette::Tritanopia, true) => ["#4dd0c8", "#ff9ec0", "#ff5c5c", "#ffd6d6", "#9a9996"],
+    })
+}
+
+fn vision_css([good, fair, poor, severe, none]: [&str; 5], high_contrast: bool, dark: bool) -> String {
+    let mut css = format!(
+        "label.latency-good, label.allowed {{ color: {good}; }}
+        label.latency-fair, label.unknown {{ color: {fair}; }}
+        label.latency-poor, label.blocked {{ color: {poor}; }}
+        label.latency-severe {{ color: {severe}; }}
+        label.latency-none, label.waiting {{ color: {none}; }}"
+    );
+    if high_contrast {
+        let text = if dark { "#000000" } else { "#ffffff" };
+        css.push_str(&format!("label.unstable-warning {{ color: {text}; background-color: {poor}; }}"));
+    }
+    css
+}
+
+thread_local! {
+    static PALETTE_PROVIDER: RefCell<Option<gtk4::CssProvider>> = const { RefCell::new(None) };
+    // The setting; the system's high contrast preference turns the mode on as well
+    static HIGH_CONTRAST: Cell<bool> = const { Cell::new(false) };
+    static PALETTE: Cell<LatencyPalette> = const { Cell::new(LatencyPalette::Standard) };
+}
+
+// Spoken/tooltip wording for a latency severity class, so it isn't conveyed by color alone
+pub fn severity_description(severity: &str) -> String {
+    match severity {
+        "latency-good" => tr("Good latency"),
+        "latency-fair" => tr("Fair latency"),
+        "latency-poor" => tr("Poor latency"),
+        "latency-severe" => tr("Very poor latency"),
+        _ => tr("Latency unknown"),
+    }
+}
+
+// Short word for a severity shown next to the latency when spelled out, empty where
+// the latency text already says it ("disconnected")
+pub fn severity_word(severity: &str) -> String {
+    match severity {
+        "latency-good" => tr("good"),
+        "latency-fair" => tr("fair"),
+        "latency-poor" => tr("poor"),
+        "latency-severe" => tr("very poor"),
+        _ => String::new(),
+    }
+}
+
+// Installs the palette for the current theme and swaps it whenever the theme changes
+pub fn install_palette() {
+    let style_manager = adw::StyleManager::default();
+    let provider = gtk4::CssProvider::new();
+    gtk4::style_context_add_provider_for_display(
+        &gtk4::gdk::Display::default().expect("Could not connect to a display."),
+        &provider,
+        gtk4::STYLE_PROVIDER_PRIORITY_APPLICATION,
+    );
+    PALETTE_PROVIDER.with(|cell| *cell.borrow_mut() = Some(provider));
+    load_palette();
+
+    style_manager.connect_dark_notify(|_| load_palette());
+    style_manager.connect_high_contrast_notify(|_| load_palette());
+}
+
+pub fn set_high_contrast(enabled: bool) {
+    HIGH_CONTRAST.set(enabled);
+    load_palette();
+}
+
+pub fn is_high_contrast() -> bool {
+    HIGH_CONTRAST.get() || adw::StyleManager::default().is_high_contrast()
+}
+
+pub fn set_latency_palette(palette: LatencyPalette) {
+    PALETTE.set(palette);
+    load_palette();
+}
+
+// Whether latencies carry their severity as a word and unstable servers a label, so
+// color is never the only way to tell them apart
+pub fn spells_out() -> bool {
+    is_high_contrast() || PALETTE.get() != LatencyPalette::Standard
+}
+
+fn load_palette() {
+    let dark = adw::StyleManager::default().is_dark();
+    let high_contrast = is_high_contrast();
+    let mut css = match vision_colors(PALETTE.get(), dark) {
+        Some(colors) => vision_css(colors, high_contrast, dark),
+        None => match (high_contrast, dark) {
+            (false, false) => LIGHT_PALETTE,
+            (false, true) => DARK_PALETTE,
+            (true, false) => LIGHT_HIGH_CONTRAST_PALETTE,
+            (true, true) => DARK_HIGH_CONTRAST_PALETTE,
+        }
+        .to_string(),
+    };
+    if high_contrast {
+        css.push_str(HIGH_CONTRAST_STYLE);
+    }
+    PALETTE_PROVIDER.with(|cell| {
+        if let Some(provider) = cell.borrow().as_ref() {
+            provider.load_from_data(&css);
+        }
+    });
+}
+
+thread_local! {
+    static LAYOUT_PROVIDER: RefCell<Option<gtk4::CssProvider>> = const { RefCell::new(None) };
+}
+
+// Tighter rows and buttons for small screens
+const COMPACT_STYLE: &str = "
+    .compact columnview > listview > row { padding-top: 0; padding-bottom: 0; min-height: 24px; }
+    .compact columnview > listview > row checkbutton { padding: 0; }
+    .compact button { min-height: 24px; padding: 2px 10px; }
+";
+
+// Large touch targets for the Steam Deck screen
+const DECK_STYLE: &str = "
+    .deck columnview > listview > row { min-height: 48px; }
+    .deck checkbutton check { min-width: 28px; min-height: 28px; }
+    .deck button { min-height: 48px; padding: 8px 20px; }
+    .deck label.gamepad-hints { padding: 6px; }
+";
+
+// Slim bar at the bottom of the main window
+const STATUS_BAR_STYLE: &str = "
+    .status-bar { padding: 2px 10px; font-size: 0.85em; }
+    .status-bar button { min-height: 0; padding: 0 6px; }
+";
+
+// Applies density and text size to the main window. Only widgets inside it are affected,
+// dialogs keep the system defaults. Deck mode overrides density and enlarges text.
+pub fn apply_layout(
+    window: &impl IsA<gtk4::Widget>,
+    density: Density,
+    font_scale: f64,
+    deck: bool,
+) {
+    window.add_css_class("myc-main");
+    if density == Density::Compact && !deck {
+        window.add_css_class("compact");
+    } else {
+        window.remove_css_class("compact");
+    }
+    if deck {
+        window.add_css_class("deck");
+    } else {
+        window.remove_css_class("deck");
+    }
+
+    let scale = if deck { font_scale * 1.25 } else { font_scale };
+    let css = format!(
+        "window.myc-main {{ font-size: {:.0}%; }}\n{}{}{}",
+        scale * 100.0,
+        STATUS_BAR_STYLE,
+        COMPACT_STYLE,
+        DECK_STYLE
+    );
+    LAYOUT_PROVIDER.with(|cell| {
+        let mut cell = cell.borrow_mut();
+        let provider = cell.get_or_insert_with(|| {
+            let provider = gtk4::CssProvider::new();
+            gtk4::style_context_add_provider_for_display(
+                &gtk4::gdk::Display::default().expect("Could not connect to a display."),
+                &provider,
+                gtk4::STYLE_PROVIDER_PRIORITY_APPLICATION,
+            );
+            provider
+        });
+        provider.load_from_data(&css);
+    });
+}
+
+pub fn apply_color_scheme(scheme: ColorScheme) {
+    adw::StyleManager::default().set_color_scheme(match scheme {
+        ColorScheme::System => adw::ColorScheme::Default,
+        ColorScheme::Light => adw::ColorScheme::ForceLight,
+        ColorScheme::Dark => adw::ColorScheme::ForceDark,
+    });
+}
@@ -0,0 +1,193 @@
+use crate::i18n::{tr, trf};
+use crate::settings::Role;
+use ksni::menu::{CheckmarkItem, RadioGroup, RadioItem, StandardItem, SubMenu};
+use ksni::{MenuItem, ToolTip, TrayMethods};
+use std::sync::mpsc::Sender;
+use std::sync::Arc;
+use tokio::runtime::Runtime;
+
+// Requests from the tray menu, handled on the GTK main thread
+pub enum TrayCommand {
+    ApplyLast,
+    // Gatekeep with only the fastest stable server
+    ApplyBest,
+    // Loads the named profile and applies it
+    ApplyProfile(String),
+    Revert,
+    ShowWindow,
+    Quit,
+}
+
+struct AppTray {
+    commands: Sender<TrayCommand>,
+    match_region: String,
+    profiles: Vec<String>,
+    active_profile: Option<String>,
+    // Profiles filling the Killer/Survivor slots
+    roles: Vec<(Role, String)>,
+}
+
+impl AppTray {
+    fn command_item(label: &str, command: fn() -> TrayCommand) -> MenuItem<Self> {
+        StandardItem {
+            label: label.to_string(),
+            activate: Box::new(move |tray: &mut Self| {
+                let _ = tray.commands.send(command());
+            }),
+            ..Default::default()
+        }
+        .into()
+    }
+}
+
+impl AppTray {
+    // Picking a profile applies it right away
+    fn profiles_menu(&self) -> MenuItem<Self> {
+        let selected = self
+            .active_profile
+            .as_ref()
+            .and_then(|active| self.profiles.iter().position(|name| name == active))
+            .unwrap_or(usize::MAX);
+        SubMenu {
+            label: tr("Apply profile"),
+            enabled: !self.profiles.is_empty(),
+            submenu: vec![RadioGroup {
+                selected,
+                select: Box::new(|tray: &mut Self, index: usize| {
+                    if let Some(name) = tray.profiles.get(index) {
+                        let _ = tray.commands.send(TrayCommand::ApplyProfile(name.clone()));
+                    }
+                }),
+                options: self
+                    .profiles
+                    .iter()
+                    .map(|name| RadioItem {
+                        label: name.replace('_', "__"),
+                        ..Default::default()
+                    })
+                    .collect(),
+            }
+            .into()],
+            ..Default::default()
+        }
+        .into()
+    }
+}
+
+impl AppTray {
+    // The same one-click switch as in the window, for the roles that have a profile
+    fn role_items(&self) -> Vec<MenuItem<Self>> {
+        self.roles
+            .iter()
+            .map(|(role, name)| {
+                let name = name.clone();
+                CheckmarkItem {
+                    label: trf("Play as {}", &[&tr(role.label())]),
+                    checked: self.active_profile.as_ref() == Some(&name),
+                    activate: Box::new(move |tray: &mut Self| {
+                        let _ = tray.commands.send(TrayCommand::ApplyProfile(name.clone()));
+                    }),
+                    ..Default::default()
+                }
+                .into()
+            })
+            .collect()
+    }
+}
+
+impl ksni::Tray for AppTray {
+    fn id(&self) -> String {
+        "make-your-choice".to_string()
+    }
+
+    fn title(&self) -> String {
+        "Make Your Choice".to_string()
+    }
+
+    fn icon_name(&self) -> String {
+        "make-your-choice".to_string()
+    }
+
+    fn tool_tip(&self) -> ToolTip {
+        ToolTip {
+            title: "Make Your Choice".to_string(),
+            description: trf("Connected to: {}", &[&self.match_region]),
+            ..Default::default()
+        }
+    }
+
+    fn activate(&mut self, _x: i32, _y: i32) {
+        let _ = self.commands.send(TrayCommand::ShowWindow);
+    }
+
+    fn menu(&self) -> Vec<MenuItem<Self>> {
+        let mut menu = vec![
+            StandardItem {
+                label: trf("Connected to: {}", &[&self.match_region]),
+                enabled: false,
+                ..Default::default()
+            }
+            .into(),
+            MenuItem::Separator,
+        ];
+        menu.extend(self.role_items());
+        menu.extend([
+            Self::command_item(&tr("Apply last selection"), || TrayCommand::ApplyLast),
+            Self::command_item(&tr("Best server only"), || TrayCommand::ApplyBest),
+            self.profiles_menu(),
+            Self::command_item(&tr("Revert to default"), || TrayCommand::Revert),
+            MenuItem::Separator,
+            Self::command_item(&tr("Show window"), || TrayCommand::ShowWindow),
+            Self::command_item(&tr("Quit"), || TrayCommand::Quit),
+        ]);
+        menu
+    }
+}
+
+#[derive(Clone)]
+pub struct TrayHandle {
+    handle: ksni::Handle<AppTray>,
+    runtime: Arc<Runtime>,
+}
+
+impl TrayHandle {
+    // Registers the StatusNotifierItem. Returns None when the desktop has no tray host.
+    pub fn spawn(runtime: Arc<Runtime>, commands: Sender<TrayCommand>) -> Option<Self> {
+        let tray = AppTray {
+            commands,
+            match_region: tr("Waiting for match..."),
+            profiles: Vec::new(),
+            active_profile: None,
+            roles: Vec::new(),
+        };
+
+        match runtime.block_on(tray.spawn()) {
+            Ok(handle) => Some(Self { handle, runtime }),
+            Err(e) => {
+                log_warn!("tray", "Failed to register tray icon: {}", e);
+                None
+            }
+        }
+    }
+
+    pub fn set_match_region(&self, region: &str) {
+        let handle = self.handle.clone();
+        let region = region.to_string();
+        self.runtime.spawn(async move {
+            handle.update(move |tray| tray.match_region = region).await;
+        });
+    }
+
+    pub fn set_profiles(&self, profiles: Vec<String>, active: Option<String>, roles: Vec<(Role, String)>) {
+        let handle = self.handle.clone();
+        self.runtime.spawn(async move {
+            handle
+                .update(move |tray| {
+                    tray.profiles = profiles;
+                    tray.active_profile = active;
+                    tray.roles = roles;
+                })
+                .await;
+        });
+    }
+}
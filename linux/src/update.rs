@@ -1,11 +1,39 @@
+use crate::settings::UpdateChannel;
 use anyhow::{Context, Result};
 use serde::Deserialize;
 
-#[derive(Debug, Deserialize)]
-struct Release {
-    tag_name: String,
+#[derive(Debug, Clone, Deserialize)]
+pub struct Release {
+    pub tag_name: String,
+    pub html_url: String,
+    #[serde(default)]
+    pub prerelease: bool,
+    #[serde(default)]
+    draft: bool,
+    #[serde(default)]
+    pub assets: Vec<Asset>,
+    // Changelog in GitHub flavored Markdown
+    #[serde(default)]
+    pub body: Option<String>,
+    // Listed by the fallback source rather than GitHub. Its files can't be checked
+    // against anything it doesn't serve itself, so they are never installed.
+    #[serde(skip)]
+    pub from_mirror: bool,
 }
 
+// A file attached to a release
+#[derive(Debug, Clone, Deserialize)]
+pub struct Asset {
+    pub name: String,
+    pub browser_download_url: String,
+    // 0 when the source doesn't say
+    #[serde(default)]
+    pub size: u64,
+}
+
+// Attempts after the first one, for network blips e.g. right after login
+const RETRIES: u32 = 3;
+
 #[derive(Clone)]
 pub struct UpdateChecker {
     developer: String,
@@ -22,33 +50,135 @@ impl UpdateChecker {
         }
     }
 
-    pub async fn check_for_updates(&self) -> Result<Option<String>> {
+    // The newest release on `channel` if it is newer than the running version. `mirror`
+    // is asked when GitHub can't be reached.
+    pub async fn check_for_updates(
+        &self,
+        channel: UpdateChannel,
+        mirror: Option<String>,
+    ) -> Result<Option<Release>> {
         let url = format!(
             "https://api.github.com/repos/{}/{}/releases",
             self.developer, self.repo
         );
 
-        let client = reqwest::Client::new();
-        let releases: Vec<Release> = client
-            .get(&url)
-            .header("User-Agent", "make-your-choice")
-            .send()
-            .await
-            .context("Failed to fetch releases")?
-            .json()
-            .await
-            .context("Failed to parse release JSON")?;
-
-        if let Some(latest) = releases.first() {
-            if latest.tag_name.to_lowercase() != self.current_version.to_lowercase() {
-                return Ok(Some(latest.tag_name.clone()));
-            }
-        }
+        let releases: Vec<Release> = match mirror {
+            None => crate::github::get_json(&url, RETRIES)
+                .await
+                .context("Failed to fetch releases")?,
+            // The mirror is asked before settling for GitHub's cached answer
+            Some(mirror) => match crate::github::get_json_fresh(&url, RETRIES).await {
+                Ok(releases) => releases,
+                Err(e) => {
+                    log_warn!("update", "GitHub failed, asking the mirror {}: {:#}", mirror, e);
+                    match fetch_mirror(&mirror).await {
+                        Ok(releases) => releases,
+                        Err(mirror_error) => crate::github::cached_json(&url).with_context(|| {
+                            format!(
+                                "Failed to fetch releases from GitHub ({:#}) and the mirror ({:#})",
+                                e, mirror_error
+                            )
+                        })?,
+                    }
+                }
+            },
+        };
 
-        Ok(None)
+        // GitHub lists releases newest first
+        let latest = releases.into_iter().find(|release| {
+            !release.draft && (channel == UpdateChannel::PreRelease || !release.prerelease)
+        });
+
+        Ok(latest.filter(|release| is_newer(&release.tag_name, &self.current_version)))
     }
+}
 
-    pub fn get_releases_url(&self) -> String {
-        format!("https://github.com/{}/{}/releases/latest", self.developer, self.repo)
+// Release list from a fallback source. Gitea, Forgejo and Codeberg answer in the
+// GitHub format, as does a plain JSON manifest with the same fields. GitLab's
+// /api/v4/projects/<id>/releases is converted.
+async fn fetch_mirror(url: &str) -> Result<Vec<Release>> {
+    let json: serde_json::Value = crate::net::client(Some(std::time::Duration::from_secs(10)))?
+        .get(url)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .context("Failed to reach the mirror")?
+        .json()
+        .await
+        .context("The mirror did not answer with JSON")?;
+
+    let mut releases = if url.contains("/api/v4/") {
+        gitlab_releases(&json)
+    } else {
+        serde_json::from_value(json).context("The mirror's release list has an unknown format")?
+    };
+    for release in &mut releases {
+        release.from_mirror = true;
+    }
+    Ok(releases)
+}
+
+fn gitlab_releases(json: &serde_json::Value) -> Vec<Release> {
+    let str_field = |value: &serde_json::Value, key: &str| {
+        value.get(key).and_then(|v| v.as_str()).unwrap_or_default().to_string()
+    };
+    json.as_array()
+        .into_iter()
+        .flatten()
+        .map(|release| Release {
+            tag_name: str_field(release, "tag_name"),
+            html_url: release
+                .pointer("/_links/self")
+                .and_then(|v| v.as_str())
+                .unwrap_or_default()
+                .to_string(),
+            prerelease: release
+                .get("upcoming_release")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false),
+            draft: false,
+            assets: release
+                .pointer("/assets/links")
+                .and_then(|v| v.as_array())
+                .into_iter()
+                .flatten()
+                .map(|link| Asset {
+                    name: str_field(link, "name"),
+                    browser_download_url: str_field(link, "url"),
+                    size: 0,
+                })
+                .collect(),
+            body: release.get("description").and_then(|v| v.as_str()).map(str::to_string),
+            from_mirror: true,
+        })
+        .collect()
+}
+
+// Compares "v2.4.0" style tags number by number. A release beats a pre-release of the
+// same version ("2.4.0" > "2.4.0-beta.1"). Tags that don't parse only count as newer
+// when they differ.
+fn is_newer(tag: &str, current: &str) -> bool {
+    fn parse(version: &str) -> Option<(Vec<u64>, bool)> {
+        let version = version.trim().trim_start_matches(['v', 'V']);
+        let (numbers, pre) = match version.split_once('-') {
+            Some((numbers, _)) => (numbers, true),
+            None => (version, false),
+        };
+        let numbers = numbers
+            .split('.')
+            .map(|n| n.parse().ok())
+            .collect::<Option<Vec<u64>>>()?;
+        Some((numbers, pre))
+    }
+
+    match (parse(tag), parse(current)) {
+        (Some((tag_numbers, tag_pre)), Some((current_numbers, current_pre))) => {
+            match tag_numbers.cmp(&current_numbers) {
+                std::cmp::Ordering::Greater => true,
+                std::cmp::Ordering::Less => false,
+                std::cmp::Ordering::Equal => current_pre && !tag_pre,
+            }
+        }
+        _ => !tag.eq_ignore_ascii_case(current),
     }
 }
@@ -0,0 +1,70 @@
+use crate::control::Command;
+use crate::i18n::trf;
+use std::collections::HashMap;
+
+// myc:// links, registered as a URL handler by the .desktop file, so launchers, links in
+// the Discord and Stream Deck "open URL" buttons can drive the app. The running window
+// handles them; when it isn't running it is started first.
+//   myc://apply?profile=EU
+//   myc://apply?regions=eu-central-1,eu-west-1&mode=gatekeep
+//   myc://apply-last
+//   myc://revert
+//   myc://show
+pub const SCHEME: &str = "myc";
+
+pub enum Request {
+    Control(Command),
+    ApplyLast,
+    Show,
+}
+
+// "a=1&b=x%20y" with the values decoded, "+" counts as a space like in web forms
+fn query_params(query: &str) -> HashMap<String, String> {
+    query
+        .split('&')
+        .filter_map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            let value = glib::Uri::unescape_string(&value.replace('+', " "), None)?;
+            Some((key.to_string(), value.to_string()))
+        })
+        .collect()
+}
+
+pub fn parse(uri: &str) -> Result<Request, String> {
+    let parsed = glib::Uri::parse(uri, glib::UriFlags::ENCODED_QUERY).map_err(|e| e.to_string())?;
+    if !parsed.scheme().eq_ignore_ascii_case(SCHEME) {
+        return Err(trf("Not a {}:// link", &[&SCHEME]));
+    }
+    let params = parsed.query().map(|query| query_params(&query)).unwrap_or_default();
+    // myc://apply names the action as the host, myc:apply as the path
+    let action = parsed
+        .host()
+        .filter(|host| !host.is_empty())
+        .map(|host| host.to_lowercase())
+        .unwrap_or_else(|| parsed.path().trim_matches('/').to_lowercase());
+
+    match action.as_str() {
+        "apply" => {
+            if let Some(profile) = params.get("profile") {
+                return Ok(Request::Control(Command::ApplyProfile(profile.clone())));
+            }
+            let regions: Vec<String> = params
+                .get("regions")
+                .map(|regions| {
+                    regions
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|region| !region.is_empty())
+                        .map(String::from)
+                        .collect()
+                })
+                .unwrap_or_default();
+            let mode = params.get("mode").cloned().unwrap_or_default();
+            Ok(Request::Control(Command::ApplySelection(regions, mode)))
+        }
+        "apply-last" => Ok(Request::ApplyLast),
+        "revert" => Ok(Request::Control(Command::Revert)),
+        "show" | "" => Ok(Request::Show),
+        action => Err(trf("Unknown link action \"{}\"", &[&action])),
+    }
+}
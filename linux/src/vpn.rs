@@ -0,0 +1,81 @@
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+// Active VPN tunnels. Pings are measured through a tunnel that carries the internet
+// traffic, and the game picks its server by the same latency, so both then reflect the
+// VPN server's location rather than the player's. Linux only, other systems report none.
+
+// Names VPN clients give their interfaces: OpenVPN and most others use tun/tap and
+// WireGuard wg, the rest pick their own
+const PREFIXES: &[&str] = &[
+    "tun", "tap", "wg", "ppp", "tailscale", "nordlynx", "proton", "mullvad", "CloudflareWARP", "cscotun", "gpd",
+    "zt", "vpn", "ipsec",
+];
+// Any public address does, only the interface the route leaves through matters
+const PROBE_ADDRESS: &str = "1.1.1.1";
+const NET_DIR: &str = "/sys/class/net";
+
+pub struct Tunnel {
+    pub interface: String,
+    // Internet traffic goes through it, not only a private network
+    pub default_route: bool,
+}
+
+fn is_tunnel(name: &str) -> bool {
+    PREFIXES.iter().any(|prefix| name.starts_with(prefix))
+        // tun and tap devices, whichever client made them
+        || Path::new(NET_DIR).join(name).join("tun_flags").exists()
+}
+
+// Tunnels report "unknown" rather than "up"
+fn is_up(name: &str) -> bool {
+    fs::read_to_string(Path::new(NET_DIR).join(name).join("operstate")).is_ok_and(|state| state.trim() != "down")
+}
+
+// The interface public traffic leaves through. `ip route get` follows policy routing,
+// which wg-quick and several VPN clients use instead of replacing the default route;
+// the main table in /proc/net/route is the fallback.
+fn route_interface() -> Option<String> {
+    let output = Command::new("ip").args(["-o", "route", "get", PROBE_ADDRESS]).output();
+    if let Some(output) = output.ok().filter(|output| output.status.success()) {
+        let route = String::from_utf8_lossy(&output.stdout);
+        let mut words = route.split_whitespace();
+        if words.any(|word| word == "dev") {
+            return words.next().map(str::to_string);
+        }
+    }
+
+    // Iface Destination Gateway Flags RefCnt Use Metric Mask ..., in hex
+    let table = fs::read_to_string("/proc/net/route").ok()?;
+    table.lines().skip(1).find_map(|line| {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        (fields.get(1) == Some(&"00000000") && fields.get(7) == Some(&"00000000")).then(|| fields[0].to_string())
+    })
+}
+
+pub fn detect() -> Vec<Tunnel> {
+    let Ok(entries) = fs::read_dir(NET_DIR) else {
+        return Vec::new();
+    };
+    let route = route_interface();
+    let mut tunnels: Vec<Tunnel> = entries
+        .flatten()
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+        .filter(|name| is_tunnel(name) && is_up(name))
+        .map(|interface| Tunnel {
+            default_route: route.as_deref() == Some(interface.as_str()),
+            interface,
+        })
+        .collect();
+    tunnels.sort_by(|a, b| a.interface.cmp(&b.interface));
+    tunnels
+}
+
+// The tunnel internet traffic goes through, if any
+pub fn routed() -> Option<String> {
+    detect()
+        .into_iter()
+        .find(|tunnel| tunnel.default_route)
+        .map(|tunnel| tunnel.interface)
+}